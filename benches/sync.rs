@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+use bevy_tokio_tasks::TokioTasksRuntime;
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use num::bigint::BigUint;
+use num::ToPrimitive;
+use starknet_ff::FieldElement;
+use std::str::FromStr;
+use std::sync::mpsc;
+use steering::conversions::fixed_to_f32;
+use steering::enemy::{
+    update_enemies, Enemy, EnemyId, EnemyMotion, Lane, Lanes, RacerId, SyncedPosition,
+    UpdateEnemies,
+};
+use steering::tasks::tokio_tasks_plugin;
+
+const ENEMIES_NB: u32 = 100;
+const HOPS_NB: u64 = 100;
+
+fn bench_update_enemies(c: &mut Criterion) {
+    let mut app = App::new();
+    app.init_resource::<Lanes>()
+        .add_event::<UpdateEnemies>()
+        .add_system(update_enemies);
+    let model_id = FieldElement::ONE;
+    for id in 0..ENEMIES_NB {
+        app.world.spawn((
+            Transform::default(),
+            Enemy::default(),
+            EnemyId(id.into()),
+            RacerId(model_id),
+            SyncedPosition::default(),
+            EnemyMotion::default(),
+            Lane::default(),
+        ));
+    }
+
+    let mut group = c.benchmark_group("update_enemies");
+    group.throughput(Throughput::Elements(ENEMIES_NB as u64));
+    group.bench_function("events", |b| {
+        b.iter(|| {
+            app.world.send_event(UpdateEnemies {
+                model_id,
+                positions: (0..ENEMIES_NB)
+                    .map(|id| (id.into(), vec![(id * 2).into(), (id * 3).into()]))
+                    .collect(),
+            });
+            app.update();
+        })
+    });
+    group.finish();
+}
+
+/// The previous `fixed_to_f32`, parsing the decimal string of the felt.
+fn fixed_to_f32_biguint(val: FieldElement) -> f32 {
+    (BigUint::from_str(&val.to_string()).unwrap() / (BigUint::from(1_u8) << 64_u32))
+        .to_f32()
+        .unwrap()
+}
+
+fn bench_fixed_to_f32(c: &mut Criterion) {
+    let value: FieldElement = (123u128 << 64 | 1u128 << 63).into();
+
+    let mut group = c.benchmark_group("fixed_to_f32");
+    group.bench_function("limbs", |b| b.iter(|| fixed_to_f32(black_box(value))));
+    group.bench_function("biguint", |b| {
+        b.iter(|| fixed_to_f32_biguint(black_box(value)))
+    });
+    group.finish();
+}
+
+fn bench_main_thread_hop(c: &mut Criterion) {
+    let mut app = App::new();
+    app.add_plugin(tokio_tasks_plugin());
+
+    let mut group = c.benchmark_group("main_thread_hop");
+    group.throughput(Throughput::Elements(HOPS_NB));
+    group.bench_function("run_on_main_thread", |b| {
+        b.iter(|| {
+            let (tx, rx) = mpsc::channel();
+            app.world
+                .resource::<TokioTasksRuntime>()
+                .spawn_background_task(move |mut ctx| async move {
+                    for _ in 0..HOPS_NB {
+                        ctx.run_on_main_thread(|_| ()).await;
+                    }
+                    tx.send(()).unwrap();
+                });
+
+            while rx.try_recv().is_err() {
+                app.update();
+            }
+        })
+    });
+    group.bench_function("message_channel", |b| {
+        b.iter(|| {
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+            app.world
+                .resource::<TokioTasksRuntime>()
+                .spawn_background_task(move |_ctx| async move {
+                    for _ in 0..HOPS_NB {
+                        tx.send(()).unwrap();
+                    }
+                });
+
+            let mut received = 0;
+            while received < HOPS_NB {
+                app.update();
+                while rx.try_recv().is_ok() {
+                    received += 1;
+                }
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_update_enemies,
+    bench_fixed_to_f32,
+    bench_main_thread_hop
+);
+criterion_main!(benches);
@@ -0,0 +1,152 @@
+//! Generates `world.rs`, included by `src/world.rs`, from the world manifest written by
+//! `sozo build`: a struct per component and a `SystemCall` constructor per system.
+
+use serde_json::Value;
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Written by `sozo build`, which is run again whenever the Cairo package changes.
+const MANIFEST: &str = "target/dev/manifest.json";
+const SOZO: &str = "sozo";
+
+fn main() {
+    println!("cargo:rerun-if-changed=Scarb.toml");
+    for entry in fs::read_dir("src").unwrap_or_else(|e| panic!("Read src: {e}")) {
+        let path = entry.unwrap().path();
+        if path
+            .extension()
+            .map_or(false, |extension| extension == "cairo")
+        {
+            println!("cargo:rerun-if-changed={}", path.display());
+        }
+    }
+    let status = Command::new(SOZO)
+        .arg("build")
+        .status()
+        .unwrap_or_else(|e| panic!("Run `{SOZO} build` to write {MANIFEST}: {e}"));
+    if !status.success() {
+        panic!("`{SOZO} build` failed: {status}");
+    }
+
+    let json = fs::read_to_string(MANIFEST).unwrap_or_else(|e| panic!("Read {MANIFEST}: {e}"));
+    let manifest: Value =
+        serde_json::from_str(&json).unwrap_or_else(|e| panic!("Parse {MANIFEST}: {e}"));
+
+    let mut out = String::new();
+    for component in entries(&manifest, "components") {
+        write_component(&mut out, component);
+    }
+    writeln!(out, "impl SystemCall {{").unwrap();
+    for system in entries(&manifest, "systems") {
+        write_system(&mut out, system);
+    }
+    writeln!(out, "}}").unwrap();
+
+    let path = Path::new(&env::var("OUT_DIR").unwrap()).join("world.rs");
+    fs::write(&path, out).unwrap_or_else(|e| panic!("Write {}: {e}", path.display()));
+}
+
+fn entries<'a>(manifest: &'a Value, section: &str) -> &'a Vec<Value> {
+    manifest[section]
+        .as_array()
+        .unwrap_or_else(|| panic!("{MANIFEST} has no `{section}`"))
+}
+
+fn name(entry: &Value) -> &str {
+    entry["name"]
+        .as_str()
+        .unwrap_or_else(|| panic!("Unnamed entry in {MANIFEST}: {entry}"))
+}
+
+/// Rust type decoding a cairo type, `None` when it is only known by its calldata.
+fn rust_type(cairo_type: &str) -> Option<&'static str> {
+    // Fully qualified, e.g. `core::integer::u128`, or not
+    match cairo_type.rsplit("::").next().unwrap() {
+        "felt252" | "ContractAddress" | "ClassHash" => Some("FieldElement"),
+        "bool" => Some("bool"),
+        "u8" => Some("u8"),
+        "u16" => Some("u16"),
+        "u32" => Some("u32"),
+        "u64" => Some("u64"),
+        "u128" => Some("u128"),
+        "Fixed" => Some("Fixed"),
+        "Vec2" => Some("FixedVec2"),
+        _ => None,
+    }
+}
+
+fn write_component(out: &mut String, component: &Value) {
+    let name = name(component);
+    let members: Vec<(&str, &str)> = entries(component, "members")
+        .iter()
+        // Keys are passed along with the component name, not stored in its values
+        .filter(|member| !member["key"].as_bool().unwrap_or(false))
+        .map(|member| {
+            let cairo_type = member["type"].as_str().unwrap_or_default();
+            let rust_type = rust_type(cairo_type)
+                .unwrap_or_else(|| panic!("Unsupported type `{cairo_type}` in `{name}`"));
+            (self::name(member), rust_type)
+        })
+        .collect();
+
+    writeln!(out, "/// `{name}` component of the world.").unwrap();
+    writeln!(out, "#[derive(Clone, Copy, Debug, PartialEq)]").unwrap();
+    writeln!(out, "pub struct {name} {{").unwrap();
+    for (member, rust_type) in &members {
+        writeln!(out, "    pub {member}: {rust_type},").unwrap();
+    }
+    writeln!(out, "}}\n").unwrap();
+
+    writeln!(out, "impl WorldComponent for {name} {{").unwrap();
+    writeln!(out, "    const NAME: &'static str = \"{name}\";").unwrap();
+    writeln!(out, "}}\n").unwrap();
+
+    let sizes: Vec<String> = members
+        .iter()
+        .map(|(_, rust_type)| format!("<{rust_type} as Decode>::SIZE"))
+        .collect();
+    writeln!(out, "impl Decode for {name} {{").unwrap();
+    writeln!(out, "    const SIZE: usize = {};", sizes.join(" + ")).unwrap();
+    writeln!(
+        out,
+        "    fn decode(values: &[FieldElement]) -> eyre::Result<Self> {{"
+    )
+    .unwrap();
+    writeln!(out, "        let mut values = values;").unwrap();
+    writeln!(out, "        Ok(Self {{").unwrap();
+    for (member, _) in &members {
+        writeln!(out, "            {member}: take(&mut values)?,").unwrap();
+    }
+    writeln!(out, "        }})").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "}}\n").unwrap();
+}
+
+/// Inputs of the `execute` function of a system, but the `Context` the world passes.
+fn inputs(system: &Value) -> impl Iterator<Item = &Value> {
+    entries(system, "inputs")
+        .iter()
+        .filter(|input| name(input) != "ctx")
+}
+
+fn write_system(out: &mut String, system: &Value) {
+    let name = name(system);
+    let inputs: Vec<String> = inputs(system)
+        .map(|input| {
+            let cairo_type = input["type"].as_str().unwrap_or_default();
+            let rust_type = rust_type(cairo_type).unwrap_or("impl Calldata");
+            format!("{}: {rust_type}", self::name(input))
+        })
+        .collect();
+
+    writeln!(out, "    /// Executes the `{name}` system.").unwrap();
+    writeln!(out, "    pub fn {name}({}) -> Self {{", inputs.join(", ")).unwrap();
+    write!(out, "        Self::new(\"{name}\")").unwrap();
+    for input in inputs(system) {
+        write!(out, ".arg({})", self::name(input)).unwrap();
+    }
+    writeln!(out, "\n    }}\n").unwrap();
+}
@@ -0,0 +1,37 @@
+//! Drive a car down the road against scripted traffic, without a world.
+//!
+//! `cargo run --example offline_arcade --no-default-features`
+
+use bevy::prelude::*;
+use steering::configs::{SETTINGS_PATH, WINDOW_HEIGHT, WINDOW_WIDTH};
+use steering::input_map::InputMapPlugin;
+use steering::{MaxDistanceTravelled, SimulationPlugin, StandalonePlugin};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
+        .add_plugin(InputMapPlugin {
+            settings: SETTINGS_PATH.into(),
+        })
+        .add_plugin(SimulationPlugin)
+        .add_plugin(StandalonePlugin)
+        .add_startup_system(setup_camera)
+        .add_system(follow_leader)
+        .run();
+}
+
+fn setup_camera(mut commands: Commands) {
+    commands.spawn(Camera2dBundle {
+        transform: Transform::from_xyz(WINDOW_WIDTH / 2.0, WINDOW_HEIGHT / 2.0, 0.0),
+        ..default()
+    });
+}
+
+fn follow_leader(
+    max_distance_travelled: Res<MaxDistanceTravelled>,
+    mut camera: Query<&mut Transform, With<Camera>>,
+) {
+    for mut transform in camera.iter_mut() {
+        transform.translation.y = max_distance_travelled.0;
+    }
+}
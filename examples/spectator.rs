@@ -0,0 +1,46 @@
+//! Follow the racers already on the world without sending any transaction.
+//!
+//! `cargo run --example spectator -- [racers]`
+
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::EguiPlugin;
+use steering::configs::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use steering::{
+    DojoConfig, DojoHudPlugin, DojoPlugin, MaxDistanceTravelled, Racers, ReadOnly, SimulationPlugin,
+};
+
+fn main() {
+    let mut app = App::new();
+    // The racers of the default model otherwise
+    if let Some(racers) = std::env::args().nth(1) {
+        let racers = racers.parse().expect("Number of racers to follow");
+        app.insert_resource(Racers::numbered(racers));
+    }
+    app.add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
+        .add_plugin(EguiPlugin)
+        .add_plugin(SimulationPlugin)
+        .insert_resource(ReadOnly)
+        .add_plugin(DojoPlugin::new(
+            DojoConfig::load().expect("Load dojo config"),
+        ))
+        .add_plugin(DojoHudPlugin)
+        .add_startup_system(setup_camera)
+        .add_system(follow_leader)
+        .run();
+}
+
+fn setup_camera(mut commands: Commands) {
+    commands.spawn(Camera2dBundle {
+        transform: Transform::from_xyz(WINDOW_WIDTH / 2.0, WINDOW_HEIGHT / 2.0, 0.0),
+        ..default()
+    });
+}
+
+fn follow_leader(
+    max_distance_travelled: Res<MaxDistanceTravelled>,
+    mut camera: Query<&mut Transform, With<Camera>>,
+) {
+    for mut transform in camera.iter_mut() {
+        transform.translation.y = max_distance_travelled.0;
+    }
+}
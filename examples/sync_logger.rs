@@ -0,0 +1,48 @@
+//! Follow the racers on the world headlessly, without sending any transaction, and log what the
+//! world sends back.
+//!
+//! `cargo run --example sync_logger`
+
+use bevy::app::ScheduleRunnerSettings;
+use bevy::prelude::*;
+use bevy::utils::Duration;
+use steering::configs::TRAIN_TIME_STEP;
+use steering::logging::LoggingPlugin;
+use steering::{
+    CarCrashed, DojoConfig, DojoPlugin, ReadOnly, SimulationPlugin, UpdateCar, UpdateEnemies,
+};
+
+fn main() {
+    App::new()
+        .add_plugin(LoggingPlugin)
+        .insert_resource(ScheduleRunnerSettings::run_loop(Duration::from_secs_f32(
+            TRAIN_TIME_STEP,
+        )))
+        .add_plugins(MinimalPlugins)
+        .add_plugin(AssetPlugin::default())
+        .add_plugin(TransformPlugin)
+        .add_plugin(HierarchyPlugin)
+        .add_plugin(SimulationPlugin)
+        .insert_resource(ReadOnly)
+        .add_plugin(DojoPlugin::new(
+            DojoConfig::load().expect("Load dojo config"),
+        ))
+        .add_system(log_world_updates)
+        .run();
+}
+
+fn log_world_updates(
+    mut cars: EventReader<UpdateCar>,
+    mut enemies: EventReader<UpdateEnemies>,
+    mut crashes: EventReader<CarCrashed>,
+) {
+    for car in cars.iter() {
+        info!("Vehicle of {:#x}: {:?}", car.model_id, car.vehicle);
+    }
+    for enemies in enemies.iter() {
+        info!("{} enemy positions", enemies.0.len());
+    }
+    for crash in crashes.iter() {
+        info!("{:#x} crashed at {}", crash.model_id, crash.position);
+    }
+}
@@ -0,0 +1,204 @@
+use crate::configs;
+use crate::enemy::{Enemy, EnemyDefinitions, EnemyType};
+use crate::simulation::RoadTile;
+use bevy::prelude::*;
+use bevy_prototype_debug_lines::{DebugLines, DebugLinesPlugin};
+use serde::{Deserialize, Serialize};
+
+/// Colorblind-safe enemies, on with [`AccessibilityConfig::colorblind_palette`]: each
+/// [`EnemyType`] is tinted and outlined with its own color of the Okabe-Ito palette and marked
+/// with its own shape, so the types tell apart by more than their hue.
+pub struct ColorblindPalettePlugin;
+
+impl Plugin for ColorblindPalettePlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<DebugLinesPlugin>() {
+            app.add_plugin(DebugLinesPlugin::default());
+        }
+        app.init_resource::<AccessibilityConfig>()
+            .init_resource::<EnemyDefinitions>()
+            .add_systems((tint_enemies, outline_enemies));
+    }
+}
+
+/// Darkens the background and the road under [`AccessibilityConfig::high_contrast`], so the cars
+/// and enemies stand out.
+pub struct HighContrastPlugin;
+
+impl Plugin for HighContrastPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AccessibilityConfig>()
+            .add_system(apply_high_contrast);
+    }
+}
+
+/// Accessibility options, saved in the `accessibility` section of the settings. Every system
+/// they change reads them from here.
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct AccessibilityConfig {
+    pub colorblind_palette: bool,
+    pub high_contrast: bool,
+    /// No camera shake and no leaning cars or enemies.
+    pub reduced_motion: bool,
+}
+
+impl AccessibilityConfig {
+    /// Whether `config`, when there is one, lets the camera and sprites move on their own.
+    pub fn allows_motion(config: Option<&Self>) -> bool {
+        !config.map_or(false, |config| config.reduced_motion)
+    }
+}
+
+/// Swaps the clear color for [`configs::HIGH_CONTRAST_BACKGROUND`] while on, restoring the one it
+/// replaced once off, and tints the road tiles.
+fn apply_high_contrast(
+    config: Res<AccessibilityConfig>,
+    mut clear_color: ResMut<ClearColor>,
+    mut replaced: Local<Option<Color>>,
+    mut roads: Query<&mut Sprite, With<RoadTile>>,
+) {
+    if config.high_contrast && replaced.is_none() {
+        *replaced = Some(clear_color.0);
+        clear_color.0 = configs::HIGH_CONTRAST_BACKGROUND;
+    } else if !config.high_contrast {
+        if let Some(color) = replaced.take() {
+            clear_color.0 = color;
+        }
+    }
+
+    let tint = if config.high_contrast {
+        configs::HIGH_CONTRAST_ROAD_TINT
+    } else {
+        Color::WHITE
+    };
+    for mut sprite in roads.iter_mut() {
+        if sprite.color != tint {
+            sprite.color = tint;
+        }
+    }
+}
+
+fn palette_color(enemy_type: &EnemyType) -> Color {
+    match enemy_type {
+        EnemyType::Simple => configs::COLORBLIND_SIMPLE_COLOR,
+        EnemyType::Horizontal(_) => configs::COLORBLIND_HORIZONTAL_COLOR,
+        EnemyType::Truck => configs::COLORBLIND_TRUCK_COLOR,
+    }
+}
+
+/// Segments of the marker of `enemy_type` around `center`: a diamond for simple cars, a
+/// double-headed arrow for the ones changing lanes and a crossed square for trucks.
+fn marker(enemy_type: &EnemyType, center: Vec2) -> Vec<(Vec2, Vec2)> {
+    let r = configs::COLORBLIND_MARKER_SIZE / 2.0;
+    let segments = match enemy_type {
+        EnemyType::Simple => {
+            let tips = [Vec2::X * r, Vec2::Y * r, -Vec2::X * r, -Vec2::Y * r];
+            (0..4).map(|i| (tips[i], tips[(i + 1) % 4])).collect()
+        }
+        EnemyType::Horizontal(_) => {
+            let (left, right) = (-Vec2::X * r, Vec2::X * r);
+            let head = r / 2.0;
+            vec![
+                (left, right),
+                (left, left + Vec2::new(head, head)),
+                (left, left + Vec2::new(head, -head)),
+                (right, right + Vec2::new(-head, head)),
+                (right, right + Vec2::new(-head, -head)),
+            ]
+        }
+        EnemyType::Truck => {
+            let corners = [
+                Vec2::new(-r, -r),
+                Vec2::new(-r, r),
+                Vec2::new(r, r),
+                Vec2::new(r, -r),
+            ];
+            let mut segments: Vec<_> = (0..4).map(|i| (corners[i], corners[(i + 1) % 4])).collect();
+            segments.push((corners[0], corners[2]));
+            segments.push((corners[1], corners[3]));
+            segments
+        }
+    };
+    segments
+        .into_iter()
+        .map(|(start, end)| (center + start, center + end))
+        .collect()
+}
+
+fn tint_enemies(
+    accessibility: Res<AccessibilityConfig>,
+    mut enemies: Query<(&EnemyType, &mut Sprite), With<Enemy>>,
+) {
+    for (enemy_type, mut sprite) in enemies.iter_mut() {
+        let color = if accessibility.colorblind_palette {
+            palette_color(enemy_type)
+        } else {
+            Color::WHITE
+        };
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+}
+
+/// Outlines the collider of every enemy and draws its marker over it, each frame.
+fn outline_enemies(
+    accessibility: Res<AccessibilityConfig>,
+    definitions: Res<EnemyDefinitions>,
+    mut lines: ResMut<DebugLines>,
+    enemies: Query<(&EnemyType, &GlobalTransform), With<Enemy>>,
+) {
+    if !accessibility.colorblind_palette {
+        return;
+    }
+    for (enemy_type, transform) in enemies.iter() {
+        let color = palette_color(enemy_type);
+        let half = enemy_type.half_extents(&definitions);
+        let corners = [
+            Vec2::new(-half.x, -half.y),
+            Vec2::new(-half.x, half.y),
+            Vec2::new(half.x, half.y),
+            Vec2::new(half.x, -half.y),
+        ]
+        .map(|corner| transform.transform_point(corner.extend(0.0)));
+        for i in 0..4 {
+            lines.line_colored(corners[i], corners[(i + 1) % 4], 0.0, color);
+        }
+
+        let center = transform.translation();
+        for (start, end) in marker(enemy_type, center.truncate()) {
+            lines.line_colored(start.extend(center.z), end.extend(center.z), 0.0, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enemy_types_differ_by_color_and_shape() {
+        let types = [
+            EnemyType::Simple,
+            EnemyType::Horizontal(3.0),
+            EnemyType::Truck,
+        ];
+        let center = Vec2::new(100.0, 200.0);
+        let markers: Vec<_> = types.iter().map(|t| marker(t, center)).collect();
+        let counts: Vec<_> = markers.iter().map(Vec::len).collect();
+        assert_eq!(counts, vec![4, 5, 6]);
+
+        let r = configs::COLORBLIND_MARKER_SIZE / 2.0;
+        for (start, end) in markers.iter().flatten() {
+            for point in [start, end] {
+                assert!((*point - center).abs().max_element() <= r);
+            }
+        }
+
+        let colors: Vec<_> = types.iter().map(palette_color).collect();
+        assert_ne!(colors[0], colors[1]);
+        assert_ne!(colors[1], colors[2]);
+        assert_ne!(colors[0], colors[2]);
+    }
+}
@@ -0,0 +1,376 @@
+use crate::car::{Car, CarCrashed, Model};
+use crate::configs;
+use crate::enemy::Enemy;
+use crate::finish::RaceFinished;
+use crate::near_miss::NearMiss;
+use crate::photo::InputMode;
+use crate::sensors::{SensorSet, Sensors};
+use bevy::utils::HashMap;
+use bevy::{log, prelude::*};
+use serde::{Deserialize, Serialize};
+use starknet_ff::FieldElement;
+
+/// Whooshes of the enemies passing the watched car and a sting on each of its near misses,
+/// panned to the side they happen on.
+pub struct TrafficAudioPlugin;
+
+impl Plugin for TrafficAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<NearMiss>()
+            .init_resource::<Volumes>()
+            .add_startup_system(load_traffic_sounds)
+            .add_systems((play_passing_whooshes, play_near_miss_stings).after(SensorSet));
+    }
+}
+
+/// Background music: cycles the tracks of `assets/music/menu/` while no car races and those of
+/// `assets/music/race/` during a race, crossfading between them. Crashes of the watched car duck
+/// it and its finishes speed it up for a moment.
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CarCrashed>()
+            .add_event::<RaceFinished>()
+            .init_resource::<Volumes>()
+            .add_startup_system(load_music)
+            .add_systems((react_to_race, play_music).chain().after(SensorSet));
+    }
+}
+
+/// Volume of all sounds and of each category, from 0 to 1, saved in the `audio` section of the
+/// settings.
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct Volumes {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+}
+
+impl Default for Volumes {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            music: 1.0,
+            sfx: 1.0,
+        }
+    }
+}
+
+impl Volumes {
+    /// Scale of the music volume.
+    pub fn music(&self) -> f32 {
+        self.master * self.music
+    }
+
+    /// Scale of the whooshes and stings.
+    pub fn sfx(&self) -> f32 {
+        self.master * self.sfx
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Theme {
+    Menu,
+    Race,
+}
+
+/// Track playing, or fading out, and its sink once the audio output started it.
+struct Track {
+    theme: Theme,
+    index: usize,
+    sink: Handle<AudioSink>,
+}
+
+#[derive(Resource, Default)]
+struct Music {
+    menu: Vec<Handle<AudioSource>>,
+    race: Vec<Handle<AudioSource>>,
+    current: Option<Track>,
+    previous: Option<Track>,
+    mix: MusicMix,
+}
+
+impl Music {
+    fn playlist(&self, theme: Theme) -> &[Handle<AudioSource>] {
+        match theme {
+            Theme::Menu => &self.menu,
+            Theme::Race => &self.race,
+        }
+    }
+}
+
+/// Volume and speed of the music over time.
+#[derive(Default, Debug, PartialEq)]
+struct MusicMix {
+    /// Seconds since the current track started.
+    playing: f32,
+    /// Seconds left of the crash ducking and of the finish rush.
+    duck: f32,
+    rush: f32,
+}
+
+impl MusicMix {
+    fn advance(&mut self, seconds: f32) {
+        self.playing += seconds;
+        self.duck = (self.duck - seconds).max(0.0);
+        self.rush = (self.rush - seconds).max(0.0);
+    }
+
+    /// Volumes of the current track and of the one it crossfades from.
+    fn volumes(&self) -> (f32, f32) {
+        let fade = (self.playing / configs::MUSIC_CROSSFADE_SECONDS).min(1.0);
+        let ducked = self.duck / configs::MUSIC_DUCK_SECONDS;
+        let volume = configs::MUSIC_VOLUME * (1.0 - ducked * (1.0 - configs::MUSIC_DUCK_VOLUME));
+        (volume * fade, volume * (1.0 - fade))
+    }
+
+    fn speed(&self) -> f32 {
+        1.0 + (configs::MUSIC_RUSH_SPEED - 1.0) * self.rush / configs::MUSIC_RUSH_SECONDS
+    }
+}
+
+fn load_music(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let load = |folder: &str| match asset_server.load_folder(folder) {
+        Ok(tracks) => tracks.into_iter().map(|track| track.typed()).collect(),
+        Err(e) => {
+            log::warn!("Load music {folder}: {e}");
+            Vec::new()
+        }
+    };
+    commands.insert_resource(Music {
+        menu: load("music/menu"),
+        race: load("music/race"),
+        ..default()
+    });
+}
+
+fn react_to_race(
+    mut music: ResMut<Music>,
+    sensors: Option<Res<Sensors>>,
+    mut crashes: EventReader<CarCrashed>,
+    mut finishes: EventReader<RaceFinished>,
+    cars: Query<&Model, With<Car>>,
+    // Kept once its run ended, the car leaves the race along with its `Model`
+    mut watched: Local<Option<FieldElement>>,
+) {
+    if crashes.iter().any(|crash| Some(crash.model_id) == *watched) {
+        music.mix.duck = configs::MUSIC_DUCK_SECONDS;
+    }
+    if finishes
+        .iter()
+        .any(|finish| Some(finish.model_id) == *watched)
+    {
+        music.mix.rush = configs::MUSIC_RUSH_SECONDS;
+    }
+    let car = sensors.and_then(|sensors| sensors.car);
+    if let Some(Ok(model)) = car.map(|car| cars.get(car)) {
+        *watched = Some(model.id);
+    }
+}
+
+/// Starts the next track when the theme changes or the track played for
+/// [`configs::MUSIC_TRACK_SECONDS`], and mixes it with the one it replaces.
+fn play_music(
+    time: Res<Time>,
+    audio: Res<Audio>,
+    sinks: Res<Assets<AudioSink>>,
+    volumes: Res<Volumes>,
+    mut music: ResMut<Music>,
+    input_mode: Option<Res<State<InputMode>>>,
+    cars: Query<(), With<Car>>,
+) {
+    let racing = !cars.is_empty() && input_mode.map_or(true, |mode| mode.0 == InputMode::Race);
+    let theme = if racing { Theme::Race } else { Theme::Menu };
+    music.mix.advance(time.delta_seconds());
+
+    let next = match &music.current {
+        Some(track) if track.theme != theme => Some(0),
+        Some(track) if music.mix.playing >= configs::MUSIC_TRACK_SECONDS => Some(track.index + 1),
+        Some(_) => None,
+        None => Some(0),
+    };
+    let playlist = music.playlist(theme);
+    if let Some(index) = next.filter(|_| !playlist.is_empty()) {
+        let index = index % playlist.len();
+        let sink = audio.play_with_settings(playlist[index].clone(), PlaybackSettings::LOOP);
+        let track = Track {
+            theme,
+            index,
+            sink: sinks.get_handle(sink),
+        };
+        if let Some(previous) = music.previous.take() {
+            if let Some(sink) = sinks.get(&previous.sink) {
+                sink.stop();
+            }
+        }
+        music.previous = music.current.replace(track);
+        music.mix.playing = 0.0;
+    }
+
+    let (volume, previous_volume) = music.mix.volumes();
+    let speed = music.mix.speed();
+    for (track, volume) in [(&music.current, volume), (&music.previous, previous_volume)] {
+        if let Some(sink) = track.as_ref().and_then(|track| sinks.get(&track.sink)) {
+            sink.set_volume(volume * volumes.music());
+            sink.set_speed(speed);
+        }
+    }
+}
+
+#[derive(Resource)]
+struct TrafficSounds {
+    whoosh: Handle<AudioSource>,
+    near_miss: Handle<AudioSource>,
+}
+
+fn load_traffic_sounds(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(TrafficSounds {
+        whoosh: asset_server.load("whoosh.wav"),
+        near_miss: asset_server.load("near-miss.wav"),
+    });
+}
+
+/// Where a sound at `position` is heard from, relative to a listener at `car` facing up, in
+/// [`configs::AUDIO_PIXELS_PER_UNIT`].
+fn emitter(car: Vec3, position: Vec3) -> Vec3 {
+    ((position - car) / configs::AUDIO_PIXELS_PER_UNIT)
+        .truncate()
+        .extend(0.0)
+}
+
+/// Whether an enemy went past the car, from how far ahead of it it was last frame and is now.
+fn passed(previous_dy: f32, dy: f32) -> bool {
+    previous_dy >= 0.0 && dy < 0.0
+}
+
+fn play(audio: &Audio, volumes: &Volumes, sound: &Handle<AudioSource>, volume: f32, emitter: Vec3) {
+    audio.play_spatial_with_settings(
+        sound.clone(),
+        PlaybackSettings::ONCE.with_volume(volume * volumes.sfx()),
+        Transform::IDENTITY,
+        configs::AUDIO_EAR_GAP,
+        emitter,
+    );
+}
+
+/// Plays a whoosh each time an enemy within [`configs::AUDIO_PASS_DISTANCE`] sideways goes
+/// past the watched car.
+fn play_passing_whooshes(
+    audio: Res<Audio>,
+    volumes: Res<Volumes>,
+    sounds: Res<TrafficSounds>,
+    sensors: Res<Sensors>,
+    cars: Query<&Transform, With<Car>>,
+    enemies: Query<(Entity, &Transform), With<Enemy>>,
+    mut previous: Local<HashMap<Entity, f32>>,
+) {
+    let Some(Ok(car)) = sensors.car.map(|car| cars.get(car)) else {
+        previous.clear();
+        return;
+    };
+
+    let mut current = HashMap::new();
+    for (enemy, transform) in enemies.iter() {
+        let offset = transform.translation - car.translation;
+        if offset.x.abs() > configs::AUDIO_PASS_DISTANCE {
+            continue;
+        }
+        if previous
+            .get(&enemy)
+            .map_or(false, |previous_dy| passed(*previous_dy, offset.y))
+        {
+            let emitter = emitter(car.translation, transform.translation);
+            play(
+                &audio,
+                &volumes,
+                &sounds.whoosh,
+                configs::AUDIO_WHOOSH_VOLUME,
+                emitter,
+            );
+        }
+        current.insert(enemy, offset.y);
+    }
+    *previous = current;
+}
+
+fn play_near_miss_stings(
+    audio: Res<Audio>,
+    volumes: Res<Volumes>,
+    sounds: Res<TrafficSounds>,
+    sensors: Res<Sensors>,
+    mut near_misses: EventReader<NearMiss>,
+    cars: Query<(&Transform, &Model), With<Car>>,
+    enemies: Query<&Transform, With<Enemy>>,
+) {
+    let watched = sensors.car.and_then(|car| cars.get(car).ok());
+    for near_miss in near_misses.iter() {
+        let Some((car, model)) = watched else {
+            continue;
+        };
+        if model.id != near_miss.model_id {
+            continue;
+        }
+        let Ok(enemy) = enemies.get(near_miss.enemy) else {
+            continue;
+        };
+        let emitter = emitter(car.translation, enemy.translation);
+        play(
+            &audio,
+            &volumes,
+            &sounds.near_miss,
+            configs::AUDIO_STING_VOLUME,
+            emitter,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_passing_enemies_are_heard_from_their_side() {
+        assert!(passed(10.0, -5.0));
+        assert!(passed(0.0, -0.1));
+        assert!(!passed(-5.0, -10.0));
+        assert!(!passed(20.0, 10.0));
+
+        let car = Vec3::new(300.0, 1000.0, 0.0);
+        let left = Vec3::new(300.0 - configs::AUDIO_PIXELS_PER_UNIT, 1000.0, 1.0);
+        assert_eq!(emitter(car, left), Vec3::new(-1.0, 0.0, 0.0));
+        let ahead_right = car + Vec3::new(50.0, 100.0, 0.0);
+        let emitter = emitter(car, ahead_right);
+        assert!(emitter.x > 0.0 && emitter.y > 0.0);
+    }
+
+    #[test]
+    fn test_music_crossfades_and_reacts_to_the_race() {
+        let mut mix = MusicMix::default();
+        assert_eq!(mix.volumes(), (0.0, configs::MUSIC_VOLUME));
+        mix.advance(configs::MUSIC_CROSSFADE_SECONDS / 2.0);
+        let (volume, previous) = mix.volumes();
+        assert!((volume - configs::MUSIC_VOLUME / 2.0).abs() < 1e-6);
+        assert!((previous - configs::MUSIC_VOLUME / 2.0).abs() < 1e-6);
+        mix.advance(configs::MUSIC_CROSSFADE_SECONDS);
+        assert_eq!(mix.volumes(), (configs::MUSIC_VOLUME, 0.0));
+
+        // Ducked right after a crash, back to full volume once it is over
+        mix.duck = configs::MUSIC_DUCK_SECONDS;
+        let ducked = configs::MUSIC_VOLUME * configs::MUSIC_DUCK_VOLUME;
+        assert!((mix.volumes().0 - ducked).abs() < 1e-6);
+        mix.rush = configs::MUSIC_RUSH_SECONDS;
+        assert_eq!(mix.speed(), configs::MUSIC_RUSH_SPEED);
+        mix.advance(configs::MUSIC_DUCK_SECONDS.max(configs::MUSIC_RUSH_SECONDS));
+        assert_eq!(mix.volumes().0, configs::MUSIC_VOLUME);
+        assert_eq!(mix.speed(), 1.0);
+
+        let volumes = Volumes {
+            master: 0.5,
+            music: 0.5,
+            sfx: 0.0,
+        };
+        assert_eq!((volumes.music(), volumes.sfx()), (0.25, 0.0));
+    }
+}
@@ -0,0 +1,633 @@
+use crate::configs;
+use crate::system_call::SystemCall;
+use async_trait::async_trait;
+use bevy::log;
+use bevy::prelude::Resource;
+use eyre::{eyre, Result};
+use starknet::accounts::{Account as _, Call, ConnectedAccount, SingleOwnerAccount};
+use starknet::core::types::{
+    BlockId, BlockTag, FieldElement, FunctionCall, MaybePendingTransactionReceipt,
+    PendingTransactionReceipt, TransactionReceipt,
+};
+use starknet::core::utils::{cairo_short_string_to_felt, get_selector_from_name};
+use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::{JsonRpcClient, Provider};
+use starknet::signers::LocalWallet;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+
+/// The world operations the dojo layer relies on.
+/// Implemented by [`WorldBackend`] for a deployed world and by [`MockBackend`] for tests.
+#[async_trait]
+pub trait DojoBackend: Send + Sync + 'static {
+    /// Executes `system` with `calldata` through the world.
+    async fn execute(&self, system: &str, calldata: Vec<FieldElement>) -> Result<Execution>;
+    /// Executes a typed [`SystemCall`].
+    async fn execute_call(&self, call: SystemCall) -> Result<Execution> {
+        self.execute(call.system, call.calldata).await
+    }
+    /// Reads the values of `component` for the entity identified by `keys`.
+    async fn entity(&self, component: &str, keys: Vec<FieldElement>) -> Result<Vec<FieldElement>>;
+    /// Latest block of the chain.
+    async fn head(&self) -> Result<BlockHead>;
+    /// Reads `component` as of the `head` block.
+    async fn entity_at(
+        &self,
+        component: &str,
+        keys: Vec<FieldElement>,
+        _head: BlockHead,
+    ) -> Result<Vec<FieldElement>> {
+        self.entity(component, keys).await
+    }
+    /// Fee token balance of the account transactions are sent from, in wei.
+    async fn balance(&self) -> Result<u128>;
+    /// Asks a dev network faucet to top up the account, `Ok(false)` when there is none.
+    async fn request_funds(&self) -> Result<bool>;
+    /// Resolves the handle of `component` ahead of the first read.
+    async fn resolve_component(&self, _component: &str) -> Result<()> {
+        Ok(())
+    }
+    /// Resolves the handle of `system` ahead of the first execution.
+    async fn resolve_system(&self, _system: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// What an executed system cost, as reported by its receipt.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Execution {
+    pub fee: FieldElement,
+    /// `None` while the transaction is pending.
+    pub block_number: Option<u64>,
+}
+
+/// Block a state read was made at.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BlockHead {
+    pub number: u64,
+    pub hash: FieldElement,
+}
+
+/// Backend used by the dojo background tasks. Insert it before adding `DojoPlugin` to
+/// replace the default [`WorldBackend`].
+#[derive(Resource, Clone)]
+pub struct Backend(pub Arc<dyn DojoBackend>);
+
+pub type Account = SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet>;
+
+pub struct WorldBackend {
+    /// The block ID to use for all contract calls.
+    block_id: BlockId,
+    /// Address of the world contract, systems and components are called through it by name.
+    world: FieldElement,
+    /// The account to use for performing execution on the World contract.
+    account: Arc<Account>,
+    /// Sizes of the components resolved so far, by name.
+    components: Handles<FieldElement>,
+    /// The ERC20 transaction fees are paid in.
+    fee_token: FieldElement,
+    /// Prefunded Katana account topping up `account`.
+    faucet: Option<Account>,
+}
+
+impl WorldBackend {
+    pub fn new(world_address: FieldElement, account: Account) -> Self {
+        Self {
+            world: world_address,
+            account: Arc::new(account),
+            components: Handles::default(),
+            block_id: BlockId::Tag(BlockTag::Latest),
+            fee_token: FieldElement::from_hex_be(configs::FEE_TOKEN_ADDRESS).unwrap(),
+            faucet: None,
+        }
+    }
+
+    pub fn with_faucet(mut self, faucet: Account) -> Self {
+        self.faucet = Some(faucet);
+        self
+    }
+
+    /// Calls the `entry_point` view of `contract_address`.
+    async fn call(
+        &self,
+        contract_address: FieldElement,
+        entry_point: &str,
+        calldata: Vec<FieldElement>,
+        block_id: BlockId,
+    ) -> Result<Vec<FieldElement>> {
+        let request = FunctionCall {
+            contract_address,
+            entry_point_selector: get_selector_from_name(entry_point)?,
+            calldata,
+        };
+        Ok(self.account.provider().call(request, block_id).await?)
+    }
+
+    /// Class hash the world registered `name` under, from its `kind` view.
+    async fn class_hash(&self, kind: &str, name: &str) -> Result<FieldElement> {
+        let calldata = vec![cairo_short_string_to_felt(name)?];
+        let latest = BlockId::Tag(BlockTag::Latest);
+        match self.call(self.world, kind, calldata, latest).await?[..] {
+            [class_hash] if class_hash != FieldElement::ZERO => Ok(class_hash),
+            _ => Err(eyre!("Unknown {kind} {name}")),
+        }
+    }
+
+    /// Values stored per entity by `component`, its `size` called through the world executor.
+    async fn component_size(&self, component: &str) -> Result<FieldElement> {
+        let class_hash = self.class_hash("component", component).await?;
+        // Resolved at the latest block, the layout of a component doesn't change between reads
+        let latest = BlockId::Tag(BlockTag::Latest);
+        let executor = match self.call(self.world, "executor", vec![], latest).await?[..] {
+            [executor] => executor,
+            ref result => return Err(eyre!("Unexpected executor result {result:?}")),
+        };
+        let calldata = vec![
+            class_hash,
+            get_selector_from_name("size")?,
+            FieldElement::ZERO,
+        ];
+        // Span of the values returned, its length first
+        match self.call(executor, "call", calldata, latest).await?[..] {
+            [_, size] => Ok(size),
+            ref result => Err(eyre!("Unexpected {component} size {result:?}")),
+        }
+    }
+
+    async fn read(
+        &self,
+        component: &str,
+        keys: Vec<FieldElement>,
+        block_id: BlockId,
+    ) -> Result<Vec<FieldElement>> {
+        let size = self
+            .components
+            .get(component, self.component_size(component))
+            .await?;
+        match self.entity_values(component, *size, &keys, block_id).await {
+            Ok(values) => Ok(values),
+            // Reads change nothing so they are safe to send again
+            Err(e) => {
+                log::warn!("Read {component}, resolving it again: {e}");
+                let size = self
+                    .components
+                    .resolve(component, self.component_size(component))
+                    .await?;
+                self.entity_values(component, *size, &keys, block_id).await
+            }
+        }
+    }
+
+    /// The `size` values of `component` for the entity of `keys`.
+    async fn entity_values(
+        &self,
+        component: &str,
+        size: FieldElement,
+        keys: &[FieldElement],
+        block_id: BlockId,
+    ) -> Result<Vec<FieldElement>> {
+        // Component, entity query (partition and keys), then offset and length of the values
+        let mut calldata = vec![
+            cairo_short_string_to_felt(component)?,
+            FieldElement::ZERO,
+            FieldElement::from(keys.len()),
+        ];
+        calldata.extend_from_slice(keys);
+        calldata.extend([FieldElement::ZERO, size]);
+        let values = self.call(self.world, "entity", calldata, block_id).await?;
+        // Span of the values, its length first
+        Ok(values.into_iter().skip(1).collect())
+    }
+
+    /// What the transaction cost, once the node indexed its receipt. The transaction was
+    /// accepted already, so a receipt still missing after [`configs::DOJO_RECEIPT_TIMEOUT`] is
+    /// reported as pending rather than as a failure.
+    async fn execution(&self, transaction_hash: FieldElement) -> Execution {
+        let interval = std::time::Duration::from_secs_f32(configs::DOJO_RECEIPT_POLL_INTERVAL);
+        let timeout = std::time::Duration::from_secs_f32(configs::DOJO_RECEIPT_TIMEOUT);
+        let provider = self.account.provider();
+        let poll = async {
+            loop {
+                match provider.get_transaction_receipt(transaction_hash).await {
+                    Ok(receipt) => return receipt,
+                    // Not indexed yet
+                    Err(_) => tokio::time::sleep(interval).await,
+                }
+            }
+        };
+        match tokio::time::timeout(timeout, poll).await {
+            Ok(MaybePendingTransactionReceipt::Receipt(TransactionReceipt::Invoke(receipt))) => {
+                Execution {
+                    fee: receipt.actual_fee,
+                    block_number: Some(receipt.block_number),
+                }
+            }
+            Ok(MaybePendingTransactionReceipt::PendingReceipt(
+                PendingTransactionReceipt::Invoke(receipt),
+            )) => Execution {
+                fee: receipt.actual_fee,
+                block_number: None,
+            },
+            Ok(_) => Execution::default(),
+            Err(_) => {
+                log::warn!(
+                    "No receipt for {transaction_hash:#x} within {}s",
+                    configs::DOJO_RECEIPT_TIMEOUT
+                );
+                Execution::default()
+            }
+        }
+    }
+}
+
+/// World handles resolved once and kept until a read through them fails, e.g. once the world
+/// was redeployed.
+struct Handles<T>(Mutex<HashMap<String, Arc<T>>>);
+
+impl<T> Default for Handles<T> {
+    fn default() -> Self {
+        Self(Mutex::new(HashMap::new()))
+    }
+}
+
+impl<T> Handles<T> {
+    /// The handle of `name`, resolved by `resolve` the first time.
+    async fn get<E>(
+        &self,
+        name: &str,
+        resolve: impl Future<Output = std::result::Result<T, E>>,
+    ) -> std::result::Result<Arc<T>, E> {
+        let cached = self.0.lock().unwrap().get(name).cloned();
+        if let Some(handle) = cached {
+            return Ok(handle);
+        }
+        self.resolve(name, resolve).await
+    }
+
+    /// Replaces the handle of `name`, dropping it when `resolve` fails.
+    async fn resolve<E>(
+        &self,
+        name: &str,
+        resolve: impl Future<Output = std::result::Result<T, E>>,
+    ) -> std::result::Result<Arc<T>, E> {
+        self.0.lock().unwrap().remove(name);
+        let handle = Arc::new(resolve.await?);
+        self.0
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), handle.clone());
+        Ok(handle)
+    }
+}
+
+#[async_trait]
+impl DojoBackend for WorldBackend {
+    async fn execute(&self, system: &str, calldata: Vec<FieldElement>) -> Result<Execution> {
+        // Sent once: the world resolves the system by name, so a redeployed system needs no
+        // handle resolved again, and a failed send may still have been accepted
+        let mut execute_calldata = vec![
+            cairo_short_string_to_felt(system)?,
+            FieldElement::from(calldata.len()),
+        ];
+        execute_calldata.extend(calldata);
+        let transaction = self
+            .account
+            .execute(vec![Call {
+                to: self.world,
+                selector: get_selector_from_name("execute")?,
+                calldata: execute_calldata,
+            }])
+            .send()
+            .await?;
+
+        Ok(self.execution(transaction.transaction_hash).await)
+    }
+
+    async fn entity(&self, component: &str, keys: Vec<FieldElement>) -> Result<Vec<FieldElement>> {
+        self.read(component, keys, self.block_id).await
+    }
+
+    async fn head(&self) -> Result<BlockHead> {
+        let head = self.account.provider().block_hash_and_number().await?;
+        Ok(BlockHead {
+            number: head.block_number,
+            hash: head.block_hash,
+        })
+    }
+
+    async fn entity_at(
+        &self,
+        component: &str,
+        keys: Vec<FieldElement>,
+        head: BlockHead,
+    ) -> Result<Vec<FieldElement>> {
+        // Fails once the block is no longer part of the chain
+        self.read(component, keys, BlockId::Hash(head.hash)).await
+    }
+
+    async fn balance(&self) -> Result<u128> {
+        let request = FunctionCall {
+            contract_address: self.fee_token,
+            entry_point_selector: get_selector_from_name("balanceOf")?,
+            calldata: vec![self.account.address()],
+        };
+        let balance = self.account.provider().call(request, self.block_id).await?;
+        // Uint256 as its low and high words
+        match balance.as_slice() {
+            [low, high] if *high == FieldElement::ZERO => {
+                u128::try_from(*low).map_err(|_| eyre!("Invalid balance {low}"))
+            }
+            [_, _] => Ok(u128::MAX),
+            _ => Err(eyre!("Unexpected balanceOf result {balance:?}")),
+        }
+    }
+
+    async fn request_funds(&self) -> Result<bool> {
+        let Some(faucet) = &self.faucet else {
+            return Ok(false);
+        };
+        // Only dev networks hand out fees
+        if self.account.provider().chain_id().await? != cairo_short_string_to_felt("KATANA")? {
+            return Ok(false);
+        }
+
+        faucet
+            .execute(vec![Call {
+                to: self.fee_token,
+                selector: get_selector_from_name("transfer")?,
+                calldata: vec![
+                    self.account.address(),
+                    configs::DOJO_FAUCET_AMOUNT.into(),
+                    FieldElement::ZERO,
+                ],
+            }])
+            .send()
+            .await?;
+        Ok(true)
+    }
+
+    async fn resolve_component(&self, component: &str) -> Result<()> {
+        self.components
+            .get(component, self.component_size(component))
+            .await?;
+        Ok(())
+    }
+
+    async fn resolve_system(&self, system: &str) -> Result<()> {
+        self.class_hash("system", system).await?;
+        Ok(())
+    }
+}
+
+/// Sends the calls to the backend of one of several nodes, failing over to the next one after
+/// [`configs::DOJO_FAILOVER_FAILURES`] failed calls in a row. A call not answered within
+/// [`configs::DOJO_CALL_TIMEOUT`] fails, so a hung node can't stall the queue of a task.
+pub struct FailoverBackend {
+    endpoints: Vec<(String, Arc<dyn DojoBackend>)>,
+    /// Index of the endpoint in use, and its failures in a row.
+    active: Mutex<(usize, u32)>,
+    status: watch::Sender<String>,
+}
+
+impl FailoverBackend {
+    /// Backends by endpoint, in the order they are failed over to.
+    pub fn new(endpoints: Vec<(String, Arc<dyn DojoBackend>)>) -> Self {
+        assert!(!endpoints.is_empty(), "No endpoint to send dojo calls to");
+        let (status, _) = watch::channel(endpoints[0].0.clone());
+        Self {
+            endpoints,
+            active: Mutex::new((0, 0)),
+            status,
+        }
+    }
+
+    /// Endpoint the calls are sent to, changed on each failover.
+    pub fn subscribe(&self) -> watch::Receiver<String> {
+        self.status.subscribe()
+    }
+
+    async fn call<'a, T, F>(
+        &'a self,
+        call: impl FnOnce(&'a dyn DojoBackend) -> F + Send,
+    ) -> Result<T>
+    where
+        F: Future<Output = Result<T>> + Send + 'a,
+    {
+        let index = self.active.lock().unwrap().0;
+        let timeout = std::time::Duration::from_secs_f32(configs::DOJO_CALL_TIMEOUT);
+        let pending = call(self.endpoints[index].1.as_ref());
+        let result = tokio::time::timeout(timeout, pending)
+            .await
+            .unwrap_or_else(|_| Err(eyre!("No answer within {}s", configs::DOJO_CALL_TIMEOUT)));
+
+        let mut active = self.active.lock().unwrap();
+        // Another call may have failed over meanwhile
+        if active.0 != index {
+            return result;
+        }
+        if result.is_ok() {
+            active.1 = 0;
+            return result;
+        }
+        active.1 += 1;
+        if active.1 >= configs::DOJO_FAILOVER_FAILURES && self.endpoints.len() > 1 {
+            *active = ((index + 1) % self.endpoints.len(), 0);
+            let endpoint = &self.endpoints[active.0].0;
+            log::warn!(
+                "Failing over from {} to {endpoint}",
+                self.endpoints[index].0
+            );
+            self.status.send_replace(endpoint.clone());
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl DojoBackend for FailoverBackend {
+    async fn execute(&self, system: &str, calldata: Vec<FieldElement>) -> Result<Execution> {
+        self.call(|backend| backend.execute(system, calldata)).await
+    }
+
+    async fn entity(&self, component: &str, keys: Vec<FieldElement>) -> Result<Vec<FieldElement>> {
+        self.call(|backend| backend.entity(component, keys)).await
+    }
+
+    async fn head(&self) -> Result<BlockHead> {
+        self.call(|backend| backend.head()).await
+    }
+
+    async fn entity_at(
+        &self,
+        component: &str,
+        keys: Vec<FieldElement>,
+        head: BlockHead,
+    ) -> Result<Vec<FieldElement>> {
+        self.call(|backend| backend.entity_at(component, keys, head))
+            .await
+    }
+
+    async fn balance(&self) -> Result<u128> {
+        self.call(|backend| backend.balance()).await
+    }
+
+    async fn request_funds(&self) -> Result<bool> {
+        self.call(|backend| backend.request_funds()).await
+    }
+
+    async fn resolve_component(&self, component: &str) -> Result<()> {
+        self.call(|backend| backend.resolve_component(component))
+            .await
+    }
+
+    async fn resolve_system(&self, system: &str) -> Result<()> {
+        self.call(|backend| backend.resolve_system(system)).await
+    }
+}
+
+/// In-memory world: entities are set by the caller and executions are recorded.
+#[derive(Default)]
+pub struct MockBackend {
+    entities: Mutex<Vec<(String, Vec<FieldElement>, Vec<FieldElement>)>>,
+    executions: Mutex<Vec<(String, Vec<FieldElement>)>>,
+    /// `None` for an account that can always pay.
+    balance: Mutex<Option<u128>>,
+    head: Mutex<BlockHead>,
+}
+
+impl MockBackend {
+    pub fn set_entity(&self, component: &str, keys: Vec<FieldElement>, values: Vec<FieldElement>) {
+        let mut entities = self.entities.lock().unwrap();
+        entities.retain(|(c, k, _)| c != component || *k != keys);
+        entities.push((component.to_string(), keys, values));
+    }
+
+    /// Block the following reads are made at.
+    pub fn set_head(&self, head: BlockHead) {
+        *self.head.lock().unwrap() = head;
+    }
+
+    pub fn set_balance(&self, balance: u128) {
+        *self.balance.lock().unwrap() = Some(balance);
+    }
+
+    /// Systems executed so far with their calldata, in order.
+    pub fn executions(&self) -> Vec<(String, Vec<FieldElement>)> {
+        self.executions.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl DojoBackend for MockBackend {
+    async fn execute(&self, system: &str, calldata: Vec<FieldElement>) -> Result<Execution> {
+        self.executions
+            .lock()
+            .unwrap()
+            .push((system.to_string(), calldata));
+        Ok(Execution::default())
+    }
+
+    async fn entity(&self, component: &str, keys: Vec<FieldElement>) -> Result<Vec<FieldElement>> {
+        self.entities
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(c, k, _)| c == component && *k == keys)
+            .map(|(_, _, values)| values.clone())
+            .ok_or_else(|| eyre!("No `{component}` entity for keys {keys:?}"))
+    }
+
+    async fn head(&self) -> Result<BlockHead> {
+        Ok(*self.head.lock().unwrap())
+    }
+
+    async fn balance(&self) -> Result<u128> {
+        Ok(self.balance.lock().unwrap().unwrap_or(u128::MAX))
+    }
+
+    async fn request_funds(&self) -> Result<bool> {
+        Ok(false)
+    }
+}
+
+/// Backend whose every call fails, a node that went down.
+#[cfg(test)]
+struct DownBackend;
+
+#[cfg(test)]
+#[async_trait]
+impl DojoBackend for DownBackend {
+    async fn execute(&self, _system: &str, _calldata: Vec<FieldElement>) -> Result<Execution> {
+        Err(eyre!("Down"))
+    }
+
+    async fn entity(
+        &self,
+        _component: &str,
+        _keys: Vec<FieldElement>,
+    ) -> Result<Vec<FieldElement>> {
+        Err(eyre!("Down"))
+    }
+
+    async fn head(&self) -> Result<BlockHead> {
+        Err(eyre!("Down"))
+    }
+
+    async fn balance(&self) -> Result<u128> {
+        Err(eyre!("Down"))
+    }
+
+    async fn request_funds(&self) -> Result<bool> {
+        Err(eyre!("Down"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handles_are_resolved_once_until_resolved_again() {
+        let handles = Handles::<u32>::default();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let first = handles.get("drive", async { Ok::<_, ()>(1) }).await;
+            let cached = handles.get("drive", async { Ok::<_, ()>(2) }).await;
+            assert_eq!((*first.unwrap(), *cached.unwrap()), (1, 1));
+
+            let resolved = handles.resolve("drive", async { Ok::<_, ()>(3) }).await;
+            assert_eq!(*resolved.unwrap(), 3);
+
+            // Dropped when resolving fails, so the next call resolves it again
+            assert!(handles.resolve("drive", async { Err(()) }).await.is_err());
+            let next = handles.get("drive", async { Ok::<_, ()>(4) }).await;
+            assert_eq!(*next.unwrap(), 4);
+        });
+    }
+
+    #[test]
+    fn test_calls_fail_over_to_the_next_endpoint() {
+        let mock = Arc::new(MockBackend::default());
+        let backend = FailoverBackend::new(vec![
+            ("down".to_string(), Arc::new(DownBackend)),
+            ("up".to_string(), mock.clone()),
+        ]);
+        let status = backend.subscribe();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            for _ in 0..configs::DOJO_FAILOVER_FAILURES {
+                assert!(backend.execute("drive", vec![]).await.is_err());
+            }
+            assert_eq!(*status.borrow(), "up");
+            backend.execute("drive", vec![]).await.unwrap();
+        });
+        assert_eq!(mock.executions(), vec![("drive".to_string(), vec![])]);
+    }
+}
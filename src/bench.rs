@@ -0,0 +1,70 @@
+use crate::car::UpdateCar;
+use crate::configs;
+use crate::enemy::UpdateEnemies;
+use crate::metrics::Metrics;
+use bevy::app::AppExit;
+use bevy::log;
+use bevy::prelude::*;
+
+/// Runtime counterpart of the criterion benches: counts sync events reaching the ECS for
+/// [`configs::BENCH_SYNC_DURATION`] seconds, logs the throughput and exits.
+pub struct BenchSyncPlugin;
+
+impl Plugin for BenchSyncPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BenchSyncStats>()
+            .add_system(bench_sync_system);
+    }
+}
+
+#[derive(Resource, Default)]
+struct BenchSyncStats {
+    elapsed: f32,
+    frames: u32,
+    car_updates: u32,
+    enemy_updates: u32,
+}
+
+fn bench_sync_system(
+    time: Res<Time>,
+    metrics: Res<Metrics>,
+    mut stats: ResMut<BenchSyncStats>,
+    mut update_car: EventReader<UpdateCar>,
+    mut update_enemies: EventReader<UpdateEnemies>,
+    mut exit: EventWriter<AppExit>,
+) {
+    stats.elapsed += time.delta_seconds();
+    stats.frames += 1;
+    stats.car_updates += update_car.iter().count() as u32;
+    stats.enemy_updates += update_enemies.iter().map(|e| e.0.len() as u32).sum::<u32>();
+
+    if stats.elapsed < configs::BENCH_SYNC_DURATION {
+        return;
+    }
+
+    let per_second = |count: u32| count as f32 / stats.elapsed;
+    let mean_latency = |call: &str| {
+        let histogram = metrics.sync_latency.with_label_values(&[call]);
+        match histogram.get_sample_count() {
+            0 => 0.0,
+            count => histogram.get_sample_sum() / count as f64,
+        }
+    };
+
+    log::info!(
+        "Sync bench over {:.1}s: {:.1} fps, {:.1} vehicle updates/s, {:.1} enemy updates/s, {} drive calls",
+        stats.elapsed,
+        per_second(stats.frames),
+        per_second(stats.car_updates),
+        per_second(stats.enemy_updates),
+        metrics.drive_calls.get(),
+    );
+    log::info!(
+        "Mean latency: drive {:.3}s, vehicle {:.3}s, enemy {:.3}s",
+        mean_latency("drive"),
+        mean_latency("vehicle"),
+        mean_latency("enemy"),
+    );
+
+    exit.send(AppExit);
+}
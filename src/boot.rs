@@ -0,0 +1,258 @@
+use crate::configs;
+use crate::locale::Locale;
+use bevy::prelude::*;
+use bevy_inspector_egui::{bevy_egui::EguiContexts, egui};
+
+/// Covers the window with the logo and the startup steps registered in [`BootProgress`] until
+/// they are all done, the race being paused meanwhile. A step that hangs stays visible instead
+/// of leaving a black window, one that gives up switches to an error screen offering to
+/// [`RetryBoot`].
+pub struct BootScreenPlugin;
+
+impl Plugin for BootScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<BootState>()
+            .init_resource::<BootProgress>()
+            .init_resource::<Locale>()
+            .add_event::<RetryBoot>()
+            .add_startup_system(load_logo)
+            .add_system(pause_race.in_schedule(OnEnter(BootState::Booting)))
+            .add_system(resume_race.in_schedule(OnEnter(BootState::Ready)))
+            .add_systems(
+                (advance_boot, boot_screen_system)
+                    .chain()
+                    .in_set(OnUpdate(BootState::Booting)),
+            )
+            .add_system(boot_error_screen_system.in_set(OnUpdate(BootState::Failed)));
+    }
+}
+
+#[derive(States, Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+pub enum BootState {
+    #[default]
+    Booting,
+    /// A step gave up, the race stays paused until it is retried.
+    Failed,
+    Ready,
+}
+
+/// The failed steps are tried again.
+pub struct RetryBoot;
+
+/// Startup steps, in the order they are shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BootStep {
+    ParseConfig,
+    ConnectRpc,
+    ResolveWorld,
+    FetchSystems,
+}
+
+impl BootStep {
+    fn text_key(self) -> &'static str {
+        match self {
+            BootStep::ParseConfig => "boot.parse_config",
+            BootStep::ConnectRpc => "boot.connect_rpc",
+            BootStep::ResolveWorld => "boot.resolve_world",
+            BootStep::FetchSystems => "boot.fetch_systems",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum StepStatus {
+    #[default]
+    Pending,
+    Running,
+    Done,
+    /// The last attempt failed, the step is retried.
+    Retrying(String),
+    /// The step gave up until a [`RetryBoot`].
+    Failed(String),
+}
+
+/// Startup steps registered by the plugins, booting is over once they are all done.
+#[derive(Resource, Default, Debug)]
+pub struct BootProgress {
+    steps: Vec<(BootStep, StepStatus)>,
+    /// Shown on the error screen, by the key of their label.
+    details: Vec<(&'static str, String)>,
+}
+
+impl BootProgress {
+    /// Adds `step` as pending, unless it was registered already.
+    pub fn register(&mut self, step: BootStep) {
+        if self.status(step).is_none() {
+            self.steps.push((step, StepStatus::Pending));
+        }
+    }
+
+    /// Sets the status of `step`, registering it if needed.
+    pub fn set(&mut self, step: BootStep, status: StepStatus) {
+        self.register(step);
+        if let Some((_, current)) = self.steps.iter_mut().find(|(s, _)| *s == step) {
+            *current = status;
+        }
+    }
+
+    pub fn status(&self, step: BootStep) -> Option<&StepStatus> {
+        self.steps
+            .iter()
+            .find(|(s, _)| *s == step)
+            .map(|(_, status)| status)
+    }
+
+    /// Sets what the error screen shows as `label`, e.g. the node the steps talk to.
+    pub fn set_detail(&mut self, label: &'static str, value: String) {
+        match self.details.iter_mut().find(|(l, _)| *l == label) {
+            Some((_, current)) => *current = value,
+            None => self.details.push((label, value)),
+        }
+    }
+
+    pub fn has_failed(&self) -> bool {
+        self.steps
+            .iter()
+            .any(|(_, status)| matches!(status, StepStatus::Failed(_)))
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.steps
+            .iter()
+            .all(|(_, status)| *status == StepStatus::Done)
+    }
+}
+
+#[derive(Resource)]
+struct BootLogo(Handle<Image>);
+
+fn load_logo(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(BootLogo(asset_server.load(configs::BOOT_LOGO_PATH)));
+}
+
+fn pause_race(mut time: ResMut<Time>) {
+    time.pause();
+}
+
+fn resume_race(mut time: ResMut<Time>) {
+    time.unpause();
+}
+
+fn advance_boot(progress: Res<BootProgress>, mut next_state: ResMut<NextState<BootState>>) {
+    if progress.has_failed() {
+        next_state.set(BootState::Failed);
+    } else if progress.is_done() {
+        next_state.set(BootState::Ready);
+    }
+}
+
+fn boot_screen_system(
+    mut contexts: EguiContexts,
+    locale: Res<Locale>,
+    progress: Res<BootProgress>,
+    logo: Option<Res<BootLogo>>,
+    time: Res<Time>,
+    mut next_state: ResMut<NextState<BootState>>,
+) {
+    let logo = logo.map(|logo| contexts.add_image(logo.0.clone_weak()));
+    egui::CentralPanel::default().show(contexts.ctx_mut(), |ui| {
+        ui.vertical_centered(|ui| {
+            ui.add_space(ui.available_height() / 4.0);
+            if let Some(logo) = logo {
+                ui.image(
+                    logo,
+                    egui::vec2(configs::BOOT_LOGO_SIZE, configs::BOOT_LOGO_SIZE),
+                );
+            }
+            ui.add_space(20.0);
+            for (step, status) in &progress.steps {
+                let text = locale.text(step.text_key());
+                let (text, color) = match status {
+                    StepStatus::Pending => (text.to_string(), egui::Color32::DARK_GRAY),
+                    StepStatus::Running => (format!("{text}…"), egui::Color32::YELLOW),
+                    StepStatus::Done => (format!("{text} ✔"), egui::Color32::GREEN),
+                    StepStatus::Retrying(e) => (format!("{text}: {e}"), egui::Color32::GOLD),
+                    StepStatus::Failed(e) => (format!("{text}: {e}"), egui::Color32::RED),
+                };
+                ui.colored_label(color, text);
+            }
+            ui.add_space(20.0);
+            ui.label(locale.format(
+                "boot.elapsed",
+                &[("seconds", &format!("{:.0}", time.raw_elapsed_seconds()))],
+            ));
+            if ui.button(locale.text("boot.skip")).clicked() {
+                next_state.set(BootState::Ready);
+            }
+        });
+    });
+}
+
+fn boot_error_screen_system(
+    mut contexts: EguiContexts,
+    locale: Res<Locale>,
+    mut progress: ResMut<BootProgress>,
+    mut next_state: ResMut<NextState<BootState>>,
+    mut retries: EventWriter<RetryBoot>,
+) {
+    let mut retry = false;
+    egui::CentralPanel::default().show(contexts.ctx_mut(), |ui| {
+        ui.vertical_centered(|ui| {
+            ui.add_space(ui.available_height() / 4.0);
+            ui.heading(locale.text("boot.failed"));
+            ui.add_space(20.0);
+            for (step, status) in &progress.steps {
+                if let StepStatus::Failed(e) = status {
+                    let text = locale.text(step.text_key());
+                    ui.colored_label(egui::Color32::RED, format!("{text}: {e}"));
+                }
+            }
+            for (label, value) in &progress.details {
+                ui.label(format!("{}: {value}", locale.text(label)));
+            }
+            ui.add_space(20.0);
+            retry = ui.button(locale.text("boot.retry")).clicked();
+        });
+    });
+
+    if retry {
+        for (_, status) in &mut progress.steps {
+            if matches!(status, StepStatus::Failed(_)) {
+                *status = StepStatus::Running;
+            }
+        }
+        retries.send(RetryBoot);
+        next_state.set(BootState::Booting);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boot_is_over_once_every_registered_step_is_done() {
+        let mut progress = BootProgress::default();
+        assert!(progress.is_done());
+
+        progress.register(BootStep::ParseConfig);
+        progress.register(BootStep::ConnectRpc);
+        progress.set(BootStep::ParseConfig, StepStatus::Done);
+        assert!(!progress.is_done());
+
+        progress.set(BootStep::ConnectRpc, StepStatus::Retrying("timeout".into()));
+        progress.register(BootStep::ConnectRpc);
+        assert_eq!(
+            progress.status(BootStep::ConnectRpc),
+            Some(&StepStatus::Retrying("timeout".into()))
+        );
+        assert!(!progress.has_failed());
+        progress.set(BootStep::ConnectRpc, StepStatus::Failed("timeout".into()));
+        assert!(progress.has_failed());
+        progress.set(BootStep::ConnectRpc, StepStatus::Done);
+        assert!(progress.is_done());
+
+        let order: Vec<_> = progress.steps.iter().map(|(step, _)| *step).collect();
+        assert_eq!(order, [BootStep::ParseConfig, BootStep::ConnectRpc]);
+    }
+}
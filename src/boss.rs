@@ -0,0 +1,254 @@
+use crate::car::Car;
+use crate::configs;
+use crate::enemy::{enemy_bundle, EnemyDefinitions, EnemyType, Lane, Lanes};
+use crate::locale::Locale;
+use crate::population::{GenerationSet, RespawnPopulation};
+use crate::resources::PhysicsConfig;
+use bevy::log;
+use bevy::prelude::*;
+use bevy_inspector_egui::{bevy_egui::EguiContexts, egui};
+use std::f32::consts::TAU;
+
+/// Every [`configs::BOSS_MILESTONE`] pixels the leading car drives, warns of and then spawns an
+/// articulated truck ahead of it: its cab sweeps the lanes and its trailers follow in its wake,
+/// each with its own collider.
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BossEncounter>()
+            .init_resource::<Lanes>()
+            .init_resource::<PhysicsConfig>()
+            .init_resource::<EnemyDefinitions>()
+            .add_event::<RespawnPopulation>()
+            .add_systems(
+                (reset_boss, run_boss_encounter, sweep_boss)
+                    .chain()
+                    .after(GenerationSet),
+            );
+    }
+}
+
+/// Warning banner shown before the boss comes in.
+pub struct BossHudPlugin;
+
+impl Plugin for BossHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Locale>()
+            .add_system(boss_warning_system);
+    }
+}
+
+#[derive(Resource, Debug, PartialEq)]
+pub enum BossEncounter {
+    /// Waiting for the leading car to reach `milestone`.
+    Idle { milestone: f32 },
+    /// Banner up, the boss spawns once `left` seconds are over.
+    Warning { milestone: f32, left: f32 },
+    /// Sweeping the road up from `y`, spawned `elapsed` seconds ago.
+    Active {
+        milestone: f32,
+        y: f32,
+        elapsed: f32,
+    },
+}
+
+impl Default for BossEncounter {
+    fn default() -> Self {
+        Self::Idle {
+            milestone: configs::BOSS_MILESTONE,
+        }
+    }
+}
+
+/// Cab, at index 0, or trailer of the boss.
+#[derive(Component)]
+pub struct BossSegment(pub usize);
+
+/// Position and heading of the segment at `index`, `elapsed` seconds after the boss spawned at
+/// `y`. Each segment sweeps like the cab did [`configs::BOSS_SWEEP_LAG`] seconds before it.
+fn segment_pose(lanes: &Lanes, y: f32, elapsed: f32, index: usize) -> (Vec2, f32) {
+    let center = (lanes.x_min + lanes.x_max) / 2.0;
+    let amplitude = (lanes.x_max - lanes.x_min) / 2.0 - lanes.width();
+    let frequency = TAU / configs::BOSS_SWEEP_PERIOD;
+    let t = elapsed - index as f32 * configs::BOSS_SWEEP_LAG;
+    let x = center + amplitude * (frequency * t).sin();
+    let segment_y =
+        y + configs::BOSS_SPEED * elapsed - index as f32 * configs::BOSS_SEGMENT_SPACING;
+    // Pointing up the road, turned towards where it sweeps
+    let x_speed = amplitude * frequency * (frequency * t).cos();
+    let angle = -x_speed.atan2(configs::BOSS_SPEED);
+    (Vec2::new(x, segment_y), angle)
+}
+
+/// Whether a boss warned of once the leading car reached `lead_y` comes in before the finish
+/// line, the car driving on at [`configs::STANDALONE_CAR_SPEED`] during the warning.
+fn before_finish(lead_y: f32) -> bool {
+    let spawn_y = lead_y
+        + configs::BOSS_WARNING_SECONDS * configs::STANDALONE_CAR_SPEED
+        + configs::BOSS_SPAWN_AHEAD;
+    spawn_y < configs::FINISH_LINE_Y
+}
+
+/// The segments are enemies, cleared with the others between generations.
+fn reset_boss(mut respawn: EventReader<RespawnPopulation>, mut encounter: ResMut<BossEncounter>) {
+    if respawn.iter().count() > 0 {
+        *encounter = BossEncounter::default();
+    }
+}
+
+fn run_boss_encounter(
+    mut commands: Commands,
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    lanes: Res<Lanes>,
+    physics: Res<PhysicsConfig>,
+    definitions: Res<EnemyDefinitions>,
+    mut encounter: ResMut<BossEncounter>,
+    cars: Query<&Transform, With<Car>>,
+    segments: Query<(Entity, &Transform), With<BossSegment>>,
+) {
+    let Some(lead_y) = cars
+        .iter()
+        .map(|transform| transform.translation.y)
+        .reduce(f32::max)
+    else {
+        return;
+    };
+
+    match *encounter {
+        BossEncounter::Idle { milestone } if lead_y >= milestone && before_finish(lead_y) => {
+            log::info!("Boss ahead at y: {lead_y}");
+            *encounter = BossEncounter::Warning {
+                milestone,
+                left: configs::BOSS_WARNING_SECONDS,
+            };
+        }
+        BossEncounter::Idle { .. } => {}
+        BossEncounter::Warning { milestone, left } => {
+            let left = left - time.delta_seconds();
+            if left > 0.0 {
+                *encounter = BossEncounter::Warning { milestone, left };
+                return;
+            }
+            let y = lead_y + configs::BOSS_SPAWN_AHEAD;
+            for index in 0..configs::BOSS_SEGMENTS {
+                let (position, _) = segment_pose(&lanes, y, 0.0, index);
+                commands.spawn((
+                    enemy_bundle(
+                        EnemyType::Truck,
+                        position.extend(0.0),
+                        Vec2::ZERO,
+                        lanes.lane_at(position.x),
+                        &physics,
+                        &definitions,
+                        &asset_server,
+                    ),
+                    BossSegment(index),
+                ));
+            }
+            *encounter = BossEncounter::Active {
+                milestone,
+                y,
+                elapsed: 0.0,
+            };
+        }
+        BossEncounter::Active {
+            milestone,
+            y,
+            elapsed,
+        } => {
+            // Over once every segment was hit or left behind
+            let behind = lead_y - configs::WINDOW_HEIGHT;
+            let passed = segments
+                .iter()
+                .all(|(_, transform)| transform.translation.y < behind);
+            if passed {
+                for (segment, _) in segments.iter() {
+                    commands.entity(segment).despawn_recursive();
+                }
+                *encounter = BossEncounter::Idle {
+                    milestone: milestone + configs::BOSS_MILESTONE,
+                };
+            } else {
+                *encounter = BossEncounter::Active {
+                    milestone,
+                    y,
+                    elapsed: elapsed + time.delta_seconds(),
+                };
+            }
+        }
+    }
+}
+
+fn sweep_boss(
+    encounter: Res<BossEncounter>,
+    lanes: Res<Lanes>,
+    mut segments: Query<(&BossSegment, &mut Transform, &mut Lane)>,
+) {
+    let BossEncounter::Active { y, elapsed, .. } = *encounter else {
+        return;
+    };
+    for (segment, mut transform, mut lane) in segments.iter_mut() {
+        let (position, angle) = segment_pose(&lanes, y, elapsed, segment.0);
+        transform.translation = position.extend(0.0);
+        transform.rotation = Quat::from_rotation_z(angle);
+        transform.scale = Vec3::new(configs::BOSS_SCALE, configs::BOSS_SCALE, 1.0);
+        *lane = lanes.lane_at(position.x);
+    }
+}
+
+fn boss_warning_system(
+    mut contexts: EguiContexts,
+    locale: Res<Locale>,
+    encounter: Option<Res<BossEncounter>>,
+) {
+    let Some(BossEncounter::Warning { left, .. }) = encounter.as_deref() else {
+        return;
+    };
+    // Blinks twice a second
+    if left.fract() < 0.25 || (0.5..0.75).contains(&left.fract()) {
+        return;
+    }
+    egui::Area::new("boss_warning")
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 120.0))
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(
+                egui::RichText::new(locale.text("hud.boss_warning"))
+                    .size(40.0)
+                    .strong()
+                    .color(egui::Color32::RED),
+            );
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trailers_follow_the_cab_on_the_road() {
+        let lanes = Lanes::default();
+        for step in 0..100 {
+            let elapsed = step as f32 * 0.1;
+            let poses: Vec<_> = (0..configs::BOSS_SEGMENTS)
+                .map(|index| segment_pose(&lanes, 1000.0, elapsed, index).0)
+                .collect();
+            for (index, position) in poses.iter().enumerate() {
+                assert!(position.x > lanes.x_min && position.x < lanes.x_max);
+                if index > 0 {
+                    assert!(position.y < poses[index - 1].y);
+                }
+            }
+        }
+
+        // A trailer goes where the cab was
+        let cab = segment_pose(&lanes, 0.0, 1.0, 0).0;
+        let trailer = segment_pose(&lanes, 0.0, 1.0 + configs::BOSS_SWEEP_LAG, 1).0;
+        assert!((cab.x - trailer.x).abs() < 1e-3);
+
+        // The first milestone leaves room for the boss before the finish line, not the next
+        assert!(before_finish(configs::BOSS_MILESTONE));
+        assert!(!before_finish(configs::BOSS_MILESTONE * 2.0));
+    }
+}
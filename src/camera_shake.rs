@@ -0,0 +1,140 @@
+use crate::accessibility::AccessibilityConfig;
+use crate::car::{Car, Model};
+use crate::configs;
+use crate::enemy::EnemyMotion;
+use crate::resources::{Racers, RunStats, Settings};
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+use bevy_pancam::PanCam;
+use bevy_rapier2d::prelude::*;
+use bevy_rapier2d::rapier::geometry::CollisionEventFlags;
+use rand::Rng;
+
+/// Shakes the main camera when the player car crashes, harder the faster the impact. Each crash
+/// adds trauma that decays over time, the shake growing with its square. Toggled with
+/// `Settings::is_camera_shake`, always off under [`AccessibilityConfig::reduced_motion`].
+pub struct CameraShakePlugin;
+
+impl Plugin for CameraShakePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraShake>()
+            .add_system(remove_camera_shake.in_base_set(CoreSet::PreUpdate))
+            .add_system(add_crash_trauma)
+            .add_system(
+                apply_camera_shake
+                    .in_base_set(CoreSet::PostUpdate)
+                    .before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct CameraShake {
+    /// Between 0 and 1.
+    pub trauma: f32,
+    /// Offset and angle added to the camera this frame, taken back before the next one.
+    applied: (Vec2, f32),
+}
+
+impl CameraShake {
+    /// Trauma of an impact at `speed` pixels per second.
+    fn add_impact(&mut self, speed: f32) {
+        self.trauma = (self.trauma + speed / configs::CAMERA_SHAKE_FULL_IMPACT_SPEED).min(1.0);
+    }
+}
+
+fn add_crash_trauma(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut shake: ResMut<CameraShake>,
+    settings: Option<Res<Settings>>,
+    accessibility: Option<Res<AccessibilityConfig>>,
+    racers: Option<Res<Racers>>,
+    run_stats: Option<Res<RunStats>>,
+    cars: Query<&Model, With<Car>>,
+    enemies: Query<&EnemyMotion>,
+) {
+    let enabled = settings.map_or(true, |settings| settings.is_camera_shake)
+        && AccessibilityConfig::allows_motion(accessibility.as_deref());
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Started(entity1, entity2, flags) = collision_event else {
+            continue;
+        };
+        if flags.contains(CollisionEventFlags::SENSOR) || !enabled {
+            continue;
+        }
+
+        for (car, other) in [(entity1, entity2), (entity2, entity1)] {
+            let Ok(model) = cars.get(*car) else {
+                continue;
+            };
+            let player = racers
+                .as_ref()
+                .and_then(|racers| racers.0.first())
+                .map_or(true, |player| *player == model.id);
+            if !player {
+                continue;
+            }
+
+            // Cars drive up the road, walls don't move
+            let car_velocity = Vec2::new(
+                0.0,
+                run_stats
+                    .as_ref()
+                    .and_then(|stats| stats.0.get(&model.id))
+                    .map_or(0.0, |stats| stats.speed * configs::DOJO_TO_BEVY_RATIO_Y),
+            );
+            let other_velocity = enemies
+                .get(*other)
+                .map_or(Vec2::ZERO, |motion| motion.velocity);
+            shake.add_impact((car_velocity - other_velocity).length());
+        }
+    }
+}
+
+fn remove_camera_shake(
+    mut shake: ResMut<CameraShake>,
+    mut cameras: Query<&mut Transform, With<PanCam>>,
+) {
+    let (offset, angle) = std::mem::take(&mut shake.applied);
+    for mut transform in cameras.iter_mut() {
+        transform.translation -= offset.extend(0.0);
+        transform.rotate_z(-angle);
+    }
+}
+
+fn apply_camera_shake(
+    time: Res<Time>,
+    mut shake: ResMut<CameraShake>,
+    mut cameras: Query<&mut Transform, With<PanCam>>,
+) {
+    shake.trauma = (shake.trauma - configs::CAMERA_SHAKE_DECAY * time.delta_seconds()).max(0.0);
+    if shake.trauma == 0.0 {
+        return;
+    }
+
+    let intensity = shake.trauma * shake.trauma;
+    let mut rng = rand::thread_rng();
+    let mut noise = || rng.gen_range(-1.0..=1.0);
+    let offset = Vec2::new(noise(), noise()) * configs::CAMERA_SHAKE_MAX_OFFSET * intensity;
+    let angle = noise() * configs::CAMERA_SHAKE_MAX_ANGLE * intensity;
+    for mut transform in cameras.iter_mut() {
+        transform.translation += offset.extend(0.0);
+        transform.rotate_z(angle);
+    }
+    shake.applied = (offset, angle);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trauma_scales_with_impact_and_saturates() {
+        let mut shake = CameraShake::default();
+        shake.add_impact(configs::CAMERA_SHAKE_FULL_IMPACT_SPEED / 4.0);
+        assert_eq!(shake.trauma, 0.25);
+
+        shake.add_impact(configs::CAMERA_SHAKE_FULL_IMPACT_SPEED * 2.0);
+        assert_eq!(shake.trauma, 1.0);
+    }
+}
@@ -0,0 +1,229 @@
+use crate::cli::Cli;
+use crate::configs;
+use crate::conversions::felt_to_short_string;
+use crate::display::{road_projection, DisplaySettings};
+use crate::input_map::{Action, ActionState};
+use crate::resources::Racers;
+use bevy::log;
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
+use bevy_image_export::{
+    ImageExportBundle, ImageExportPlugin, ImageExportSettings, ImageExportSource,
+};
+use bevy_pancam::PanCam;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Writes rendered frames as PNG files under a run directory, next to a `run.json` describing
+/// the run. [`Action::CaptureScreenshot`] saves a single frame and
+/// [`Action::CaptureSequence`] toggles recording every frame.
+pub struct CapturePlugin {
+    /// Directory receiving the run directories.
+    pub directory: PathBuf,
+    /// Record every frame from startup.
+    pub sequence: bool,
+}
+
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        app.add_plugin(ImageExportPlugin::default())
+            .insert_resource(Capture {
+                directory: self.directory.join(started_at.to_string()),
+                started_at,
+                sequence: self.sequence,
+                screenshot: false,
+                metadata_written: false,
+            })
+            .add_startup_system(setup_capture_camera)
+            .add_systems((capture_input, follow_main_camera, update_exporter).chain());
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct Capture {
+    /// Run directory the frames are written to.
+    pub(crate) directory: PathBuf,
+    started_at: u64,
+    /// Record every frame.
+    pub(crate) sequence: bool,
+    screenshot: bool,
+    metadata_written: bool,
+}
+
+/// Saved as `run.json` in the run directory.
+#[derive(Serialize)]
+struct RunMetadata {
+    version: &'static str,
+    started_at: u64,
+    command: String,
+    racers: Vec<String>,
+    width: u32,
+    height: u32,
+}
+
+/// Renders the same view as the main camera into the exported image.
+#[derive(Component)]
+struct CaptureCamera;
+
+/// Exported image, at the resolution of the [`DisplaySettings`] the app started with.
+#[derive(Resource)]
+struct CaptureSource {
+    source: Handle<ImageExportSource>,
+    width: u32,
+    height: u32,
+}
+
+fn setup_capture_camera(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut export_sources: ResMut<Assets<ImageExportSource>>,
+    display: Option<Res<DisplaySettings>>,
+) {
+    let (width, height) = display.map_or(
+        (configs::WINDOW_WIDTH as u32, configs::WINDOW_HEIGHT as u32),
+        |display| display.resolution,
+    );
+    let mut image = Image::new_fill(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    image.texture_descriptor.usage |= TextureUsages::COPY_SRC | TextureUsages::RENDER_ATTACHMENT;
+    let image = images.add(image);
+
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                target: RenderTarget::Image(image.clone()),
+                is_active: false,
+                ..default()
+            },
+            projection: road_projection(),
+            ..default()
+        },
+        CaptureCamera,
+    ));
+    commands.insert_resource(CaptureSource {
+        source: export_sources.add(image.into()),
+        width,
+        height,
+    });
+}
+
+fn capture_input(actions: Res<ActionState>, mut capture: ResMut<Capture>) {
+    if actions.just_pressed(Action::CaptureScreenshot) {
+        capture.screenshot = true;
+    }
+    if actions.just_pressed(Action::CaptureSequence) {
+        capture.sequence = !capture.sequence;
+        log::info!(
+            "Frame capture {}",
+            if capture.sequence {
+                "started"
+            } else {
+                "stopped"
+            }
+        );
+    }
+}
+
+fn follow_main_camera(
+    main_cameras: Query<
+        (&Transform, &OrthographicProjection),
+        (With<PanCam>, Without<CaptureCamera>),
+    >,
+    mut capture_cameras: Query<(&mut Transform, &mut OrthographicProjection), With<CaptureCamera>>,
+) {
+    if let (Some((main, main_projection)), Ok((mut capture, mut projection))) =
+        (main_cameras.iter().next(), capture_cameras.get_single_mut())
+    {
+        *capture = *main;
+        // Frames match the zoom of the window
+        projection.scale = main_projection.scale;
+    }
+}
+
+fn update_exporter(
+    mut commands: Commands,
+    mut capture: ResMut<Capture>,
+    source: Res<CaptureSource>,
+    cli: Option<Res<Cli>>,
+    racers: Option<Res<Racers>>,
+    mut cameras: Query<&mut Camera, With<CaptureCamera>>,
+    exporters: Query<Entity, With<Handle<ImageExportSource>>>,
+) {
+    let active = capture.sequence || capture.screenshot;
+    capture.screenshot = false;
+
+    if let Ok(mut camera) = cameras.get_single_mut() {
+        camera.is_active = active;
+    }
+
+    match (active, exporters.get_single()) {
+        (true, Err(_)) => {
+            if !capture.metadata_written {
+                write_metadata(&capture, &source, cli.as_deref(), racers.as_deref());
+                capture.metadata_written = true;
+            }
+            commands.spawn(ImageExportBundle {
+                source: source.source.clone(),
+                settings: ImageExportSettings {
+                    output_dir: capture.directory.to_string_lossy().into_owned(),
+                    extension: "png".into(),
+                },
+            });
+        }
+        (false, Ok(exporter)) => commands.entity(exporter).despawn(),
+        _ => {}
+    }
+}
+
+fn write_metadata(
+    capture: &Capture,
+    source: &CaptureSource,
+    cli: Option<&Cli>,
+    racers: Option<&Racers>,
+) {
+    let metadata = RunMetadata {
+        version: env!("CARGO_PKG_VERSION"),
+        started_at: capture.started_at,
+        command: cli
+            .map(|cli| format!("{:?}", cli.command()))
+            .unwrap_or_default(),
+        racers: racers
+            .map(|racers| {
+                racers
+                    .0
+                    .iter()
+                    .map(|id| felt_to_short_string(*id))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        width: source.width,
+        height: source.height,
+    };
+
+    let result = fs::create_dir_all(&capture.directory).and_then(|_| {
+        let json = serde_json::to_string_pretty(&metadata)?;
+        fs::write(capture.directory.join("run.json"), json)
+    });
+    if let Err(e) = result {
+        log::error!(
+            "Write capture metadata in {}: {e}",
+            capture.directory.display()
+        );
+    }
+}
@@ -1,10 +1,15 @@
-use crate::dojo::fixed_to_f32;
+use crate::accessibility::AccessibilityConfig;
+use crate::conversions::dojo_to_bevy_coordinate;
+use crate::input_map::{Action, ActionState};
+use crate::nn::Net;
+use crate::sensors::{SensorSet, Sensors};
+use crate::speed_zone::SpeedZones;
+use crate::world::{Decode, Vehicle};
 use crate::*;
-use crate::{dojo::dojo_to_bevy_coordinate, nn::Net};
 use bevy::{log, math::vec3, prelude::*};
-use bevy_prototype_debug_lines::DebugLinesPlugin;
 use bevy_rapier2d::prelude::*;
-use starknet::core::{types::FieldElement, utils::cairo_short_string_to_felt};
+use bevy_rapier2d::rapier::geometry::CollisionEventFlags;
+use starknet_ff::FieldElement;
 
 pub struct CarPlugin;
 
@@ -31,6 +36,37 @@ pub struct Model {
 #[derive(Component)]
 pub struct Fitness(pub f32);
 
+/// Last `Vehicle` synced from the world, empty until the first sync.
+#[derive(Component, Default)]
+pub struct SyncedVehicle(pub Vec<FieldElement>);
+
+/// Synced position the car is easing towards, `None` once reached.
+#[derive(Component, Default)]
+pub struct SyncTarget(pub Option<Vec2>);
+
+/// Heading from the synced steer, in radians counterclockwise.
+#[derive(Component, Default)]
+pub struct Heading(pub f32);
+
+/// Sprite of a car, its child so it can lean without turning the rapier body.
+#[derive(Component)]
+pub struct CarSprite;
+
+/// Lean of the [`CarSprite`] of a car, eased towards its lateral acceleration.
+#[derive(Component, Default)]
+pub struct Banking {
+    last_x: Option<f32>,
+    velocity_x: f32,
+    pub lean: f32,
+}
+
+/// Furthest `y` reached and for how long it has not increased.
+#[derive(Component, Default)]
+pub struct Progress {
+    pub y: f32,
+    pub stalled_for: f32,
+}
+
 // #[derive(Resource, Default)]
 // struct RayCastSensors(Vec<(f32, f32)>);
 
@@ -39,10 +75,15 @@ pub struct Fitness(pub f32);
 
 #[derive(Bundle)]
 pub struct CarBundle {
-    sprite_bundle: SpriteBundle,
+    spatial_bundle: SpatialBundle,
     car: Car,
     fitness: Fitness,
     model: Model,
+    synced: SyncedVehicle,
+    sync_target: SyncTarget,
+    heading: Heading,
+    banking: Banking,
+    progress: Progress,
     // speed: Speed,
     velocity: Velocity,
     mass: ColliderMassProperties,
@@ -57,50 +98,294 @@ pub struct CarBundle {
 
 impl Plugin for CarPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugin(DebugLinesPlugin::default())
-            .add_event::<SpawnCar>()
+        app.add_event::<SpawnCar>()
             .add_event::<UpdateCar>()
+            .add_event::<CarStalled>()
+            .add_event::<CarCrashed>()
+            .init_resource::<RunStats>()
             // .register_type::<TurnSpeed>()
             // .register_type::<Speed>()
             // .insert_resource(RayCastSensors::default())
             // .add_startup_system(setup)
             // .add_systems((car_render_system, spawn_cars));
-            .add_systems((spawn_car, update_car, collision_events_system));
+            .add_systems((
+                spawn_car,
+                update_car,
+                run_stats_system,
+                collision_events_system,
+                stall_watchdog_system,
+                despawn_removed_car_sprites,
+            ))
+            .add_system(predict_outputs.after(SensorSet))
+            .add_system(follow_sync_target.in_schedule(CoreSchedule::FixedUpdate))
+            .add_system(
+                drive_standalone
+                    .run_if(resource_exists::<Standalone>())
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(
+                bank_cars
+                    .after(follow_sync_target)
+                    .after(drive_standalone)
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            );
         // .add_system(sensors_system)
         // .add_system(car_nn_controlled_system.in_schedule(CoreSchedule::FixedUpdate));
     }
 }
 
-pub struct SpawnCar;
+pub struct SpawnCar {
+    pub model_id: FieldElement,
+    /// Where the car starts, when the client chose it, in bevy coordinates.
+    pub position: Option<Vec2>,
+}
 
 fn spawn_car(
     mut events: EventReader<SpawnCar>,
     asset_server: Res<AssetServer>,
     mut commands: Commands,
 ) {
-    for _ in events.iter() {
-        let model_id = cairo_short_string_to_felt(configs::MODEL_NAME).unwrap();
-        commands.spawn(CarBundle::new(&asset_server, model_id));
+    for e in events.iter() {
+        let mut car = CarBundle::new(e.model_id);
+        if let Some(position) = e.position {
+            car.spatial_bundle.transform.translation.x = position.x;
+            car.spatial_bundle.transform.translation.y = position.y;
+        }
+        commands.spawn(car).with_children(|car| {
+            car.spawn((
+                SpriteBundle {
+                    texture: asset_server.load("agent.png"),
+                    ..default()
+                },
+                CarSprite,
+            ));
+        });
+    }
+}
+
+/// Despawns the sprites of the cars whose run ended, once their [`CarBundle`] is removed.
+fn despawn_removed_car_sprites(mut commands: Commands, mut removed: RemovedComponents<Car>) {
+    for entity in removed.iter() {
+        if let Some(mut car) = commands.get_entity(entity) {
+            car.despawn_descendants();
+        }
     }
 }
 
 pub struct UpdateCar {
+    pub model_id: FieldElement,
     pub vehicle: Vec<FieldElement>,
 }
 
 fn update_car(
     mut events: EventReader<UpdateCar>,
-    mut query: Query<(&mut Transform, &Model), With<Car>>,
+    mut query: Query<
+        (
+            &mut Transform,
+            &Model,
+            &mut SyncedVehicle,
+            &mut SyncTarget,
+            &mut Heading,
+        ),
+        With<Car>,
+    >,
 ) {
     for e in events.iter() {
-        if let Ok((mut transform, model)) = query.get_single_mut() {
+        let vehicle = match Vehicle::decode(&e.vehicle) {
+            Ok(vehicle) => vehicle,
+            Err(err) => {
+                log::error!("Decode vehicle ({}): {err}", e.model_id);
+                continue;
+            }
+        };
+        for (mut transform, model, mut synced, mut target, mut heading) in query.iter_mut() {
+            if model.id != e.model_id {
+                continue;
+            }
+
             let (new_x, new_y) =
-                dojo_to_bevy_coordinate(fixed_to_f32(e.vehicle[0]), fixed_to_f32(e.vehicle[2]));
+                dojo_to_bevy_coordinate(vehicle.position.x.to_f32(), vehicle.position.y.to_f32());
 
             log::info!("Vehicle Position ({}), x: {new_x}, y: {new_y}", model.id);
 
-            transform.translation.x = new_x;
-            transform.translation.y = new_y;
+            // The first sync places the car, the next ones are eased by `follow_sync_target`
+            if synced.0.is_empty() {
+                transform.translation.x = new_x;
+                transform.translation.y = new_y;
+            } else {
+                target.0 = Some(Vec2::new(new_x, new_y));
+            }
+            // The world steers to the right with a positive angle, clockwise on screen. The
+            // collider turns with the sprite.
+            heading.0 = -vehicle.steer.to_f32_exact();
+            transform.rotation = Quat::from_rotation_z(heading.0);
+            synced.0 = e.vehicle.clone();
+        }
+    }
+}
+
+/// Derives the [`RunStats`] of each car from its synced positions.
+fn run_stats_system(
+    time: Res<Time>,
+    mut events: EventReader<UpdateCar>,
+    mut stats: ResMut<RunStats>,
+    spawned: Query<&Model, Added<Car>>,
+) {
+    for model in spawned.iter() {
+        stats.0.insert(model.id, CarRunStats::default());
+    }
+    for e in events.iter() {
+        let Ok(vehicle) = Vehicle::decode(&e.vehicle) else {
+            continue;
+        };
+        let position = Vec2::new(vehicle.position.x.to_f32(), vehicle.position.y.to_f32());
+        stats
+            .0
+            .entry(e.model_id)
+            .or_default()
+            .record(position, time.elapsed_seconds());
+    }
+}
+
+/// Moves cars towards their synced position by [`CAR_SYNC_STIFFNESS`], so they don't snap once
+/// per sync interval.
+fn follow_sync_target(
+    fixed_time: Res<FixedTime>,
+    mut query: Query<(&mut Transform, &mut SyncTarget), With<Car>>,
+) {
+    let blend = 1.0 - (-CAR_SYNC_STIFFNESS * fixed_time.period.as_secs_f32()).exp();
+    for (mut transform, mut target) in query.iter_mut() {
+        let Some(position) = target.0 else {
+            continue;
+        };
+
+        let current = transform.translation.truncate();
+        let next = if current.distance(position) < CAR_SYNC_SNAP_DISTANCE {
+            target.0 = None;
+            position
+        } else {
+            current.lerp(position, blend)
+        };
+        transform.translation.x = next.x;
+        transform.translation.y = next.y;
+    }
+}
+
+/// Banks cars into their sideways moves, by [`CAR_BANK_PER_ACCELERATION`] of their lateral
+/// acceleration up to [`CAR_BANK_MAX`], eased at [`CAR_BANK_RESPONSIVENESS`]. Upright under
+/// [`AccessibilityConfig::reduced_motion`]. Only the [`CarSprite`] leans, the body and its
+/// collider keep the synced [`Heading`].
+fn bank_cars(
+    fixed_time: Res<FixedTime>,
+    accessibility: Option<Res<AccessibilityConfig>>,
+    mut cars: Query<(&Transform, &mut Banking, &Children), With<Car>>,
+    mut sprites: Query<&mut Transform, (With<CarSprite>, Without<Car>)>,
+) {
+    let dt = fixed_time.period.as_secs_f32();
+    let blend = 1.0 - (-CAR_BANK_RESPONSIVENESS * dt).exp();
+    for (transform, mut banking, children) in cars.iter_mut() {
+        let x = transform.translation.x;
+        let velocity_x = banking.last_x.map_or(0.0, |last_x| (x - last_x) / dt);
+        let acceleration = (velocity_x - banking.velocity_x) / dt;
+        banking.last_x = Some(x);
+        banking.velocity_x = velocity_x;
+
+        // Leaning right, clockwise, when accelerating to the right
+        let target = if AccessibilityConfig::allows_motion(accessibility.as_deref()) {
+            (-acceleration * CAR_BANK_PER_ACCELERATION).clamp(-CAR_BANK_MAX, CAR_BANK_MAX)
+        } else {
+            0.0
+        };
+        banking.lean += (target - banking.lean) * blend;
+        let mut sprites = sprites.iter_many_mut(children.iter());
+        while let Some(mut sprite) = sprites.fetch_next() {
+            sprite.rotation = Quat::from_rotation_z(banking.lean);
+        }
+    }
+}
+
+/// Feeds the hit fractions of the [`Sensors`] rays to the network of the watched car.
+fn predict_outputs(sensors: Option<Res<Sensors>>, mut cars: Query<&mut Model, With<Car>>) {
+    let Some(sensors) = sensors else {
+        return;
+    };
+    let Some(Ok(mut model)) = sensors.car.map(|car| cars.get_mut(car)) else {
+        return;
+    };
+    let inputs: Vec<f64> = sensors
+        .rays
+        .iter()
+        .map(|ray| ray.hit.unwrap_or(1.0) as f64)
+        .collect();
+    if model.nn.n_inputs() == inputs.len() {
+        model.nn_outputs = model.nn.predict(&inputs);
+    }
+}
+
+/// Drives [`Standalone`] cars up the road at [`STANDALONE_CAR_SPEED`], steered by the
+/// [`Action::SteerLeft`] and [`Action::SteerRight`] bindings and slowed down in [`SpeedZones`].
+fn drive_standalone(
+    fixed_time: Res<FixedTime>,
+    hold_drive: Option<Res<HoldDrive>>,
+    actions: Option<Res<ActionState>>,
+    zones: Option<Res<SpeedZones>>,
+    mut query: Query<&mut Transform, With<Car>>,
+) {
+    if hold_drive.is_some() {
+        return;
+    }
+    let steer = actions.map_or(0.0, |actions| {
+        let left = actions.pressed(Action::SteerLeft) as i32 as f32;
+        let right = actions.pressed(Action::SteerRight) as i32 as f32;
+        right - left
+    });
+
+    let dt = fixed_time.period.as_secs_f32();
+    let velocity = Vec2::new(steer * STANDALONE_STEER_SPEED, STANDALONE_CAR_SPEED);
+    for mut transform in query.iter_mut() {
+        let velocity = zones.as_ref().map_or(velocity, |zones| {
+            zones.velocity_at(transform.translation.y, velocity)
+        });
+        transform.translation.y += velocity.y * dt;
+        // Up to the walls past the shoulders, the cars can drive on them and crash into the walls
+        transform.translation.x = (transform.translation.x + velocity.x * dt)
+            .clamp(ROAD_X_MIN - SHOULDER_WIDTH, ROAD_X_MAX + SHOULDER_WIDTH);
+    }
+}
+
+/// A car stopped progressing for [`CAR_STALL_TIMEOUT`] seconds and was taken off the race.
+pub struct CarStalled {
+    pub model_id: FieldElement,
+}
+
+/// Ends the run of cars whose synced `y` did not increase for [`CAR_STALL_TIMEOUT`] seconds, the
+/// same way a collision does. Held cars, e.g. during the start countdown, are not stalled.
+fn stall_watchdog_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    hold_drive: Option<Res<HoldDrive>>,
+    mut stalled: EventWriter<CarStalled>,
+    mut query: Query<(Entity, &Transform, &Model, &mut Progress), With<Car>>,
+) {
+    if hold_drive.is_some() {
+        return;
+    }
+    for (entity, transform, model, mut progress) in query.iter_mut() {
+        if transform.translation.y > progress.y {
+            progress.y = transform.translation.y;
+            progress.stalled_for = 0.0;
+            continue;
+        }
+
+        progress.stalled_for += time.delta_seconds();
+        if progress.stalled_for >= CAR_STALL_TIMEOUT {
+            log::info!(
+                "Vehicle ({}) stalled at y: {} for {CAR_STALL_TIMEOUT}s, run finished",
+                model.id,
+                progress.y
+            );
+            commands.entity(entity).remove::<CarBundle>();
+            stalled.send(CarStalled { model_id: model.id });
         }
     }
 }
@@ -135,13 +420,33 @@ fn update_car(
 //     }
 // }
 
+/// A car hit an enemy or a wall.
+pub struct CarCrashed {
+    pub model_id: FieldElement,
+    /// Where the car was when it crashed.
+    pub position: Vec2,
+}
+
 fn collision_events_system(
     mut commands: Commands,
     mut collision_events: EventReader<CollisionEvent>,
+    mut crashed: EventWriter<CarCrashed>,
+    cars: Query<(&Transform, &Model), With<Car>>,
 ) {
     for collision_event in collision_events.iter() {
         match collision_event {
-            CollisionEvent::Started(entity1, entity2, _) => {
+            // Sensors only detect enemies, e.g. near misses
+            CollisionEvent::Started(entity1, entity2, flags)
+                if !flags.contains(CollisionEventFlags::SENSOR) =>
+            {
+                for entity in [entity1, entity2] {
+                    if let Ok((transform, model)) = cars.get(*entity) {
+                        crashed.send(CarCrashed {
+                            model_id: model.id,
+                            position: transform.translation.truncate(),
+                        });
+                    }
+                }
                 commands.entity(*entity2).remove::<CarBundle>();
                 commands.entity(*entity1).remove::<CarBundle>();
             }
@@ -280,19 +585,23 @@ fn collision_events_system(
 // }
 
 impl CarBundle {
-    pub fn new(asset_server: &AssetServer, model_id: FieldElement) -> Self {
+    /// The body of a car, its [`CarSprite`] is spawned as its child.
+    pub fn new(model_id: FieldElement) -> Self {
         // let mut rng = rand::thread_rng();
         // let rand_x = rng.gen_range(800.0..1100.0);
 
         Self {
-            sprite_bundle: SpriteBundle {
+            spatial_bundle: SpatialBundle::from_transform(
                 // TODO: make cordinate dynamic
-                transform: Transform::from_xyz(298.0, 0.0, 0.0).with_scale(vec3(2.5, 2.5, 1.0)),
-                texture: asset_server.load("agent.png"),
-                ..default()
-            },
+                Transform::from_xyz(298.0, 0.0, 0.0).with_scale(vec3(2.5, 2.5, 1.0)),
+            ),
             car: Car,
             fitness: Fitness(0.0),
+            synced: SyncedVehicle::default(),
+            sync_target: SyncTarget::default(),
+            heading: Heading::default(),
+            banking: Banking::default(),
+            progress: Progress::default(),
             model: Model {
                 nn: Net::new(vec![
                     NUM_RAY_CASTS as usize,
@@ -307,27 +616,219 @@ impl CarBundle {
             velocity: Velocity::zero(),
             mass: ColliderMassProperties::Mass(3000.0),
             rigid_body: RigidBody::Dynamic,
-            collider: Collider::cuboid(5.0, 8.0),
+            collider: Collider::cuboid(CAR_COLLIDER.x, CAR_COLLIDER.y),
             events: ActiveEvents::COLLISION_EVENTS,
             damping: Damping {
                 angular_damping: 100.0,
-                linear_damping: 100.0,
+                linear_damping: CAR_LINEAR_DAMPING,
             },
             // sleep: Sleeping::disabled(),
             ccd: Ccd::enabled(),
-            collision_groups: CollisionGroups {
-                memberships: Group::GROUP_1,
-                filters: Group::GROUP_2,
-            },
+            collision_groups: CAR_COLLISION_GROUPS,
         }
     }
 
-    pub fn with_model(asset_server: &AssetServer, model: &Net) -> Self {
+    pub fn with_model(model: &Net) -> Self {
         // TODO: generate dojo id
         let mode_id = FieldElement::from_dec_str("0").unwrap();
 
-        let mut car = CarBundle::new(asset_server, mode_id);
+        let mut car = CarBundle::new(mode_id);
         car.model.nn = model.clone();
         car
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::utils::{Duration, Instant};
+
+    fn spawn_test_car(app: &mut App, y: f32) -> Entity {
+        app.world
+            .spawn((
+                Car,
+                Transform::from_xyz(298.0, y, 0.0),
+                Model {
+                    nn: Net::new(vec![1, 1]),
+                    nn_outputs: Vec::new(),
+                    id: FieldElement::ONE,
+                },
+                Progress::default(),
+            ))
+            .id()
+    }
+
+    fn update_after(app: &mut App, start: Instant, seconds: f32) {
+        app.world
+            .resource_mut::<Time>()
+            .update_with_instant(start + Duration::from_secs_f32(seconds));
+        app.update();
+    }
+
+    #[test]
+    fn test_stall_watchdog_ends_stalled_run() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_event::<CarStalled>()
+            .add_event::<CarCrashed>()
+            .add_system(stall_watchdog_system);
+        let start = app.world.resource::<Time>().startup();
+        let moving = spawn_test_car(&mut app, 0.0);
+        let stalled = spawn_test_car(&mut app, 100.0);
+
+        for i in 1..=((CAR_STALL_TIMEOUT as usize) + 2) {
+            app.world
+                .get_mut::<Transform>(moving)
+                .unwrap()
+                .translation
+                .y = i as f32;
+            update_after(&mut app, start, i as f32);
+        }
+
+        assert!(app.world.get::<Car>(moving).is_some());
+        assert!(app.world.get::<Car>(stalled).is_none());
+    }
+
+    #[test]
+    fn test_update_car_eases_to_synced_position() {
+        let mut app = App::new();
+        app.insert_resource(FixedTime::new_from_secs(0.05))
+            .add_event::<UpdateCar>()
+            .add_systems((update_car, follow_sync_target.after(update_car)));
+        let car = spawn_test_car(&mut app, 0.0);
+        app.world.entity_mut(car).insert((
+            SyncedVehicle(vec![FieldElement::ZERO; 8]),
+            SyncTarget::default(),
+            Heading::default(),
+        ));
+        let from = app.world.get::<Transform>(car).unwrap().translation;
+
+        let mut vehicle = vec![FieldElement::ZERO; 8];
+        vehicle[0] = FieldElement::from(100_u128 << 64);
+        vehicle[2] = FieldElement::from(500_u128 << 64);
+        app.world.send_event(UpdateCar {
+            model_id: FieldElement::ONE,
+            vehicle,
+        });
+        app.update();
+
+        let (x, y) = dojo_to_bevy_coordinate(100.0, 500.0);
+        let to = Vec3::new(x, y, 0.0);
+        let translation = app.world.get::<Transform>(car).unwrap().translation;
+        assert!(translation.distance(from) > 0.0);
+        assert!(translation.distance(to) < from.distance(to));
+
+        for _ in 0..40 {
+            app.update();
+        }
+        assert_eq!(app.world.get::<Transform>(car).unwrap().translation, to);
+        assert!(app.world.get::<SyncTarget>(car).unwrap().0.is_none());
+    }
+
+    #[test]
+    fn test_update_car_turns_with_the_synced_steer() {
+        let mut app = App::new();
+        app.add_event::<UpdateCar>().add_system(update_car);
+        let car = spawn_test_car(&mut app, 0.0);
+        app.world.entity_mut(car).insert((
+            SyncedVehicle::default(),
+            SyncTarget::default(),
+            Heading::default(),
+        ));
+
+        // Steering left half a radian
+        let mut vehicle = vec![FieldElement::ZERO; 8];
+        vehicle[4] = FieldElement::from(1_u128 << 63);
+        vehicle[5] = FieldElement::ONE;
+        app.world.send_event(UpdateCar {
+            model_id: FieldElement::ONE,
+            vehicle,
+        });
+        app.update();
+
+        let rotation = app.world.get::<Transform>(car).unwrap().rotation;
+        assert!((rotation.to_euler(EulerRot::XYZ).2 - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_cars_bank_into_sideways_moves() {
+        let mut app = App::new();
+        app.insert_resource(FixedTime::new_from_secs(0.05))
+            .add_system(bank_cars);
+        let car = spawn_test_car(&mut app, 0.0);
+        let sprite = app.world.spawn((Transform::default(), CarSprite)).id();
+        app.world
+            .entity_mut(car)
+            .insert(Banking::default())
+            .push_children(&[sprite]);
+        let lean = |app: &App| app.world.get::<Banking>(car).unwrap().lean;
+
+        app.update();
+        assert_eq!(lean(&app), 0.0);
+
+        // Speeding up to the right leans right, up to the limit
+        for i in 1..=3 {
+            app.world.get_mut::<Transform>(car).unwrap().translation.x += 10.0 * i as f32;
+            app.update();
+        }
+        assert!(lean(&app) < 0.0 && lean(&app) >= -CAR_BANK_MAX);
+        let rotation = app.world.get::<Transform>(sprite).unwrap().rotation;
+        assert!((rotation.to_euler(EulerRot::XYZ).2 - lean(&app)).abs() < 1e-5);
+        // The body, and its collider, don't lean
+        assert_eq!(
+            app.world.get::<Transform>(car).unwrap().rotation,
+            Quat::IDENTITY
+        );
+
+        // Back upright once moving straight again
+        for _ in 0..100 {
+            app.update();
+        }
+        assert!(lean(&app).abs() < 1e-3);
+
+        // Upright whatever the moves under reduced motion
+        app.insert_resource(AccessibilityConfig {
+            reduced_motion: true,
+            ..default()
+        });
+        for i in 1..=3 {
+            app.world.get_mut::<Transform>(car).unwrap().translation.x += 10.0 * i as f32;
+            app.update();
+        }
+        assert!(lean(&app).abs() < 1e-3);
+    }
+
+    fn interacts(a: CollisionGroups, b: CollisionGroups) -> bool {
+        a.memberships.intersects(b.filters) && b.memberships.intersects(a.filters)
+    }
+
+    #[test]
+    fn test_collision_groups() {
+        assert!(interacts(CAR_COLLISION_GROUPS, ENEMY_COLLISION_GROUPS));
+        assert!(interacts(CAR_COLLISION_GROUPS, BOUNDARY_COLLISION_GROUPS));
+        assert!(interacts(SENSOR_COLLISION_GROUPS, ENEMY_COLLISION_GROUPS));
+        assert!(!interacts(CAR_COLLISION_GROUPS, CAR_COLLISION_GROUPS));
+        assert!(!interacts(ENEMY_COLLISION_GROUPS, ENEMY_COLLISION_GROUPS));
+        assert!(!interacts(
+            ENEMY_COLLISION_GROUPS,
+            BOUNDARY_COLLISION_GROUPS
+        ));
+        assert!(!interacts(
+            SENSOR_COLLISION_GROUPS,
+            BOUNDARY_COLLISION_GROUPS
+        ));
+        assert!(!interacts(SENSOR_COLLISION_GROUPS, CAR_COLLISION_GROUPS));
+    }
+
+    #[test]
+    fn test_run_stats_from_synced_positions() {
+        let mut stats = CarRunStats::default();
+
+        stats.record(Vec2::ZERO, 0.0);
+        stats.record(Vec2::new(3.0, 4.0), 0.5);
+        assert_eq!((stats.distance, stats.speed), (5.0, 10.0));
+
+        stats.record(Vec2::new(3.0, 4.0), 1.0);
+        assert_eq!((stats.distance, stats.speed), (5.0, 0.0));
+    }
+}
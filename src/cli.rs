@@ -0,0 +1,161 @@
+use crate::difficulty::Difficulty;
+use crate::gui::OverlayStyle;
+use bevy::prelude::Resource;
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+/// Command line arguments of the simulation.
+#[derive(Parser, Resource, Clone, Debug)]
+#[command(author, version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Measure the throughput of the sync pipeline, log a summary and exit.
+    #[arg(long, global = true)]
+    pub bench_sync: bool,
+    /// Draw only the HUD, leaderboard and neural net panel, for compositing over other footage.
+    #[arg(long, global = true, value_enum)]
+    pub overlay: Option<OverlayStyle>,
+    /// Write every rendered frame to a run directory under this one.
+    #[arg(long, global = true, num_args = 0..=1, default_missing_value = crate::configs::CAPTURE_DIRECTORY)]
+    pub capture: Option<PathBuf>,
+    /// Start from a snapshot saved with F5 instead of an empty road.
+    #[arg(long, global = true)]
+    pub restore: Option<PathBuf>,
+    /// Enemies and road preset, can be changed in the models menu.
+    #[arg(long, global = true, value_enum, default_value_t = Difficulty::Normal)]
+    pub difficulty: Difficulty,
+    /// Enemies per racer, instead of the count of the difficulty.
+    #[arg(long, global = true)]
+    pub enemies: Option<u32>,
+    /// Write every dojo read and transaction result to this SQLite database.
+    #[cfg(feature = "event-log")]
+    #[arg(long, global = true)]
+    pub event_log: Option<PathBuf>,
+    /// Rhai script hooked to the spawns, crashes and frames of the run.
+    #[cfg(feature = "scripting")]
+    #[arg(long, global = true)]
+    pub script: Option<PathBuf>,
+    /// Serve the metrics on every interface instead of this machine only.
+    #[arg(long, global = true)]
+    pub metrics_public: bool,
+    /// Stream the synced race to `watch` viewers.
+    #[cfg(feature = "onchain")]
+    #[arg(long, global = true)]
+    pub relay: bool,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum Command {
+    /// Race a single car in a window (default).
+    Play {
+        /// Record the synced world to this file, to watch it again with `replay`.
+        #[arg(long)]
+        record: Option<PathBuf>,
+        /// Models offered in the model selection, the first one is spawned. Repeat to add more.
+        #[arg(long = "model")]
+        models: Vec<String>,
+        /// Play without a world: cars and enemies move on their own, steered with the arrow
+        /// keys. Always the case in builds without the `onchain` feature.
+        #[arg(long)]
+        standalone: bool,
+        /// Let an external process steer the car over a WebSocket instead of the model.
+        #[cfg(feature = "onchain")]
+        #[arg(long)]
+        controller: bool,
+    },
+    /// Race many cars headless, as fast as the world syncs.
+    #[cfg(feature = "onchain")]
+    Train {
+        /// Number of racers spawned on the world.
+        #[arg(long, default_value_t = crate::configs::TRAIN_NUM_RACERS)]
+        racers: u32,
+    },
+    /// Play back a file written by `play --record`.
+    Replay { file: PathBuf },
+    /// Render a file written by `play --record` off-screen to a GIF, then exit.
+    Export {
+        file: PathBuf,
+        /// GIF written, `file` with a `gif` extension by default.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Browse the past races of a model indexed by Torii and play them back.
+    #[cfg(feature = "onchain")]
+    Races {
+        /// Model id searched at startup.
+        #[arg(long)]
+        model: Option<String>,
+    },
+    /// Follow racers already on the world without sending any transaction.
+    #[cfg(feature = "onchain")]
+    Spectate {
+        /// Follow this many racers spawned by `train` instead of the `play` one.
+        #[arg(long)]
+        racers: Option<u32>,
+    },
+    /// Race two models side by side on mirrored lanes of the same road.
+    #[cfg(feature = "onchain")]
+    Race {
+        /// Model id of the left racer.
+        model_a: String,
+        /// Model id of the right racer.
+        model_b: String,
+    },
+    /// Race against the clock to a fixed finish line, keeping personal bests per model.
+    #[cfg(feature = "onchain")]
+    TimeTrial {
+        /// Model id of the racer, the `play` one by default.
+        #[arg(long)]
+        model: Option<String>,
+    },
+    /// Run a single elimination bracket of head-to-head races between models.
+    #[cfg(feature = "onchain")]
+    Tournament {
+        /// Model ids, paired in this order in the first round.
+        #[arg(num_args = 2..)]
+        models: Vec<String>,
+    },
+    /// Follow a race streamed by a host running with `--relay`, without an RPC node.
+    #[cfg(feature = "onchain")]
+    Watch {
+        #[arg(default_value = crate::configs::RELAY_URL)]
+        url: String,
+        /// Public key the host logs on start, other hosts are refused.
+        #[arg(long)]
+        host: starknet_ff::FieldElement,
+    },
+    /// Migrate the world to a fresh local Katana with `sozo` and use it from now on.
+    #[cfg(feature = "onchain")]
+    Deploy,
+}
+
+impl Cli {
+    pub fn command(&self) -> Command {
+        self.command.clone().unwrap_or(Command::Play {
+            record: None,
+            models: Vec::new(),
+            standalone: false,
+            #[cfg(feature = "onchain")]
+            controller: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_subcommand_plays() {
+        let cli = Cli::try_parse_from(["drive-ai"]).unwrap();
+        assert!(matches!(
+            cli.command(),
+            Command::Play {
+                record: None,
+                standalone: false,
+                ..
+            }
+        ));
+    }
+}
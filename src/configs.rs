@@ -1,4 +1,19 @@
-use bevy::prelude::Color;
+use bevy::prelude::{Color, GamepadButtonType, KeyCode, Vec2};
+use bevy_rapier2d::prelude::{CollisionGroups, Group, SolverGroups};
+
+/// Enemies and road of a difficulty.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DifficultyPreset {
+    /// Enemies spawned per racer, at most `DOJO_ENEMIES_NB` on chain.
+    pub enemy_count: u32,
+    /// Speed range of the enemies up the road, in pixels per second. Only standalone
+    /// enemies drive on their own, the world moves the others.
+    pub enemy_speed: (f32, f32),
+    /// Chance of an enemy being a truck.
+    pub truck_chance: f64,
+    /// Width of the lanes enemies drive in, centered on the road, in pixels.
+    pub road_width: f32,
+}
 
 /// Main
 pub const NUM_ROAD_TILES: u32 = 1;
@@ -7,6 +22,8 @@ pub const ROAD_SPRITE_H: f32 = 288.0;
 pub const NUM_ENEMY_CARS: u32 = 140;
 pub const SPRITE_SCALE_FACTOR: f32 = 6.0;
 pub const BACKGROUND_COLOR: Color = Color::BLACK;
+/// Default window size. The cameras show at least this much of the world whatever the window
+/// size, see `display::road_projection`.
 pub const WINDOW_WIDTH: f32 = ROAD_SPRITE_W * SPRITE_SCALE_FACTOR;
 pub const WINDOW_HEIGHT: f32 = 1000.0;
 
@@ -30,8 +47,137 @@ pub const NUM_RAY_CASTS: u32 = 8;
 pub const RAYCAST_SPREAD_ANGLE_DEG: f32 = 140.0;
 pub const RAYCAST_START_ANGLE_DEG: f32 = 20.0;
 pub const RAYCAST_MAX_TOI: f32 = 250.0;
+/// Seconds without forward progress before a car is considered crashed.
+pub const CAR_STALL_TIMEOUT: f32 = 10.0;
+pub const CAR_LINEAR_DAMPING: f32 = 100.0;
+/// Collider half extents, before the sprite scale.
+pub const CAR_COLLIDER: Vec2 = Vec2::new(5.0, 8.0);
+/// Pixels per second a standalone car drives up the road.
+pub const STANDALONE_CAR_SPEED: f32 = 300.0;
+/// Pixels per second a standalone car moves sideways while steering.
+pub const STANDALONE_STEER_SPEED: f32 = 200.0;
+/// How fast a car catches up with its synced position, per second: higher is snappier.
+pub const CAR_SYNC_STIFFNESS: f32 = 15.0;
+/// Distance to its synced position under which a car is put right on it.
+pub const CAR_SYNC_SNAP_DISTANCE: f32 = 0.1;
+/// Radians a car banks by per pixel/s² of lateral acceleration, and at most.
+pub const CAR_BANK_PER_ACCELERATION: f32 = 0.0005;
+pub const CAR_BANK_MAX: f32 = 0.25;
+/// How fast a car's bank follows its acceleration, per second: higher is more responsive.
+pub const CAR_BANK_RESPONSIVENESS: f32 = 8.0;
 // pub const RAYCAST_THICKNESS: f32 = 0.3;
 
+/// Enemy
+/// Folder of the `.ron` enemy definitions, one per kind of enemy.
+pub const ENEMIES_DIR: &str = "assets/enemies";
+/// Collider half extents of the built-in definitions, before the sprite scale.
+pub const ENEMY_COLLIDER: Vec2 = Vec2::new(4.0, 8.0);
+pub const TRUCK_COLLIDER: Vec2 = Vec2::new(6.0, 15.0);
+/// Seconds an enemy takes to move to the lane it was synced in.
+pub const ENEMY_LANE_CHANGE_SECONDS: f32 = 0.3;
+/// Radians the enemy sprite leans towards its new lane halfway through the change.
+pub const ENEMY_LANE_CHANGE_TILT: f32 = 0.2;
+
+/// Formations
+pub const FORMATIONS_PATH: &str = "assets/formations.json";
+/// How far ahead of the leading car formations are spawned, in pixels.
+pub const FORMATION_SPAWN_AHEAD: f32 = WINDOW_HEIGHT;
+
+/// Shoulders
+/// Width of the shoulder between the lanes and the walls on each side, in pixels.
+pub const SHOULDER_WIDTH: f32 = 60.0;
+/// Linear damping of cars on the shoulder, instead of `CAR_LINEAR_DAMPING`.
+pub const SHOULDER_LINEAR_DAMPING: f32 = 400.0;
+/// Fitness lost per second on the shoulder.
+pub const SHOULDER_FITNESS_PENALTY: f32 = 0.5;
+
+/// Speed zones
+pub const SPEED_ZONES_PATH: &str = "assets/speed_zones.json";
+pub const SPEED_ZONE_MAX_SPEED_COLOR: Color = Color::rgba(1.0, 0.8, 0.0, 0.25);
+pub const SPEED_ZONE_DRAG_COLOR: Color = Color::rgba(0.2, 0.5, 1.0, 0.25);
+
+/// Boss
+/// Distance between boss encounters, in pixels, a quarter of the track for one to come in before
+/// the finish line.
+pub const BOSS_MILESTONE: f32 = FINISH_LINE_Y / 4.0;
+pub const BOSS_WARNING_SECONDS: f32 = 1.5;
+/// How far ahead of the leading car the boss comes in, in pixels.
+pub const BOSS_SPAWN_AHEAD: f32 = WINDOW_HEIGHT * 0.4;
+/// Cab and trailers.
+pub const BOSS_SEGMENTS: usize = 3;
+pub const BOSS_SEGMENT_SPACING: f32 = 110.0;
+pub const BOSS_SCALE: f32 = 4.0;
+/// Pixels per second up the road.
+pub const BOSS_SPEED: f32 = 60.0;
+/// Seconds of a sweep from one side of the road to the other and back.
+pub const BOSS_SWEEP_PERIOD: f32 = 4.0;
+/// Seconds a segment lags behind the one in front of it.
+pub const BOSS_SWEEP_LAG: f32 = 0.35;
+
+/// Difficulty
+pub const DIFFICULTY_EASY: DifficultyPreset = DifficultyPreset {
+    enemy_count: 6,
+    enemy_speed: (20.0, 40.0),
+    truck_chance: 0.1,
+    road_width: ROAD_W,
+};
+pub const DIFFICULTY_NORMAL: DifficultyPreset = DifficultyPreset {
+    enemy_count: DOJO_ENEMIES_NB,
+    enemy_speed: (40.0, 80.0),
+    truck_chance: 1.0 / 3.0,
+    road_width: ROAD_W,
+};
+pub const DIFFICULTY_NIGHTMARE: DifficultyPreset = DifficultyPreset {
+    enemy_count: DOJO_ENEMIES_NB,
+    enemy_speed: (80.0, 160.0),
+    truck_chance: 0.6,
+    road_width: ROAD_W * 0.75,
+};
+
+/// Physics
+/// Seconds simulated by each physics step, whatever the frame rate.
+pub const PHYSICS_TIME_STEP: f32 = 1.0 / 60.0;
+/// Defaults of the `PhysicsConfig` resource.
+pub const ENEMY_MASS: f32 = 1.0;
+pub const ENEMY_FRICTION: f32 = 100.0;
+pub const ENEMY_LINEAR_DAMPING: f32 = 2.0;
+pub const ENEMY_ANGULAR_DAMPING: f32 = 2.0;
+/// Collision groups: cars hit enemies and boundaries, enemies never hit each other, boundaries
+/// only block cars and sensors are never part of the solver.
+pub const CAR_GROUP: Group = Group::GROUP_1;
+pub const ENEMY_GROUP: Group = Group::GROUP_2;
+pub const BOUNDARY_GROUP: Group = Group::GROUP_3;
+pub const SENSOR_GROUP: Group = Group::GROUP_4;
+pub const CAR_COLLISION_GROUPS: CollisionGroups = CollisionGroups {
+    memberships: CAR_GROUP,
+    filters: ENEMY_GROUP.union(BOUNDARY_GROUP),
+};
+pub const ENEMY_COLLISION_GROUPS: CollisionGroups = CollisionGroups {
+    memberships: ENEMY_GROUP,
+    filters: CAR_GROUP.union(SENSOR_GROUP),
+};
+pub const BOUNDARY_COLLISION_GROUPS: CollisionGroups = CollisionGroups {
+    memberships: BOUNDARY_GROUP,
+    filters: CAR_GROUP,
+};
+pub const SENSOR_COLLISION_GROUPS: CollisionGroups = CollisionGroups {
+    memberships: SENSOR_GROUP,
+    filters: ENEMY_GROUP,
+};
+pub const SENSOR_SOLVER_GROUPS: SolverGroups = SolverGroups {
+    memberships: SENSOR_GROUP,
+    filters: Group::NONE,
+};
+/// Shows the `PhysicsConfig` inspector.
+pub const PHYSICS_INSPECTOR_KEY: KeyCode = KeyCode::F7;
+
+/// Near miss
+/// How far past the car collider enemies are detected, before the sprite scale.
+pub const NEAR_MISS_SENSOR_MARGIN: f32 = 6.0;
+/// Style points of a near miss.
+pub const NEAR_MISS_POINTS: u32 = 10;
+pub const NEAR_MISS_POPUP_SECONDS: f32 = 1.5;
+
 /// NN
 pub const NUM_HIDDEN_NODES: usize = 15;
 pub const NUM_OUPUT_NODES: usize = 3;
@@ -42,15 +188,324 @@ pub const NN_S_ACTIVATION_THRESHOLD: f64 = 0.8;
 /// Others
 pub const FONT_RES_PATH: &str = "Magero.ttf";
 
+/// Overlay
+pub const OVERLAY_CHROMA_KEY: Color = Color::rgb(0.0, 1.0, 0.0);
+pub const OVERLAY_RENDER_LAYER: u8 = 1;
+pub const LEADERBOARD_SIZE: usize = 10;
+
+/// Input
+/// User settings, holding the key and gamepad bindings.
+pub const SETTINGS_PATH: &str = "settings.json";
+pub const BINDINGS_KEY: KeyCode = KeyCode::F2;
+pub const PAUSE_KEY: KeyCode = KeyCode::P;
+pub const PAUSE_BUTTON: GamepadButtonType = GamepadButtonType::Start;
+/// Steering of a `--controller` run.
+pub const STEER_LEFT_KEY: KeyCode = KeyCode::Left;
+pub const STEER_RIGHT_KEY: KeyCode = KeyCode::Right;
+pub const STEER_LEFT_BUTTON: GamepadButtonType = GamepadButtonType::DPadLeft;
+pub const STEER_RIGHT_BUTTON: GamepadButtonType = GamepadButtonType::DPadRight;
+
+/// Display
+/// Window sizes offered in the display settings.
+pub const DISPLAY_RESOLUTIONS: [(u32, u32); 5] = [
+    (WINDOW_WIDTH as u32, WINDOW_HEIGHT as u32),
+    (1280, 720),
+    (1600, 900),
+    (1920, 1080),
+    (2560, 1440),
+];
+/// Monitors offered in the display settings, besides the primary one.
+pub const DISPLAY_MONITORS: usize = 4;
+/// Frame limit set when turning the limiter on, and the lowest one allowed.
+pub const DISPLAY_DEFAULT_FRAME_LIMIT: f32 = 60.0;
+pub const DISPLAY_MIN_FRAME_LIMIT: f32 = 1.0;
+
+/// Localization
+/// Folder of the `<language>.json` UI texts, picked by the `language` setting.
+pub const LOCALES_DIR: &str = "assets/locales";
+/// Language shipped in the binary, the fallback of texts missing from the others.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Boot
+pub const BOOT_LOGO_PATH: &str = "agent.png";
+pub const BOOT_LOGO_SIZE: f32 = 128.0;
+
+/// Capture
+pub const CAPTURE_DIRECTORY: &str = "captures";
+pub const CAPTURE_SCREENSHOT_KEY: KeyCode = KeyCode::F12;
+pub const CAPTURE_SEQUENCE_KEY: KeyCode = KeyCode::F11;
+
+/// Export
+/// Simulated frames per second of an exported GIF.
+pub const EXPORT_FPS: u32 = 20;
+/// Exported frames size, relative to the window.
+pub const EXPORT_SCALE: f32 = 0.5;
+/// Seconds still captured after the last replayed event.
+pub const EXPORT_TAIL_SECONDS: f32 = 1.0;
+/// Frames waited for the last captured frames to be written before encoding.
+pub const EXPORT_FLUSH_FRAMES: u32 = 10;
+
+/// Camera
+/// Zoom bounds of the mouse wheel, below 1 zooms in.
+pub const CAMERA_MIN_SCALE: f32 = 0.5;
+pub const CAMERA_MAX_SCALE: f32 = 2.0;
+/// How far past the window edges the view can be dragged sideways.
+pub const CAMERA_FRAMING_MARGIN: f32 = WINDOW_WIDTH / 2.0;
+pub const CAMERA_RESET_KEY: KeyCode = KeyCode::Home;
+
+/// Inset
+/// Side of the square inset in a window of the default height, in logical pixels. The inset
+/// keeps this share of the window height at any resolution.
+pub const INSET_SIZE: f32 = 240.0;
+pub const INSET_MARGIN: f32 = 10.0;
+/// Zoom of the inset, below 1 zooms in: it shows `INSET_SIZE * INSET_SCALE` of the world.
+pub const INSET_SCALE: f32 = 0.5;
+
+/// Controls HUD
+/// Side of the steering wheel dial, in logical pixels.
+pub const CONTROLS_HUD_SIZE: f32 = 64.0;
+/// Radians the wheel turns at full steer either way.
+pub const CONTROLS_HUD_WHEEL_LOCK: f32 = 2.5 * std::f32::consts::PI / 2.0;
+/// World speed filling the throttle bar.
+pub const CONTROLS_HUD_MAX_SPEED: f32 = 50.0;
+
+/// Audio
+/// Pixels from the car for a sound to be heard a unit away, the ears being
+/// [`AUDIO_EAR_GAP`] units apart.
+pub const AUDIO_PIXELS_PER_UNIT: f32 = 100.0;
+pub const AUDIO_EAR_GAP: f32 = 1.0;
+/// How far sideways an enemy passing the car can be to be heard.
+pub const AUDIO_PASS_DISTANCE: f32 = 250.0;
+pub const AUDIO_WHOOSH_VOLUME: f32 = 0.5;
+pub const AUDIO_STING_VOLUME: f32 = 0.8;
+/// Music
+pub const MUSIC_VOLUME: f32 = 0.4;
+/// Seconds a track plays before the next one of its playlist.
+pub const MUSIC_TRACK_SECONDS: f32 = 180.0;
+pub const MUSIC_CROSSFADE_SECONDS: f32 = 2.0;
+/// Share of the volume left right after a crash, recovered over the ducking seconds.
+pub const MUSIC_DUCK_VOLUME: f32 = 0.3;
+pub const MUSIC_DUCK_SECONDS: f32 = 1.5;
+/// Playback speed right after a finish, back to normal over the rush seconds.
+pub const MUSIC_RUSH_SPEED: f32 = 1.15;
+pub const MUSIC_RUSH_SECONDS: f32 = 4.0;
+
+/// Camera shake
+/// Impact speed, in pixels per second, filling the trauma at once.
+pub const CAMERA_SHAKE_FULL_IMPACT_SPEED: f32 = 800.0;
+/// Trauma lost per second.
+pub const CAMERA_SHAKE_DECAY: f32 = 1.5;
+/// Pixels and radians the camera moves by at full trauma.
+pub const CAMERA_SHAKE_MAX_OFFSET: f32 = 30.0;
+pub const CAMERA_SHAKE_MAX_ANGLE: f32 = 0.05;
+
+/// Accessibility
+/// Okabe-Ito colors of each enemy type in the colorblind palette.
+pub const COLORBLIND_SIMPLE_COLOR: Color = Color::rgb(0.34, 0.71, 0.91);
+pub const COLORBLIND_HORIZONTAL_COLOR: Color = Color::rgb(0.9, 0.62, 0.0);
+pub const COLORBLIND_TRUCK_COLOR: Color = Color::rgb(0.8, 0.47, 0.65);
+/// Width of the shape marking the enemy type, in pixels.
+pub const COLORBLIND_MARKER_SIZE: f32 = 12.0;
+pub const HIGH_CONTRAST_BACKGROUND: Color = Color::BLACK;
+/// Tint darkening the road under high contrast.
+pub const HIGH_CONTRAST_ROAD_TINT: Color = Color::rgb(0.4, 0.4, 0.4);
+
+/// Photo mode
+pub const PHOTO_MODE_KEY: KeyCode = KeyCode::F6;
+/// Free camera pan speed, in pixels per second at the default zoom.
+pub const FREE_CAMERA_SPEED: f32 = 600.0;
+/// Radians per second.
+pub const FREE_CAMERA_ROTATION_SPEED: f32 = 1.0;
+/// Share of the zoom changed by a mouse wheel line.
+pub const FREE_CAMERA_ZOOM_STEP: f32 = 0.1;
+pub const FREE_CAMERA_MIN_SCALE: f32 = 0.1;
+pub const FREE_CAMERA_MAX_SCALE: f32 = 5.0;
+
+/// Snapshot
+pub const SNAPSHOT_DIRECTORY: &str = "snapshots";
+pub const SNAPSHOT_KEY: KeyCode = KeyCode::F5;
+
+/// History
+pub const RESULTS_DIRECTORY: &str = "results";
+pub const HISTORY_KEY: KeyCode = KeyCode::F9;
+pub const SCORE_CONFIRMATION_SECONDS: f32 = 5.0;
+/// One JSON line per generation, appended as they end.
+pub const GENERATION_STATS_PATH: &str = "results/generations.jsonl";
+
+/// Training plot
+pub const TRAINING_PLOT_KEY: KeyCode = KeyCode::F3;
+pub const TRAINING_CSV_PATH: &str = "results/fitness.csv";
+
+/// Crash heatmap
+pub const HEATMAP_PATH: &str = "results/crash_heatmap.json";
+pub const HEATMAP_KEY: KeyCode = KeyCode::F4;
+/// Side of a heatmap cell, in pixels.
+pub const HEATMAP_CELL_SIZE: f32 = 40.0;
+
+/// Start countdown
+/// Seconds cars are held once spawned before `drive` is sent.
+pub const START_COUNTDOWN_SECONDS: f32 = 3.0;
+/// Seconds "GO!" stays on screen.
+pub const START_GO_SECONDS: f32 = 1.0;
+
+/// Finish line
+/// Track `y` ending the race, where the checkered line is drawn before the top wall. The world
+/// checks finishes against the same line, `FINISH_LINE_Y` of `score.cairo`.
+pub const FINISH_LINE_Y: f32 = 1600.0;
+
+/// Time trial
+/// Distance to the finish line, in grid units.
+pub const TIME_TRIAL_TRACK_LENGTH: f32 = 900.0;
+pub const TIME_TRIAL_BESTS_PATH: &str = "results/personal_bests.json";
+
 /// Dojo
 pub const JSON_RPC_ENDPOINT: &str = "http://0.0.0.0:5050";
+/// Nodes the dojo calls fail over to, in order, when the current one keeps failing.
+pub const JSON_RPC_FALLBACK_ENDPOINTS: &[&str] = &[];
+/// Failed dojo calls in a row before failing over to the next node.
+pub const DOJO_FAILOVER_FAILURES: u32 = 3;
+/// Seconds a dojo call is given to answer before it counts as failed.
+pub const DOJO_CALL_TIMEOUT: f32 = 10.0;
+/// Seconds between polls for the receipt of a sent transaction, and how long it is polled for
+/// before the transaction is reported as pending. Below `DOJO_CALL_TIMEOUT`, which the whole
+/// execution has to fit in.
+pub const DOJO_RECEIPT_POLL_INTERVAL: f32 = 0.25;
+pub const DOJO_RECEIPT_TIMEOUT: f32 = 5.0;
+/// Chain id signed for when no node answers at startup, each being given the timeout seconds.
+pub const DEFAULT_CHAIN_ID: &str = "KATANA";
+pub const DOJO_CHAIN_ID_TIMEOUT: f32 = 3.0;
 pub const ACCOUNT_ADDRESS: &str =
     "0x03ee9e18edc71a6df30ac3aca2e0b02a198fbce19b7480a63a0d71cbd76652e0"; // katana account 0
 pub const ACCOUNT_SECRET_KEY: &str =
     "0x0300001800000000300000180000000000030000000000003006001800006600";
+/// Systems resolved while booting, so the first calls don't wait on them.
+pub const DOJO_BOOT_SYSTEMS: &[&str] = &[
+    "spawn_racer",
+    "drive",
+    "drive_manual",
+    "evolve",
+    "report_collision",
+    "submit_score",
+    "finish_race",
+];
+/// Seconds between two attempts of a failed boot step, and the attempts made before showing
+/// the error screen.
+pub const DOJO_BOOT_RETRY_DELAY: f32 = 2.0;
+pub const DOJO_BOOT_ATTEMPTS: u32 = 5;
+/// Seconds between two attempts of a failed `evolve` call, the next generation waits for it.
+pub const DOJO_EVOLVE_RETRY_DELAY: f32 = 2.0;
 pub const WORLD_ADDRESS: &str = "0x26065106fa319c3981618e7567480a50132f23932226a51c219ffb8e47daa84";
+/// Written by `deploy`, its world address replaces `WORLD_ADDRESS`.
+pub const LOCAL_CONFIG_PATH: &str = "drive-ai.local.json";
+/// `sozo` binary migrating the world, looked up in `PATH`.
+pub const SOZO_BIN: &str = "sozo";
+/// ERC20 transaction fees are paid in.
+pub const FEE_TOKEN_ADDRESS: &str =
+    "0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7";
+/// Prefunded account topping up `ACCOUNT_ADDRESS` when its balance is too low, on Katana only.
+pub const FAUCET_ADDRESS: &str =
+    "0x033c627a3e5213790e246a917770ce23d7e562baa5b4d2917c23b1be6d91961c"; // katana account 1
+pub const FAUCET_SECRET_KEY: &str =
+    "0x0333803103001800039980190300d206608b0070db0012135bd1fb5f6282170b";
+/// Fee token balance, in wei, under which no transaction is sent.
+pub const DOJO_MIN_BALANCE: u128 = 10_u128.pow(16);
+/// Wei the faucet sends.
+pub const DOJO_FAUCET_AMOUNT: u128 = 10_u128.pow(18);
+/// Seconds between checks for racers to spawn.
 pub const DOJO_SYNC_INTERVAL: f32 = 0.1;
+/// Seconds between `drive` transactions.
+pub const DOJO_DRIVE_INTERVAL: f32 = 0.1;
+/// Seconds between `Vehicle` queries.
+pub const DOJO_VEHICLE_INTERVAL: f32 = 0.1;
+/// Seconds between enemy `Position` queries, the most expensive sync.
+pub const DOJO_ENEMIES_INTERVAL: f32 = 0.2;
 pub const DOJO_GRID_WIDTH: f32 = 400.0;
 pub const DOJO_GRID_HEIGHT: f32 = 1000.0;
 pub const DOJO_ENEMIES_NB: u32 = 10;
+/// Lanes the road is split into, the world keeps enemy `i` in lane `i`.
+pub const DOJO_LANES_NB: u32 = DOJO_ENEMIES_NB;
+/// Enemy `Position` queries in flight at once per racer.
+pub const DOJO_ENEMIES_CONCURRENCY: usize = 4;
+/// Seconds an enemy keeps moving at its synced velocity without a new position.
+pub const DOJO_EXTRAPOLATION_HORIZON: f32 = 0.5;
+/// Entity updates applied from the dojo tasks per frame, the rest wait for the next frames.
+pub const DOJO_FRAME_BUDGET: usize = 200;
+/// Seconds between checks of the node latency.
+pub const DOJO_HEALTH_INTERVAL: f32 = 2.0;
+/// Latency, in seconds, over which the connection is shown as slow.
+pub const DOJO_SLOW_LATENCY: f32 = 0.3;
+/// Sync ticks of a command are spaced at least this many node latencies apart.
+pub const DOJO_LATENCY_INTERVAL_FACTOR: f32 = 2.0;
+/// Seconds over which command channel rejections are counted before warning about them.
+pub const DOJO_SYNC_STATS_WINDOW: f32 = 10.0;
+/// Share of the sync ticks of a window a channel can reject before it is reported congested.
+pub const DOJO_REJECTION_WARNING_RATE: f32 = 0.5;
+pub const DOJO_REJECTION_TOAST_SECONDS: f32 = 5.0;
+/// Threads of the tokio runtime running the background tasks, one per core when `None`.
+pub const TOKIO_WORKER_THREADS: Option<usize> = None;
+/// Seconds the background tasks are given to finish their pending commands on exit.
+pub const DOJO_SHUTDOWN_TIMEOUT: f32 = 5.0;
+/// Blocks whose hash is kept to recognize a reorg.
+pub const DOJO_TRACKED_BLOCKS: usize = 64;
+/// Crash reports waiting for the `report_collision` task.
+pub const DOJO_COLLISIONS_CAPACITY: usize = 64;
+/// Finished runs waiting for the `submit_score` or `finish_race` task.
+pub const DOJO_SCORES_CAPACITY: usize = 64;
 pub const MODEL_NAME: &str = "model";
+/// Models offered in the model selection when none are given on the command line.
+pub const MODEL_NAMES: &[&str] = &[MODEL_NAME];
+
+/// Controller
+/// Only reachable from this machine: the controller drives with the local account.
+pub const CONTROLLER_ENDPOINT: &str = "127.0.0.1:9200";
+pub const CONTROLLER_OBSERVATIONS_CAPACITY: usize = 64;
+
+/// Relay
+/// Only reachable from this machine, bind `0.0.0.0` to stream to other ones.
+pub const RELAY_ENDPOINT: &str = "127.0.0.1:9300";
+pub const RELAY_URL: &str = "ws://127.0.0.1:9300";
+pub const RELAY_FRAMES_CAPACITY: usize = 256;
+/// Secret key of this install, generated on first use: the host signs the streamed events
+/// with it and viewers prove their identity with it.
+pub const RELAY_KEY_PATH: &str = "relay.key";
+/// Public keys of the viewers the host lets in, any viewer when empty.
+pub const RELAY_VIEWERS: &[&str] = &[];
+
+/// Race browser
+/// GraphQL endpoint of the Torii indexer of the world.
+pub const TORII_GRAPHQL_ENDPOINT: &str = "http://0.0.0.0:8080/graphql";
+/// Store events fetched per search, the oldest races are dropped past it.
+pub const TORII_EVENTS_LIMIT: usize = 10_000;
+pub const RACE_BROWSER_KEY: KeyCode = KeyCode::F8;
+
+/// Console
+pub const CONSOLE_KEY: KeyCode = KeyCode::Grave;
+/// Lines kept in the console, the oldest are dropped past it.
+pub const CONSOLE_HISTORY: usize = 200;
+
+/// Scripting
+/// Operations a script hook may run before it is aborted, so a runaway `on_tick` can't freeze
+/// the frame.
+pub const SCRIPT_MAX_OPERATIONS: u64 = 100_000;
+pub const SCRIPT_MAX_CALL_LEVELS: usize = 32;
+pub const SCRIPT_MAX_STRING_SIZE: usize = 4096;
+/// World systems a script can have waiting to be executed, its further calls are dropped.
+pub const SCRIPT_CALL_QUEUE: usize = 16;
+
+/// Train
+pub const TRAIN_NUM_RACERS: u32 = 16;
+pub const TRAIN_TIME_STEP: f32 = 1.0 / 60.0;
+
+/// Bench
+pub const BENCH_SYNC_DURATION: f32 = 60.0;
+
+/// Metrics
+/// Only reachable from this machine, the metrics name the account and world addresses.
+pub const METRICS_ENDPOINT: &str = "127.0.0.1:9100";
+/// Bound instead with `--metrics-public`, for a Prometheus scraping from another machine.
+pub const METRICS_PUBLIC_ENDPOINT: &str = "0.0.0.0:9100";
+
+/// Logging
+pub const LOG_DIRECTORY: &str = "logs";
+pub const LOG_FILE_PREFIX: &str = "drive-ai.log";
+pub const LOG_FILTER: &str = "info,wgpu=error,naga=warn";
@@ -0,0 +1,267 @@
+use crate::backend::{Backend, DojoBackend};
+use crate::configs;
+use crate::input_map::{Action, ActionState};
+use crate::tasks::tokio_tasks_plugin;
+use crate::world::{Completion, Generation, Position, Racer, Score, Vehicle, WorldComponent};
+use bevy::prelude::*;
+use bevy_inspector_egui::{bevy_egui::EguiContexts, egui};
+use bevy_tokio_tasks::{TokioTasksPlugin, TokioTasksRuntime};
+use eyre::{eyre, Result};
+use starknet::core::types::FieldElement;
+use starknet::core::utils::cairo_short_string_to_felt;
+use std::str::FromStr;
+use tokio::sync::mpsc;
+
+/// Developer console toggled with [`Action::Console`], executing world systems and reading
+/// components through the dojo [`Backend`], e.g. `exec drive 0x12` or `query Position 0x12 3`.
+pub struct DevConsolePlugin;
+
+impl Plugin for DevConsolePlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<TokioTasksPlugin>() {
+            app.add_plugin(tokio_tasks_plugin());
+        }
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        app.insert_resource(DevConsole {
+            visible: false,
+            input: String::new(),
+            lines: Vec::new(),
+            sender,
+            receiver,
+        })
+        .add_system(dev_console_system);
+    }
+}
+
+/// A line typed in the console.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConsoleCommand {
+    /// `exec <system> <calldata>..`
+    Exec {
+        system: String,
+        calldata: Vec<FieldElement>,
+    },
+    /// `query <component> <keys>..`
+    Query {
+        component: String,
+        keys: Vec<FieldElement>,
+    },
+}
+
+impl FromStr for ConsoleCommand {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_whitespace();
+        let verb = words.next().ok_or("Empty command")?;
+        let name = words
+            .next()
+            .ok_or_else(|| format!("Usage: {verb} <name> <felts>.."))?
+            .to_string();
+        let felts = words.map(parse_felt).collect::<Result<Vec<_>, _>>()?;
+        match verb {
+            "exec" => Ok(ConsoleCommand::Exec {
+                system: name,
+                calldata: felts,
+            }),
+            "query" => Ok(ConsoleCommand::Query {
+                component: name,
+                keys: felts,
+            }),
+            _ => Err(format!("Unknown command {verb}, expected exec or query")),
+        }
+    }
+}
+
+/// A hex or decimal felt, or a short string such as a model name.
+pub(crate) fn parse_felt(word: &str) -> Result<FieldElement, String> {
+    FieldElement::from_str(word)
+        .or_else(|_| cairo_short_string_to_felt(word))
+        .map_err(|_| format!("Invalid felt {word}"))
+}
+
+/// Values of a known component in its debug form, the raw felts otherwise.
+fn decode_component(component: &str, values: &[FieldElement]) -> Result<String> {
+    fn decode<T: WorldComponent + std::fmt::Debug>(values: &[FieldElement]) -> Result<String> {
+        Ok(format!("{:?}", T::decode(values)?))
+    }
+
+    match component {
+        Completion::NAME => decode::<Completion>(values),
+        Generation::NAME => decode::<Generation>(values),
+        Position::NAME => decode::<Position>(values),
+        Racer::NAME => decode::<Racer>(values),
+        Score::NAME => decode::<Score>(values),
+        Vehicle::NAME => decode::<Vehicle>(values),
+        _ => Ok(values
+            .iter()
+            .map(|value| format!("{value:#x}"))
+            .collect::<Vec<_>>()
+            .join(" ")),
+    }
+}
+
+async fn run(backend: &dyn DojoBackend, command: ConsoleCommand) -> Result<String> {
+    match command {
+        ConsoleCommand::Exec { system, calldata } => {
+            let execution = backend.execute(&system, calldata).await?;
+            Ok(match execution.block_number {
+                Some(block) => format!("Fee {:#x}, block {block}", execution.fee),
+                None => format!("Fee {:#x}, pending", execution.fee),
+            })
+        }
+        ConsoleCommand::Query { component, keys } => {
+            let values = backend.entity(&component, keys).await?;
+            if values.is_empty() {
+                return Err(eyre!("No {component} for these keys"));
+            }
+            decode_component(&component, &values)
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum ConsoleLine {
+    Input(String),
+    Output(String),
+    Error(String),
+}
+
+#[derive(Resource)]
+struct DevConsole {
+    visible: bool,
+    input: String,
+    /// Up to [`configs::CONSOLE_HISTORY`] lines, the oldest first.
+    lines: Vec<ConsoleLine>,
+    sender: mpsc::UnboundedSender<Result<String, String>>,
+    receiver: mpsc::UnboundedReceiver<Result<String, String>>,
+}
+
+impl DevConsole {
+    fn push(&mut self, line: ConsoleLine) {
+        self.lines.push(line);
+        let overflow = self.lines.len().saturating_sub(configs::CONSOLE_HISTORY);
+        self.lines.drain(..overflow);
+    }
+
+    fn submit(&mut self, backend: Option<&Backend>, runtime: &TokioTasksRuntime) {
+        let line = std::mem::take(&mut self.input);
+        if line.trim().is_empty() {
+            return;
+        }
+        self.push(ConsoleLine::Input(line.clone()));
+        let command = match line.parse::<ConsoleCommand>() {
+            Ok(command) => command,
+            Err(e) => return self.push(ConsoleLine::Error(e)),
+        };
+        let Some(backend) = backend else {
+            return self.push(ConsoleLine::Error("No world connected".to_string()));
+        };
+
+        let backend = backend.0.clone();
+        let sender = self.sender.clone();
+        runtime.spawn_background_task(move |_ctx| async move {
+            let output = run(backend.as_ref(), command)
+                .await
+                .map_err(|e| e.to_string());
+            let _ = sender.send(output);
+        });
+    }
+}
+
+fn dev_console_system(
+    mut contexts: EguiContexts,
+    actions: Res<ActionState>,
+    runtime: Res<TokioTasksRuntime>,
+    backend: Option<Res<Backend>>,
+    mut console: ResMut<DevConsole>,
+) {
+    while let Ok(output) = console.receiver.try_recv() {
+        let line = match output {
+            Ok(output) => ConsoleLine::Output(output),
+            Err(e) => ConsoleLine::Error(e),
+        };
+        console.push(line);
+    }
+    let opened = actions.just_pressed(Action::Console) && !console.visible;
+    if actions.just_pressed(Action::Console) {
+        console.visible = !console.visible;
+    }
+    if !console.visible {
+        return;
+    }
+
+    let mut submit = false;
+    egui::TopBottomPanel::top("dev_console")
+        .resizable(true)
+        .show(contexts.ctx_mut(), |ui| {
+            egui::ScrollArea::vertical()
+                .max_height(200.0)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for line in &console.lines {
+                        match line {
+                            ConsoleLine::Input(input) => ui.monospace(format!("> {input}")),
+                            ConsoleLine::Output(output) => ui.monospace(output),
+                            ConsoleLine::Error(e) => ui.colored_label(egui::Color32::RED, e),
+                        };
+                    }
+                });
+            let input = ui.add(
+                egui::TextEdit::singleline(&mut console.input)
+                    .desired_width(f32::INFINITY)
+                    .font(egui::TextStyle::Monospace)
+                    .hint_text("exec <system> <calldata>.. | query <component> <keys>.."),
+            );
+            submit = input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            // Other fields keep the focus they are given, the input takes it back after a command
+            if opened || submit {
+                input.request_focus();
+            }
+        });
+
+    if submit {
+        console.submit(backend.as_deref(), &runtime);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::MockBackend;
+    use crate::conversions::short_string_to_felt;
+
+    #[test]
+    fn test_commands_are_parsed_and_queries_decoded() {
+        assert_eq!(
+            "exec drive 0x12".parse(),
+            Ok(ConsoleCommand::Exec {
+                system: "drive".to_string(),
+                calldata: vec![FieldElement::from(0x12_u32)],
+            })
+        );
+        assert_eq!(
+            "query Position model 3".parse(),
+            Ok(ConsoleCommand::Query {
+                component: "Position".to_string(),
+                keys: vec![short_string_to_felt("model"), FieldElement::THREE],
+            })
+        );
+        assert!("drive 0x12".parse::<ConsoleCommand>().is_err());
+        assert!("exec".parse::<ConsoleCommand>().is_err());
+
+        let mock = MockBackend::default();
+        let keys = vec![FieldElement::ONE, FieldElement::TWO];
+        mock.set_entity(Position::NAME, keys.clone(), vec![FieldElement::THREE; 2]);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let query = ConsoleCommand::Query {
+            component: Position::NAME.to_string(),
+            keys,
+        };
+        let output = runtime.block_on(run(&mock, query)).unwrap();
+        assert_eq!(output, format!("{:?}", Position { x: 3, y: 3 }));
+    }
+}
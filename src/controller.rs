@@ -0,0 +1,334 @@
+use crate::backend::{Backend, DojoBackend};
+use crate::car::UpdateCar;
+use crate::configs;
+use crate::conversions::dojo_to_bevy_coordinate;
+use crate::dojo::{DojoTasks, ExternalDriver};
+use crate::enemy::UpdateEnemies;
+use crate::input_map::{Action, ActionState};
+use crate::metrics::Metrics;
+use crate::resources::{HoldDrive, Racers};
+use crate::speed_zone::{SpeedZones, ZoneEffect};
+use crate::system_call::{CairoEnum, SystemCall};
+use crate::tasks::{tokio_tasks_plugin, SupervisedTask};
+use crate::world::{Decode, Vehicle};
+use bevy::log;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_tokio_tasks::{TokioTasksPlugin, TokioTasksRuntime};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use starknet::core::types::FieldElement;
+use std::sync::Arc;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, mpsc};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{error, info_span, Instrument};
+
+/// WebSocket server on [`configs::CONTROLLER_ENDPOINT`] letting an external process drive the
+/// cars: each [`ControllerCommand`] is sent as a `drive_manual` transaction and every vehicle
+/// sync is streamed back as an [`Observation`]. The `drive` system is no longer sent on sync ticks.
+/// The first racer can also be steered with [`Action::SteerLeft`] and [`Action::SteerRight`].
+pub struct ControllerPlugin;
+
+impl Plugin for ControllerPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<TokioTasksPlugin>() {
+            app.add_plugin(tokio_tasks_plugin());
+        }
+
+        let (observations, _) = broadcast::channel(configs::CONTROLLER_OBSERVATIONS_CAPACITY);
+
+        app.init_resource::<Metrics>()
+            .init_resource::<Racers>()
+            .init_resource::<DojoTasks>()
+            .insert_resource(ExternalDriver)
+            .insert_resource(Observations(observations))
+            .add_startup_system(serve_controller)
+            .add_startup_system(steer_with_actions_thread)
+            .add_system(steer_with_actions)
+            .add_system(stream_observations);
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Steer {
+    Straight,
+    Left,
+    Right,
+}
+
+impl CairoEnum for Steer {
+    /// Index of the matching `Direction` variant.
+    fn variant_index(self) -> u8 {
+        match self {
+            Steer::Straight => 0,
+            Steer::Left => 1,
+            Steer::Right => 2,
+        }
+    }
+}
+
+/// Message received from a controller, e.g. `{"steer": "left"}`.
+/// Without `model_id` the first racer is driven.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct ControllerCommand {
+    pub model_id: Option<FieldElement>,
+    pub steer: Steer,
+}
+
+/// Message sent to controllers after each vehicle sync, in dojo grid coordinates.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Observation {
+    pub model_id: FieldElement,
+    pub x: f32,
+    pub y: f32,
+    /// Radians, negative to the left.
+    pub steer: f32,
+    /// Limited by the speed zone the car is in, if any.
+    pub speed: f32,
+    pub zone: Option<ZoneEffect>,
+    /// Last known position of each enemy of the racer, by enemy id.
+    pub enemies: Vec<[f32; 2]>,
+}
+
+#[derive(Resource)]
+struct Observations(broadcast::Sender<String>);
+
+#[derive(Resource)]
+struct SteerCommand(mpsc::Sender<Steer>);
+
+fn serve_controller(
+    backend: Res<Backend>,
+    metrics: Res<Metrics>,
+    racers: Res<Racers>,
+    observations: Res<Observations>,
+    runtime: ResMut<TokioTasksRuntime>,
+) {
+    let backend = backend.0.clone();
+    let metrics = metrics.clone();
+    let default_model_id = racers.0.first().copied();
+    let observations = observations.0.clone();
+
+    runtime.spawn_background_task(move |_ctx| async move {
+        let listener = match TcpListener::bind(configs::CONTROLLER_ENDPOINT).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!(error = %e, endpoint = configs::CONTROLLER_ENDPOINT, "Bind controller endpoint");
+                return;
+            }
+        };
+
+        loop {
+            match listener.accept().await {
+                Ok((stream, address)) => {
+                    let backend = backend.clone();
+                    let metrics = metrics.clone();
+                    let observations = observations.subscribe();
+                    tokio::spawn(
+                        async move {
+                            if let Err(e) = handle_controller(
+                                stream,
+                                backend,
+                                metrics,
+                                default_model_id,
+                                observations,
+                            )
+                            .await
+                            {
+                                error!(error = %e, "Controller connection");
+                            }
+                        }
+                        .instrument(info_span!("controller", %address)),
+                    );
+                }
+                Err(e) => error!(error = %e, "Accept controller connection"),
+            }
+        }
+    });
+}
+
+async fn handle_controller(
+    stream: TcpStream,
+    backend: Arc<dyn DojoBackend>,
+    metrics: Metrics,
+    default_model_id: Option<FieldElement>,
+    mut observations: broadcast::Receiver<String>,
+) -> Result<(), tokio_tungstenite::tungstenite::Error> {
+    let (mut sink, mut messages) = tokio_tungstenite::accept_async(stream).await?.split();
+
+    loop {
+        tokio::select! {
+            message = messages.next() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => return Ok(()),
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Err(e),
+                };
+
+                match serde_json::from_str::<ControllerCommand>(&text) {
+                    Ok(command) => match command.model_id.or(default_model_id) {
+                        Some(model_id) => {
+                            drive_manual(backend.as_ref(), &metrics, model_id, command.steer).await;
+                        }
+                        None => error!(message = %text, "Controller command without a racer"),
+                    },
+                    Err(e) => error!(error = %e, message = %text, "Parse controller command"),
+                }
+            }
+            observation = observations.recv() => match observation {
+                Ok(observation) => sink.send(Message::Text(observation)).await?,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("Controller lagging behind, skipped {skipped} observations");
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+        }
+    }
+}
+
+fn steer_with_actions_thread(
+    mut commands: Commands,
+    backend: Res<Backend>,
+    metrics: Res<Metrics>,
+    racers: Res<Racers>,
+    runtime: ResMut<TokioTasksRuntime>,
+    mut tasks: ResMut<DojoTasks>,
+) {
+    let Some(model_id) = racers.0.first().copied() else {
+        log::error!("Steer with the actions: no racer to steer");
+        return;
+    };
+    let (tx, rx) = mpsc::channel::<Steer>(8);
+    commands.insert_resource(SteerCommand(tx));
+
+    let backend = backend.0.clone();
+    let metrics = metrics.clone();
+    let rx = Arc::new(tokio::sync::Mutex::new(rx));
+    let task = SupervisedTask::spawn(runtime.runtime(), "drive_manual", move |mut stop| {
+        let backend = backend.clone();
+        let metrics = metrics.clone();
+        let rx = rx.clone();
+        async move {
+            let mut rx = rx.lock().await;
+            while let Some(steer) = stop.recv(&mut rx).await {
+                drive_manual(backend.as_ref(), &metrics, model_id, steer).await;
+            }
+        }
+    });
+    tasks.0.push(task);
+}
+
+/// Sends the steering of the actions whenever it changes, once the race started.
+fn steer_with_actions(
+    actions: Option<Res<ActionState>>,
+    hold_drive: Option<Res<HoldDrive>>,
+    steer_command: Option<Res<SteerCommand>>,
+    mut last_steer: Local<Option<Steer>>,
+) {
+    let (Some(actions), Some(steer_command)) = (actions, steer_command) else {
+        return;
+    };
+    if hold_drive.is_some() {
+        return;
+    }
+    let steer = match (
+        actions.pressed(Action::SteerLeft),
+        actions.pressed(Action::SteerRight),
+    ) {
+        (true, false) => Steer::Left,
+        (false, true) => Steer::Right,
+        _ => Steer::Straight,
+    };
+    if *last_steer.get_or_insert(Steer::Straight) == steer {
+        return;
+    }
+    if let Err(e) = steer_command.0.try_send(steer) {
+        log::error!("Send steer command: {e}");
+        return;
+    }
+    *last_steer = Some(steer);
+}
+
+async fn drive_manual(
+    backend: &dyn DojoBackend,
+    metrics: &Metrics,
+    model_id: FieldElement,
+    steer: Steer,
+) {
+    let span = info_span!("dojo_call", call = "drive_manual", model = %model_id);
+    metrics.drive_calls.inc();
+    let timer = metrics
+        .sync_latency
+        .with_label_values(&["drive_manual"])
+        .start_timer();
+    let result = backend
+        .execute_call(SystemCall::drive_manual(model_id, steer))
+        .instrument(span.clone())
+        .await;
+    timer.observe_duration();
+
+    if let Err(e) = result {
+        metrics.failures.with_label_values(&["drive_manual"]).inc();
+        error!(parent: &span, error = %e, "Run drive_manual system");
+    }
+}
+
+fn stream_observations(
+    observations: Res<Observations>,
+    mut update_car: EventReader<UpdateCar>,
+    mut update_enemies: EventReader<UpdateEnemies>,
+    zones: Option<Res<SpeedZones>>,
+    mut enemies: Local<HashMap<FieldElement, Vec<[f32; 2]>>>,
+) {
+    for enemy in update_enemies.iter().flat_map(|e| e.0.iter()) {
+        let Ok(index) = u32::try_from(enemy.enemy_id) else {
+            continue;
+        };
+        // As many as the enemies of the run, which can change between runs
+        let positions = enemies.entry(enemy.model_id).or_default();
+        let index = index as usize;
+        if positions.len() <= index {
+            positions.resize(index + 1, [0.0; 2]);
+        }
+        positions[index] = enemy.position.to_array();
+    }
+
+    if observations.0.receiver_count() == 0 {
+        update_car.clear();
+        return;
+    }
+
+    for e in update_car.iter() {
+        let Ok(vehicle) = Vehicle::decode(&e.vehicle) else {
+            continue;
+        };
+        let (x, y) = (vehicle.position.x.to_f32(), vehicle.position.y.to_f32());
+        let mut speed = vehicle.speed.to_f32();
+        let (_, track_y) = dojo_to_bevy_coordinate(x, y);
+        let zone = zones.as_ref().and_then(|zones| zones.effect_at(track_y));
+        if let Some(zone) = zone {
+            // Zones are in pixels per second
+            speed =
+                zone.apply(speed * configs::DOJO_TO_BEVY_RATIO_Y) / configs::DOJO_TO_BEVY_RATIO_Y;
+        }
+        let observation = Observation {
+            model_id: e.model_id,
+            x,
+            y,
+            steer: vehicle.steer.to_f32(),
+            speed,
+            zone,
+            enemies: enemies.get(&e.model_id).cloned().unwrap_or_default(),
+        };
+
+        match serde_json::to_string(&observation) {
+            Ok(observation) => {
+                // Only fails when every controller disconnected in the meantime.
+                let _ = observations.0.send(observation);
+            }
+            Err(e) => log::error!("Serialize observation: {e}"),
+        }
+    }
+}
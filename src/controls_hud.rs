@@ -0,0 +1,97 @@
+use crate::car::{Car, SyncedVehicle};
+use crate::configs;
+use crate::locale::Locale;
+use crate::sensors::{SensorSet, Sensors};
+use crate::world::{Decode, Vehicle};
+use bevy::prelude::*;
+use bevy_inspector_egui::{bevy_egui::EguiContexts, egui};
+use std::f32::consts::FRAC_PI_2;
+
+/// Steering wheel dial and throttle bar of the watched car, from its last synced `Vehicle`, so
+/// the controls show even when the car barely moves.
+pub struct ControlsHudPlugin;
+
+impl Plugin for ControlsHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Locale>()
+            .add_system(controls_hud_system.after(SensorSet));
+    }
+}
+
+/// Steering wheel turn, in radians, for a steer of the world: its ±π/2 range turns the wheel
+/// by [`configs::CONTROLS_HUD_WHEEL_LOCK`] either way.
+fn wheel_angle(steer: f32) -> f32 {
+    steer.clamp(-FRAC_PI_2, FRAC_PI_2) / FRAC_PI_2 * configs::CONTROLS_HUD_WHEEL_LOCK
+}
+
+/// Share of the throttle bar filled at `speed`.
+fn throttle(speed: f32) -> f32 {
+    (speed.abs() / configs::CONTROLS_HUD_MAX_SPEED).min(1.0)
+}
+
+fn controls_hud_system(
+    mut contexts: EguiContexts,
+    locale: Res<Locale>,
+    sensors: Option<Res<Sensors>>,
+    cars: Query<&SyncedVehicle, With<Car>>,
+) {
+    let Some(Ok(synced)) = sensors
+        .and_then(|sensors| sensors.car)
+        .map(|car| cars.get(car))
+    else {
+        return;
+    };
+    let Ok(vehicle) = Vehicle::decode(&synced.0) else {
+        return;
+    };
+    let steer = vehicle.steer.to_f32_exact();
+    let speed = vehicle.speed.to_f32_exact();
+
+    let size = configs::CONTROLS_HUD_SIZE;
+    egui::Area::new("controls_hud")
+        .anchor(egui::Align2::CENTER_BOTTOM, egui::vec2(0.0, -10.0))
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                let (rect, _) =
+                    ui.allocate_exact_size(egui::vec2(size, size), egui::Sense::hover());
+                let painter = ui.painter_at(rect);
+                let radius = size / 2.0 - 2.0;
+                let stroke = egui::Stroke::new(3.0, egui::Color32::WHITE);
+                painter.circle_stroke(rect.center(), radius, stroke);
+                // The top spoke turns clockwise when steering right
+                let angle = wheel_angle(steer);
+                let spoke = egui::vec2(angle.sin(), -angle.cos()) * radius;
+                painter.line_segment([rect.center(), rect.center() + spoke], stroke);
+                painter.circle_filled(rect.center(), 4.0, egui::Color32::WHITE);
+
+                ui.vertical(|ui| {
+                    let degrees = format!("{:+.0}", steer.to_degrees());
+                    ui.label(locale.format("hud.steer", &[("steer", &degrees)]));
+                    let speed_text = format!("{speed:.0}");
+                    ui.add(
+                        egui::ProgressBar::new(throttle(speed))
+                            .desired_width(size * 1.5)
+                            .text(locale.format("hud.throttle", &[("speed", &speed_text)])),
+                    );
+                });
+            });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_controls_are_scaled_to_their_widgets() {
+        assert_eq!(wheel_angle(0.0), 0.0);
+        assert_eq!(wheel_angle(FRAC_PI_2), configs::CONTROLS_HUD_WHEEL_LOCK);
+        assert_eq!(
+            wheel_angle(-FRAC_PI_2 * 2.0),
+            -configs::CONTROLS_HUD_WHEEL_LOCK
+        );
+
+        assert_eq!(throttle(configs::CONTROLS_HUD_MAX_SPEED / 2.0), 0.5);
+        assert_eq!(throttle(configs::CONTROLS_HUD_MAX_SPEED * 2.0), 1.0);
+    }
+}
@@ -0,0 +1,159 @@
+use crate::configs;
+use crate::ROAD_X_MIN;
+use starknet_ff::FieldElement;
+
+/// Big-endian `(p - 1) / 2`, i.e. 2^250 + 17 * 2^191: felts above it are read as negative.
+const HALF_PRIME: [u8; 32] = {
+    let mut bytes = [0; 32];
+    bytes[0] = 0x04;
+    bytes[7] = 0x08;
+    bytes[8] = 0x80;
+    bytes
+};
+
+/// Magnitude of a felt read as signed, and whether it is negative: `0 - x` computed on chain
+/// wraps around to a felt close to the field modulus.
+fn signed_magnitude(val: FieldElement) -> (FieldElement, bool) {
+    if val.to_bytes_be() > HALF_PRIME {
+        (FieldElement::ZERO - val, true)
+    } else {
+        (val, false)
+    }
+}
+
+/// Felt read as a signed integer, `None` when it doesn't fit.
+pub fn felt_to_i128(val: FieldElement) -> Option<i128> {
+    let (magnitude, negative) = signed_magnitude(val);
+    let magnitude = i128::try_from(u128::try_from(magnitude).ok()?).ok()?;
+    Some(if negative { -magnitude } else { magnitude })
+}
+
+/// Integer part of a signed 64.64 fixed point felt, read from its big-endian limbs: dropping
+/// the lowest limb divides by 2^64.
+pub fn fixed_to_f32(val: FieldElement) -> f32 {
+    let (val, negative) = signed_magnitude(val);
+    if negative {
+        return -fixed_to_f32(val);
+    }
+    let bytes = val.to_bytes_be();
+    let high = u64::from_be_bytes(bytes[..8].try_into().unwrap());
+    let integer = u128::from_be_bytes(bytes[8..24].try_into().unwrap());
+    if high == 0 {
+        integer as f32
+    } else {
+        (high as f64 * 2f64.powi(128) + integer as f64) as f32
+    }
+}
+
+/// Like [`fixed_to_f32`] but keeping the fractional part, for values such as the steering
+/// angle that are mostly below 1.
+pub fn fixed_to_f32_exact(val: FieldElement) -> f32 {
+    let (val, negative) = signed_magnitude(val);
+    let fraction = u64::from_be_bytes(val.to_bytes_be()[24..].try_into().unwrap());
+    let value = fixed_to_f32(val) + (fraction as f64 / 2f64.powi(64)) as f32;
+    if negative {
+        -value
+    } else {
+        value
+    }
+}
+
+/// Encodes a non-negative value as a 64.64 fixed point felt, the inverse of [`fixed_to_f32`]
+/// for whole numbers.
+pub fn f32_to_fixed(val: f32) -> FieldElement {
+    FieldElement::from((val as f64 * 2f64.powi(64)) as u128)
+}
+
+pub fn dojo_to_bevy_coordinate(dojo_x: f32, dojo_y: f32) -> (f32, f32) {
+    let bevy_x = dojo_x * configs::DOJO_TO_BEVY_RATIO_X + ROAD_X_MIN;
+    let bevy_y = dojo_y * configs::DOJO_TO_BEVY_RATIO_Y;
+
+    // log::info!("dojo_x: {}, dojo_y: {}", dojo_x, dojo_y);
+    // log::info!("bevy_x: {}, bevy_y: {}", bevy_x, bevy_y);
+
+    (bevy_x, bevy_y)
+}
+
+/// Inverse of [`dojo_to_bevy_coordinate`], for positions authored by the client.
+pub fn bevy_to_dojo_coordinate(bevy_x: f32, bevy_y: f32) -> (f32, f32) {
+    let dojo_x = (bevy_x - ROAD_X_MIN) / configs::DOJO_TO_BEVY_RATIO_X;
+    let dojo_y = bevy_y / configs::DOJO_TO_BEVY_RATIO_Y;
+
+    (dojo_x, dojo_y)
+}
+
+/// Encodes an ASCII string of at most 31 characters as a cairo short string.
+pub fn short_string_to_felt(value: &str) -> FieldElement {
+    FieldElement::from_byte_slice_be(value.as_bytes()).unwrap()
+}
+
+/// Decodes a cairo short string, e.g. a model name. Non UTF-8 bytes are replaced.
+pub fn felt_to_short_string(value: FieldElement) -> String {
+    let bytes = value.to_bytes_be();
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[start..]).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num::bigint::BigUint;
+    use num::ToPrimitive;
+    use rand::Rng;
+
+    #[test]
+    fn test_coordinate_round_trip() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let dojo_x = rng.gen_range(0.0..configs::DOJO_GRID_WIDTH);
+            let dojo_y = rng.gen_range(0.0..configs::DOJO_GRID_HEIGHT);
+
+            let (bevy_x, bevy_y) = dojo_to_bevy_coordinate(dojo_x, dojo_y);
+            let (x, y) = bevy_to_dojo_coordinate(bevy_x, bevy_y);
+            assert!((x - dojo_x).abs() < 1e-3, "x: {dojo_x} -> {bevy_x} -> {x}");
+            assert!((y - dojo_y).abs() < 1e-3, "y: {dojo_y} -> {bevy_y} -> {y}");
+
+            let (round_x, round_y) = dojo_to_bevy_coordinate(x, y);
+            assert!((round_x - bevy_x).abs() < 1e-3);
+            assert!((round_y - bevy_y).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_fixed_round_trip() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let value = rng.gen_range(0..1_000_000) as f32;
+            assert_eq!(fixed_to_f32(f32_to_fixed(value)), value);
+        }
+        assert_eq!(f32_to_fixed(200.0), FieldElement::from(200_u128 << 64));
+    }
+
+    #[test]
+    fn test_fixed_matches_big_integer_division() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let value = FieldElement::from(rng.gen::<u128>());
+            let expected = (BigUint::from_bytes_be(&value.to_bytes_be()) >> 64_u32).to_f32();
+            assert_eq!(Some(fixed_to_f32(value)), expected);
+        }
+        assert_eq!(
+            fixed_to_f32(FieldElement::from(123_u128 << 64 | 1 << 63)),
+            123.0
+        );
+    }
+
+    #[test]
+    fn test_felts_past_half_the_modulus_are_negative() {
+        let minus = |value: u128| FieldElement::ZERO - FieldElement::from(value);
+        assert_eq!(felt_to_i128(minus(20)), Some(-20));
+        assert_eq!(felt_to_i128(FieldElement::from(20_u8)), Some(20));
+        assert_eq!(felt_to_i128(minus(0)), Some(0));
+        assert_eq!(felt_to_i128(FieldElement::from(u128::MAX)), None);
+
+        assert_eq!(fixed_to_f32(minus(3 << 64)), -3.0);
+        assert_eq!(fixed_to_f32(minus(200 << 64)), -200.0);
+        assert_eq!(fixed_to_f32_exact(minus(3 << 64 | 1 << 63)), -3.5);
+        assert_eq!(fixed_to_f32_exact(FieldElement::from(1_u128 << 62)), 0.25);
+    }
+}
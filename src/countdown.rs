@@ -0,0 +1,150 @@
+use crate::car::Car;
+use crate::configs;
+use crate::locale::Locale;
+use crate::population::{GenerationSet, RespawnPopulation};
+use crate::resources::HoldDrive;
+use bevy::prelude::*;
+use bevy_inspector_egui::{bevy_egui::EguiContexts, egui};
+
+/// Holds every race for a 3-2-1 countdown once its cars are spawned: enemies spawn and get
+/// synced meanwhile, but no `drive` is sent before the start, so races don't start whenever the
+/// first sync tick fires.
+pub struct CountdownPlugin;
+
+impl Plugin for CountdownPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StartCountdown>()
+            .insert_resource(HoldDrive)
+            .add_event::<RespawnPopulation>()
+            .add_systems(
+                (restart_countdown, run_countdown)
+                    .chain()
+                    .after(GenerationSet),
+            );
+    }
+}
+
+/// Countdown digits in the middle of the screen.
+pub struct CountdownHudPlugin;
+
+impl Plugin for CountdownHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Locale>()
+            .add_system(countdown_hud_system.after(run_countdown));
+    }
+}
+
+#[derive(Resource, Default, Debug, PartialEq)]
+pub enum StartCountdown {
+    /// Until the cars of the race are spawned.
+    #[default]
+    Waiting,
+    /// Seconds left before the cars are released.
+    Counting(f32),
+    /// Seconds since the cars were released.
+    Racing(f32),
+}
+
+impl StartCountdown {
+    pub fn is_racing(&self) -> bool {
+        matches!(self, StartCountdown::Racing(_))
+    }
+}
+
+fn restart_countdown(
+    mut commands: Commands,
+    mut respawn_population: EventReader<RespawnPopulation>,
+    mut countdown: ResMut<StartCountdown>,
+) {
+    if respawn_population.iter().count() > 0 {
+        *countdown = StartCountdown::Waiting;
+        // Held before the cars exist, so the first sync tick can't drive them
+        commands.insert_resource(HoldDrive);
+    }
+}
+
+fn run_countdown(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut countdown: ResMut<StartCountdown>,
+    cars: Query<(), With<Car>>,
+) {
+    match *countdown {
+        StartCountdown::Waiting if !cars.is_empty() => {
+            *countdown = StartCountdown::Counting(configs::START_COUNTDOWN_SECONDS);
+        }
+        StartCountdown::Waiting => {}
+        StartCountdown::Counting(left) => {
+            let left = left - time.delta_seconds();
+            if left <= 0.0 {
+                *countdown = StartCountdown::Racing(0.0);
+                commands.remove_resource::<HoldDrive>();
+            } else {
+                *countdown = StartCountdown::Counting(left);
+            }
+        }
+        StartCountdown::Racing(since) => {
+            *countdown = StartCountdown::Racing(since + time.delta_seconds());
+        }
+    }
+}
+
+fn countdown_hud_system(
+    mut contexts: EguiContexts,
+    locale: Res<Locale>,
+    countdown: Res<StartCountdown>,
+) {
+    let text = match *countdown {
+        StartCountdown::Counting(left) => format!("{}", left.ceil()),
+        StartCountdown::Racing(since) if since < configs::START_GO_SECONDS => {
+            locale.text("hud.go").to_string()
+        }
+        _ => return,
+    };
+    egui::Area::new("start_countdown")
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(egui::RichText::new(text).size(80.0).strong());
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::utils::Duration;
+
+    #[test]
+    fn test_drive_is_held_until_the_countdown_ends() {
+        let mut app = App::new();
+        app.init_resource::<Time>().add_plugin(CountdownPlugin);
+        let start = app.world.resource::<Time>().startup();
+        let update_after = |app: &mut App, seconds: f32| {
+            app.world
+                .resource_mut::<Time>()
+                .update_with_instant(start + Duration::from_secs_f32(seconds));
+            app.update();
+        };
+
+        // Held while waiting for the cars
+        update_after(&mut app, 0.0);
+        update_after(&mut app, 5.0);
+        assert_eq!(
+            *app.world.resource::<StartCountdown>(),
+            StartCountdown::Waiting
+        );
+        assert!(app.world.contains_resource::<HoldDrive>());
+
+        app.world.spawn(Car);
+        update_after(&mut app, 6.0);
+        update_after(&mut app, 7.0);
+        assert!(app.world.contains_resource::<HoldDrive>());
+        update_after(&mut app, 6.0 + configs::START_COUNTDOWN_SECONDS);
+        assert!(app.world.resource::<StartCountdown>().is_racing());
+        assert!(!app.world.contains_resource::<HoldDrive>());
+
+        // Held again for the next generation
+        app.world.send_event(RespawnPopulation { generation: 1 });
+        update_after(&mut app, 10.0);
+        assert!(app.world.contains_resource::<HoldDrive>());
+    }
+}
@@ -0,0 +1,109 @@
+use crate::configs;
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use starknet::core::types::FieldElement;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::str::FromStr;
+use tracing::info;
+
+/// Settings written by `deploy`, overriding the matching `configs` constants.
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq)]
+pub struct LocalConfig {
+    pub world_address: Option<FieldElement>,
+}
+
+impl LocalConfig {
+    /// Empty when `path` doesn't exist yet.
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// The deployed world, [`configs::WORLD_ADDRESS`] until `deploy` ran.
+    pub fn world_address(&self) -> FieldElement {
+        self.world_address
+            .unwrap_or_else(|| FieldElement::from_str(configs::WORLD_ADDRESS).unwrap())
+    }
+}
+
+/// Builds the Cairo package and migrates it with `sozo` to the node at
+/// [`configs::JSON_RPC_ENDPOINT`], declaring and registering the drive systems and components.
+/// The new world address is saved to [`configs::LOCAL_CONFIG_PATH`] for the next runs.
+pub fn deploy() -> Result<FieldElement> {
+    sozo(&["build"])?;
+    let output = sozo(&["migrate", "--rpc-url", configs::JSON_RPC_ENDPOINT])?;
+    let world_address = parse_world_address(&output)
+        .ok_or_else(|| eyre!("No world address in the migration output:\n{output}"))?;
+
+    let path = Path::new(configs::LOCAL_CONFIG_PATH);
+    let mut config = LocalConfig::load(path);
+    config.world_address = Some(world_address);
+    config.save(path)?;
+    let world = format!("{world_address:#x}");
+    info!(%world, path = %path.display(), "World deployed");
+
+    Ok(world_address)
+}
+
+/// Runs `sozo` in the Scarb package, returns its standard output.
+fn sozo(args: &[&str]) -> Result<String> {
+    info!(args = ?args, "Run sozo");
+    let output = Command::new(configs::SOZO_BIN)
+        .args(args)
+        .output()
+        .map_err(|e| eyre!("Run `{} {}`: {e}", configs::SOZO_BIN, args.join(" ")))?;
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    if !output.status.success() {
+        return Err(eyre!(
+            "`{} {}` failed: {}\n{stdout}{}",
+            configs::SOZO_BIN,
+            args.join(" "),
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(stdout)
+}
+
+/// Address from the `World at address 0x...` line `sozo migrate` ends with.
+fn parse_world_address(output: &str) -> Option<FieldElement> {
+    output
+        .lines()
+        .filter(|line| line.contains("World at address"))
+        .filter_map(|line| line.split_whitespace().find(|word| word.starts_with("0x")))
+        .filter_map(|address| FieldElement::from_hex_be(address.trim_end_matches('.')).ok())
+        .last()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_world_address_from_migration_output() {
+        let output = "\
+[1] Building World state...
+[2] Migrating...
+  > Registered 4 components
+  > Registered 6 systems
+
+🎉 Successfully migrated World at address 0x26065106fa319c3981618e7567480a50132f23932226a51c219ffb8e47daa84
+";
+        assert_eq!(
+            parse_world_address(output),
+            Some(FieldElement::from_str(configs::WORLD_ADDRESS).unwrap())
+        );
+        assert_eq!(parse_world_address("Nothing to migrate"), None);
+    }
+}
@@ -0,0 +1,69 @@
+use crate::configs::{self, DifficultyPreset};
+use crate::enemy::{EnemyCount, Lanes};
+use bevy::prelude::*;
+use clap::ValueEnum;
+
+/// Keeps the [`Lanes`] of the road and the [`EnemyCount`] in line with the [`Difficulty`], the
+/// enemies read them when they are spawned.
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Difficulty>()
+            .init_resource::<Lanes>()
+            .init_resource::<EnemyCount>()
+            .add_system(apply_difficulty.run_if(resource_changed::<Difficulty>()));
+    }
+}
+
+/// Named [`DifficultyPreset`] of the `/// Difficulty` section of the config file, picked with
+/// `--difficulty` or in the models menu, applied from the next run on.
+#[derive(Resource, ValueEnum, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Nightmare,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Nightmare];
+
+    pub fn preset(self) -> DifficultyPreset {
+        match self {
+            Difficulty::Easy => configs::DIFFICULTY_EASY,
+            Difficulty::Normal => configs::DIFFICULTY_NORMAL,
+            Difficulty::Nightmare => configs::DIFFICULTY_NIGHTMARE,
+        }
+    }
+}
+
+fn apply_difficulty(
+    difficulty: Res<Difficulty>,
+    mut lanes: ResMut<Lanes>,
+    mut enemy_count: ResMut<EnemyCount>,
+) {
+    let preset = difficulty.preset();
+    *lanes = Lanes::centered(preset.road_width, lanes.count);
+    // The count given on start up holds until another difficulty is picked
+    if !difficulty.is_added() {
+        *enemy_count = EnemyCount(preset.enemy_count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normal_lanes_cover_the_road() {
+        let lanes = Lanes::centered(Difficulty::Normal.preset().road_width, 4);
+        assert_eq!(lanes.x_min, configs::ROAD_X_MIN);
+        assert_eq!(lanes.x_max, configs::ROAD_X_MAX);
+
+        // Narrower roads keep the same center
+        let narrow = Lanes::centered(Difficulty::Nightmare.preset().road_width, 4);
+        assert!(narrow.width() < lanes.width());
+        assert_eq!(narrow.x_min + narrow.x_max, lanes.x_min + lanes.x_max);
+    }
+}
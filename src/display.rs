@@ -0,0 +1,166 @@
+use crate::configs;
+use bevy::prelude::*;
+use bevy::render::camera::ScalingMode;
+use bevy::utils::{Duration, Instant};
+use bevy::window::{MonitorSelection, PresentMode, PrimaryWindow, WindowMode, WindowPosition};
+use serde::{Deserialize, Serialize};
+
+/// Applies the [`DisplaySettings`] to the primary window whenever they change, and holds frames
+/// to their frame limit. The world keeps its coordinates at any resolution: cameras using
+/// [`road_projection`] scale it to their viewport.
+pub struct DisplayPlugin;
+
+impl Plugin for DisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DisplaySettings>()
+            .add_system(apply_display_settings.run_if(resource_changed::<DisplaySettings>()))
+            .add_system(limit_frame_rate.in_base_set(CoreSet::Last));
+    }
+}
+
+/// Window mode, size and monitor, saved in the `display` section of the settings.
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct DisplaySettings {
+    pub mode: WindowMode,
+    /// Width and height of the window, in logical pixels, when it isn't borderless.
+    pub resolution: (u32, u32),
+    /// Index of the monitor the window opens on, the primary one when `None`.
+    pub monitor: Option<usize>,
+    pub vsync: bool,
+    /// Frames per second the app is held to, uncapped when `None`. Long runs syncing once a
+    /// second don't need more than a few.
+    pub frame_limit: Option<f32>,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            mode: WindowMode::Windowed,
+            resolution: (configs::WINDOW_WIDTH as u32, configs::WINDOW_HEIGHT as u32),
+            monitor: None,
+            vsync: true,
+            frame_limit: None,
+        }
+    }
+}
+
+impl DisplaySettings {
+    /// Centers the window on its monitor only when it changed since the `applied` settings, so
+    /// the window stays where it was moved otherwise.
+    pub fn apply(&self, window: &mut Window, applied: Option<&Self>) {
+        let (width, height) = self.resolution;
+        window.mode = self.mode;
+        window.resolution.set(width as f32, height as f32);
+        if applied.map_or(true, |applied| applied.monitor != self.monitor) {
+            let monitor = self
+                .monitor
+                .map_or(MonitorSelection::Primary, MonitorSelection::Index);
+            window.position = WindowPosition::Centered(monitor);
+        }
+        window.present_mode = if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        };
+    }
+
+    /// Shortest time a frame may take, if limited.
+    fn frame_duration(&self) -> Option<Duration> {
+        self.frame_limit
+            .map(|fps| Duration::from_secs_f32(1.0 / fps.max(configs::DISPLAY_MIN_FRAME_LIMIT)))
+    }
+}
+
+/// Projection showing at least [`configs::WINDOW_WIDTH`] by [`configs::WINDOW_HEIGHT`] of the
+/// world in its viewport, whatever its size: a wide window shows more of the roadside, a tall one
+/// more of the road ahead, never less of the track than the default window.
+pub fn road_projection() -> OrthographicProjection {
+    framed_projection(configs::WINDOW_WIDTH)
+}
+
+/// Projection showing at least `width` by [`configs::WINDOW_HEIGHT`] of the world in its viewport.
+pub fn framed_projection(width: f32) -> OrthographicProjection {
+    OrthographicProjection {
+        scaling_mode: ScalingMode::AutoMin {
+            min_width: width,
+            min_height: configs::WINDOW_HEIGHT,
+        },
+        ..Camera2dBundle::default().projection
+    }
+}
+
+fn apply_display_settings(
+    settings: Res<DisplaySettings>,
+    mut applied: Local<Option<DisplaySettings>>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    for mut window in windows.iter_mut() {
+        settings.apply(&mut window, applied.as_ref());
+    }
+    *applied = Some(*settings);
+}
+
+/// Sleeps the rest of the frame when it took less than [`DisplaySettings::frame_duration`].
+fn limit_frame_rate(settings: Res<DisplaySettings>, mut frame_end: Local<Option<Instant>>) {
+    if let (Some(duration), Some(end)) = (settings.frame_duration(), *frame_end) {
+        if let Some(left) = duration.checked_sub(end.elapsed()) {
+            std::thread::sleep(left);
+        }
+    }
+    *frame_end = Some(Instant::now());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_settings_apply_to_the_primary_window() {
+        let mut app = App::new();
+        app.add_plugin(DisplayPlugin);
+        let window_entity = app.world.spawn((Window::default(), PrimaryWindow)).id();
+        app.update();
+        let size = |app: &App| {
+            let window = app.world.get::<Window>(window_entity).unwrap();
+            (window.resolution.width(), window.resolution.height())
+        };
+        assert_eq!(size(&app), (configs::WINDOW_WIDTH, configs::WINDOW_HEIGHT));
+
+        *app.world.resource_mut::<DisplaySettings>() = DisplaySettings {
+            mode: WindowMode::BorderlessFullscreen,
+            resolution: (1920, 1080),
+            monitor: Some(1),
+            vsync: false,
+            frame_limit: Some(20.0),
+        };
+        app.update();
+        assert_eq!(size(&app), (1920.0, 1080.0));
+        let window = app.world.get::<Window>(window_entity).unwrap();
+        assert_eq!(window.mode, WindowMode::BorderlessFullscreen);
+        assert_eq!(
+            window.position,
+            WindowPosition::Centered(MonitorSelection::Index(1))
+        );
+        assert_eq!(window.present_mode, PresentMode::AutoNoVsync);
+
+        // A window moved by hand stays there when the monitor is kept
+        let moved = WindowPosition::At(IVec2::new(100, 100));
+        app.world.get_mut::<Window>(window_entity).unwrap().position = moved;
+        app.world.resource_mut::<DisplaySettings>().vsync = true;
+        app.update();
+        let window = app.world.get::<Window>(window_entity).unwrap();
+        assert_eq!(window.position, moved);
+        assert_eq!(window.present_mode, PresentMode::AutoVsync);
+
+        // Frames held to the limit
+        let start = Instant::now();
+        for _ in 0..3 {
+            app.update();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(100));
+
+        let json = serde_json::to_value(DisplaySettings::default()).unwrap();
+        assert_eq!(json["mode"], "Windowed");
+    }
+}
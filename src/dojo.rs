@@ -1,379 +1,2145 @@
+use crate::backend::{
+    Account, Backend, BlockHead, DojoBackend, Execution, FailoverBackend, WorldBackend,
+};
+use crate::boot::{BootProgress, BootStep, RetryBoot, StepStatus};
 use crate::car::Car;
+use crate::car::CarCrashed;
 use crate::car::Model;
 use crate::car::SpawnCar;
 use crate::car::UpdateCar;
 use crate::configs;
+use crate::conversions::{
+    bevy_to_dojo_coordinate, dojo_to_bevy_coordinate, f32_to_fixed, felt_to_short_string,
+    fixed_to_f32,
+};
+use crate::deploy::LocalConfig;
 use crate::enemy::SpawnEnemies;
-use crate::enemy::UpdateEnemy;
-use crate::ROAD_X_MIN;
-use bevy::ecs::system::SystemState;
-use bevy::log;
+use crate::enemy::{EnemyCount, EnemyPosition, EnemySeed, UpdateEnemies};
+use crate::finish::RaceFinished;
+use crate::history::{RunCost, RunFinished, ScoreSubmitted};
+use crate::locale::Locale;
+use crate::metrics::Metrics;
+use crate::population::{GenerationSet, RespawnPopulation};
+use crate::race::HeadToHead;
+use crate::resources::{HoldDrive, Racers};
+use crate::sync_scheduler::SyncScheduler;
+use crate::system_call::{Fixed, FixedVec2, SystemCall};
+use crate::tasks::{tokio_tasks_plugin, SupervisedTask};
+use crate::toast::Toasts;
+use crate::world::{Position, Vehicle, WorldComponent};
+use async_trait::async_trait;
+use bevy::app::AppExit;
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
+use bevy::utils::{Duration, HashMap};
+use bevy_inspector_egui::{bevy_egui::EguiContexts, egui};
 use bevy_rapier2d::prelude::*;
-use bevy_tokio_tasks::TaskContext;
 use bevy_tokio_tasks::{TokioTasksPlugin, TokioTasksRuntime};
-use dojo_client::contract::world::WorldContract;
-use num::bigint::BigUint;
-use num::{FromPrimitive, ToPrimitive};
+use futures_util::stream::{self, FuturesUnordered, StreamExt};
 use rand::Rng;
 use starknet::accounts::SingleOwnerAccount;
-use starknet::core::types::{BlockId, BlockTag, FieldElement};
+use starknet::core::types::FieldElement;
 use starknet::core::utils::cairo_short_string_to_felt;
 use starknet::providers::jsonrpc::HttpTransport;
-use starknet::providers::JsonRpcClient;
+use starknet::providers::{JsonRpcClient, Provider};
 use starknet::signers::{LocalWallet, SigningKey};
-use std::ops::Div;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
 use std::str::FromStr;
-use std::sync::Arc;
-use tokio::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{mpsc, watch};
+use tracing::{debug, error, info, info_span, warn, Instrument};
 use url::Url;
 
-pub fn rand_felt_fixed_point() -> FieldElement {
+/// Random start `x` on the left half of the road, snapped to the grid so the car rendered at
+/// spawn is where the world puts it.
+pub fn rand_spawn_x() -> FieldElement {
     let mut rng = rand::thread_rng();
-    ((rng.gen::<u128>() % 200) << 64).into()
+    let bevy_x = rng.gen_range(configs::ROAD_X_MIN..configs::ROAD_X_MIN + configs::ROAD_W / 2.0);
+    let (dojo_x, _) = bevy_to_dojo_coordinate(bevy_x, 0.0);
+    f32_to_fixed(dojo_x.floor())
 }
 
+/// Account on the node at `endpoint`, signing for `chain_id`.
+fn new_account(endpoint: &Url, signer: LocalSigner, chain_id: FieldElement) -> Account {
+    SingleOwnerAccount::new(
+        JsonRpcClient::new(HttpTransport::new(endpoint.clone())),
+        LocalWallet::from_signing_key(SigningKey::from_secret_scalar(signer.secret_key)),
+        signer.address,
+        chain_id,
+    )
+}
+
+/// Chain id of the first of `urls` answering within [`configs::DOJO_CHAIN_ID_TIMEOUT`],
+/// `default` when none can be reached.
+async fn fetch_chain_id(urls: &[Url], default: &str) -> FieldElement {
+    let timeout = std::time::Duration::from_secs_f32(configs::DOJO_CHAIN_ID_TIMEOUT);
+    for endpoint in urls {
+        let provider = JsonRpcClient::new(HttpTransport::new(endpoint.clone()));
+        match tokio::time::timeout(timeout, provider.chain_id()).await {
+            Ok(Ok(chain_id)) => return chain_id,
+            Ok(Err(e)) => error!(error = %e, %endpoint, "Fetch chain id"),
+            Err(_) => error!(%endpoint, "Fetch chain id: no answer"),
+        }
+    }
+    warn!(
+        default,
+        "No node answered, signing for the default chain id"
+    );
+    cairo_short_string_to_felt(default).unwrap()
+}
+
+/// Where the boot task fetches the chain id from, and where it goes once applied. Absent when
+/// a [`Backend`] was inserted before [`DojoPlugin`].
 #[derive(Resource)]
-pub struct DojoEnv {
-    /// The block ID to use for all contract calls.
-    block_id: BlockId,
-    /// The address of the world contract.
-    world_address: FieldElement,
-    /// The account to use for performing execution on the World contract.
-    account: Arc<SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet>>,
-}
-
-impl DojoEnv {
-    fn new(
-        world_address: FieldElement,
-        account: SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet>,
-    ) -> Self {
+struct ChainId {
+    urls: Vec<Url>,
+    default: String,
+    sender: watch::Sender<Option<FieldElement>>,
+}
+
+/// [`WorldBackend`] of the node at `endpoint`, built once the boot task fetched the chain id
+/// its accounts sign for. Calls wait for it.
+struct ChainBackend {
+    endpoint: Url,
+    world: FieldElement,
+    signer: LocalSigner,
+    faucet: Option<LocalSigner>,
+    chain_id: watch::Receiver<Option<FieldElement>>,
+    backend: tokio::sync::OnceCell<WorldBackend>,
+}
+
+impl ChainBackend {
+    async fn backend(&self) -> eyre::Result<&WorldBackend> {
+        self.backend
+            .get_or_try_init(|| async {
+                let mut chain_id = self.chain_id.clone();
+                let chain_id = chain_id.wait_for(Option::is_some).await?.unwrap();
+                let account = new_account(&self.endpoint, self.signer, chain_id);
+                let mut backend = WorldBackend::new(self.world, account);
+                if let Some(faucet) = self.faucet {
+                    backend = backend.with_faucet(new_account(&self.endpoint, faucet, chain_id));
+                }
+                Ok::<_, eyre::Report>(backend)
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl DojoBackend for ChainBackend {
+    async fn execute(&self, system: &str, calldata: Vec<FieldElement>) -> eyre::Result<Execution> {
+        self.backend().await?.execute(system, calldata).await
+    }
+
+    async fn entity(
+        &self,
+        component: &str,
+        keys: Vec<FieldElement>,
+    ) -> eyre::Result<Vec<FieldElement>> {
+        self.backend().await?.entity(component, keys).await
+    }
+
+    async fn head(&self) -> eyre::Result<BlockHead> {
+        self.backend().await?.head().await
+    }
+
+    async fn entity_at(
+        &self,
+        component: &str,
+        keys: Vec<FieldElement>,
+        head: BlockHead,
+    ) -> eyre::Result<Vec<FieldElement>> {
+        self.backend().await?.entity_at(component, keys, head).await
+    }
+
+    async fn balance(&self) -> eyre::Result<u128> {
+        self.backend().await?.balance().await
+    }
+
+    async fn request_funds(&self) -> eyre::Result<bool> {
+        self.backend().await?.request_funds().await
+    }
+
+    async fn resolve_component(&self, component: &str) -> eyre::Result<()> {
+        self.backend().await?.resolve_component(component).await
+    }
+
+    async fn resolve_system(&self, system: &str) -> eyre::Result<()> {
+        self.backend().await?.resolve_system(system).await
+    }
+}
+
+/// Account address and the secret key signing for it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LocalSigner {
+    pub address: FieldElement,
+    pub secret_key: FieldElement,
+}
+
+impl LocalSigner {
+    fn from_configs(address: &str, secret_key: &str) -> Self {
+        Self {
+            address: FieldElement::from_str(address).unwrap(),
+            secret_key: FieldElement::from_str(secret_key).unwrap(),
+        }
+    }
+}
+
+/// Seconds between the sync ticks of each command.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct SyncIntervals {
+    pub spawn: f32,
+    pub drive: f32,
+    pub vehicle: f32,
+    pub enemies: f32,
+}
+
+impl Default for SyncIntervals {
+    fn default() -> Self {
         Self {
-            world_address,
-            account: Arc::new(account),
-            block_id: BlockId::Tag(BlockTag::Latest),
+            spawn: configs::DOJO_SYNC_INTERVAL,
+            drive: configs::DOJO_DRIVE_INTERVAL,
+            vehicle: configs::DOJO_VEHICLE_INTERVAL,
+            enemies: configs::DOJO_ENEMIES_INTERVAL,
         }
     }
 }
 
-pub struct DojoPlugin;
+/// How [`DojoPlugin`] reaches the world. The default one has no node, for a [`Backend`] inserted
+/// before the plugin: see [`DojoConfig::load`] for the nodes of `configs` and the world deployed
+/// last.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DojoConfig {
+    /// Node the calls are sent to, followed by the ones they fail over to.
+    pub urls: Vec<Url>,
+    pub world: FieldElement,
+    /// Account transactions are sent from.
+    pub signer: LocalSigner,
+    /// Prefunded account topping up the signer on a Katana node.
+    pub faucet: Option<LocalSigner>,
+    /// Chain id signed for when no node answers.
+    pub default_chain_id: String,
+    pub intervals: SyncIntervals,
+}
+
+impl Default for DojoConfig {
+    fn default() -> Self {
+        Self {
+            urls: Vec::new(),
+            world: FieldElement::from_str(configs::WORLD_ADDRESS).unwrap(),
+            signer: LocalSigner::from_configs(
+                configs::ACCOUNT_ADDRESS,
+                configs::ACCOUNT_SECRET_KEY,
+            ),
+            faucet: Some(LocalSigner::from_configs(
+                configs::FAUCET_ADDRESS,
+                configs::FAUCET_SECRET_KEY,
+            )),
+            default_chain_id: configs::DEFAULT_CHAIN_ID.to_string(),
+            intervals: SyncIntervals::default(),
+        }
+    }
+}
+
+impl DojoConfig {
+    /// The nodes of `configs`, on the world saved to [`configs::LOCAL_CONFIG_PATH`] by the last
+    /// deploy. Fails on a node URL that can't be used, naming its setting.
+    pub fn load() -> eyre::Result<Self> {
+        let mut urls = vec![parse_rpc_url(
+            "JSON_RPC_ENDPOINT",
+            configs::JSON_RPC_ENDPOINT,
+        )?];
+        for (i, endpoint) in configs::JSON_RPC_FALLBACK_ENDPOINTS.iter().enumerate() {
+            urls.push(parse_rpc_url(
+                &format!("JSON_RPC_FALLBACK_ENDPOINTS[{i}]"),
+                endpoint,
+            )?);
+        }
+        Ok(Self {
+            urls,
+            world: LocalConfig::load(Path::new(configs::LOCAL_CONFIG_PATH)).world_address(),
+            ..Self::default()
+        })
+    }
+}
+
+/// `endpoint` as the URL of a JSON-RPC node, the error names the `key` it was set in.
+fn parse_rpc_url(key: &str, endpoint: &str) -> eyre::Result<Url> {
+    let url = Url::parse(endpoint).map_err(|e| eyre::eyre!("{key} {endpoint:?}: {e}"))?;
+    match url.scheme() {
+        "http" | "https" => Ok(url),
+        scheme => Err(eyre::eyre!(
+            "{key} {endpoint:?}: unsupported scheme {scheme:?}, expected http or https"
+        )),
+    }
+}
+
+/// Syncs the racers with the world. The backend is built from the [`DojoConfig`] unless a
+/// [`Backend`] was inserted before.
+#[derive(Default)]
+pub struct DojoPlugin {
+    config: DojoConfig,
+}
+
+impl DojoPlugin {
+    pub fn new(config: DojoConfig) -> Self {
+        Self { config }
+    }
+}
 
 impl Plugin for DojoPlugin {
     fn build(&self, app: &mut App) {
-        let url = Url::parse(configs::JSON_RPC_ENDPOINT).unwrap();
-        let account_address = FieldElement::from_str(configs::ACCOUNT_ADDRESS).unwrap();
-        let account = SingleOwnerAccount::new(
-            JsonRpcClient::new(HttpTransport::new(url)),
-            LocalWallet::from_signing_key(SigningKey::from_secret_scalar(
-                FieldElement::from_str(configs::ACCOUNT_SECRET_KEY).unwrap(),
-            )),
-            account_address,
-            cairo_short_string_to_felt("KATANA").unwrap(),
-        );
+        if !app.is_plugin_added::<TokioTasksPlugin>() {
+            app.add_plugin(tokio_tasks_plugin());
+        }
+
+        let config = &self.config;
+        if !app.world.contains_resource::<Backend>() {
+            // Fetched by the boot task, the window opens meanwhile
+            let (sender, chain_id) = watch::channel(None);
 
-        let world_address = FieldElement::from_str(configs::WORLD_ADDRESS).unwrap();
+            // A provider and handles of its own for each node
+            let endpoints = config
+                .urls
+                .iter()
+                .map(|endpoint| {
+                    let backend: Arc<dyn DojoBackend> = Arc::new(ChainBackend {
+                        endpoint: endpoint.clone(),
+                        world: config.world,
+                        signer: config.signer,
+                        faucet: config.faucet,
+                        chain_id: chain_id.clone(),
+                        backend: tokio::sync::OnceCell::new(),
+                    });
+                    (endpoint.to_string(), backend)
+                })
+                .collect();
+            let backend = FailoverBackend::new(endpoints);
+            app.insert_resource(ChainId {
+                urls: config.urls.clone(),
+                default: config.default_chain_id.clone(),
+                sender,
+            })
+            .insert_resource(RpcEndpoint(backend.subscribe().borrow().clone()))
+            .insert_resource(RpcEndpointChanges(backend.subscribe()))
+            .insert_resource(Backend(Arc::new(backend)));
+        }
 
-        app.add_plugin(TokioTasksPlugin::default())
-            .insert_resource(DojoEnv::new(world_address, account))
+        // The config was parsed before the window opened
+        let mut boot = app.world.get_resource_or_insert_with(BootProgress::default);
+        boot.set(BootStep::ParseConfig, StepStatus::Done);
+        for step in [
+            BootStep::ConnectRpc,
+            BootStep::ResolveWorld,
+            BootStep::FetchSystems,
+        ] {
+            boot.register(step);
+        }
+        boot.set_detail("boot.world", format!("{:#x}", config.world));
+
+        let (messages_tx, messages_rx) = mpsc::unbounded_channel();
+        let (alive_racers, _) = watch::channel(Vec::new());
+        let (lineup, _) = watch::channel(Lineup::default());
+
+        app.init_resource::<Metrics>()
+            .init_resource::<Racers>()
+            .init_resource::<EnemySeed>()
+            .init_resource::<EnemyCount>()
+            .insert_resource(DojoMessages {
+                sender: messages_tx,
+                receiver: messages_rx,
+            })
+            .insert_resource(AliveRacers(alive_racers))
+            .insert_resource(LineupSender(lineup))
+            .insert_resource(config.intervals)
+            .init_resource::<SyncStats>()
+            .add_event::<SyncCongested>()
+            .init_resource::<Evolution>()
+            .init_resource::<DojoTasks>()
+            .init_resource::<Funds>()
+            .init_resource::<Connection>()
+            .init_resource::<ChainTracker>()
+            .add_event::<ChainReorg>()
+            .add_event::<RpcFailover>()
+            .add_event::<RequestSpawn>()
+            .add_event::<RequestDrive>()
+            .add_event::<RespawnPopulation>()
+            .add_event::<RunCost>()
+            .add_event::<RunFinished>()
+            .add_event::<ScoreSubmitted>()
+            .add_event::<RaceFinished>()
             .add_startup_systems((
                 setup,
-                spawn_racers_thread,
-                drive_thread,
-                update_vehicle_thread,
-                update_enemies_thread,
+                dojo_commands_thread,
+                evolve_thread,
+                report_collision_thread,
+                submit_score_thread,
+                finish_race_thread,
+                health_check_thread,
+                boot_thread,
             ))
-            .add_system(sync_dojo_state);
+            .add_startup_system(spawn_spectated_racers.run_if(resource_exists::<ReadOnly>()))
+            .add_startup_system(check_funds_thread.run_if(not(resource_exists::<ReadOnly>())))
+            .add_systems(
+                (
+                    publish_alive_racers,
+                    publish_lineup,
+                    start_evolution,
+                    adapt_sync_intervals,
+                    sync_dojo_state,
+                    forward_dojo_requests,
+                )
+                    .chain()
+                    .after(GenerationSet),
+            )
+            .add_system(warn_on_rejections.after(forward_dojo_requests))
+            .add_system(apply_dojo_messages)
+            .add_event::<RetryBoot>()
+            .add_system(track_rpc_boot.run_if(resource_changed::<Connection>()))
+            .add_system(track_boot_endpoint.run_if(resource_exists_and_changed::<RpcEndpoint>()))
+            .add_system(forward_boot_retries.run_if(resource_exists::<BootRetries>()))
+            .add_system(supervise_dojo_tasks)
+            .add_system(emit_rpc_failovers.run_if(resource_exists::<RpcEndpointChanges>()))
+            .add_system(report_collisions.run_if(not(resource_exists::<ReadOnly>())))
+            .add_system(submit_scores.run_if(not(resource_exists::<ReadOnly>())))
+            .add_system(submit_finishes.run_if(not(resource_exists::<ReadOnly>())))
+            .add_system(shutdown_dojo.in_base_set(CoreSet::Last));
     }
 }
 
-fn setup(mut commands: Commands) {
-    commands.spawn(DojoSyncTime::from_seconds(configs::DOJO_SYNC_INTERVAL));
+/// Results of the background tasks, turned into events by `apply_dojo_messages` once per frame,
+/// up to [`configs::DOJO_FRAME_BUDGET`], instead of each task hopping onto the main thread.
+#[derive(Debug)]
+pub enum DojoMessage {
+    /// `position` is the start position sent to the world, in bevy coordinates.
+    RacerSpawned {
+        model_id: FieldElement,
+        position: Vec2,
+    },
+    /// `head` is the block the state was read at, `None` when it couldn't be fetched.
+    Vehicle {
+        model_id: FieldElement,
+        vehicle: Vec<FieldElement>,
+        head: Option<BlockHead>,
+    },
+    /// Positions of the enemies of a racer.
+    Enemies {
+        model_id: FieldElement,
+        positions: Vec<EnemyPosition>,
+        head: Option<BlockHead>,
+    },
+    /// Cost of a transaction sent for a racer.
+    Executed {
+        model_id: FieldElement,
+        execution: Execution,
+    },
+    /// The `submit_score` transaction of a run was accepted.
+    ScoreSubmitted {
+        model_id: FieldElement,
+        distance: u128,
+        execution: Execution,
+    },
+    /// The `evolve` system ran, failed calls are retried until it does.
+    Evolved { generation: u32 },
+    /// The chain id the accounts sign for was fetched, or defaulted.
+    ChainId(FieldElement),
+    /// The account balance was checked.
+    Funds(Funds),
+    /// Seconds the node took to answer a health check, `None` when it failed.
+    HealthChecked { latency: Option<f32> },
+    /// A startup step progressed.
+    Boot(BootStep, StepStatus),
 }
 
-#[derive(Component)]
-struct DojoSyncTime {
-    timer: Timer,
+impl DojoMessage {
+    /// Share of [`configs::DOJO_FRAME_BUDGET`] applying the message takes: an event per entity it
+    /// updates.
+    fn cost(&self) -> usize {
+        match self {
+            DojoMessage::Enemies { positions, .. } => positions.len().max(1),
+            _ => 1,
+        }
+    }
 }
 
-impl DojoSyncTime {
-    fn from_seconds(duration: f32) -> Self {
-        Self {
-            timer: Timer::from_seconds(duration, TimerMode::Repeating),
+#[derive(Resource)]
+struct DojoMessages {
+    sender: mpsc::UnboundedSender<DojoMessage>,
+    receiver: mpsc::UnboundedReceiver<DojoMessage>,
+}
+
+/// Sync ticks sent for each command and how many found one of its kind pending, to which they
+/// were merged.
+#[derive(Resource, Default, Debug)]
+pub struct SyncStats {
+    pub channels: HashMap<&'static str, ChannelStats>,
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+pub struct ChannelStats {
+    pub sent: u64,
+    pub rejected: u64,
+    /// Counts since the last [`configs::DOJO_SYNC_STATS_WINDOW`] ended.
+    window_sent: u64,
+    window_rejected: u64,
+}
+
+impl ChannelStats {
+    /// Share of the ticks of the run that found the channel full.
+    pub fn rejection_rate(&self) -> f32 {
+        rate(self.rejected, self.sent)
+    }
+}
+
+fn rate(rejected: u64, sent: u64) -> f32 {
+    let total = rejected + sent;
+    if total == 0 {
+        return 0.0;
+    }
+    rejected as f32 / total as f32
+}
+
+impl SyncStats {
+    fn record(&mut self, channel: &'static str, rejected: bool) {
+        let stats = self.channels.entry(channel).or_default();
+        if rejected {
+            stats.rejected += 1;
+            stats.window_rejected += 1;
+        } else {
+            stats.sent += 1;
+            stats.window_sent += 1;
+        }
+    }
+
+    pub fn rejected(&self) -> u64 {
+        self.channels.values().map(|stats| stats.rejected).sum()
+    }
+
+    /// Starts a new window, returns the channels that rejected more than
+    /// [`configs::DOJO_REJECTION_WARNING_RATE`] of their ticks in the one that ended.
+    fn end_window(&mut self) -> Vec<SyncCongested> {
+        let mut congested: Vec<SyncCongested> = self
+            .channels
+            .iter_mut()
+            .filter_map(|(channel, stats)| {
+                let rejection_rate = rate(stats.window_rejected, stats.window_sent);
+                stats.window_sent = 0;
+                stats.window_rejected = 0;
+                (rejection_rate > configs::DOJO_REJECTION_WARNING_RATE).then_some(SyncCongested {
+                    channel: *channel,
+                    rejection_rate,
+                })
+            })
+            .collect();
+        congested.sort_by_key(|congested| congested.channel);
+        congested
+    }
+}
+
+/// A command channel rejected too many sync ticks over the last window: the sync interval is
+/// shorter than the world takes to answer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyncCongested {
+    pub channel: &'static str,
+    pub rejection_rate: f32,
+}
+
+/// Pops up every [`SyncCongested`] for [`configs::DOJO_REJECTION_TOAST_SECONDS`] and explains
+/// why nothing happens when the account can't pay for transactions.
+pub struct DojoHudPlugin;
+
+impl Plugin for DojoHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Toasts<SyncCongested>>()
+            .init_resource::<Locale>()
+            .add_event::<SyncCongested>()
+            .add_systems((
+                sync_congested_toasts_system,
+                funds_screen_system,
+                connection_indicator_system,
+            ));
+    }
+}
+
+/// Whether the account can pay for transactions, checked before the first one is sent.
+#[derive(Resource, Default, Clone, Debug, PartialEq)]
+pub enum Funds {
+    #[default]
+    Checking,
+    Sufficient,
+    /// Under [`configs::DOJO_MIN_BALANCE`], even after asking the faucet.
+    Insufficient {
+        balance: u128,
+    },
+}
+
+/// Reachability of the node, from the last health checks.
+#[derive(Resource, Default, Clone, Debug, PartialEq)]
+pub enum Connection {
+    #[default]
+    Checking,
+    /// `latency` is in seconds.
+    Healthy { latency: f32 },
+    /// Health checks failed in a row.
+    Unreachable { failures: u32 },
+}
+
+impl Connection {
+    fn checked(&mut self, latency: Option<f32>) {
+        *self = match (latency, &self) {
+            (Some(latency), _) => Connection::Healthy { latency },
+            (None, Connection::Unreachable { failures }) => Connection::Unreachable {
+                failures: failures + 1,
+            },
+            (None, _) => Connection::Unreachable { failures: 1 },
+        };
+    }
+}
+
+/// Node the dojo calls are sent to, when they fail over between
+/// [`configs::JSON_RPC_FALLBACK_ENDPOINTS`].
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct RpcEndpoint(pub String);
+
+#[derive(Resource)]
+struct RpcEndpointChanges(watch::Receiver<String>);
+
+/// The dojo calls failed over to `endpoint` after the previous node kept failing.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RpcFailover {
+    pub endpoint: String,
+}
+
+fn emit_rpc_failovers(
+    mut changes: ResMut<RpcEndpointChanges>,
+    mut endpoint: ResMut<RpcEndpoint>,
+    mut failovers: EventWriter<RpcFailover>,
+) {
+    if !changes.0.has_changed().unwrap_or(false) {
+        return;
+    }
+    let changed = changes.0.borrow_and_update().clone();
+    warn!(endpoint = changed, "RPC failover");
+    endpoint.0 = changed.clone();
+    failovers.send(RpcFailover { endpoint: changed });
+}
+
+/// Background tasks running the dojo commands, named after their channel.
+#[derive(Resource, Default)]
+pub(crate) struct DojoTasks(pub(crate) Vec<SupervisedTask>);
+
+/// Restarts the tasks that panicked, on the commands still queued for them.
+fn supervise_dojo_tasks(mut tasks: ResMut<DojoTasks>, runtime: Res<TokioTasksRuntime>) {
+    tasks.0.retain_mut(|task| task.supervise(runtime.runtime()));
+}
+
+/// Blocks the state was read at, to notice reads from a fork or from before a block already
+/// applied.
+#[derive(Resource, Default, Debug)]
+pub struct ChainTracker {
+    /// Hash of the last [`configs::DOJO_TRACKED_BLOCKS`] block numbers read at.
+    hashes: BTreeMap<u64, FieldElement>,
+    /// Block of the last applied read of each stream.
+    last_read: HashMap<ReadStream, u64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum ReadStream {
+    Vehicle(FieldElement),
+    Enemies(FieldElement),
+}
+
+impl ChainTracker {
+    /// Records a read made at `head`, `false` when it must not be applied.
+    fn observe(&mut self, stream: ReadStream, head: BlockHead) -> bool {
+        let forked = self
+            .hashes
+            .get(&head.number)
+            .map_or(false, |hash| *hash != head.hash);
+        let stale = self
+            .last_read
+            .get(&stream)
+            .map_or(false, |last| head.number < *last);
+
+        if forked {
+            // The new chain is authoritative from the fork on
+            self.hashes.split_off(&head.number);
+            self.last_read.retain(|_, number| *number < head.number);
+        }
+        self.hashes.insert(head.number, head.hash);
+        while self.hashes.len() > configs::DOJO_TRACKED_BLOCKS {
+            self.hashes.pop_first();
+        }
+        // The next read of the stream is accepted from this block on, a stale one doesn't move it
+        // back
+        let last = self.last_read.entry(stream).or_default();
+        *last = (*last).max(head.number);
+        !forked && !stale
+    }
+}
+
+/// A state read came from a forked block or from before one already applied: it was dropped
+/// and the state of the racers is queried again.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChainReorg {
+    pub model_id: FieldElement,
+    pub read: BlockHead,
+}
+
+/// Set while the world evolves the previous generation, racers are respawned once it is done.
+#[derive(Resource, Default, Debug)]
+pub struct Evolution {
+    pub pending: bool,
+}
+
+/// Model ids of the cars currently alive, read by the background tasks on each command.
+#[derive(Resource)]
+struct AliveRacers(watch::Sender<Vec<FieldElement>>);
+
+/// Racers to spawn and how, re-read by the spawn task on each command so a tournament can
+/// change them between races.
+#[derive(Default, Clone)]
+struct Lineup {
+    model_ids: Vec<FieldElement>,
+    head_to_head: Option<HeadToHead>,
+    /// Enemy seed of the generation, shared by its racers.
+    seed: FieldElement,
+    /// Enemies of each racer synced from the world.
+    enemy_count: EnemyCount,
+}
+
+#[derive(Resource)]
+struct LineupSender(watch::Sender<Lineup>);
+
+/// Insert before adding `DojoPlugin` to follow racers already on the world without sending
+/// any transaction.
+#[derive(Resource)]
+pub struct ReadOnly;
+
+/// Cars are driven by an external controller, `drive` is not sent on sync ticks.
+#[derive(Resource)]
+pub struct ExternalDriver;
+
+fn setup(mut commands: Commands, intervals: Res<SyncIntervals>) {
+    commands.spawn(SyncScheduler::new(*intervals));
+}
+
+fn publish_alive_racers(alive_racers: Res<AliveRacers>, cars: Query<&Model, With<Car>>) {
+    let model_ids: Vec<FieldElement> = cars.iter().map(|model| model.id).collect();
+    alive_racers.0.send_if_modified(|current| {
+        if *current == model_ids {
+            return false;
+        }
+        *current = model_ids;
+        true
+    });
+}
+
+fn publish_lineup(
+    lineup: Res<LineupSender>,
+    racers: Res<Racers>,
+    head_to_head: Option<Res<HeadToHead>>,
+    enemy_seed: Res<EnemySeed>,
+    enemy_count: Res<EnemyCount>,
+) {
+    let race_changed = head_to_head
+        .as_ref()
+        .map_or(false, |race| race.is_changed());
+    // A new count waits for the next lineup, the enemies of the current run stay synced
+    if racers.is_changed() || race_changed || enemy_seed.is_changed() {
+        lineup.0.send_replace(Lineup {
+            model_ids: racers.0.clone(),
+            head_to_head: head_to_head.map(|race| race.clone()),
+            seed: enemy_seed.0,
+            enemy_count: *enemy_count,
+        });
+    }
+}
+
+/// Events the background task results are turned into.
+#[derive(SystemParam)]
+struct DojoEvents<'w> {
+    spawn_car: EventWriter<'w, SpawnCar>,
+    spawn_enemies: EventWriter<'w, SpawnEnemies>,
+    update_car: EventWriter<'w, UpdateCar>,
+    update_enemies: EventWriter<'w, UpdateEnemies>,
+    run_costs: EventWriter<'w, RunCost>,
+    scores_submitted: EventWriter<'w, ScoreSubmitted>,
+    reorgs: EventWriter<'w, ChainReorg>,
+}
+
+fn apply_dojo_messages(
+    mut messages: ResMut<DojoMessages>,
+    mut chain: ResMut<ChainTracker>,
+    mut evolution: ResMut<Evolution>,
+    mut funds: ResMut<Funds>,
+    mut connection: ResMut<Connection>,
+    mut boot: ResMut<BootProgress>,
+    chain_id: Option<Res<ChainId>>,
+    mut events: DojoEvents,
+) {
+    // Messages past the budget wait in the channel, so a burst of reads is spread over frames
+    let mut spent = 0;
+    // Sent as one batch once the messages of the frame are applied
+    let mut enemy_positions = Vec::new();
+    while spent < configs::DOJO_FRAME_BUDGET {
+        let Ok(message) = messages.receiver.try_recv() else {
+            break;
+        };
+        spent += message.cost();
+        let read = match &message {
+            DojoMessage::Vehicle {
+                model_id,
+                head: Some(head),
+                ..
+            } => Some((*model_id, ReadStream::Vehicle(*model_id), *head)),
+            DojoMessage::Enemies {
+                model_id,
+                head: Some(head),
+                ..
+            } => Some((*model_id, ReadStream::Enemies(*model_id), *head)),
+            _ => None,
+        };
+        if let Some((model_id, stream, head)) = read {
+            if !chain.observe(stream, head) {
+                warn!(model = %model_id, block = head.number, "Chain reorg, stale read dropped");
+                events.reorgs.send(ChainReorg {
+                    model_id,
+                    read: head,
+                });
+                continue;
+            }
+        }
+
+        match message {
+            DojoMessage::RacerSpawned { model_id, position } => {
+                events.spawn_enemies.send(SpawnEnemies { model_id });
+                events.spawn_car.send(SpawnCar {
+                    model_id,
+                    position: Some(position),
+                });
+            }
+            DojoMessage::Vehicle {
+                model_id, vehicle, ..
+            } => events.update_car.send(UpdateCar { model_id, vehicle }),
+            DojoMessage::Enemies { positions, .. } => enemy_positions.extend(positions),
+            DojoMessage::Executed {
+                model_id,
+                execution,
+            } => events.run_costs.send(RunCost {
+                model_id,
+                fee: execution.fee,
+                block_number: execution.block_number,
+            }),
+            DojoMessage::ScoreSubmitted {
+                model_id,
+                distance,
+                execution,
+            } => events.scores_submitted.send(ScoreSubmitted {
+                model_id,
+                distance,
+                block_number: execution.block_number,
+            }),
+            DojoMessage::Evolved { generation } => {
+                debug!(generation, "Generation evolved");
+                evolution.pending = false;
+            }
+            DojoMessage::ChainId(id) => {
+                if let Some(chain_id) = &chain_id {
+                    chain_id.sender.send_replace(Some(id));
+                }
+                boot.set_detail("boot.chain_id", felt_to_short_string(id));
+            }
+            DojoMessage::Funds(checked) => *funds = checked,
+            DojoMessage::HealthChecked { latency } => connection.checked(latency),
+            DojoMessage::Boot(step, status) => boot.set(step, status),
+        }
+    }
+    if !enemy_positions.is_empty() {
+        events.update_enemies.send(UpdateEnemies(enemy_positions));
+    }
+}
+
+/// Sends every finished run to the `submit_score` task, the world scores it from its own state.
+fn submit_scores(mut finished: EventReader<RunFinished>, submit: Res<SubmitScoreCommand>) {
+    for run in finished.iter() {
+        let (_, distance) = bevy_to_dojo_coordinate(configs::ROAD_X_MIN, run.0.distance);
+        let score = Score {
+            model_id: run.0.model_id,
+            distance: distance.max(0.0) as u128,
+        };
+        if let Err(e) = submit.0.try_send(score) {
+            error!(model = %run.0.model_id, error = %e, "Send submit_score command");
+        }
+    }
+}
+
+/// Sends every car crossing the finish line to the `finish_race` task, the world times the race
+/// from its own state.
+fn submit_finishes(mut finished: EventReader<RaceFinished>, finish: Res<FinishRaceCommand>) {
+    for race in finished.iter() {
+        if let Err(e) = finish.0.try_send(race.model_id) {
+            error!(model = %race.model_id, error = %e, "Send finish_race command");
+        }
+    }
+}
+
+/// Asks the world to evolve the ended generation. Spectators respawn the followed racers
+/// right away instead.
+fn start_evolution(
+    mut respawn_population: EventReader<RespawnPopulation>,
+    mut evolution: ResMut<Evolution>,
+    evolve: Res<EvolveCommand>,
+    read_only: Option<Res<ReadOnly>>,
+    racers: Res<Racers>,
+    mut spawn_car: EventWriter<SpawnCar>,
+    mut spawn_enemies: EventWriter<SpawnEnemies>,
+) {
+    for respawn in respawn_population.iter() {
+        if read_only.is_some() {
+            for model_id in racers.0.iter().copied() {
+                spawn_enemies.send(SpawnEnemies { model_id });
+                spawn_car.send(SpawnCar {
+                    model_id,
+                    position: None,
+                });
+            }
+            continue;
+        }
+
+        match evolve.0.try_send(respawn.generation) {
+            Ok(()) => evolution.pending = true,
+            Err(e) => error!(generation = respawn.generation, error = %e, "Send evolve command"),
+        }
+    }
+}
+
+/// Tells the world about crashes seen by the client, the sprites are already gone.
+fn report_collisions(mut crashed: EventReader<CarCrashed>, report: Res<ReportCollisionCommand>) {
+    for crash in crashed.iter() {
+        if let Err(e) = report.0.try_send(crash.model_id) {
+            error!(model = %crash.model_id, error = %e, "Send report_collision command");
+        }
+    }
+}
+
+fn spawn_spectated_racers(
+    racers: Res<Racers>,
+    mut spawn_car: EventWriter<SpawnCar>,
+    mut spawn_enemies: EventWriter<SpawnEnemies>,
+) {
+    for model_id in racers.0.iter().copied() {
+        spawn_enemies.send(SpawnEnemies { model_id });
+        spawn_car.send(SpawnCar {
+            model_id,
+            position: None,
+        });
+    }
+}
+
+fn adapt_sync_intervals(connection: Res<Connection>, mut schedulers: Query<&mut SyncScheduler>) {
+    // An unreachable node keeps the last intervals, the commands fail fast anyway
+    let Connection::Healthy { latency } = *connection else {
+        return;
+    };
+    if connection.is_changed() {
+        for mut scheduler in schedulers.iter_mut() {
+            scheduler.adapt(latency);
+        }
+    }
+}
+
+/// Sync commands of the dojo task. A command of each kind waits at most: sent while one is
+/// pending it is rejected, the pending one reads the latest state when it runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DojoCommand {
+    SpawnRacers,
+    Drive,
+    UpdateVehicle,
+    UpdateEnemies,
+}
+
+/// Order due reads are sent in: the enemies, the most expensive one, last.
+const SYNC_PRIORITY: [DojoCommand; 2] = [DojoCommand::UpdateVehicle, DojoCommand::UpdateEnemies];
+
+impl DojoCommand {
+    /// Name the command is counted under in [`SyncStats`] and the metrics.
+    fn channel(self) -> &'static str {
+        match self {
+            DojoCommand::SpawnRacers => "spawn_racers",
+            DojoCommand::Drive => "drive",
+            DojoCommand::UpdateVehicle => "update_vehicle",
+            DojoCommand::UpdateEnemies => "update_enemies",
+        }
+    }
+
+    async fn run(self, context: CommandContext) {
+        match self {
+            DojoCommand::SpawnRacers => spawn_racers(context).await,
+            DojoCommand::Drive => drive(context).await,
+            DojoCommand::UpdateVehicle => update_vehicle(context).await,
+            DojoCommand::UpdateEnemies => update_enemies(context).await,
+        }
+    }
+}
+
+/// Sends commands to the dojo task, keeping track of the ones not started yet.
+#[derive(Resource)]
+pub struct DojoCommandSender {
+    sender: mpsc::UnboundedSender<DojoCommand>,
+    pending: Arc<Mutex<HashSet<DojoCommand>>>,
+}
+
+impl DojoCommandSender {
+    /// Fails with `Full` while a command of the same kind is pending.
+    pub fn try_send(&self, command: DojoCommand) -> Result<(), TrySendError<DojoCommand>> {
+        if self.sender.is_closed() {
+            return Err(TrySendError::Closed(command));
+        }
+        if !self.pending.lock().unwrap().insert(command) {
+            return Err(TrySendError::Full(command));
+        }
+        self.sender
+            .send(command)
+            .map_err(|e| TrySendError::Closed(e.0))
+    }
+}
+
+/// The command sender, with the bookkeeping of rejected commands.
+#[derive(SystemParam)]
+struct DojoCommands<'w> {
+    sender: Res<'w, DojoCommandSender>,
+    metrics: Res<'w, Metrics>,
+    stats: ResMut<'w, SyncStats>,
+}
+
+impl DojoCommands<'_> {
+    /// Sends the `due` reads in [`SYNC_PRIORITY`] order. Once a read is congested, the lower
+    /// priority reads are dropped for this tick rather than queued behind it.
+    fn dispatch(&mut self, due: &[DojoCommand]) {
+        let mut congested = false;
+        for command in SYNC_PRIORITY
+            .into_iter()
+            .filter(|command| due.contains(command))
+        {
+            let channel = command.channel();
+            if congested {
+                self.stats.record(channel, true);
+                self.metrics
+                    .dropped_ticks
+                    .with_label_values(&[channel])
+                    .inc();
+                debug!(channel, "Tick dropped, a higher priority read is congested");
+                continue;
+            }
+
+            let result = self.sender.try_send(command);
+            congested = matches!(result, Err(TrySendError::Full(_)));
+            self.record(command, result);
+        }
+    }
+
+    fn send(&mut self, command: DojoCommand) {
+        let result = self.sender.try_send(command);
+        self.record(command, result);
+    }
+
+    fn record(&mut self, command: DojoCommand, result: Result<(), TrySendError<DojoCommand>>) {
+        let channel = command.channel();
+        match result {
+            Ok(()) => self.stats.record(channel, false),
+            // A command is already pending and reads the latest state when it runs
+            Err(TrySendError::Full(_)) => {
+                self.stats.record(channel, true);
+                self.metrics
+                    .dropped_ticks
+                    .with_label_values(&[channel])
+                    .inc();
+                debug!(channel, "Tick coalesced into pending command");
+            }
+            Err(TrySendError::Closed(_)) => error!(channel, "Send command: channel closed"),
         }
     }
 }
 
+/// Resources deciding which transactions sync ticks send.
+#[derive(SystemParam)]
+struct TransactionGate<'w> {
+    read_only: Option<Res<'w, ReadOnly>>,
+    external_driver: Option<Res<'w, ExternalDriver>>,
+    hold_drive: Option<Res<'w, HoldDrive>>,
+    funds: Res<'w, Funds>,
+}
+
+impl TransactionGate<'_> {
+    fn sends_transactions(&self) -> bool {
+        self.read_only.is_none() && *self.funds == Funds::Sufficient
+    }
+
+    fn sends_drive(&self) -> bool {
+        self.sends_transactions() && self.external_driver.is_none() && self.hold_drive.is_none()
+    }
+}
+
 fn sync_dojo_state(
-    mut dojo_sync_time: Query<&mut DojoSyncTime>,
+    mut schedulers: Query<&mut SyncScheduler>,
     time: Res<Time>,
-    drive: Res<DriveCommand>,
-    update_vehicle: Res<UpdateVehicleCommand>,
-    update_enemies: Res<UpdateEnemiesCommand>,
-    spawn_racers: Res<SpawnRacersCommand>,
+    mut commands: DojoCommands,
+    mut spawns: EventWriter<RequestSpawn>,
+    mut drives: EventWriter<RequestDrive>,
+    mut reorgs: EventReader<ChainReorg>,
     cars: Query<&Collider, With<Car>>,
 ) {
-    let mut dojo_time = dojo_sync_time.single_mut();
+    // Despawned by `shutdown_dojo`
+    let Ok(mut scheduler) = schedulers.get_single_mut() else {
+        return;
+    };
+    // Reads dropped on a reorg are made again right away
+    if reorgs.iter().count() > 0 {
+        scheduler.resync();
+    }
+
+    let _span = info_span!("sync_tick", has_cars = !cars.is_empty()).entered();
+
+    let mut due = Vec::with_capacity(SYNC_PRIORITY.len());
+    for command in scheduler.tick(time.delta(), !cars.is_empty()) {
+        match command {
+            DojoCommand::SpawnRacers => spawns.send(RequestSpawn),
+            DojoCommand::Drive => drives.send(RequestDrive),
+            read => due.push(read),
+        }
+    }
+    commands.dispatch(&due);
+}
+
+/// Asks for the racers of the lineup to be spawned, sent by the sync timer while no car is alive
+/// or by any system needing a spawn.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RequestSpawn;
+
+/// Asks for a `drive` of the alive racers, sent by the sync timer or by any system needing one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RequestDrive;
+
+/// Forwards the requests of the frame to the dojo task, each at most once, when the
+/// [`TransactionGate`] lets them through.
+fn forward_dojo_requests(
+    mut spawns: EventReader<RequestSpawn>,
+    mut drives: EventReader<RequestDrive>,
+    mut commands: DojoCommands,
+    gate: TransactionGate,
+    evolution: Res<Evolution>,
+) {
+    if spawns.iter().count() > 0 && gate.sends_transactions() && !evolution.pending {
+        commands.send(DojoCommand::SpawnRacers);
+    }
+    if drives.iter().count() > 0 && gate.sends_drive() {
+        commands.send(DojoCommand::Drive);
+    }
+}
+
+/// Every [`configs::DOJO_SYNC_STATS_WINDOW`], warns about the channels that rejected too many
+/// ticks.
+fn warn_on_rejections(
+    time: Res<Time>,
+    mut elapsed: Local<f32>,
+    mut stats: ResMut<SyncStats>,
+    mut congested: EventWriter<SyncCongested>,
+) {
+    *elapsed += time.delta_seconds();
+    if *elapsed < configs::DOJO_SYNC_STATS_WINDOW {
+        return;
+    }
+    *elapsed = 0.0;
 
-    if dojo_time.timer.just_finished() {
-        dojo_time.timer.reset();
-        if cars.is_empty() {
-            if let Err(e) = spawn_racers.try_send() {
-                log::error!("Spawn racers channel: {e}");
+    for channel in stats.end_window() {
+        warn!(
+            channel = channel.channel,
+            rejection_rate = channel.rejection_rate,
+            "Command channel congested, the sync interval is too short"
+        );
+        congested.send(channel);
+    }
+}
+
+fn sync_congested_toasts_system(
+    mut contexts: EguiContexts,
+    locale: Res<Locale>,
+    time: Res<Time>,
+    mut congested: EventReader<SyncCongested>,
+    mut toasts: ResMut<Toasts<SyncCongested>>,
+) {
+    toasts.update(
+        congested.iter(),
+        configs::DOJO_REJECTION_TOAST_SECONDS,
+        time.delta_seconds(),
+    );
+    if toasts.is_empty() {
+        return;
+    }
+
+    egui::Window::new(locale.text("errors.sync_congested_title"))
+        .title_bar(false)
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-20.0, -20.0))
+        .show(contexts.ctx_mut(), |ui| {
+            for congested in toasts.iter() {
+                let rate = format!("{:.0}", congested.rejection_rate * 100.0);
+                ui.label(locale.format(
+                    "errors.sync_congested",
+                    &[("channel", &congested.channel), ("rate", &rate)],
+                ));
             }
-        } else {
-            if let Err(e) = update_vehicle.try_send() {
-                log::error!("Update vehicle channel: {e}");
+        });
+}
+
+/// On [`AppExit`], stops the sync ticks and asks the background tasks to end once the commands
+/// they were sent are done, waiting for them up to [`configs::DOJO_SHUTDOWN_TIMEOUT`].
+fn shutdown_dojo(world: &mut World) {
+    let exiting = world
+        .get_resource::<Events<AppExit>>()
+        .map_or(false, |exits| !exits.is_empty());
+    if !exiting {
+        return;
+    }
+    let Some(tasks) = world.remove_resource::<DojoTasks>() else {
+        return;
+    };
+
+    let schedulers: Vec<Entity> = world
+        .query_filtered::<Entity, With<SyncScheduler>>()
+        .iter(world)
+        .collect();
+    for entity in schedulers {
+        world.despawn(entity);
+    }
+    for task in &tasks.0 {
+        task.stop();
+    }
+
+    info!(tasks = tasks.0.len(), "Waiting for pending dojo commands");
+    let deadline = tokio::time::Instant::now()
+        + std::time::Duration::from_secs_f32(configs::DOJO_SHUTDOWN_TIMEOUT);
+    let runtime = world.resource::<TokioTasksRuntime>().runtime();
+    runtime.block_on(async {
+        for task in tasks.0 {
+            let channel = task.name;
+            let mut task = task.into_handle();
+            match tokio::time::timeout_at(deadline, &mut task).await {
+                Ok(Ok(())) => info!(channel, "Dojo task finished"),
+                Ok(Err(e)) => error!(channel, error = %e, "Dojo task failed"),
+                Err(_) => {
+                    task.abort();
+                    warn!(channel, "Dojo task timed out, pending commands lost");
+                }
+            }
+        }
+    });
+}
+
+fn connection_indicator_system(
+    mut contexts: EguiContexts,
+    locale: Res<Locale>,
+    connection: Option<Res<Connection>>,
+    endpoint: Option<Res<RpcEndpoint>>,
+) {
+    let Some(connection) = connection else {
+        return;
+    };
+    let (text, color) = match *connection {
+        Connection::Checking => (
+            locale.text("errors.connecting").to_string(),
+            egui::Color32::GRAY,
+        ),
+        Connection::Healthy { latency } if latency > configs::DOJO_SLOW_LATENCY => {
+            (format!("{:.0} ms", latency * 1000.0), egui::Color32::YELLOW)
+        }
+        Connection::Healthy { latency } => {
+            (format!("{:.0} ms", latency * 1000.0), egui::Color32::GREEN)
+        }
+        Connection::Unreachable { failures } => {
+            let text = locale.format("errors.unreachable", &[("failures", &failures)]);
+            (text, egui::Color32::RED)
+        }
+    };
+    egui::Area::new("connection")
+        .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-10.0, 10.0))
+        .show(contexts.ctx_mut(), |ui| {
+            let label = ui.colored_label(color, format!("● {text}"));
+            if let Some(endpoint) = endpoint {
+                label.on_hover_text(&endpoint.0);
             }
-            if let Err(e) = drive.try_send() {
-                log::error!("Drive channel: {e}");
+        });
+}
+
+fn funds_screen_system(mut contexts: EguiContexts, locale: Res<Locale>, funds: Option<Res<Funds>>) {
+    let Some(Funds::Insufficient { balance }) = funds.as_deref() else {
+        return;
+    };
+
+    egui::Window::new(locale.text("errors.insufficient_funds"))
+        .collapsible(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(locale.format(
+                "errors.insufficient_funds_balance",
+                &[
+                    ("account", &configs::ACCOUNT_ADDRESS),
+                    ("balance", balance),
+                    ("min", &configs::DOJO_MIN_BALANCE),
+                ],
+            ));
+            ui.label(locale.text("errors.fund_the_account"));
+        });
+}
+
+/// Checks the account can pay for transactions, asking the faucet for funds when it can't.
+fn check_funds_thread(
+    backend: Res<Backend>,
+    messages: Res<DojoMessages>,
+    runtime: ResMut<TokioTasksRuntime>,
+) {
+    let backend = backend.0.clone();
+    let messages = messages.sender.clone();
+
+    runtime.spawn_background_task(move |_ctx| async move {
+        let mut balance = backend.balance().await;
+        if matches!(balance, Ok(balance) if balance < configs::DOJO_MIN_BALANCE) {
+            match backend.request_funds().await {
+                Ok(true) => {
+                    info!("Account funded by the faucet");
+                    balance = backend.balance().await;
+                }
+                Ok(false) => {}
+                Err(e) => error!(error = %e, "Request funds from the faucet"),
             }
-            if let Err(e) = update_enemies.try_send() {
-                log::error!("Update enemies channel: {e}");
+        }
+
+        let funds = match balance {
+            Ok(balance) if balance < configs::DOJO_MIN_BALANCE => {
+                error!(
+                    balance,
+                    required = configs::DOJO_MIN_BALANCE,
+                    "Account balance too low to pay for transactions"
+                );
+                Funds::Insufficient { balance }
+            }
+            Ok(_) => Funds::Sufficient,
+            // Transactions report their own errors
+            Err(e) => {
+                error!(error = %e, "Query account balance");
+                Funds::Sufficient
+            }
+        };
+        let _ = messages.send(DojoMessage::Funds(funds));
+    });
+}
+
+/// Every [`configs::DOJO_HEALTH_INTERVAL`], times a block number query to the node. Not a
+/// command task: it ends with the app rather than being waited for on exit.
+fn health_check_thread(
+    backend: Res<Backend>,
+    metrics: Res<Metrics>,
+    messages: Res<DojoMessages>,
+    runtime: ResMut<TokioTasksRuntime>,
+) {
+    let metrics = metrics.clone();
+    let backend = backend.0.clone();
+    let messages = messages.sender.clone();
+
+    runtime.spawn_background_task(move |_ctx| async move {
+        let mut interval =
+            tokio::time::interval(Duration::from_secs_f32(configs::DOJO_HEALTH_INTERVAL));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let timer = metrics
+                .sync_latency
+                .with_label_values(&["health"])
+                .start_timer();
+            let result = backend.head().await;
+            let latency = timer.stop_and_record() as f32;
+
+            let latency = match result {
+                Ok(_) => Some(latency),
+                Err(e) => {
+                    metrics.failures.with_label_values(&["health"]).inc();
+                    warn!(error = %e, "Health check");
+                    None
+                }
+            };
+            if messages
+                .send(DojoMessage::HealthChecked { latency })
+                .is_err()
+            {
+                break;
             }
         }
-    } else {
-        dojo_time.timer.tick(time.delta());
+    });
+}
+
+/// Connecting to the node is done with the first health check it answers.
+fn track_rpc_boot(connection: Res<Connection>, mut boot: ResMut<BootProgress>) {
+    let status = match *connection {
+        Connection::Checking => StepStatus::Running,
+        Connection::Healthy { .. } => StepStatus::Done,
+        Connection::Unreachable { failures } => {
+            StepStatus::Retrying(format!("{failures} failed health checks"))
+        }
+    };
+    if boot.status(BootStep::ConnectRpc) != Some(&StepStatus::Done) {
+        boot.set(BootStep::ConnectRpc, status);
+    }
+}
+
+fn track_boot_endpoint(endpoint: Res<RpcEndpoint>, mut boot: ResMut<BootProgress>) {
+    boot.set_detail("boot.endpoint", endpoint.0.clone());
+}
+
+/// Wakes up the boot task once it gave up on a step.
+#[derive(Resource)]
+struct BootRetries(mpsc::UnboundedSender<()>);
+
+fn forward_boot_retries(mut retries: EventReader<RetryBoot>, sender: Res<BootRetries>) {
+    for _ in retries.iter() {
+        let _ = sender.0.send(());
     }
 }
 
-fn spawn_racers_thread(
-    env: Res<DojoEnv>,
+/// Fetches the chain id, then resolves the world components and the systems ahead of the first
+/// calls, reporting each step to the boot screen. A step is retried every [`configs::DOJO_BOOT_RETRY_DELAY`] up to
+/// [`configs::DOJO_BOOT_ATTEMPTS`] times, then fails until a [`RetryBoot`].
+fn boot_thread(
+    backend: Res<Backend>,
+    messages: Res<DojoMessages>,
+    chain_id: Option<Res<ChainId>>,
     runtime: ResMut<TokioTasksRuntime>,
     mut commands: Commands,
 ) {
-    let (tx, mut rx) = mpsc::channel::<()>(8);
-    commands.insert_resource(SpawnRacersCommand(tx));
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    commands.insert_resource(BootRetries(tx));
 
-    let account = env.account.clone();
-    let world_address = env.world_address;
-    let block_id = env.block_id;
+    let backend = backend.0.clone();
+    let messages = messages.sender.clone();
+    let chain_id = chain_id.map(|chain_id| (chain_id.urls.clone(), chain_id.default.clone()));
 
-    runtime.spawn_background_task(move |mut ctx| async move {
-        let world = WorldContract::new(world_address, account.as_ref());
-        let spawn_racer_system = world.system("spawn_racer", block_id).await.unwrap();
+    runtime.spawn_background_task(move |_ctx| async move {
+        if let Some((urls, default)) = chain_id {
+            let chain_id = fetch_chain_id(&urls, &default).await;
+            if messages.send(DojoMessage::ChainId(chain_id)).is_err() {
+                return;
+            }
+        }
+        let retry_delay = Duration::from_secs_f32(configs::DOJO_BOOT_RETRY_DELAY);
+        let steps: [(BootStep, &[&str]); 2] = [
+            (BootStep::ResolveWorld, &[Vehicle::NAME, Position::NAME]),
+            (BootStep::FetchSystems, configs::DOJO_BOOT_SYSTEMS),
+        ];
+        for (step, names) in steps {
+            let _ = messages.send(DojoMessage::Boot(step, StepStatus::Running));
+            for name in names {
+                let mut attempts = 0;
+                loop {
+                    let result = match step {
+                        BootStep::FetchSystems => backend.resolve_system(name).await,
+                        _ => backend.resolve_component(name).await,
+                    };
+                    let Err(e) = result else {
+                        break;
+                    };
+                    attempts += 1;
+                    if attempts < configs::DOJO_BOOT_ATTEMPTS {
+                        warn!(name, attempts, error = %e, "Resolve world handle");
+                        let status = StepStatus::Retrying(format!("{name}: {e}"));
+                        if messages.send(DojoMessage::Boot(step, status)).is_err() {
+                            return;
+                        }
+                        tokio::time::sleep(retry_delay).await;
+                        continue;
+                    }
 
-        while let Some(_) = rx.recv().await {
-            let model_id = cairo_short_string_to_felt(configs::MODEL_NAME).unwrap();
+                    error!(name, error = %e, "Resolve world handle, waiting for a retry");
+                    let status = StepStatus::Failed(format!("{name}: {e}"));
+                    if messages.send(DojoMessage::Boot(step, status)).is_err()
+                        || rx.recv().await.is_none()
+                    {
+                        return;
+                    }
+                    attempts = 0;
+                }
+            }
+            info!(?step, "Boot step done");
+            let _ = messages.send(DojoMessage::Boot(step, StepStatus::Done));
+        }
+    });
+}
 
-            match spawn_racer_system
-                .execute(vec![
-                    model_id,
-                    rand_felt_fixed_point(),
-                    FieldElement::ZERO,
-                    FieldElement::ZERO,
-                    FieldElement::ZERO,
-                ])
-                .await
-            {
-                Ok(_) => {
-                    ctx.run_on_main_thread(move |ctx| {
-                        let mut state: SystemState<(
-                            EventWriter<SpawnCar>,
-                            EventWriter<SpawnEnemies>,
-                        )> = SystemState::new(ctx.world);
-                        let (mut spawn_car, mut spawn_enemies) = state.get_mut(ctx.world);
-
-                        spawn_enemies.send(SpawnEnemies);
-                        spawn_car.send(SpawnCar);
-                    })
-                    .await;
+/// What the commands of the dojo task read and report to.
+#[derive(Clone)]
+struct CommandContext {
+    backend: Arc<dyn DojoBackend>,
+    metrics: Metrics,
+    alive_racers: watch::Receiver<Vec<FieldElement>>,
+    lineup: watch::Receiver<Lineup>,
+    messages: mpsc::UnboundedSender<DojoMessage>,
+}
+
+/// Runs the [`DojoCommand`]s, each kind one at a time: a command received while one of its kind
+/// runs waits for it, the others start right away.
+fn dojo_commands_thread(
+    backend: Res<Backend>,
+    metrics: Res<Metrics>,
+    alive_racers: Res<AliveRacers>,
+    lineup: Res<LineupSender>,
+    messages: Res<DojoMessages>,
+    runtime: ResMut<TokioTasksRuntime>,
+    mut tasks: ResMut<DojoTasks>,
+    mut commands: Commands,
+) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let pending = Arc::new(Mutex::new(HashSet::new()));
+    commands.insert_resource(DojoCommandSender {
+        sender: tx,
+        pending: pending.clone(),
+    });
+
+    let context = CommandContext {
+        backend: backend.0.clone(),
+        metrics: metrics.clone(),
+        alive_racers: alive_racers.0.subscribe(),
+        lineup: lineup.0.subscribe(),
+        messages: messages.sender.clone(),
+    };
+    let rx = Arc::new(tokio::sync::Mutex::new(rx));
+    // Received while one of their kind ran, kept across restarts so they still run
+    let waiting = Arc::new(Mutex::new(HashSet::new()));
+
+    let task = SupervisedTask::spawn(runtime.runtime(), "dojo_commands", move |mut stop| {
+        let context = context.clone();
+        let pending = pending.clone();
+        let rx = rx.clone();
+        let waiting = waiting.clone();
+        async move {
+            let mut rx = rx.lock().await;
+            let mut running = FuturesUnordered::new();
+            let mut busy = HashSet::new();
+            let start = |command: DojoCommand| {
+                pending.lock().unwrap().remove(&command);
+                let context = context.clone();
+                async move {
+                    command.run(context).await;
+                    command
                 }
-                Err(e) => {
-                    log::error!("Run spawn_racer system: {e}");
+            };
+            // Left waiting by a panic, the command they waited for is gone
+            let restarted: Vec<_> = waiting.lock().unwrap().drain().collect();
+            for command in restarted {
+                busy.insert(command);
+                running.push(start(command));
+            }
+
+            // Once the task is asked to stop, the commands already sent still run
+            let mut open = true;
+            loop {
+                tokio::select! {
+                    _ = stop.stopped(), if open => rx.close(),
+                    command = rx.recv(), if open => match command {
+                        Some(command) if busy.contains(&command) => {
+                            waiting.lock().unwrap().insert(command);
+                        }
+                        Some(command) => {
+                            busy.insert(command);
+                            running.push(start(command));
+                        }
+                        None => open = false,
+                    },
+                    Some(done) = running.next() => {
+                        let waited = waiting.lock().unwrap().remove(&done);
+                        if waited {
+                            running.push(start(done));
+                        } else {
+                            busy.remove(&done);
+                        }
+                    }
+                    else => break,
                 }
             }
         }
     });
+    tasks.0.push(task);
+}
+
+/// Spawns the racers of the lineup.
+async fn spawn_racers(context: CommandContext) {
+    let CommandContext {
+        backend,
+        metrics,
+        lineup,
+        messages,
+        ..
+    } = context;
+    let Lineup {
+        model_ids,
+        head_to_head,
+        seed,
+        enemy_count,
+    } = lineup.borrow().clone();
+    // Capped to the lanes of the world, so it fits the cairo `u8`
+    let enemies = enemy_count.spawned() as u8;
+    let race_x = rand_spawn_x();
+    for (index, model_id) in model_ids.iter().copied().enumerate() {
+        // Head-to-head racers share the road and start on mirrored lanes
+        let (x, seed) = match &head_to_head {
+            Some(race) => (race.start_x(index, race_x), race.seed),
+            None => (rand_spawn_x(), seed),
+        };
+        let span = info_span!("dojo_call", call = "spawn_racer", model = %model_id);
+
+        let timer = metrics
+            .sync_latency
+            .with_label_values(&["spawn_racer"])
+            .start_timer();
+        let result = backend
+            .execute_call(SystemCall::spawn_racer(
+                model_id,
+                Racers::model_system(model_id),
+                FixedVec2 {
+                    x: Fixed::from_mag(x),
+                    y: Fixed::ZERO,
+                },
+                seed,
+                enemies,
+            ))
+            .instrument(span.clone())
+            .await;
+        timer.observe_duration();
+
+        match result {
+            Ok(execution) => {
+                let (bevy_x, bevy_y) = dojo_to_bevy_coordinate(fixed_to_f32(x), 0.0);
+                let _ = messages.send(DojoMessage::RacerSpawned {
+                    model_id,
+                    position: Vec2::new(bevy_x, bevy_y),
+                });
+                let _ = messages.send(DojoMessage::Executed {
+                    model_id,
+                    execution,
+                });
+            }
+            Err(e) => {
+                metrics.failures.with_label_values(&["spawn_racer"]).inc();
+                error!(parent: &span, error = %e, "Run spawn_racer system");
+            }
+        }
+    }
 }
 
-fn drive_thread(env: Res<DojoEnv>, runtime: ResMut<TokioTasksRuntime>, mut commands: Commands) {
-    let (tx, mut rx) = mpsc::channel::<()>(8);
-    commands.insert_resource(DriveCommand(tx));
+/// Sends `drive` for every alive racer.
+async fn drive(context: CommandContext) {
+    let CommandContext {
+        backend,
+        metrics,
+        alive_racers,
+        messages,
+        ..
+    } = context;
+    let model_ids = alive_racers.borrow().clone();
+    for model_id in model_ids {
+        let span = info_span!("dojo_call", call = "drive", model = %model_id);
+        metrics.drive_calls.inc();
+        let timer = metrics
+            .sync_latency
+            .with_label_values(&["drive"])
+            .start_timer();
+        let result = backend
+            .execute_call(SystemCall::drive(model_id))
+            .instrument(span.clone())
+            .await;
+        timer.observe_duration();
 
-    let account = env.account.clone();
-    let world_address = env.world_address;
-    let block_id = env.block_id;
+        match result {
+            Ok(execution) => {
+                let _ = messages.send(DojoMessage::Executed {
+                    model_id,
+                    execution,
+                });
+            }
+            Err(e) => {
+                metrics.failures.with_label_values(&["drive"]).inc();
+                error!(parent: &span, error = %e, "Run drive system");
+            }
+        }
+    }
+}
 
-    runtime.spawn_background_task(move |ctx| async move {
-        let world = WorldContract::new(world_address, account.as_ref());
+/// Reads the `Vehicle` of every alive racer.
+async fn update_vehicle(context: CommandContext) {
+    let CommandContext {
+        backend,
+        metrics,
+        alive_racers,
+        messages,
+        ..
+    } = context;
+    let model_ids = alive_racers.borrow().clone();
+    let head = fetch_head(backend.as_ref(), &metrics).await;
+    for model_id in model_ids {
+        let span = info_span!("dojo_call", call = "vehicle", model = %model_id);
+        let timer = metrics
+            .sync_latency
+            .with_label_values(&["vehicle"])
+            .start_timer();
+        let result = read_entity(backend.as_ref(), Vehicle::NAME, vec![model_id], head)
+            .instrument(span.clone())
+            .await;
+        timer.observe_duration();
 
-        let drive_system = world.system("drive", block_id).await.unwrap();
+        match result {
+            Ok(vehicle) => {
+                let _ = messages.send(DojoMessage::Vehicle {
+                    model_id,
+                    vehicle,
+                    head,
+                });
+            }
+            Err(e) => {
+                metrics.failures.with_label_values(&["vehicle"]).inc();
+                error!(parent: &span, error = %e, "Query `Vehicle` component");
+            }
+        }
+    }
+}
 
-        while let Some(_) = rx.recv().await {
-            let model_id = get_model_id(ctx.clone()).await;
+/// Reads the enemy `Position`s of every alive racer.
+async fn update_enemies(context: CommandContext) {
+    let CommandContext {
+        backend,
+        metrics,
+        alive_racers,
+        lineup,
+        messages,
+    } = context;
+    let model_ids = alive_racers.borrow().clone();
+    let enemy_count = lineup.borrow().enemy_count;
+    let head = fetch_head(backend.as_ref(), &metrics).await;
+    for model_id in model_ids {
+        let backend = backend.as_ref();
+        let metrics = &metrics;
+        let positions: Vec<_> = stream::iter(enemy_count.ids())
+            .map(|i| fetch_enemy(backend, metrics, model_id, i.into(), head))
+            .buffer_unordered(configs::DOJO_ENEMIES_CONCURRENCY)
+            .filter_map(|position| async move { position })
+            .collect()
+            .await;
 
-            match model_id {
-                Some(model_id) => {
-                    if let Err(e) = drive_system.execute(vec![model_id]).await {
-                        log::error!("Run drive system: {e}");
+        if !positions.is_empty() {
+            let _ = messages.send(DojoMessage::Enemies {
+                model_id,
+                positions,
+                head,
+            });
+        }
+    }
+}
+
+fn evolve_thread(
+    backend: Res<Backend>,
+    metrics: Res<Metrics>,
+    messages: Res<DojoMessages>,
+    runtime: ResMut<TokioTasksRuntime>,
+    mut tasks: ResMut<DojoTasks>,
+    mut commands: Commands,
+) {
+    let (tx, rx) = mpsc::channel::<u32>(1);
+    commands.insert_resource(EvolveCommand(tx));
+
+    let metrics = metrics.clone();
+    let backend = backend.0.clone();
+    let messages = messages.sender.clone();
+    let rx = Arc::new(tokio::sync::Mutex::new(rx));
+
+    let task = SupervisedTask::spawn(runtime.runtime(), "evolve", move |mut stop| {
+        let metrics = metrics.clone();
+        let backend = backend.clone();
+        let messages = messages.clone();
+        let rx = rx.clone();
+        async move {
+            let mut rx = rx.lock().await;
+            let retry_delay = Duration::from_secs_f32(configs::DOJO_EVOLVE_RETRY_DELAY);
+            while let Some(generation) = stop.recv(&mut rx).await {
+                let span = info_span!("dojo_call", call = "evolve", generation);
+                // The generation stays pending until the world evolved it
+                loop {
+                    let timer = metrics
+                        .sync_latency
+                        .with_label_values(&["evolve"])
+                        .start_timer();
+                    let result = backend
+                        .execute_call(SystemCall::evolve())
+                        .instrument(span.clone())
+                        .await;
+                    timer.observe_duration();
+
+                    let Err(e) = result else {
+                        let _ = messages.send(DojoMessage::Evolved { generation });
+                        break;
+                    };
+                    metrics.failures.with_label_values(&["evolve"]).inc();
+                    error!(parent: &span, error = %e, "Run evolve system, retrying");
+                    tokio::select! {
+                        _ = stop.stopped() => return,
+                        _ = tokio::time::sleep(retry_delay) => {}
                     }
                 }
-                None => {}
             }
         }
     });
+    tasks.0.push(task);
 }
 
-fn update_vehicle_thread(
-    env: Res<DojoEnv>,
+fn report_collision_thread(
+    backend: Res<Backend>,
+    metrics: Res<Metrics>,
     runtime: ResMut<TokioTasksRuntime>,
+    mut tasks: ResMut<DojoTasks>,
     mut commands: Commands,
 ) {
-    let (tx, mut rx) = mpsc::channel::<()>(16);
-    commands.insert_resource(UpdateVehicleCommand(tx));
-
-    let account = env.account.clone();
-    let world_address = env.world_address;
-    let block_id = env.block_id;
-
-    runtime.spawn_background_task(move |mut ctx| async move {
-        let world = WorldContract::new(world_address, account.as_ref());
-        let vehicle_component = world.component("Vehicle", block_id).await.unwrap();
-
-        while let Some(_) = rx.recv().await {
-            let model_id = get_model_id(ctx.clone()).await;
-
-            if let Some(model_id) = model_id {
-                match vehicle_component
-                    .entity(FieldElement::ZERO, vec![model_id], block_id)
-                    .await
-                {
-                    Ok(vehicle) => {
-                        ctx.run_on_main_thread(move |ctx| {
-                            let mut state: SystemState<EventWriter<UpdateCar>> =
-                                SystemState::new(ctx.world);
-                            let mut update_car = state.get_mut(ctx.world);
-
-                            update_car.send(UpdateCar { vehicle })
-                        })
-                        .await;
+    let (tx, rx) = mpsc::channel::<FieldElement>(configs::DOJO_COLLISIONS_CAPACITY);
+    commands.insert_resource(ReportCollisionCommand(tx));
+
+    let metrics = metrics.clone();
+    let backend = backend.0.clone();
+    let rx = Arc::new(tokio::sync::Mutex::new(rx));
+
+    let task = SupervisedTask::spawn(runtime.runtime(), "report_collision", move |mut stop| {
+        let metrics = metrics.clone();
+        let backend = backend.clone();
+        let rx = rx.clone();
+        async move {
+            let mut rx = rx.lock().await;
+            while let Some(model_id) = stop.recv(&mut rx).await {
+                let span = info_span!("dojo_call", call = "report_collision", model = %model_id);
+                let timer = metrics
+                    .sync_latency
+                    .with_label_values(&["report_collision"])
+                    .start_timer();
+                let result = backend
+                    .execute_call(SystemCall::report_collision(model_id))
+                    .instrument(span.clone())
+                    .await;
+                timer.observe_duration();
+
+                if let Err(e) = result {
+                    metrics
+                        .failures
+                        .with_label_values(&["report_collision"])
+                        .inc();
+                    error!(parent: &span, error = %e, "Run report_collision system");
+                }
+            }
+        }
+    });
+    tasks.0.push(task);
+}
+
+fn submit_score_thread(
+    backend: Res<Backend>,
+    metrics: Res<Metrics>,
+    messages: Res<DojoMessages>,
+    runtime: ResMut<TokioTasksRuntime>,
+    mut tasks: ResMut<DojoTasks>,
+    mut commands: Commands,
+) {
+    let (tx, rx) = mpsc::channel::<Score>(configs::DOJO_SCORES_CAPACITY);
+    commands.insert_resource(SubmitScoreCommand(tx));
+
+    let metrics = metrics.clone();
+    let backend = backend.0.clone();
+    let messages = messages.sender.clone();
+    let rx = Arc::new(tokio::sync::Mutex::new(rx));
+
+    let task = SupervisedTask::spawn(runtime.runtime(), "submit_score", move |mut stop| {
+        let metrics = metrics.clone();
+        let backend = backend.clone();
+        let messages = messages.clone();
+        let rx = rx.clone();
+        async move {
+            let mut rx = rx.lock().await;
+            while let Some(score) = stop.recv(&mut rx).await {
+                let span = info_span!("dojo_call", call = "submit_score", model = %score.model_id);
+                let timer = metrics
+                    .sync_latency
+                    .with_label_values(&["submit_score"])
+                    .start_timer();
+                let result = backend
+                    .execute_call(SystemCall::submit_score(score.model_id))
+                    .instrument(span.clone())
+                    .await;
+                timer.observe_duration();
+
+                match result {
+                    Ok(execution) => {
+                        let _ = messages.send(DojoMessage::ScoreSubmitted {
+                            model_id: score.model_id,
+                            distance: score.distance,
+                            execution,
+                        });
                     }
                     Err(e) => {
-                        log::error!("Query `Vehicle` component: {e}");
+                        metrics.failures.with_label_values(&["submit_score"]).inc();
+                        error!(parent: &span, error = %e, "Run submit_score system");
                     }
                 }
             }
         }
     });
+    tasks.0.push(task);
 }
 
-fn update_enemies_thread(
-    env: Res<DojoEnv>,
+fn finish_race_thread(
+    backend: Res<Backend>,
+    metrics: Res<Metrics>,
+    messages: Res<DojoMessages>,
     runtime: ResMut<TokioTasksRuntime>,
+    mut tasks: ResMut<DojoTasks>,
     mut commands: Commands,
 ) {
-    let (tx, mut rx) = mpsc::channel::<()>(16);
-    commands.insert_resource(UpdateEnemiesCommand(tx));
-
-    let account = env.account.clone();
-    let world_address = env.world_address;
-    let block_id = env.block_id;
-
-    runtime.spawn_background_task(move |mut ctx| async move {
-        let world = WorldContract::new(world_address, account.as_ref());
-        let position_component = world.component("Position", block_id).await.unwrap();
-
-        while let Some(_) = rx.recv().await {
-            let model_id = get_model_id(ctx.clone()).await;
-
-            if let Some(model_id) = model_id {
-                // TODO: query multiple enemies at once
-                for i in 0..configs::DOJO_ENEMIES_NB {
-                    let enemy_id: FieldElement = i.into();
-
-                    match position_component
-                        .entity(
-                            FieldElement::ZERO,
-                            vec![model_id, enemy_id.into()],
-                            block_id,
-                        )
-                        .await
-                    {
-                        Ok(position) => {
-                            ctx.run_on_main_thread(move |ctx| {
-                                let mut state: SystemState<EventWriter<UpdateEnemy>> =
-                                    SystemState::new(ctx.world);
-                                let mut update_enemy = state.get_mut(ctx.world);
-
-                                update_enemy.send(UpdateEnemy { position, enemy_id })
-                            })
-                            .await
-                        }
-                        Err(e) => {
-                            log::error!("Query `Position` component: {e}");
-                        }
+    let (tx, rx) = mpsc::channel::<FieldElement>(configs::DOJO_SCORES_CAPACITY);
+    commands.insert_resource(FinishRaceCommand(tx));
+
+    let metrics = metrics.clone();
+    let backend = backend.0.clone();
+    let messages = messages.sender.clone();
+    let rx = Arc::new(tokio::sync::Mutex::new(rx));
+
+    let task = SupervisedTask::spawn(runtime.runtime(), "finish_race", move |mut stop| {
+        let metrics = metrics.clone();
+        let backend = backend.clone();
+        let messages = messages.clone();
+        let rx = rx.clone();
+        async move {
+            let mut rx = rx.lock().await;
+            while let Some(model_id) = stop.recv(&mut rx).await {
+                let span = info_span!("dojo_call", call = "finish_race", model = %model_id);
+                let timer = metrics
+                    .sync_latency
+                    .with_label_values(&["finish_race"])
+                    .start_timer();
+                let result = backend
+                    .execute_call(SystemCall::finish_race(model_id))
+                    .instrument(span.clone())
+                    .await;
+                timer.observe_duration();
+
+                match result {
+                    Ok(execution) => {
+                        let _ = messages.send(DojoMessage::Executed {
+                            model_id,
+                            execution,
+                        });
+                    }
+                    Err(e) => {
+                        metrics.failures.with_label_values(&["finish_race"]).inc();
+                        error!(parent: &span, error = %e, "Run finish_race system");
                     }
                 }
             }
         }
     });
+    tasks.0.push(task);
 }
 
-#[derive(Resource)]
-pub struct SpawnRacersCommand(mpsc::Sender<()>);
+/// Latest block, the reads of a sync tick are all made at it.
+async fn fetch_head(backend: &dyn DojoBackend, metrics: &Metrics) -> Option<BlockHead> {
+    match backend.head().await {
+        Ok(head) => Some(head),
+        Err(e) => {
+            metrics.failures.with_label_values(&["head"]).inc();
+            error!(error = %e, "Query latest block");
+            None
+        }
+    }
+}
 
-// TODO: derive macro?
-impl SpawnRacersCommand {
-    pub fn try_send(&self) -> Result<(), mpsc::error::TrySendError<()>> {
-        self.0.try_send(())
+/// Reads `component` at `head`, or at the latest block when it is unknown.
+async fn read_entity(
+    backend: &dyn DojoBackend,
+    component: &str,
+    keys: Vec<FieldElement>,
+    head: Option<BlockHead>,
+) -> eyre::Result<Vec<FieldElement>> {
+    match head {
+        Some(head) => backend.entity_at(component, keys, head).await,
+        None => backend.entity(component, keys).await,
     }
 }
 
-#[derive(Resource)]
-struct DriveCommand(mpsc::Sender<()>);
+async fn fetch_enemy(
+    backend: &dyn DojoBackend,
+    metrics: &Metrics,
+    model_id: FieldElement,
+    enemy_id: FieldElement,
+    head: Option<BlockHead>,
+) -> Option<EnemyPosition> {
+    let span = info_span!(
+        "dojo_call",
+        call = "enemy",
+        model = %model_id,
+        enemy = %enemy_id
+    );
+
+    let timer = metrics
+        .sync_latency
+        .with_label_values(&["enemy"])
+        .start_timer();
+    let result = read_entity(backend, Position::NAME, vec![model_id, enemy_id], head)
+        .instrument(span.clone())
+        .await;
+    timer.observe_duration();
 
-// TODO: derive macro?
-impl DriveCommand {
-    fn try_send(&self) -> Result<(), mpsc::error::TrySendError<()>> {
-        self.0.try_send(())
+    let position = match result {
+        Ok(values) => values,
+        Err(e) => {
+            metrics.failures.with_label_values(&["enemy"]).inc();
+            error!(parent: &span, error = %e, "Query `Position` component");
+            return None;
+        }
+    };
+    match EnemyPosition::decode(model_id, enemy_id, &position) {
+        Ok(position) => Some(position),
+        Err(e) => {
+            error!(parent: &span, error = %e, "Decode `Position` component");
+            None
+        }
     }
 }
 
+/// Generation that just ended, sent to the `evolve` task.
 #[derive(Resource)]
-struct UpdateVehicleCommand(mpsc::Sender<()>);
+struct EvolveCommand(mpsc::Sender<u32>);
 
-impl UpdateVehicleCommand {
-    fn try_send(&self) -> Result<(), mpsc::error::TrySendError<()>> {
-        self.0.try_send(())
-    }
+/// Model ids of the crashed racers, sent to the `report_collision` task.
+#[derive(Resource)]
+struct ReportCollisionCommand(mpsc::Sender<FieldElement>);
+
+/// A finished run submitted to the world.
+struct Score {
+    model_id: FieldElement,
+    /// Grid units, as the client saw it, for the confirmation toast.
+    distance: u128,
 }
 
 #[derive(Resource)]
-pub struct UpdateEnemiesCommand(mpsc::Sender<()>);
+struct SubmitScoreCommand(mpsc::Sender<Score>);
 
-impl UpdateEnemiesCommand {
-    pub fn try_send(&self) -> Result<(), mpsc::error::TrySendError<()>> {
-        self.0.try_send(())
+/// Model ids of the runs that reached the finish line.
+#[derive(Resource)]
+struct FinishRaceCommand(mpsc::Sender<FieldElement>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rpc_urls_name_their_setting() {
+        let url = parse_rpc_url("JSON_RPC_ENDPOINT", "http://localhost:5050").unwrap();
+        assert_eq!(url.port(), Some(5050));
+
+        let error = parse_rpc_url("JSON_RPC_ENDPOINT", "localhost:5050").unwrap_err();
+        assert!(error.to_string().starts_with("JSON_RPC_ENDPOINT"));
+        let error = parse_rpc_url("JSON_RPC_FALLBACK_ENDPOINTS[0]", "not a url").unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("JSON_RPC_FALLBACK_ENDPOINTS[0]"));
     }
-}
 
-pub fn fixed_to_f32(val: FieldElement) -> f32 {
-    BigUint::from_str(&val.to_string())
-        .unwrap()
-        .div(BigUint::from_i8(2).unwrap().pow(64))
-        .to_f32()
-        .unwrap()
-}
+    #[test]
+    fn test_chain_tracker_drops_forked_and_stale_reads() {
+        let head = |number, hash: u32| BlockHead {
+            number,
+            hash: hash.into(),
+        };
+        let vehicle = ReadStream::Vehicle(FieldElement::ONE);
+        let enemies = ReadStream::Enemies(FieldElement::ONE);
+        let mut chain = ChainTracker::default();
 
-pub fn dojo_to_bevy_coordinate(dojo_x: f32, dojo_y: f32) -> (f32, f32) {
-    let bevy_x = dojo_x * configs::DOJO_TO_BEVY_RATIO_X + ROAD_X_MIN;
-    let bevy_y = dojo_y * configs::DOJO_TO_BEVY_RATIO_Y;
+        assert!(chain.observe(vehicle, head(10, 10)));
+        assert!(chain.observe(enemies, head(11, 11)));
+        // Streams are ordered on their own, the vehicle read of block 10 may arrive last
+        assert!(chain.observe(vehicle, head(10, 10)));
 
-    // log::info!("dojo_x: {}, dojo_y: {}", dojo_x, dojo_y);
-    // log::info!("bevy_x: {}, bevy_y: {}", bevy_x, bevy_y);
+        // Block 11 was replaced
+        assert!(!chain.observe(vehicle, head(11, 12)));
+        assert!(chain.observe(enemies, head(11, 12)));
 
-    (bevy_x, bevy_y)
-}
+        // Before the last applied read
+        assert!(chain.observe(vehicle, head(13, 13)));
+        assert!(!chain.observe(vehicle, head(12, 14)));
+        assert!(!chain.observe(vehicle, head(12, 14)));
+        assert!(chain.observe(vehicle, head(13, 13)));
+    }
+
+    #[test]
+    fn test_health_checks_track_the_connection() {
+        let mut connection = Connection::default();
+        connection.checked(None);
+        connection.checked(None);
+        assert_eq!(connection, Connection::Unreachable { failures: 2 });
+        connection.checked(Some(0.5));
+        assert_eq!(connection, Connection::Healthy { latency: 0.5 });
+    }
 
-async fn get_model_id(mut ctx: TaskContext) -> Option<FieldElement> {
-    ctx.run_on_main_thread(move |ctx| {
-        let mut state: SystemState<Query<&Model, With<Car>>> = SystemState::new(ctx.world);
-        let query = state.get(ctx.world);
+    #[test]
+    fn test_a_command_of_each_kind_is_pending_at_once() {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let pending = Arc::new(Mutex::new(HashSet::new()));
+        let commands = DojoCommandSender {
+            sender,
+            pending: pending.clone(),
+        };
 
-        match query.get_single() {
-            Ok(model) => Some(model.id),
-            Err(_) => None,
+        assert!(commands.try_send(DojoCommand::Drive).is_ok());
+        assert!(commands.try_send(DojoCommand::UpdateVehicle).is_ok());
+        assert!(matches!(
+            commands.try_send(DojoCommand::Drive),
+            Err(TrySendError::Full(DojoCommand::Drive))
+        ));
+
+        // Started by the dojo task
+        assert_eq!(receiver.try_recv().unwrap(), DojoCommand::Drive);
+        pending.lock().unwrap().remove(&DojoCommand::Drive);
+        assert!(commands.try_send(DojoCommand::Drive).is_ok());
+
+        drop(receiver);
+        assert!(matches!(
+            commands.try_send(DojoCommand::SpawnRacers),
+            Err(TrySendError::Closed(_))
+        ));
+    }
+
+    #[test]
+    fn test_sync_stats_flag_congested_windows() {
+        let mut stats = SyncStats::default();
+        for rejected in [false, true, true, true] {
+            stats.record("update_enemies", rejected);
+        }
+        for rejected in [false, false, false, true] {
+            stats.record("update_vehicle", rejected);
         }
-    })
-    .await
+
+        assert_eq!(
+            stats.end_window(),
+            vec![SyncCongested {
+                channel: "update_enemies",
+                rejection_rate: 0.75,
+            }]
+        );
+        assert_eq!(stats.rejected(), 4);
+
+        // Counts of the whole run are kept, the window starts over
+        stats.record("update_enemies", false);
+        assert!(stats.end_window().is_empty());
+        assert_eq!(stats.channels["update_enemies"].rejection_rate(), 0.6);
+    }
 }
@@ -3,7 +3,7 @@ use cubit::types::vec2::Vec2;
 use cubit::types::fixed::FixedTrait;
 use drive_ai::racer::{CAR_HEIGHT as CAR_HEIGHT_SCALED, CAR_WIDTH as CAR_WIDTH_SCALED};
 
-/// Number of enemies to spawn.
+/// Most enemies a racer can be spawned with, one per lane.
 const ENEMIES_NB: u8 = 10;
 
 /// Height of the grid.
@@ -12,6 +12,9 @@ const GRID_HEIGHT: u128 = 1000;
 const GRID_WIDTH: u128 = 400;
 
 const HALF_GRID_WIDTH: u128 = 200;
+/// Width of the lane each enemy drives in: [`GRID_WIDTH`] / [`ENEMIES_NB`].
+/// The client splits the road in the same lanes.
+const LANE_WIDTH: u128 = 40;
 
 const CAR_HEIGHT: u128 = 32;
 const CAR_WIDTH: u128 = 16;
@@ -68,19 +71,22 @@ mod spawn_enemies {
     use dojo::world::Context;
 
     use drive_ai::Vehicle;
-    use super::{Position, ENEMIES_NB, GRID_HEIGHT, GRID_WIDTH, CAR_WIDTH};
+    use super::{Position, GRID_HEIGHT, LANE_WIDTH, CAR_WIDTH};
 
-    /// Spawn [`ENEMIES_NB`] enemies. Each enemy has its own x range that corresponds to: 
-    /// [`GRID_WIDTH`] * car_nb / [`ENEMIES_NB`] to [`GRID_WIDTH`] * (car_nb + 1) / [`ENEMIES_NB`]
+    /// Spawn `enemies` enemies, at most [`ENEMIES_NB`].
+    /// Each enemy has its own x range that corresponds to its lane:
+    /// [`LANE_WIDTH`] * car_nb to [`LANE_WIDTH`] * (car_nb + 1)
     /// So they don't spawn on top of each other.
-    /// The initial Y position is determined by model * car_nb / [GRID_HEIGHT] % GRID_HEIGHT 
+    /// The initial Y position is determined by seed * car_nb / [GRID_HEIGHT] % GRID_HEIGHT 
     /// This division is a felt division so that cars don't spawn in diagonal and are well spread out
     ///
     /// # Arguments
     ///
     /// * `ctx` - Context of the game.
     /// * `model` - The AI model id to namespace the games.
-    fn execute(ctx: Context, model: felt252) {
+    /// * `seed` - Value the enemy positions are derived from, the same seed gives the same road.
+    /// * `enemies` - Number of enemies to spawn, picked by the client.
+    fn execute(ctx: Context, model: felt252, seed: felt252, enemies: u8) {
         let mut i: usize = 0;
         // Get the grid height as [`NonZero<felt252>`] for later div.
         let grid_height: felt252 = GRID_HEIGHT.into();
@@ -92,14 +98,13 @@ mod spawn_enemies {
             low: GRID_HEIGHT.into(), high: 0_u128
         }.try_into().unwrap();
 
-        // ENEMIES_NB is a constant not set to 0 so it can't panic.
-        let x_range: u128 = GRID_WIDTH.into() / ENEMIES_NB.into() - 2 * CAR_WIDTH.into();
+        let x_range: u128 = LANE_WIDTH - 2 * CAR_WIDTH;
 
         loop {
-            if i == ENEMIES_NB.into() {
+            if i == enemies.into() {
                 break ();
             }
-            let numerator: felt252 = model.into() + i.into();
+            let numerator: felt252 = seed + i.into();
             // This value gives us a """random""" value to better spread the enemies on the grid at init.
             let base_value = felt252_div(numerator, grid_height);
             // Resize the value so it fits in the x range given for enemies so they don't overlap.
@@ -111,7 +116,7 @@ mod spawn_enemies {
                 ctx.world,
                 (model, i).into(),
                 (Position {
-                    x: x_rem.low + CAR_WIDTH + (2 * CAR_WIDTH + x_range) * i.into(), y: y_rem.low
+                    x: x_rem.low + CAR_WIDTH + LANE_WIDTH * i.into(), y: y_rem.low
                 })
             );
             i += 1;
@@ -147,6 +152,10 @@ mod tests_spawn {
         let mut calldata: Array<felt252> = ArrayTrait::new();
         // Model.
         calldata.append(1);
+        // Seed.
+        calldata.append(1);
+        // Enemies.
+        calldata.append(ENEMIES_NB.into());
         world.execute('spawn_enemies'.into(), calldata.span());
         let mut i: usize = 0;
         let players: usize = ENEMIES_NB.into();
@@ -205,7 +214,7 @@ mod move_enemies {
 
     use dojo::world::Context;
 
-    use super::{Position, CAR_HEIGHT, CAR_VELOCITY, ENEMIES_NB, GRID_HEIGHT, GRID_WIDTH, CAR_WIDTH};
+    use super::{Position, CAR_HEIGHT, CAR_VELOCITY, GRID_HEIGHT, GRID_WIDTH, LANE_WIDTH, CAR_WIDTH};
 
     /// Executes a tick for the enemies.
     /// During a tick the enemies will need to be moved/respawned if they go out of the grid.
@@ -214,12 +223,13 @@ mod move_enemies {
     ///
     /// * `ctx` - Context of the game.
     /// * `model` - The AI model id to namespace the games.
-    fn execute(ctx: Context, model: felt252) {
+    /// * `enemies` - Number of enemies the racer was spawned with.
+    fn execute(ctx: Context, model: felt252, enemies: u8) {
         // Iterate through the enemies and move them. If the are out of the grid respawn them at 
         // the top of the grid
         let mut i: u8 = 0;
         loop {
-            if i == ENEMIES_NB {
+            if i == enemies {
                 break ();
             }
             let key = (model, i).into();
@@ -264,8 +274,7 @@ mod move_enemies {
         let grid_width: felt252 = GRID_WIDTH.into();
         // GRID_WIDTH is a constant not set to 0 so it can't panic.
         let grid_width: NonZero<felt252> = grid_width.try_into().unwrap();
-        // ENEMIES_NB is a constant not set to 0 so it can't panic.
-        let x_range: u128 = GRID_WIDTH.into() / ENEMIES_NB.into() - 2 * CAR_WIDTH.into();
+        let x_range: u128 = LANE_WIDTH - 2 * CAR_WIDTH;
         let base_value = felt252_div(x.into(), grid_width);
         let (_, x_rem) = u256_safe_divmod(base_value.into(), u256_as_non_zero(x_range.into()));
         let new_y = if y <= velocity + height {
@@ -275,7 +284,7 @@ mod move_enemies {
         };
 
         Position {
-            x: x_rem.low + CAR_WIDTH + (2 * CAR_WIDTH + x_range) * enemy_nb.into(), y: new_y
+            x: x_rem.low + CAR_WIDTH + LANE_WIDTH * enemy_nb.into(), y: new_y
         }
     }
 }
@@ -352,7 +361,16 @@ mod tests_move {
         let mut calldata: Array<felt252> = ArrayTrait::new();
         // Model.
         calldata.append(1);
+        // Seed.
+        calldata.append(1);
+        // Enemies.
+        calldata.append(ENEMIES_NB.into());
         world.execute('spawn_enemies'.into(), calldata.span());
+        let mut calldata: Array<felt252> = ArrayTrait::new();
+        // Model.
+        calldata.append(1);
+        // Enemies.
+        calldata.append(ENEMIES_NB.into());
         world.execute('move_enemies'.into(), calldata.span());
         world.execute('move_enemies'.into(), calldata.span());
         let mut i: usize = 0;
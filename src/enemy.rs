@@ -1,8 +1,19 @@
-use crate::{configs::*, dojo::dojo_to_bevy_coordinate};
-use bevy::{log, math::vec3, prelude::*};
+use crate::accessibility::AccessibilityConfig;
+use crate::difficulty::Difficulty;
+use crate::race::HeadToHead;
+use crate::{
+    configs::*,
+    conversions::{dojo_to_bevy_coordinate, short_string_to_felt},
+    resources::{PhysicsConfig, Standalone},
+    speed_zone::SpeedZones,
+    world,
+};
+use bevy::{log, math::vec3, prelude::*, utils::HashMap};
 use bevy_rapier2d::prelude::*;
-use rand::{thread_rng, Rng};
-use starknet::core::types::FieldElement;
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
+use serde::Deserialize;
+use starknet_ff::FieldElement;
+use std::path::Path;
 
 pub struct EnemyPlugin;
 
@@ -14,97 +25,598 @@ pub struct Enemy {
 #[derive(Component)]
 pub struct EnemyId(pub FieldElement);
 
-#[derive(Clone, Component, Reflect)]
+/// Model id of the racer an enemy belongs to, each racer has its own set of enemies on chain.
+#[derive(Component)]
+pub struct RacerId(pub FieldElement);
+
+/// Last position synced from the world, in world coordinates, `None` until the first sync.
+#[derive(Component, Default)]
+pub struct SyncedPosition(pub Option<Vec2>);
+
+/// Velocity derived from the last two synced positions, used to keep the enemy moving between
+/// syncs for up to [`DOJO_EXTRAPOLATION_HORIZON`] seconds.
+#[derive(Component, Default)]
+pub struct EnemyMotion {
+    pub velocity: Vec2,
+    /// Seconds since the synced position last changed.
+    pub since_sync: f32,
+}
+
+/// Lane of the road an enemy drives in, numbered from the left like on the world grid.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Lane(pub u32);
+
+/// Splits the road between [`ROAD_X_MIN`] and [`ROAD_X_MAX`] in `count` lanes of equal width,
+/// the same lanes the world spawns and moves enemies in.
+#[derive(Resource, Clone, Debug)]
+pub struct Lanes {
+    pub x_min: f32,
+    pub x_max: f32,
+    pub count: u32,
+}
+
+impl Default for Lanes {
+    fn default() -> Self {
+        Self {
+            x_min: ROAD_X_MIN,
+            x_max: ROAD_X_MAX,
+            count: DOJO_LANES_NB,
+        }
+    }
+}
+
+impl Lanes {
+    /// Lanes `width` wide in total, centered on the road.
+    pub fn centered(width: f32, count: u32) -> Self {
+        let center = (ROAD_X_MIN + ROAD_X_MAX) / 2.0;
+        Self {
+            x_min: center - width / 2.0,
+            x_max: center + width / 2.0,
+            count,
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        (self.x_max - self.x_min) / self.count as f32
+    }
+
+    /// Lane at bevy `x`, clamped to the road.
+    pub fn lane_at(&self, x: f32) -> Lane {
+        let index = ((x - self.x_min) / self.width()).floor().max(0.0) as u32;
+        Lane(index.min(self.count - 1))
+    }
+
+    /// Bevy `x` of the middle of `lane`.
+    pub fn center_x(&self, lane: Lane) -> f32 {
+        self.x_min + (lane.0 as f32 + 0.5) * self.width()
+    }
+}
+
+/// Seed the enemies are laid out from. Every racer of a generation gets the same one, so they
+/// all face the same traffic; it is sent to the world with `spawn_racer` too.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EnemySeed(pub FieldElement);
+
+impl Default for EnemySeed {
+    fn default() -> Self {
+        Self::for_generation(0)
+    }
+}
+
+impl EnemySeed {
+    pub fn for_generation(generation: u32) -> Self {
+        Self(short_string_to_felt(&format!("gen-{generation}")))
+    }
+
+    /// Random number generator of the layout, the same for every racer.
+    pub fn rng(&self) -> StdRng {
+        let bytes = self.0.to_bytes_be();
+        let mut low = [0; 8];
+        low.copy_from_slice(&bytes[24..]);
+        StdRng::seed_from_u64(u64::from_be_bytes(low))
+    }
+}
+
+/// Enemies spawned per racer, picked at runtime with `--enemies`, in the models menu or by the
+/// difficulty. Read when a run starts, so a change applies from the next one.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EnemyCount(pub u32);
+
+impl Default for EnemyCount {
+    fn default() -> Self {
+        Self(DIFFICULTY_NORMAL.enemy_count)
+    }
+}
+
+impl EnemyCount {
+    /// Enemies the world spawns for a racer, at most [`DOJO_ENEMIES_NB`], one per lane.
+    pub fn spawned(self) -> u32 {
+        self.0.min(DOJO_ENEMIES_NB)
+    }
+
+    /// Ids of the enemies of a racer.
+    pub fn ids(self) -> std::ops::Range<u32> {
+        0..self.spawned()
+    }
+}
+
+/// Lateral tween of an enemy synced in another lane, from the `x` it was at to its synced `x`.
+#[derive(Component, Debug)]
+pub struct LaneChange {
+    pub from_x: f32,
+    pub to_x: f32,
+    pub elapsed: f32,
+}
+
+impl LaneChange {
+    fn new(from_x: f32, to_x: f32) -> Self {
+        Self {
+            from_x,
+            to_x,
+            elapsed: 0.0,
+        }
+    }
+
+    /// `x` and sprite lean `elapsed` seconds into the change, eased in and out.
+    fn at(&self, elapsed: f32) -> (f32, f32) {
+        let t = (elapsed / ENEMY_LANE_CHANGE_SECONDS).clamp(0.0, 1.0);
+        let eased = t * t * (3.0 - 2.0 * t);
+        let x = self.from_x + (self.to_x - self.from_x) * eased;
+        // Lean into the turn: clockwise when moving right
+        let lean = -(self.to_x - self.from_x).signum()
+            * ENEMY_LANE_CHANGE_TILT
+            * (t * std::f32::consts::PI).sin();
+        (x, lean)
+    }
+}
+
+/// How an enemy moves, with the parameters of its behavior.
+#[derive(Clone, Component, Reflect, Deserialize, Debug, PartialEq)]
 pub enum EnemyType {
     Simple,
     Horizontal(f32),
     Truck,
 }
 
+/// Sprites, size and behavior of a kind of enemy, e.g.
+/// `(behavior: Truck, sprites: ["enemy-truck.png"], scale: 3.0, half_extents: (6.0, 15.0))`.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct EnemyDefinition {
+    pub behavior: EnemyType,
+    /// One is picked at random for each enemy.
+    pub sprites: Vec<String>,
+    pub scale: f32,
+    /// Collider half extents, before the sprite scale.
+    pub half_extents: Vec2,
+}
+
+impl EnemyDefinition {
+    fn sprite(&self) -> &str {
+        let mut rng = thread_rng();
+        &self.sprites[rng.gen_range(0..self.sprites.len())]
+    }
+}
+
+/// A definition per kind of [`EnemyType`], read from the RON files of [`ENEMIES_DIR`] so
+/// traffic can be modded without rebuilding. Kinds without a file keep their built-in one.
+/// Synced with a world, which collides enemies with its own geometry, they keep their built-in
+/// size: see [`EnemyDefinitions::keep_builtin_size`].
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct EnemyDefinitions(pub Vec<EnemyDefinition>);
+
+impl Default for EnemyDefinitions {
+    fn default() -> Self {
+        let definition = |behavior, sprites: &[&str], scale, half_extents| EnemyDefinition {
+            behavior,
+            sprites: sprites.iter().map(|sprite| sprite.to_string()).collect(),
+            scale,
+            half_extents,
+        };
+        Self(vec![
+            definition(
+                EnemyType::Horizontal(3.0),
+                &[
+                    "enemy-blue-2.png",
+                    "enemy-yellow-2.png",
+                    "enemy-yellow-3.png",
+                ],
+                2.5,
+                ENEMY_COLLIDER,
+            ),
+            definition(
+                EnemyType::Simple,
+                &["enemy-blue-1.png", "enemy-yellow-1.png"],
+                2.5,
+                ENEMY_COLLIDER,
+            ),
+            definition(EnemyType::Truck, &["enemy-truck.png"], 3.0, TRUCK_COLLIDER),
+        ])
+    }
+}
+
+impl EnemyDefinitions {
+    /// Reads the `.ron` files of `dir`, in file name order.
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let mut paths = std::fs::read_dir(dir)
+            .map_err(|e| e.to_string())?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .map_or(false, |extension| extension == "ron")
+            })
+            .collect::<Vec<_>>();
+        paths.sort();
+        let sources = paths
+            .iter()
+            .map(|path| {
+                std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::parse(sources.iter().map(String::as_str))
+    }
+
+    /// Parses a definition from each source, the built-in ones filling in for missing kinds.
+    pub fn parse<'a>(sources: impl IntoIterator<Item = &'a str>) -> Result<Self, String> {
+        let mut definitions = Vec::new();
+        for source in sources {
+            let definition: EnemyDefinition = ron::from_str(source).map_err(|e| e.to_string())?;
+            if definition.sprites.is_empty() {
+                return Err(format!("No sprite for {:?}", definition.behavior));
+            }
+            if definitions
+                .iter()
+                .any(|d: &EnemyDefinition| d.behavior.same_kind(&definition.behavior))
+            {
+                return Err(format!("Two definitions for {:?}", definition.behavior));
+            }
+            definitions.push(definition);
+        }
+        for builtin in Self::default().0 {
+            if !definitions
+                .iter()
+                .any(|d| d.behavior.same_kind(&builtin.behavior))
+            {
+                definitions.push(builtin);
+            }
+        }
+        Ok(Self(definitions))
+    }
+
+    /// Puts back the built-in `scale` and `half_extents`, so client collisions match the world.
+    /// Whether a definition was resized.
+    pub fn keep_builtin_size(&mut self) -> bool {
+        let builtins = Self::default();
+        let mut resized = false;
+        for definition in self.0.iter_mut() {
+            let builtin = builtins.get(&definition.behavior);
+            resized |= definition.scale != builtin.scale
+                || definition.half_extents != builtin.half_extents;
+            definition.scale = builtin.scale;
+            definition.half_extents = builtin.half_extents;
+        }
+        resized
+    }
+
+    pub fn get(&self, enemy_type: &EnemyType) -> &EnemyDefinition {
+        self.0
+            .iter()
+            .find(|definition| definition.behavior.same_kind(enemy_type))
+            .expect("Every kind of enemy has a definition")
+    }
+
+    /// A truck with `truck_chance`, one of the cars otherwise.
+    pub fn random(&self, truck_chance: f64, rng: &mut impl Rng) -> EnemyType {
+        if rng.gen_bool(truck_chance.clamp(0.0, 1.0)) {
+            return self.get(&EnemyType::Truck).behavior.clone();
+        }
+        let cars: Vec<_> = self
+            .0
+            .iter()
+            .filter(|definition| definition.behavior != EnemyType::Truck)
+            .collect();
+        cars[rng.gen_range(0..cars.len())].behavior.clone()
+    }
+}
+
 // #[derive(Component)]
 // pub struct BoundControlTruck;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<SpawnEnemies>()
-            .add_event::<UpdateEnemy>()
-            .add_systems((spawn_enemies, update_enemy));
+        let definitions = EnemyDefinitions::load(Path::new(ENEMIES_DIR)).unwrap_or_else(|e| {
+            log::error!("Load enemy definitions {ENEMIES_DIR}: {e}");
+            EnemyDefinitions::default()
+        });
+        app.insert_resource(definitions)
+            .init_resource::<Lanes>()
+            .init_resource::<EnemySeed>()
+            .init_resource::<EnemyCount>()
+            .init_resource::<PhysicsConfig>()
+            .register_type::<PhysicsConfig>()
+            .add_event::<SpawnEnemies>()
+            .add_event::<UpdateEnemies>()
+            .add_startup_system(keep_synced_enemy_size)
+            .add_systems((spawn_enemies, update_enemies).chain())
+            .add_systems(
+                (extrapolate_enemies, tween_lane_changes)
+                    .chain()
+                    .in_schedule(CoreSchedule::FixedUpdate),
+            )
+            .add_system(apply_physics_config.run_if(resource_changed::<PhysicsConfig>()));
         // app.add_startup_system(setup)
         //     .add_system(update_enemies)
         //     .add_system(bound_control_system);
     }
 }
 
-pub struct SpawnEnemies;
+pub struct SpawnEnemies {
+    pub model_id: FieldElement,
+}
 
 fn spawn_enemies(
     mut events: EventReader<SpawnEnemies>,
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    lanes: Res<Lanes>,
+    physics: Res<PhysicsConfig>,
+    definitions: Res<EnemyDefinitions>,
+    difficulty: Option<Res<Difficulty>>,
+    seed: Res<EnemySeed>,
+    enemy_count: Res<EnemyCount>,
+    head_to_head: Option<Res<HeadToHead>>,
+    standalone: Option<Res<Standalone>>,
 ) {
-    for _ in events.iter() {
-        for id in 0..DOJO_ENEMIES_NB {
-            let enemy_type = EnemyType::random();
-            let enemy_scale = match enemy_type {
-                EnemyType::Truck => 3.0,
-                _ => 2.5,
-            };
-            let collider = match enemy_type {
-                EnemyType::Truck => Collider::cuboid(6.0, 15.0),
-                _ => Collider::cuboid(4.0, 8.0),
+    let preset = difficulty.map_or(DIFFICULTY_NORMAL, |difficulty| difficulty.preset());
+    // Head-to-head racers share the seed of their race instead
+    let seed = head_to_head.map_or(*seed, |race| EnemySeed(race.seed));
+    for e in events.iter() {
+        let mut rng = seed.rng();
+        for id in enemy_count.ids() {
+            // Placed in its lane until the first sync gives its position
+            let lane = Lane(id % lanes.count);
+            let enemy_type = definitions.random(preset.truck_chance, &mut rng);
+            // Standalone enemies are never synced, they drive up the road on their own
+            let (min_speed, max_speed) = preset.enemy_speed;
+            let velocity = if standalone.is_none() || max_speed <= min_speed {
+                Vec2::ZERO
+            } else {
+                Vec2::new(0.0, rng.gen_range(min_speed..max_speed))
             };
-
+            // TODO: workaround: spawn outside of screen because we know all enermies are spawned but don't know their positions yet
+            let translation = Vec3::new(lanes.center_x(lane), 0.0, 0.0);
             commands.spawn((
-                SpriteBundle {
-                    // TODO: workaround: spawn outside of screen because we know all enermies are spawned but don't know their positions yet
-                    transform: Transform::from_xyz(0.0, 0.0, 0.0).with_scale(vec3(
-                        enemy_scale,
-                        enemy_scale,
-                        1.0,
-                    )),
-                    texture: asset_server.load(enemy_type.get_sprite()),
-                    ..default()
-                },
-                // RigidBody::Dynamic,
-                Velocity::zero(),
-                ColliderMassProperties::Mass(1.0),
-                Friction::new(100.0),
-                ActiveEvents::COLLISION_EVENTS,
-                collider,
-                Damping {
-                    angular_damping: 2.0,
-                    linear_damping: 2.0,
-                },
-                Enemy { is_hit: false },
+                enemy_bundle(
+                    enemy_type,
+                    translation,
+                    velocity,
+                    lane,
+                    &physics,
+                    &definitions,
+                    &asset_server,
+                ),
                 EnemyId(id.into()),
-                enemy_type,
+                RacerId(e.model_id),
             ));
         }
     }
 }
 
-pub struct UpdateEnemy {
-    pub position: Vec<FieldElement>,
+/// Only standalone enemies can be resized by the definitions, synced ones collide in the world.
+fn keep_synced_enemy_size(
+    standalone: Option<Res<Standalone>>,
+    mut definitions: ResMut<EnemyDefinitions>,
+) {
+    if standalone.is_none() && definitions.keep_builtin_size() {
+        log::warn!("Enemy sizes are only modded in standalone runs, keeping the built-in ones");
+    }
+}
+
+/// Sprite, physics and motion every enemy has, whoever spawns it.
+pub fn enemy_bundle(
+    enemy_type: EnemyType,
+    translation: Vec3,
+    velocity: Vec2,
+    lane: Lane,
+    physics: &PhysicsConfig,
+    definitions: &EnemyDefinitions,
+    asset_server: &AssetServer,
+) -> impl Bundle {
+    let definition = definitions.get(&enemy_type);
+    let enemy_scale = definition.scale;
+    (
+        SpriteBundle {
+            transform: Transform::from_translation(translation).with_scale(vec3(
+                enemy_scale,
+                enemy_scale,
+                1.0,
+            )),
+            texture: asset_server.load(definition.sprite()),
+            ..default()
+        },
+        // RigidBody::Dynamic,
+        Velocity::zero(),
+        ColliderMassProperties::Mass(physics.enemy_mass),
+        Friction::new(physics.enemy_friction),
+        ActiveEvents::COLLISION_EVENTS,
+        ENEMY_COLLISION_GROUPS,
+        enemy_type.collider(definitions),
+        physics.enemy_damping(),
+        Enemy { is_hit: false },
+        SyncedPosition::default(),
+        EnemyMotion {
+            velocity,
+            since_sync: 0.0,
+        },
+        lane,
+        enemy_type,
+    )
+}
+
+/// Re-applies the [`PhysicsConfig`] to the spawned enemies, e.g. once edited in the inspector.
+fn apply_physics_config(
+    physics: Res<PhysicsConfig>,
+    mut query: Query<(&mut ColliderMassProperties, &mut Friction, &mut Damping), With<Enemy>>,
+) {
+    for (mut mass, mut friction, mut damping) in query.iter_mut() {
+        *mass = ColliderMassProperties::Mass(physics.enemy_mass);
+        *friction = Friction::new(physics.enemy_friction);
+        *damping = physics.enemy_damping();
+    }
+}
+
+/// Where the world moved an enemy of a racer, in world coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EnemyPosition {
+    pub model_id: FieldElement,
     pub enemy_id: FieldElement,
+    pub position: Vec2,
 }
 
-fn update_enemy(
-    mut events: EventReader<UpdateEnemy>,
-    mut query: Query<(&mut Transform, &EnemyId), With<Enemy>>,
+impl EnemyPosition {
+    /// Decodes the `Position` component read for the enemy.
+    pub fn decode(
+        model_id: FieldElement,
+        enemy_id: FieldElement,
+        values: &[FieldElement],
+    ) -> eyre::Result<Self> {
+        let (x, y) = world::Position::decode_signed(values)?;
+        Ok(Self {
+            model_id,
+            enemy_id,
+            position: Vec2::new(x, y),
+        })
+    }
+
+    /// Values of the `Position` component, as recorded.
+    pub fn values(&self) -> Vec<FieldElement> {
+        world::Position::encode_signed(self.position.x, self.position.y)
+    }
+}
+
+/// Positions of the enemies of every racer read in a sync round.
+pub struct UpdateEnemies(pub Vec<EnemyPosition>);
+
+#[allow(clippy::type_complexity)]
+pub fn update_enemies(
+    mut commands: Commands,
+    mut events: EventReader<UpdateEnemies>,
+    lanes: Res<Lanes>,
+    mut query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &EnemyId,
+            &RacerId,
+            &mut SyncedPosition,
+            &mut EnemyMotion,
+            &mut Lane,
+            Option<&mut LaneChange>,
+        ),
+        With<Enemy>,
+    >,
 ) {
-    for e in events.iter() {
-        let (new_x, new_y) = dojo_to_bevy_coordinate(
-            e.position[0].to_string().parse().unwrap(),
-            e.position[1].to_string().parse().unwrap(),
-        );
+    // The latest position of each enemy, applied in a single pass over them
+    let positions: HashMap<(FieldElement, FieldElement), Vec2> = events
+        .iter()
+        .flat_map(|e| e.0.iter())
+        .map(|enemy| ((enemy.model_id, enemy.enemy_id), enemy.position))
+        .collect();
+    if positions.is_empty() {
+        return;
+    }
+
+    for (
+        entity,
+        mut transform,
+        enemy_id_comp,
+        racer_id,
+        mut synced,
+        mut motion,
+        mut lane,
+        lane_change,
+    ) in query.iter_mut()
+    {
+        let Some(&chain_position) = positions.get(&(racer_id.0, enemy_id_comp.0)) else {
+            continue;
+        };
+        // Unchanged on chain, don't touch the transform to keep rapier asleep
+        if synced.0 == Some(chain_position) {
+            continue;
+        }
+        let (new_x, new_y) = dojo_to_bevy_coordinate(chain_position.x, chain_position.y);
 
-        log::info!("Enermy Position ({}), x: {new_x}, y: {new_y}", e.enemy_id);
+        if let Some(old) = synced.0 {
+            let (old_x, old_y) = dojo_to_bevy_coordinate(old.x, old.y);
+            if motion.since_sync > 0.0 {
+                motion.velocity = Vec2::new(new_x - old_x, new_y - old_y) / motion.since_sync;
+            }
+        }
+        motion.since_sync = 0.0;
+
+        let new_lane = lanes.lane_at(new_x);
+        if new_lane != *lane && synced.0.is_some() {
+            // Tweened by `tween_lane_changes` instead of extrapolated
+            motion.velocity.x = 0.0;
+            commands
+                .entity(entity)
+                .insert(LaneChange::new(transform.translation.x, new_x));
+        } else if let Some(mut lane_change) = lane_change {
+            lane_change.to_x = new_x;
+        } else {
+            transform.translation.x = new_x;
+        }
+        *lane = new_lane;
+        transform.translation.y = new_y;
+        synced.0 = Some(chain_position);
+    }
+}
 
-        for (mut transform, enemy_id_comp) in query.iter_mut() {
-            if enemy_id_comp.0 == e.enemy_id {
-                transform.translation.x = new_x;
-                transform.translation.y = new_y;
+/// Moves enemies at their synced velocity until the next sync, so they don't stutter between
+/// sync intervals. Enemies driving on their own slow down in [`SpeedZones`].
+fn extrapolate_enemies(
+    fixed_time: Res<FixedTime>,
+    zones: Option<Res<SpeedZones>>,
+    mut query: Query<(&mut Transform, &mut EnemyMotion, &SyncedPosition), With<Enemy>>,
+) {
+    let delta = fixed_time.period.as_secs_f32();
+    for (mut transform, mut motion, synced) in query.iter_mut() {
+        motion.since_sync += delta;
+        // Never synced enemies keep their spawn velocity
+        let stale = synced.0.is_some() && motion.since_sync > DOJO_EXTRAPOLATION_HORIZON;
+        if motion.velocity == Vec2::ZERO || stale {
+            continue;
+        }
+        let velocity = match &zones {
+            Some(zones) if synced.0.is_none() => {
+                zones.velocity_at(transform.translation.y, motion.velocity)
             }
+            _ => motion.velocity,
+        };
+        transform.translation += (velocity * delta).extend(0.0);
+    }
+}
+
+fn tween_lane_changes(
+    mut commands: Commands,
+    fixed_time: Res<FixedTime>,
+    accessibility: Option<Res<AccessibilityConfig>>,
+    mut query: Query<(Entity, &mut Transform, &mut LaneChange), With<Enemy>>,
+) {
+    let allows_motion = AccessibilityConfig::allows_motion(accessibility.as_deref());
+    for (entity, mut transform, mut lane_change) in query.iter_mut() {
+        lane_change.elapsed += fixed_time.period.as_secs_f32();
+        let (x, lean) = lane_change.at(lane_change.elapsed);
+        transform.translation.x = x;
+        // Turned off mid lane change, the lean is dropped rather than frozen
+        transform.rotation = if allows_motion {
+            Quat::from_rotation_z(lean)
+        } else {
+            Quat::IDENTITY
+        };
+
+        if lane_change.elapsed >= ENEMY_LANE_CHANGE_SECONDS {
+            transform.rotation = Quat::IDENTITY;
+            commands.entity(entity).remove::<LaneChange>();
         }
     }
 }
@@ -234,30 +746,219 @@ fn update_enemy(
 // }
 
 impl EnemyType {
-    pub fn random() -> Self {
-        let all_vals = [Self::Horizontal(3.0), Self::Simple, Self::Truck];
-        let mut rng = thread_rng();
-        let index = rng.gen_range(0..all_vals.len());
+    /// Whether both are the same behavior, whatever their parameters.
+    fn same_kind(&self, other: &EnemyType) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
 
-        all_vals[index].clone()
+    /// Collider half extents, before the sprite scale.
+    pub fn half_extents(&self, definitions: &EnemyDefinitions) -> Vec2 {
+        definitions.get(self).half_extents
     }
 
-    pub fn get_sprite(&self) -> &str {
-        let mut rng = thread_rng();
-        match self {
-            EnemyType::Simple => {
-                let choices = ["enemy-blue-1.png", "enemy-yellow-1.png"];
-                return choices[rng.gen_range(0..choices.len())];
-            }
-            EnemyType::Horizontal(_) => {
-                let choices = [
-                    "enemy-blue-2.png",
-                    "enemy-yellow-2.png",
-                    "enemy-yellow-3.png",
-                ];
-                return choices[rng.gen_range(0..choices.len())];
+    pub fn collider(&self, definitions: &EnemyDefinitions) -> Collider {
+        let half_extents = self.half_extents(definitions);
+        Collider::cuboid(half_extents.x, half_extents.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app_with_enemy() -> (App, Entity) {
+        let mut app = App::new();
+        app.init_resource::<Lanes>()
+            .add_event::<UpdateEnemies>()
+            .add_system(update_enemies);
+        let enemy = app
+            .world
+            .spawn((
+                Transform::default(),
+                Enemy::default(),
+                EnemyId(FieldElement::ZERO),
+                RacerId(FieldElement::ONE),
+                SyncedPosition::default(),
+                EnemyMotion::default(),
+                Lane::default(),
+            ))
+            .id();
+        (app, enemy)
+    }
+
+    fn enemy_position(model_id: FieldElement, x: f32, y: f32) -> EnemyPosition {
+        EnemyPosition {
+            model_id,
+            enemy_id: FieldElement::ZERO,
+            position: Vec2::new(x, y),
+        }
+    }
+
+    fn send_position(app: &mut App, x: f32, y: f32) {
+        let position = enemy_position(FieldElement::ONE, x, y);
+        app.world.send_event(UpdateEnemies(vec![position]));
+        app.update();
+    }
+
+    fn translation(app: &App, enemy: Entity) -> Vec3 {
+        app.world.get::<Transform>(enemy).unwrap().translation
+    }
+
+    #[test]
+    fn test_update_enemies_skips_unchanged_position() {
+        let (mut app, enemy) = app_with_enemy();
+
+        send_position(&mut app, 20.0, 100.0);
+        let (x, y) = dojo_to_bevy_coordinate(20.0, 100.0);
+        assert_eq!(translation(&app, enemy), Vec3::new(x, y, 0.0));
+
+        // Moved locally: an unchanged chain position must not overwrite it
+        app.world.get_mut::<Transform>(enemy).unwrap().translation = Vec3::ZERO;
+        send_position(&mut app, 20.0, 100.0);
+        assert_eq!(translation(&app, enemy), Vec3::ZERO);
+
+        send_position(&mut app, 20.0, 120.0);
+        let (x, y) = dojo_to_bevy_coordinate(20.0, 120.0);
+        assert_eq!(translation(&app, enemy), Vec3::new(x, y, 0.0));
+    }
+
+    #[test]
+    fn test_update_enemies_ignores_other_racers() {
+        let (mut app, enemy) = app_with_enemy();
+
+        let position = enemy_position(FieldElement::TWO, 20.0, 100.0);
+        app.world.send_event(UpdateEnemies(vec![position]));
+        app.update();
+
+        assert_eq!(translation(&app, enemy), Vec3::ZERO);
+    }
+
+    #[test]
+    fn test_update_enemies_applies_the_latest_position() {
+        let (mut app, enemy) = app_with_enemy();
+
+        for y in [100.0, 120.0] {
+            let position = enemy_position(FieldElement::ONE, 20.0, y);
+            app.world.send_event(UpdateEnemies(vec![position]));
+        }
+        app.update();
+
+        let (x, y) = dojo_to_bevy_coordinate(20.0, 120.0);
+        assert_eq!(translation(&app, enemy), Vec3::new(x, y, 0.0));
+    }
+
+    #[test]
+    fn test_update_enemies_derives_velocity() {
+        let (mut app, enemy) = app_with_enemy();
+
+        send_position(&mut app, 20.0, 100.0);
+        app.world.get_mut::<EnemyMotion>(enemy).unwrap().since_sync = 0.5;
+        send_position(&mut app, 20.0, 120.0);
+
+        let (_, y1) = dojo_to_bevy_coordinate(20.0, 100.0);
+        let (_, y2) = dojo_to_bevy_coordinate(20.0, 120.0);
+        let motion = app.world.get::<EnemyMotion>(enemy).unwrap();
+        assert_eq!(motion.velocity, Vec2::new(0.0, (y2 - y1) / 0.5));
+        assert_eq!(motion.since_sync, 0.0);
+    }
+
+    #[test]
+    fn test_lanes_match_world_lanes() {
+        let lanes = Lanes::default();
+        let world_lane_width = DOJO_GRID_WIDTH / DOJO_LANES_NB as f32;
+
+        for index in 0..DOJO_LANES_NB {
+            // The world keeps an enemy at least a car width away from its lane bounds
+            for offset in [1.0, world_lane_width / 2.0, world_lane_width - 1.0] {
+                let dojo_x = index as f32 * world_lane_width + offset;
+                let (x, _) = dojo_to_bevy_coordinate(dojo_x, 0.0);
+                assert_eq!(lanes.lane_at(x), Lane(index));
             }
-            EnemyType::Truck => "enemy-truck.png",
+            assert_eq!(lanes.lane_at(lanes.center_x(Lane(index))), Lane(index));
         }
+        assert_eq!(lanes.lane_at(ROAD_X_MIN - 10.0), Lane(0));
+        assert_eq!(lanes.lane_at(ROAD_X_MAX + 10.0), Lane(DOJO_LANES_NB - 1));
+    }
+
+    #[test]
+    fn test_same_seed_same_traffic() {
+        let definitions = EnemyDefinitions::default();
+        let layout = |seed: EnemySeed| {
+            let mut rng = seed.rng();
+            (0..DOJO_ENEMIES_NB)
+                .map(|_| matches!(definitions.random(0.5, &mut rng), EnemyType::Truck))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            layout(EnemySeed::for_generation(3)),
+            layout(EnemySeed::for_generation(3))
+        );
+        assert_ne!(EnemySeed::for_generation(3), EnemySeed::for_generation(4));
+    }
+
+    #[test]
+    fn test_enemy_count_is_capped_by_the_world() {
+        assert_eq!(EnemyCount(4).ids(), 0..4);
+        assert_eq!(EnemyCount(DOJO_ENEMIES_NB + 5).ids(), 0..DOJO_ENEMIES_NB);
+    }
+
+    #[test]
+    fn test_update_enemies_sets_lane() {
+        let (mut app, enemy) = app_with_enemy();
+
+        send_position(&mut app, 3.0 * 40.0 + 20.0, 100.0);
+        assert_eq!(*app.world.get::<Lane>(enemy).unwrap(), Lane(3));
+    }
+
+    #[test]
+    fn test_lane_change_is_tweened() {
+        let (mut app, enemy) = app_with_enemy();
+
+        send_position(&mut app, 20.0, 100.0);
+        send_position(&mut app, 3.0 * 40.0 + 20.0, 110.0);
+
+        // Stays put until tweened, then eases over and leans right halfway
+        let (from_x, _) = dojo_to_bevy_coordinate(20.0, 100.0);
+        let (to_x, _) = dojo_to_bevy_coordinate(140.0, 110.0);
+        assert_eq!(translation(&app, enemy).x, from_x);
+        let lane_change = app.world.get::<LaneChange>(enemy).unwrap();
+        let (x, lean) = lane_change.at(ENEMY_LANE_CHANGE_SECONDS / 2.0);
+        assert!((x - (from_x + to_x) / 2.0).abs() < 1e-3);
+        assert!((lean + ENEMY_LANE_CHANGE_TILT).abs() < 1e-3);
+        let (x, lean) = lane_change.at(ENEMY_LANE_CHANGE_SECONDS);
+        assert!((x - to_x).abs() < 1e-3);
+        assert!(lean.abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_enemy_definitions_are_read_from_the_assets() {
+        let definitions = EnemyDefinitions::load(Path::new(ENEMIES_DIR)).unwrap();
+        assert_eq!(definitions, EnemyDefinitions::default());
+
+        let modded = EnemyDefinitions::parse([r#"(
+            behavior: Truck,
+            sprites: ["bound-truck.png"],
+            scale: 4.0,
+            half_extents: (8.0, 20.0),
+        )"#])
+        .unwrap();
+        assert_eq!(modded.get(&EnemyType::Truck).scale, 4.0);
+        assert_eq!(EnemyType::Truck.half_extents(&modded), Vec2::new(8.0, 20.0));
+        // Kinds without a file keep their built-in definition
+        assert_eq!(modded.0.len(), 3);
+        assert_eq!(
+            modded.random(0.0, &mut thread_rng()).half_extents(&modded),
+            ENEMY_COLLIDER
+        );
+
+        // Synced with a world, only the sprites are modded
+        let mut synced = modded.clone();
+        assert!(synced.keep_builtin_size());
+        assert_eq!(synced.get(&EnemyType::Truck).sprites, ["bound-truck.png"]);
+        assert_eq!(EnemyType::Truck.half_extents(&synced), TRUCK_COLLIDER);
+        assert!(!synced.keep_builtin_size());
+
+        let truck = std::fs::read_to_string(Path::new(ENEMIES_DIR).join("truck.ron")).unwrap();
+        assert!(EnemyDefinitions::parse([truck.as_str(), truck.as_str()]).is_err());
     }
 }
@@ -0,0 +1,266 @@
+use crate::backend::{Backend, BlockHead, DojoBackend, Execution};
+use async_trait::async_trait;
+use bevy::log;
+use bevy::prelude::*;
+use eyre::Result;
+use rusqlite::{params, Connection};
+use starknet::core::types::FieldElement;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Writes every component read and transaction result of the dojo sync to a SQLite database
+/// at `path`, to compare what the client applied with the chain after the fact. Add it after
+/// `DojoPlugin`.
+pub struct EventLogPlugin {
+    pub path: PathBuf,
+}
+
+impl Plugin for EventLogPlugin {
+    fn build(&self, app: &mut App) {
+        let Some(backend) = app.world.get_resource::<Backend>() else {
+            log::error!("The event log needs the dojo backend, add it after `DojoPlugin`");
+            return;
+        };
+
+        match EventLog::open(&self.path, backend.0.clone()) {
+            Ok(event_log) => {
+                app.insert_resource(Backend(Arc::new(event_log)));
+            }
+            Err(e) => log::error!("Open event log {}: {e}", self.path.display()),
+        }
+    }
+}
+
+/// Backend recording the calls it forwards to `inner`.
+pub struct EventLog {
+    inner: Arc<dyn DojoBackend>,
+    /// Only used from the blocking threads of the runtime.
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl EventLog {
+    pub fn open(path: &Path, inner: Arc<dyn DojoBackend>) -> Result<Self> {
+        let connection = Connection::open(path)?;
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS events (
+                id INTEGER PRIMARY KEY,
+                time REAL NOT NULL,
+                kind TEXT NOT NULL,
+                name TEXT NOT NULL,
+                keys TEXT NOT NULL,
+                block_number INTEGER,
+                block_hash TEXT,
+                result TEXT,
+                error TEXT
+            );",
+        )?;
+        Ok(Self {
+            inner,
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// `kind` is `read` or `execute`, `name` the component or system and `keys` the entity
+    /// keys or calldata.
+    async fn record(
+        &self,
+        kind: &'static str,
+        name: &str,
+        keys: &[FieldElement],
+        block: (Option<u64>, Option<FieldElement>),
+        result: std::result::Result<String, String>,
+    ) {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or_default();
+        let keys = felts_json(keys);
+        let (result, error) = match result {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+
+        let connection = self.connection.clone();
+        let name = name.to_string();
+        let inserted = tokio::task::spawn_blocking(move || {
+            connection.lock().unwrap().execute(
+                "INSERT INTO events (time, kind, name, keys, block_number, block_hash, result, error)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    time,
+                    kind,
+                    name,
+                    keys,
+                    block.0.map(|number| number as i64),
+                    block.1.map(|hash| format!("{hash:#x}")),
+                    result,
+                    error
+                ],
+            )
+        })
+        .await;
+        match inserted {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => log::error!("Write event log: {e}"),
+            Err(e) => log::error!("Write event log: {e}"),
+        }
+    }
+
+    async fn record_read(
+        &self,
+        component: &str,
+        keys: &[FieldElement],
+        head: Option<BlockHead>,
+        result: &Result<Vec<FieldElement>>,
+    ) {
+        self.record(
+            "read",
+            component,
+            keys,
+            (head.map(|head| head.number), head.map(|head| head.hash)),
+            result
+                .as_ref()
+                .map(|values| felts_json(values))
+                .map_err(|e| e.to_string()),
+        )
+        .await;
+    }
+}
+
+fn felts_json(felts: &[FieldElement]) -> String {
+    serde_json::to_string(felts).unwrap_or_default()
+}
+
+#[async_trait]
+impl DojoBackend for EventLog {
+    async fn execute(&self, system: &str, calldata: Vec<FieldElement>) -> Result<Execution> {
+        let result = self.inner.execute(system, calldata.clone()).await;
+        self.record(
+            "execute",
+            system,
+            &calldata,
+            (
+                result
+                    .as_ref()
+                    .ok()
+                    .and_then(|execution| execution.block_number),
+                None,
+            ),
+            result
+                .as_ref()
+                .map(|execution| format!("{:#x}", execution.fee))
+                .map_err(|e| e.to_string()),
+        )
+        .await;
+        result
+    }
+
+    async fn entity(&self, component: &str, keys: Vec<FieldElement>) -> Result<Vec<FieldElement>> {
+        let result = self.inner.entity(component, keys.clone()).await;
+        self.record_read(component, &keys, None, &result).await;
+        result
+    }
+
+    async fn head(&self) -> Result<BlockHead> {
+        self.inner.head().await
+    }
+
+    async fn entity_at(
+        &self,
+        component: &str,
+        keys: Vec<FieldElement>,
+        head: BlockHead,
+    ) -> Result<Vec<FieldElement>> {
+        let result = self.inner.entity_at(component, keys.clone(), head).await;
+        self.record_read(component, &keys, Some(head), &result)
+            .await;
+        result
+    }
+
+    async fn balance(&self) -> Result<u128> {
+        self.inner.balance().await
+    }
+
+    async fn request_funds(&self) -> Result<bool> {
+        self.inner.request_funds().await
+    }
+
+    async fn resolve_component(&self, component: &str) -> Result<()> {
+        self.inner.resolve_component(component).await
+    }
+
+    async fn resolve_system(&self, system: &str) -> Result<()> {
+        self.inner.resolve_system(system).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::MockBackend;
+
+    #[test]
+    fn test_reads_and_executions_are_logged() {
+        let path = std::env::temp_dir().join(format!("drive-ai-events-{}.db", std::process::id()));
+        let mock = Arc::new(MockBackend::default());
+        mock.set_entity("Vehicle", vec![FieldElement::ONE], vec![FieldElement::TWO]);
+        let event_log = EventLog::open(&path, mock).unwrap();
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let head = BlockHead {
+                number: 7,
+                hash: FieldElement::THREE,
+            };
+            event_log
+                .entity_at("Vehicle", vec![FieldElement::ONE], head)
+                .await
+                .unwrap();
+            event_log
+                .entity("Position", vec![FieldElement::ONE])
+                .await
+                .unwrap_err();
+            event_log
+                .execute("drive", vec![FieldElement::ONE])
+                .await
+                .unwrap();
+        });
+
+        let connection = event_log.connection.lock().unwrap();
+        let events: Vec<(String, String, Option<i64>, Option<String>, Option<String>)> = connection
+            .prepare("SELECT kind, name, block_number, result, error FROM events ORDER BY id")
+            .unwrap()
+            .query_map([], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                ))
+            })
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        drop(connection);
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events[0],
+            (
+                "read".to_string(),
+                "Vehicle".to_string(),
+                Some(7),
+                Some("[\"2\"]".to_string()),
+                None
+            )
+        );
+        assert!(events[1].4.is_some());
+        assert_eq!(events[2].0, "execute");
+        assert_eq!(events[2].3.as_deref(), Some("0x0"));
+    }
+}
@@ -0,0 +1,160 @@
+use crate::capture::Capture;
+use crate::configs;
+use crate::replay::load_frames;
+use bevy::app::AppExit;
+use bevy::log;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy::utils::Duration;
+use bevy::window::PrimaryWindow;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::imageops::FilterType;
+use image::{Delay, Frame};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+/// Plays a recording back in a hidden window at [`configs::EXPORT_FPS`] simulated frames per
+/// second, whatever the rendering speed, and encodes the captured frames to a GIF at `output`
+/// once it ends. Needs the `ReplayPlugin` of the same `replay` and the `CapturePlugin`.
+pub struct ExportPlugin {
+    pub replay: PathBuf,
+    pub output: PathBuf,
+}
+
+impl Plugin for ExportPlugin {
+    fn build(&self, app: &mut App) {
+        let duration = match load_frames(&self.replay) {
+            Ok(frames) => frames.last().map_or(0.0, |frame| frame.time),
+            Err(e) => {
+                log::error!("Load replay file {}: {e}", self.replay.display());
+                0.0
+            }
+        };
+
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            1.0 / configs::EXPORT_FPS as f32,
+        )))
+        .insert_resource(Export {
+            output: self.output.clone(),
+            end: duration + configs::EXPORT_TAIL_SECONDS,
+            flush_frames: configs::EXPORT_FLUSH_FRAMES,
+        })
+        .add_startup_system(start_export)
+        .add_system(finish_export);
+    }
+}
+
+#[derive(Resource)]
+struct Export {
+    output: PathBuf,
+    /// Seconds since startup the capture stops at.
+    end: f32,
+    /// Frames left for the exporter to write the last captured frames.
+    flush_frames: u32,
+}
+
+fn start_export(
+    mut capture: ResMut<Capture>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    capture.sequence = true;
+    if let Ok(mut window) = windows.get_single_mut() {
+        window.visible = false;
+    }
+}
+
+fn finish_export(
+    time: Res<Time>,
+    mut export: ResMut<Export>,
+    mut capture: ResMut<Capture>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if time.elapsed_seconds() < export.end {
+        return;
+    }
+    capture.sequence = false;
+    if export.flush_frames > 0 {
+        export.flush_frames -= 1;
+        return;
+    }
+
+    match encode_gif(&capture.directory, &export.output) {
+        Ok(frames) => log::info!("Exported {frames} frames to {}", export.output.display()),
+        Err(e) => log::error!("Export {}: {e}", export.output.display()),
+    }
+    exit.send(AppExit);
+}
+
+/// PNG frames of `directory`, in capture order.
+fn frame_paths(directory: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut frames: Vec<(u64, PathBuf)> = fs::read_dir(directory)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .map_or(false, |extension| extension == "png")
+        })
+        .filter_map(|path| {
+            let number = path.file_stem()?.to_str()?.parse().ok()?;
+            Some((number, path))
+        })
+        .collect();
+    frames.sort();
+    Ok(frames.into_iter().map(|(_, path)| path).collect())
+}
+
+/// Encodes the frames captured in `directory` to a looping GIF scaled by
+/// [`configs::EXPORT_SCALE`], returns the number of frames.
+fn encode_gif(directory: &Path, output: &Path) -> eyre::Result<usize> {
+    let paths = frame_paths(directory)?;
+    let mut encoder = GifEncoder::new_with_speed(File::create(output)?, 10);
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    let delay = Delay::from_numer_denom_ms(1000, configs::EXPORT_FPS);
+    for path in paths.iter() {
+        let image = image::open(path)?.into_rgba8();
+        let image = image::imageops::resize(
+            &image,
+            (image.width() as f32 * configs::EXPORT_SCALE) as u32,
+            (image.height() as f32 * configs::EXPORT_SCALE) as u32,
+            FilterType::Triangle,
+        );
+        encoder.encode_frame(Frame::from_parts(image, 0, 0, delay))?;
+    }
+    Ok(paths.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::codecs::gif::GifDecoder;
+    use image::{AnimationDecoder, Rgba, RgbaImage};
+
+    #[test]
+    fn test_frames_are_encoded_in_capture_order() {
+        let directory =
+            std::env::temp_dir().join(format!("drive-ai-export-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        for (name, shade) in [("00010", 200), ("00002", 100), ("00001", 0)] {
+            RgbaImage::from_pixel(8, 8, Rgba([shade, shade, shade, 255]))
+                .save(directory.join(format!("{name}.png")))
+                .unwrap();
+        }
+        fs::write(directory.join("run.json"), "{}").unwrap();
+        let output = directory.join("run.gif");
+
+        assert_eq!(encode_gif(&directory, &output).unwrap(), 3);
+        let frames = GifDecoder::new(File::open(&output).unwrap())
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        let shades: Vec<u8> = frames
+            .iter()
+            .map(|frame| frame.buffer().get_pixel(0, 0)[0])
+            .collect();
+        // Palette quantization may shift the shades, not their order
+        assert!(shades.windows(2).all(|pair| pair[0] < pair[1]));
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
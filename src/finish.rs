@@ -0,0 +1,171 @@
+use crate::car::{Car, CarBundle, Model};
+use crate::configs;
+use crate::conversions::felt_to_short_string;
+use crate::locale::Locale;
+use crate::population::GenerationSet;
+use crate::resources::SimStats;
+use bevy::log;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_inspector_egui::{bevy_egui::EguiContexts, egui};
+use starknet_ff::FieldElement;
+
+/// Ends the run of cars crossing the [`FinishLine`] with a [`RaceFinished`], instead of a crash.
+pub struct FinishLinePlugin;
+
+impl Plugin for FinishLinePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FinishLine>()
+            .init_resource::<SimStats>()
+            .init_resource::<FinishResults>()
+            .add_event::<RaceFinished>()
+            .add_systems(
+                (cross_finish_line, record_results)
+                    .chain()
+                    .before(GenerationSet),
+            );
+    }
+}
+
+/// Results screen of the last race anyone finished.
+pub struct FinishResultsPlugin;
+
+impl Plugin for FinishResultsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Locale>()
+            .add_system(finish_results_system.after(record_results));
+    }
+}
+
+/// Track `y` ending the race.
+#[derive(Resource, Debug)]
+pub struct FinishLine(pub f32);
+
+impl Default for FinishLine {
+    fn default() -> Self {
+        Self(configs::FINISH_LINE_Y)
+    }
+}
+
+/// A car crossed the finish line, `time` seconds after it was spawned. Head-to-head races end
+/// with a [`crate::race::RaceFinished`] instead.
+#[derive(Clone, Debug)]
+pub struct RaceFinished {
+    pub model_id: FieldElement,
+    pub generation: u32,
+    pub time: f32,
+}
+
+/// Finish times of a generation, fastest first.
+#[derive(Resource, Default, Debug)]
+pub struct FinishResults {
+    pub generation: u32,
+    pub times: Vec<(FieldElement, f32)>,
+    pub visible: bool,
+}
+
+fn cross_finish_line(
+    mut commands: Commands,
+    time: Res<Time>,
+    finish_line: Res<FinishLine>,
+    sim_stats: Res<SimStats>,
+    cars: Query<(Entity, &Transform, &Model), With<Car>>,
+    mut race_times: Local<HashMap<Entity, f32>>,
+    mut finished: EventWriter<RaceFinished>,
+) {
+    race_times.retain(|entity, _| cars.contains(*entity));
+    for (entity, transform, model) in cars.iter() {
+        let race_time = race_times.entry(entity).or_default();
+        *race_time += time.delta_seconds();
+        if transform.translation.y < finish_line.0 {
+            continue;
+        }
+
+        log::info!("Vehicle ({}) finished in {:.2}s", model.id, race_time);
+        finished.send(RaceFinished {
+            model_id: model.id,
+            generation: sim_stats.generation_count,
+            time: *race_time,
+        });
+        commands.entity(entity).remove::<CarBundle>();
+    }
+}
+
+fn record_results(mut finished: EventReader<RaceFinished>, mut results: ResMut<FinishResults>) {
+    for finish in finished.iter() {
+        if finish.generation != results.generation {
+            results.generation = finish.generation;
+            results.times.clear();
+        }
+        results.times.push((finish.model_id, finish.time));
+        results.times.sort_by(|a, b| a.1.total_cmp(&b.1));
+        results.visible = true;
+    }
+}
+
+fn finish_results_system(
+    mut contexts: EguiContexts,
+    locale: Res<Locale>,
+    mut results: ResMut<FinishResults>,
+) {
+    if !results.visible {
+        return;
+    }
+
+    let mut close = false;
+    egui::Window::new(locale.text("results.title"))
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .collapsible(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.heading(locale.format("results.generation", &[("generation", &results.generation)]));
+            egui::Grid::new("results").striped(true).show(ui, |ui| {
+                for (rank, (model_id, time)) in results.times.iter().enumerate() {
+                    ui.label(format!("{}.", rank + 1));
+                    ui.label(felt_to_short_string(*model_id));
+                    ui.label(locale.format("hud.race_time", &[("time", &format!("{time:.2}"))]));
+                    ui.end_row();
+                }
+            });
+            close = ui.button(locale.text("results.close")).clicked();
+        });
+    if close {
+        results.visible = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nn::Net;
+
+    #[test]
+    fn test_crossing_the_line_ends_the_run() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .insert_resource(FinishLine(1000.0))
+            .add_plugin(FinishLinePlugin);
+        let spawn = |app: &mut App, id: FieldElement, y: f32| {
+            app.world
+                .spawn((
+                    Car,
+                    Transform::from_xyz(300.0, y, 0.0),
+                    Model {
+                        nn: Net::new(vec![1, 1]),
+                        nn_outputs: Vec::new(),
+                        id,
+                    },
+                ))
+                .id()
+        };
+        let racing = spawn(&mut app, FieldElement::ONE, 999.0);
+        let finished = spawn(&mut app, FieldElement::TWO, 1000.0);
+        app.update();
+
+        assert!(app.world.get::<Car>(racing).is_some());
+        assert!(app.world.get::<Car>(finished).is_none());
+        let results = app.world.resource::<FinishResults>();
+        assert!(results.visible);
+        assert_eq!(results.times.len(), 1);
+        assert_eq!(results.times[0].0, FieldElement::TWO);
+    }
+}
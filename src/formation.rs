@@ -0,0 +1,215 @@
+use crate::car::Car;
+use crate::configs;
+use crate::enemy::{enemy_bundle, EnemyDefinitions, EnemyMotion, EnemyType, Lane, Lanes};
+use crate::population::{GenerationSet, RespawnPopulation};
+use crate::resources::PhysicsConfig;
+use bevy::log;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Spawns the formations of `path` (convoy walls, zigzag gates, pincers...) along the track as
+/// the leading car gets close to them, on top of the random traffic. The schedule starts over
+/// with each generation.
+pub struct FormationPlugin {
+    pub path: PathBuf,
+}
+
+impl Plugin for FormationPlugin {
+    fn build(&self, app: &mut App) {
+        let formations = Formations::load(&self.path).unwrap_or_else(|e| {
+            log::error!("Load formations {}: {e}", self.path.display());
+            Formations::default()
+        });
+        app.insert_resource(formations)
+            .init_resource::<NextFormation>()
+            .init_resource::<Lanes>()
+            .init_resource::<PhysicsConfig>()
+            .init_resource::<EnemyDefinitions>()
+            .add_event::<RespawnPopulation>()
+            .add_systems(
+                (restart_schedule, spawn_formations, bounce_drifting_enemies)
+                    .chain()
+                    .after(GenerationSet),
+            );
+    }
+}
+
+/// An enemy of a [`Formation`].
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct FormationSlot {
+    pub lane: u32,
+    /// Pixels ahead of the start of the formation.
+    #[serde(default)]
+    pub offset: f32,
+    #[serde(default)]
+    pub truck: bool,
+    /// Pixels per second up the road.
+    #[serde(default)]
+    pub speed: f32,
+    /// Pixels per second across the road, bouncing off its sides.
+    #[serde(default)]
+    pub drift: f32,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct Formation {
+    pub enemies: Vec<FormationSlot>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct ScheduledFormation {
+    /// Track `y` the formation starts at.
+    pub at: f32,
+    pub formation: String,
+}
+
+/// Formations by name and where they show up, read from a JSON asset.
+#[derive(Resource, Deserialize, Default, Debug)]
+pub struct Formations {
+    pub formations: HashMap<String, Formation>,
+    pub schedule: Vec<ScheduledFormation>,
+}
+
+impl Formations {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::parse(&json)
+    }
+
+    /// Parses and checks the schedule only names known formations, ending before
+    /// [`configs::FINISH_LINE_Y`], sorted by track `y`.
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let mut formations: Self = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        for scheduled in formations.schedule.iter() {
+            let Some(formation) = formations.formations.get(&scheduled.formation) else {
+                return Err(format!("Unknown formation {}", scheduled.formation));
+            };
+            let end = formation
+                .enemies
+                .iter()
+                .map(|slot| scheduled.at + slot.offset)
+                .fold(scheduled.at, f32::max);
+            if end >= configs::FINISH_LINE_Y {
+                return Err(format!(
+                    "Formation {} at y: {} goes past the finish line",
+                    scheduled.formation, scheduled.at
+                ));
+            }
+        }
+        formations.schedule.sort_by(|a, b| a.at.total_cmp(&b.at));
+        Ok(formations)
+    }
+
+    /// Index past the last formation to spawn once the leading car reached `lead_y`.
+    fn due(&self, next: usize, lead_y: f32) -> usize {
+        let horizon = lead_y + configs::FORMATION_SPAWN_AHEAD;
+        next + self.schedule[next..]
+            .iter()
+            .take_while(|scheduled| scheduled.at <= horizon)
+            .count()
+    }
+}
+
+/// Index in the schedule of the next formation to spawn.
+#[derive(Resource, Default)]
+struct NextFormation(usize);
+
+/// Enemy of a formation drifting across the road.
+#[derive(Component)]
+struct Drifting;
+
+fn restart_schedule(mut respawn: EventReader<RespawnPopulation>, mut next: ResMut<NextFormation>) {
+    if respawn.iter().count() > 0 {
+        next.0 = 0;
+    }
+}
+
+fn spawn_formations(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    formations: Res<Formations>,
+    lanes: Res<Lanes>,
+    physics: Res<PhysicsConfig>,
+    definitions: Res<EnemyDefinitions>,
+    mut next: ResMut<NextFormation>,
+    cars: Query<&Transform, With<Car>>,
+) {
+    let Some(lead_y) = cars
+        .iter()
+        .map(|transform| transform.translation.y)
+        .reduce(f32::max)
+    else {
+        return;
+    };
+    let due = formations.due(next.0, lead_y);
+    for scheduled in &formations.schedule[next.0..due] {
+        log::info!("Formation {} at y: {}", scheduled.formation, scheduled.at);
+        for slot in formations.formations[&scheduled.formation].enemies.iter() {
+            let lane = Lane(slot.lane.min(lanes.count - 1));
+            let enemy_type = if slot.truck {
+                EnemyType::Truck
+            } else {
+                EnemyType::Simple
+            };
+            let translation = Vec3::new(lanes.center_x(lane), scheduled.at + slot.offset, 0.0);
+            let mut enemy = commands.spawn(enemy_bundle(
+                enemy_type,
+                translation,
+                Vec2::new(slot.drift, slot.speed),
+                lane,
+                &physics,
+                &definitions,
+                &asset_server,
+            ));
+            if slot.drift != 0.0 {
+                enemy.insert(Drifting);
+            }
+        }
+    }
+    next.0 = due;
+}
+
+fn bounce_drifting_enemies(
+    lanes: Res<Lanes>,
+    mut enemies: Query<(&Transform, &mut EnemyMotion), With<Drifting>>,
+) {
+    let half_lane = lanes.width() / 2.0;
+    for (transform, mut motion) in enemies.iter_mut() {
+        let x = transform.translation.x;
+        let leaving_left = x < lanes.x_min + half_lane && motion.velocity.x < 0.0;
+        let leaving_right = x > lanes.x_max - half_lane && motion.velocity.x > 0.0;
+        if leaving_left || leaving_right {
+            motion.velocity.x = -motion.velocity.x;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shipped_formations_are_scheduled_as_the_lead_car_advances() {
+        let formations = Formations::parse(include_str!("../assets/formations.json")).unwrap();
+        assert!(!formations.schedule.is_empty());
+
+        let first = formations.schedule[0].at;
+        let ahead = configs::FORMATION_SPAWN_AHEAD;
+        assert_eq!(formations.due(0, first - ahead - 1.0), 0);
+        assert_eq!(formations.due(0, first - ahead), 1);
+        assert_eq!(formations.due(1, first - ahead), 1);
+        assert_eq!(formations.due(0, f32::INFINITY), formations.schedule.len());
+
+        let unknown = r#"{"formations": {}, "schedule": [{"at": 1.0, "formation": "x"}]}"#;
+        assert!(Formations::parse(unknown).is_err());
+        let past_finish = format!(
+            r#"{{"formations": {{"x": {{"enemies": [{{"lane": 0, "offset": 100.0}}]}}}},
+            "schedule": [{{"at": {}, "formation": "x"}}]}}"#,
+            configs::FINISH_LINE_Y - 50.0
+        );
+        assert!(Formations::parse(&past_finish).is_err());
+    }
+}
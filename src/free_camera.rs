@@ -0,0 +1,71 @@
+use crate::configs;
+use crate::input_map::{Action, ActionState};
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+
+/// Flies the cameras marked [`FreeCamera`]: the camera [`Action`]s, arrows or WASD and Q and E
+/// by default, pan and rotate them and the mouse wheel zooms. Keeps moving while the [`Time`] is
+/// paused.
+pub struct FreeCameraPlugin;
+
+impl Plugin for FreeCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(fly_free_cameras);
+    }
+}
+
+#[derive(Component)]
+pub struct FreeCamera;
+
+fn fly_free_cameras(
+    time: Res<Time>,
+    actions: Res<ActionState>,
+    mut wheel: EventReader<MouseWheel>,
+    mut cameras: Query<(&mut Transform, &mut OrthographicProjection), With<FreeCamera>>,
+) {
+    let mut pan = Vec2::ZERO;
+    if actions.pressed(Action::CameraLeft) {
+        pan.x -= 1.0;
+    }
+    if actions.pressed(Action::CameraRight) {
+        pan.x += 1.0;
+    }
+    if actions.pressed(Action::CameraDown) {
+        pan.y -= 1.0;
+    }
+    if actions.pressed(Action::CameraUp) {
+        pan.y += 1.0;
+    }
+    let mut rotation = 0.0;
+    if actions.pressed(Action::CameraRotateLeft) {
+        rotation += 1.0;
+    }
+    if actions.pressed(Action::CameraRotateRight) {
+        rotation -= 1.0;
+    }
+    let zoom: f32 = wheel
+        .iter()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            // Pixels per line
+            MouseScrollUnit::Pixel => event.y / 20.0,
+        })
+        .sum();
+
+    let delta = time.raw_delta_seconds();
+    for (mut transform, mut projection) in cameras.iter_mut() {
+        projection.scale = (projection.scale * (1.0 - zoom * configs::FREE_CAMERA_ZOOM_STEP))
+            .clamp(
+                configs::FREE_CAMERA_MIN_SCALE,
+                configs::FREE_CAMERA_MAX_SCALE,
+            );
+        transform.rotate_z(rotation * configs::FREE_CAMERA_ROTATION_SPEED * delta);
+        // Pans along the screen axes, at the same screen speed whatever the zoom
+        let pan = transform.rotation
+            * pan.normalize_or_zero().extend(0.0)
+            * configs::FREE_CAMERA_SPEED
+            * projection.scale
+            * delta;
+        transform.translation += pan;
+    }
+}
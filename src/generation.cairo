@@ -0,0 +1,23 @@
+#[derive(Component, Serde, SerdeLen, Drop, Copy)]
+struct Generation {
+    // Number of completed generations
+    number: u32,
+}
+
+#[system]
+mod evolve {
+    use traits::Into;
+    use dojo::world::Context;
+    use super::Generation;
+
+    /// Counts one more completed generation. The world doesn't check the racers: the client calls
+    /// it once every racer of the generation crashed.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - Context of the game.
+    fn execute(ctx: Context) {
+        let generation = get !(ctx.world, 'generation'.into(), Generation);
+        set !(ctx.world, 'generation'.into(), (Generation { number: generation.number + 1 }));
+    }
+}
@@ -0,0 +1,179 @@
+use crate::car::{CarCrashed, CarStalled};
+use crate::history::RunCost;
+use crate::population::{GenerationEnded, GenerationSet};
+use bevy::log;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Appends a [`GenerationRecord`] to `path`, one JSON object per line, every time a generation
+/// ends, so long training sessions can be analyzed once the client is closed.
+pub struct GenerationStatsPlugin {
+    pub path: PathBuf,
+}
+
+impl Plugin for GenerationStatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GenerationStats {
+            path: self.path.clone(),
+            current: GenerationTally::default(),
+        })
+        .add_event::<CarCrashed>()
+        .add_event::<CarStalled>()
+        .add_event::<RunCost>()
+        .add_event::<GenerationEnded>()
+        .add_system(write_generation_stats.after(GenerationSet));
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GenerationRecord {
+    pub generation: u32,
+    /// Unix time the generation ended at.
+    pub finished_at: u64,
+    pub best_fitness: f32,
+    pub mean_fitness: f32,
+    /// Last fitness of every car, best first.
+    pub fitnesses: Vec<f32>,
+    /// Cars that hit an enemy or a wall.
+    pub collisions: u32,
+    /// Cars taken off the race for not progressing.
+    pub stalls: u32,
+    pub first_block: Option<u64>,
+    pub last_block: Option<u64>,
+}
+
+impl GenerationRecord {
+    /// Blocks between the first and the last transaction of the generation.
+    pub fn elapsed_blocks(&self) -> Option<u64> {
+        Some(self.last_block? - self.first_block?)
+    }
+}
+
+/// Crash causes and blocks of the generation in progress.
+#[derive(Default, Debug)]
+struct GenerationTally {
+    collisions: u32,
+    stalls: u32,
+    first_block: Option<u64>,
+    last_block: Option<u64>,
+}
+
+#[derive(Resource)]
+struct GenerationStats {
+    path: PathBuf,
+    current: GenerationTally,
+}
+
+fn append_record(path: &Path, record: &GenerationRecord) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)
+}
+
+/// Records of `path`, oldest first.
+pub fn load_records(path: &Path) -> std::io::Result<Vec<GenerationRecord>> {
+    fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+fn write_generation_stats(
+    mut stats: ResMut<GenerationStats>,
+    mut crashed: EventReader<CarCrashed>,
+    mut stalled: EventReader<CarStalled>,
+    mut costs: EventReader<RunCost>,
+    mut ended: EventReader<GenerationEnded>,
+) {
+    let tally = &mut stats.current;
+    tally.collisions += crashed.iter().count() as u32;
+    tally.stalls += stalled.iter().count() as u32;
+    for block in costs.iter().filter_map(|cost| cost.block_number) {
+        tally.first_block = Some(tally.first_block.map_or(block, |first| first.min(block)));
+        tally.last_block = Some(tally.last_block.map_or(block, |last| last.max(block)));
+    }
+
+    for ended in ended.iter() {
+        let tally = std::mem::take(&mut stats.current);
+        let mut fitnesses = ended.fitnesses.clone();
+        fitnesses.sort_by(|a, b| b.total_cmp(a));
+        let record = GenerationRecord {
+            generation: ended.generation,
+            finished_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            best_fitness: ended.best_fitness,
+            mean_fitness: ended.mean_fitness,
+            fitnesses,
+            collisions: tally.collisions,
+            stalls: tally.stalls,
+            first_block: tally.first_block,
+            last_block: tally.last_block,
+        };
+        if let Err(e) = append_record(&stats.path, &record) {
+            log::error!("Append generation stats {}: {e}", stats.path.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starknet_ff::FieldElement;
+
+    #[test]
+    fn test_generations_are_appended() {
+        let path = std::env::temp_dir().join(format!(
+            "drive-ai-generations-{}/generations.jsonl",
+            std::process::id()
+        ));
+
+        let mut app = App::new();
+        app.add_plugin(GenerationStatsPlugin { path: path.clone() });
+        for generation in 0..2 {
+            app.world.send_event(CarCrashed {
+                model_id: FieldElement::ONE,
+                position: Vec2::ZERO,
+            });
+            app.world.send_event(CarStalled {
+                model_id: FieldElement::TWO,
+            });
+            for block in [10, 14] {
+                app.world.send_event(RunCost {
+                    model_id: FieldElement::ONE,
+                    fee: FieldElement::ONE,
+                    block_number: Some(block + generation as u64),
+                });
+            }
+            app.update();
+            app.world.send_event(GenerationEnded {
+                generation,
+                best_fitness: 3.0,
+                mean_fitness: 2.0,
+                fitnesses: vec![1.0, 3.0],
+            });
+            app.update();
+        }
+
+        let records = load_records(&path).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].generation, 1);
+        assert_eq!(records[1].fitnesses, vec![3.0, 1.0]);
+        assert_eq!((records[1].collisions, records[1].stalls), (1, 1));
+        assert_eq!(
+            (records[1].first_block, records[1].last_block),
+            (Some(11), Some(15))
+        );
+        assert_eq!(records[1].elapsed_blocks(), Some(4));
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
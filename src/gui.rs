@@ -1,4 +1,4 @@
-use bevy::prelude::*;
+use bevy::{core_pipeline::clear_color::ClearColorConfig, prelude::*, render::view::RenderLayers};
 use bevy_inspector_egui::{
     bevy_egui::EguiContexts,
     egui::{
@@ -9,10 +9,42 @@ use bevy_inspector_egui::{
     },
 };
 
+use crate::car::{Car, Fitness, Model};
+use crate::conversions::felt_to_short_string;
+use crate::difficulty::Difficulty;
+use crate::enemy::EnemyCount;
+use crate::locale::Locale;
 use crate::*;
+use clap::ValueEnum;
 
 pub struct GuiPlugin;
 
+/// Streamer presentation: only the HUD, leaderboard and neural net panel are drawn, over a
+/// background that can be keyed out when compositing.
+pub struct OverlayPlugin {
+    pub style: OverlayStyle,
+}
+
+/// Lists the [`ModelRegistry`], picking a model replaces [`Racers`] from the next spawn on.
+pub struct ModelSelectionPlugin;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverlayStyle {
+    /// Transparent window, requires a compositor supporting it.
+    Transparent,
+    /// Solid `OVERLAY_CHROMA_KEY` background.
+    ChromaKey,
+}
+
+impl OverlayStyle {
+    pub fn clear_color(self) -> Color {
+        match self {
+            OverlayStyle::Transparent => Color::NONE,
+            OverlayStyle::ChromaKey => OVERLAY_CHROMA_KEY,
+        }
+    }
+}
+
 #[derive(Component)]
 struct GenerationCountLabel;
 #[derive(Component)]
@@ -28,15 +60,47 @@ impl Plugin for GuiPlugin {
             .insert_resource(BrainToDisplay::default())
             .insert_resource(Settings::default())
             .insert_resource(SimStats::default())
+            .init_resource::<Locale>()
             .add_system(stats_dialog_system)
             .add_system(generation_count_stats_system)
             .add_system(max_score_stats_system)
             .add_system(num_cars_stats_system)
             .add_system(car_progress_system)
+            .add_system(leaderboard_system)
             .add_system(nn_viz_system);
     }
 }
 
+impl Plugin for OverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(self.style.clear_color()))
+            .add_plugin(GuiPlugin)
+            .add_startup_system(setup_overlay_camera);
+    }
+}
+
+impl Plugin for ModelSelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ModelRegistry>()
+            .init_resource::<Racers>()
+            .init_resource::<Locale>()
+            .add_system(model_selection_system);
+    }
+}
+
+fn setup_overlay_camera(mut commands: Commands) {
+    // Sprites stay on the default layer so this camera only draws the UI
+    commands.spawn((
+        Camera2dBundle {
+            camera_2d: Camera2d {
+                clear_color: ClearColorConfig::Default,
+            },
+            ..default()
+        },
+        RenderLayers::layer(OVERLAY_RENDER_LAYER),
+    ));
+}
+
 fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     // gui
     let font = asset_server.load(FONT_RES_PATH);
@@ -190,7 +254,9 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
 
 fn stats_dialog_system(
     mut contexts: EguiContexts,
+    locale: Res<Locale>,
     sim_stats: Res<SimStats>,
+    run_stats: Res<RunStats>,
     mut settings: ResMut<Settings>,
 ) {
     let ctx = contexts.ctx_mut();
@@ -200,7 +266,7 @@ fn stats_dialog_system(
         .min_width(200.0)
         .default_pos(egui::pos2(1800.0, 1000.0))
         .show(ctx, |ui| {
-            egui::CollapsingHeader::new("Distance Travelled")
+            egui::CollapsingHeader::new(locale.text("menu.distance_travelled"))
                 .default_open(true)
                 .show(ui, |ui| {
                     let fitness_curve: PlotPoints = (0..sim_stats.fitness.len())
@@ -212,30 +278,128 @@ fn stats_dialog_system(
                         .show(ui, |plot_ui| plot_ui.line(line));
                 });
 
-            egui::CollapsingHeader::new("Settings")
+            egui::CollapsingHeader::new(locale.text("menu.run"))
                 .default_open(true)
                 .show(ui, |ui| {
-                    ui.checkbox(&mut settings.is_show_rays, "Ray casts");
+                    for (model_id, stats) in run_stats.0.iter() {
+                        ui.label(locale.format(
+                            "menu.run_stats",
+                            &[
+                                ("model", &felt_to_short_string(*model_id)),
+                                ("distance", &format!("{:.0}", stats.distance)),
+                                ("speed", &format!("{:.1}", stats.speed)),
+                            ],
+                        ));
+                    }
+                });
+
+            egui::CollapsingHeader::new(locale.text("menu.settings"))
+                .default_open(true)
+                .show(ui, |ui| {
+                    ui.checkbox(&mut settings.is_show_rays, locale.text("menu.ray_casts"));
                     ui.checkbox(
                         &mut settings.is_hide_rays_at_start,
-                        "Hide ray casts at start",
+                        locale.text("menu.hide_ray_casts_at_start"),
+                    );
+                    ui.checkbox(
+                        &mut settings.is_camera_follow,
+                        locale.text("menu.camera_follow"),
+                    );
+                    ui.checkbox(
+                        &mut settings.is_camera_shake,
+                        locale.text("menu.camera_shake"),
                     );
-                    ui.checkbox(&mut settings.is_camera_follow, "Camera follow");
                 });
 
-            egui::CollapsingHeader::new("Controls")
+            egui::CollapsingHeader::new(locale.text("menu.controls"))
                 .default_open(true)
                 .show(ui, |ui| {
-                    if ui.button("Start next generation").clicked() {
+                    if ui
+                        .button(locale.text("menu.start_next_generation"))
+                        .clicked()
+                    {
                         settings.start_next_generation = true;
                     };
-                    if ui.button("Restart Simulation").clicked() {
+                    if ui.button(locale.text("menu.restart_simulation")).clicked() {
                         settings.restart_sim = true;
                     };
                 });
         });
 }
 
+fn leaderboard_system(
+    mut contexts: EguiContexts,
+    locale: Res<Locale>,
+    query: Query<(&Model, &Fitness), With<Car>>,
+) {
+    let mut racers: Vec<_> = query
+        .iter()
+        .map(|(model, fitness)| (model.id, fitness.0))
+        .collect();
+    racers.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    egui::Window::new(locale.text("menu.leaderboard"))
+        .default_pos(egui::pos2(1800.0, 100.0))
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Grid::new("leaderboard").striped(true).show(ui, |ui| {
+                for (rank, (id, fitness)) in racers.iter().take(LEADERBOARD_SIZE).enumerate() {
+                    ui.label(format!("{}", rank + 1));
+                    ui.label(felt_to_short_string(*id));
+                    ui.label(format!("{fitness:.1}"));
+                    ui.end_row();
+                }
+            });
+        });
+}
+
+fn model_selection_system(
+    mut contexts: EguiContexts,
+    locale: Res<Locale>,
+    registry: Res<ModelRegistry>,
+    mut racers: ResMut<Racers>,
+    difficulty: Option<ResMut<Difficulty>>,
+    enemy_count: Option<ResMut<EnemyCount>>,
+) {
+    egui::Window::new(locale.text("menu.models"))
+        .default_pos(egui::pos2(20.0, 50.0))
+        .show(contexts.ctx_mut(), |ui| {
+            for id in registry.0.iter().copied() {
+                let selected = racers.0 == [id];
+                if ui
+                    .selectable_label(selected, felt_to_short_string(id))
+                    .clicked()
+                    && !selected
+                {
+                    racers.0 = vec![id];
+                }
+            }
+            if let Some(mut difficulty) = difficulty {
+                ui.separator();
+                ui.horizontal(|ui| {
+                    for option in Difficulty::ALL {
+                        let selected = *difficulty == option;
+                        if ui
+                            .selectable_label(selected, format!("{option:?}"))
+                            .clicked()
+                            && !selected
+                        {
+                            *difficulty = option;
+                        }
+                    }
+                });
+            }
+            if let Some(mut enemy_count) = enemy_count {
+                let mut count = enemy_count.0;
+                let range = 0..=configs::DOJO_ENEMIES_NB;
+                ui.add(egui::Slider::new(&mut count, range).text(locale.text("menu.enemies")));
+                if count != enemy_count.0 {
+                    enemy_count.0 = count;
+                }
+            }
+            ui.label(locale.text("menu.spawned_next_run"));
+        });
+}
+
 fn nn_viz_system(mut contexts: EguiContexts, best_brain: Res<BrainToDisplay>) {
     if best_brain.0.is_empty() {
         return;
@@ -350,27 +514,35 @@ fn nn_viz_system(mut contexts: EguiContexts, best_brain: Res<BrainToDisplay>) {
 }
 
 fn generation_count_stats_system(
+    locale: Res<Locale>,
     stats: Res<SimStats>,
     mut q_generation_text: Query<&mut Text, With<GenerationCountLabel>>,
 ) {
     let mut gen_text = q_generation_text.single_mut();
-    gen_text.sections[0].value = format!("Gen: \n{}", stats.generation_count);
+    gen_text.sections[0].value =
+        locale.format("hud.generation", &[("generation", &stats.generation_count)]);
 }
 
 fn num_cars_stats_system(
+    locale: Res<Locale>,
     stats: Res<SimStats>,
     mut q_num_cars_text: Query<&mut Text, With<CarsAliveLabel>>,
 ) {
     let mut num_cars_text = q_num_cars_text.single_mut();
-    num_cars_text.sections[0].value = format!("Cars: \n{}/{}", stats.num_cars_alive, NUM_AI_CARS);
+    num_cars_text.sections[0].value = locale.format(
+        "hud.cars",
+        &[("alive", &stats.num_cars_alive), ("total", &NUM_AI_CARS)],
+    );
 }
 
 fn max_score_stats_system(
+    locale: Res<Locale>,
     stats: Res<SimStats>,
     mut q_max_score_text: Query<&mut Text, With<MaxScoreLabel>>,
 ) {
     let mut max_score_text = q_max_score_text.single_mut();
-    max_score_text.sections[0].value = format!("Score: \n{}", stats.max_current_score as u32);
+    let score = stats.max_current_score as u32;
+    max_score_text.sections[0].value = locale.format("hud.score", &[("score", &score)]);
 }
 
 fn car_progress_system(
@@ -0,0 +1,191 @@
+use crate::car::CarCrashed;
+use crate::configs;
+use crate::input_map::{Action, ActionState};
+use bevy::log;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Counts the crashes of every run in a grid of [`configs::HEATMAP_CELL_SIZE`] cells, kept
+/// across runs in `path`.
+pub struct HeatmapPlugin {
+    pub path: PathBuf,
+}
+
+impl Plugin for HeatmapPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CrashHeatmap::load(&self.path))
+            .add_event::<CarCrashed>()
+            .add_system(record_crashes);
+    }
+}
+
+/// Draws the [`CrashHeatmap`] over the road, toggled with [`Action::Heatmap`].
+pub struct HeatmapOverlayPlugin;
+
+impl Plugin for HeatmapOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HeatmapOverlay>()
+            .add_system(heatmap_overlay_system.after(record_crashes));
+    }
+}
+
+/// Crashes per grid cell, saved as JSON.
+#[derive(Resource, Default, Debug)]
+pub struct CrashHeatmap {
+    path: PathBuf,
+    pub cells: HashMap<IVec2, u32>,
+}
+
+/// JSON objects can't have vectors as keys.
+#[derive(Serialize, Deserialize)]
+struct SavedCell {
+    x: i32,
+    y: i32,
+    crashes: u32,
+}
+
+impl CrashHeatmap {
+    pub fn load(path: &Path) -> Self {
+        let saved: Vec<SavedCell> = fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Self {
+            path: path.to_path_buf(),
+            cells: saved
+                .into_iter()
+                .map(|cell| (IVec2::new(cell.x, cell.y), cell.crashes))
+                .collect(),
+        }
+    }
+
+    /// Cell of a world position.
+    pub fn cell(position: Vec2) -> IVec2 {
+        (position / configs::HEATMAP_CELL_SIZE).floor().as_ivec2()
+    }
+
+    pub fn record(&mut self, position: Vec2) {
+        *self.cells.entry(Self::cell(position)).or_default() += 1;
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let saved: Vec<SavedCell> = self
+            .cells
+            .iter()
+            .map(|(cell, crashes)| SavedCell {
+                x: cell.x,
+                y: cell.y,
+                crashes: *crashes,
+            })
+            .collect();
+        fs::write(&self.path, serde_json::to_string(&saved)?)
+    }
+}
+
+/// Whether the overlay is shown, and whether its sprites are out of date.
+#[derive(Resource, Default)]
+struct HeatmapOverlay {
+    visible: bool,
+    stale: bool,
+}
+
+/// Sprite of a [`CrashHeatmap`] cell.
+#[derive(Component)]
+struct HeatmapCell;
+
+fn record_crashes(mut crashed: EventReader<CarCrashed>, mut heatmap: ResMut<CrashHeatmap>) {
+    if crashed.is_empty() {
+        return;
+    }
+    for crash in crashed.iter() {
+        heatmap.record(crash.position);
+    }
+    // Once a frame, a whole generation can crash at once
+    if let Err(e) = heatmap.save() {
+        log::error!("Save crash heatmap {}: {e}", heatmap.path.display());
+    }
+}
+
+fn heatmap_overlay_system(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    heatmap: Option<Res<CrashHeatmap>>,
+    mut overlay: ResMut<HeatmapOverlay>,
+    sprites: Query<Entity, With<HeatmapCell>>,
+) {
+    let Some(heatmap) = heatmap else {
+        return;
+    };
+    if actions.just_pressed(Action::Heatmap) {
+        overlay.visible = !overlay.visible;
+        overlay.stale = true;
+    }
+    if heatmap.is_changed() {
+        overlay.stale = true;
+    }
+    if !overlay.stale {
+        return;
+    }
+    overlay.stale = false;
+
+    for entity in sprites.iter() {
+        commands.entity(entity).despawn();
+    }
+    if !overlay.visible {
+        return;
+    }
+    let Some(max) = heatmap.cells.values().max().copied() else {
+        return;
+    };
+    for (cell, crashes) in heatmap.cells.iter() {
+        let heat = *crashes as f32 / max as f32;
+        let center = (cell.as_vec2() + 0.5) * configs::HEATMAP_CELL_SIZE;
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    // Yellow for the odd crash to red where most cars die
+                    color: Color::rgba(1.0, 1.0 - heat, 0.0, 0.2 + 0.5 * heat),
+                    custom_size: Some(Vec2::splat(configs::HEATMAP_CELL_SIZE)),
+                    ..default()
+                },
+                // Over the road, under the cars
+                transform: Transform::from_translation(center.extend(-1.0)),
+                ..default()
+            },
+            HeatmapCell,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crashes_are_counted_per_cell_and_saved() {
+        let path = std::env::temp_dir().join(format!(
+            "drive-ai-heatmap-{}/heatmap.json",
+            std::process::id()
+        ));
+        let mut heatmap = CrashHeatmap::load(&path);
+        assert!(heatmap.cells.is_empty());
+
+        let size = configs::HEATMAP_CELL_SIZE;
+        heatmap.record(Vec2::new(size * 0.2, size * 3.5));
+        heatmap.record(Vec2::new(size * 0.8, size * 3.1));
+        heatmap.record(Vec2::new(-size * 0.5, 0.0));
+        assert_eq!(heatmap.cells[&IVec2::new(0, 3)], 2);
+        assert_eq!(heatmap.cells[&IVec2::new(-1, 0)], 1);
+
+        heatmap.save().unwrap();
+        assert_eq!(CrashHeatmap::load(&path).cells, heatmap.cells);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
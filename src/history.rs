@@ -0,0 +1,359 @@
+use crate::car::{Car, Model};
+use crate::configs;
+use crate::conversions::felt_to_short_string;
+use crate::input_map::{Action, ActionState};
+use crate::locale::Locale;
+use crate::race::HeadToHead;
+use crate::toast::Toasts;
+use bevy::log;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_inspector_egui::{bevy_egui::EguiContexts, egui};
+use serde::{Deserialize, Serialize};
+use starknet_ff::FieldElement;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Writes a [`RunRecord`] to `directory` every time a car ends its run, and loads the records
+/// already there as the [`History`].
+pub struct HistoryPlugin {
+    pub directory: PathBuf,
+}
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(History {
+            directory: self.directory.clone(),
+            runs: load_runs(&self.directory),
+            visible: false,
+        })
+        .init_resource::<ActiveRuns>()
+        .add_event::<RunCost>()
+        .add_event::<RunFinished>()
+        .add_systems((start_runs, track_runs, add_run_costs, finish_runs).chain());
+    }
+}
+
+/// Lists past runs, toggled with [`Action::History`].
+pub struct HistoryScreenPlugin;
+
+impl Plugin for HistoryScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Locale>()
+            .init_resource::<Toasts<ScoreSubmitted>>()
+            .add_event::<ScoreSubmitted>()
+            .add_systems((history_screen_system, submitted_scores_system));
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RunRecord {
+    pub model_id: FieldElement,
+    /// Seed of the enemies the car raced against.
+    pub seed: FieldElement,
+    /// Furthest `y` reached.
+    pub distance: f32,
+    /// Seconds between the car spawn and the end of its run.
+    pub duration: f32,
+    /// Fees of the transactions sent for the run.
+    pub fee: FieldElement,
+    pub first_block: Option<u64>,
+    pub last_block: Option<u64>,
+    /// Unix time the run ended at.
+    pub finished_at: u64,
+}
+
+impl RunRecord {
+    fn new(model_id: FieldElement, seed: FieldElement) -> Self {
+        Self {
+            model_id,
+            seed,
+            distance: 0.0,
+            duration: 0.0,
+            fee: FieldElement::ZERO,
+            first_block: None,
+            last_block: None,
+            finished_at: 0,
+        }
+    }
+}
+
+/// Cost of a transaction sent for a racer.
+pub struct RunCost {
+    pub model_id: FieldElement,
+    pub fee: FieldElement,
+    pub block_number: Option<u64>,
+}
+
+/// A car ended its run, sent once its record is saved.
+pub struct RunFinished(pub RunRecord);
+
+/// The world accepted the `submit_score` transaction of a run.
+#[derive(Clone, Debug)]
+pub struct ScoreSubmitted {
+    pub model_id: FieldElement,
+    /// Grid units.
+    pub distance: u128,
+    pub block_number: Option<u64>,
+}
+
+/// Finished runs, oldest first.
+#[derive(Resource)]
+pub struct History {
+    pub directory: PathBuf,
+    pub runs: Vec<RunRecord>,
+    pub visible: bool,
+}
+
+/// Runs in progress by model id, the car entity is set once it is spawned.
+#[derive(Resource, Default)]
+struct ActiveRuns(HashMap<FieldElement, (Option<Entity>, RunRecord)>);
+
+fn load_runs(directory: &Path) -> Vec<RunRecord> {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return Vec::new();
+    };
+
+    let mut runs: Vec<RunRecord> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let name = path.file_name()?.to_string_lossy();
+            if !name.starts_with("run-") || !name.ends_with(".json") {
+                return None;
+            }
+            let run = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()));
+            match run {
+                Ok(run) => Some(run),
+                Err(e) => {
+                    log::warn!("Skip run record {}: {e}", path.display());
+                    None
+                }
+            }
+        })
+        .collect();
+    runs.sort_by_key(|run| run.finished_at);
+    runs
+}
+
+fn start_runs(
+    mut active: ResMut<ActiveRuns>,
+    head_to_head: Option<Res<HeadToHead>>,
+    cars: Query<(Entity, &Model), Added<Car>>,
+) {
+    for (entity, model) in cars.iter() {
+        let seed = head_to_head.as_ref().map_or(model.id, |race| race.seed);
+        let (run_entity, run) = active
+            .0
+            .entry(model.id)
+            .or_insert_with(|| (None, RunRecord::new(model.id, seed)));
+        *run_entity = Some(entity);
+        run.seed = seed;
+    }
+}
+
+fn track_runs(
+    time: Res<Time>,
+    mut active: ResMut<ActiveRuns>,
+    cars: Query<(&Transform, &Model), With<Car>>,
+) {
+    for (transform, model) in cars.iter() {
+        if let Some((_, run)) = active.0.get_mut(&model.id) {
+            run.distance = run.distance.max(transform.translation.y);
+            run.duration += time.delta_seconds();
+        }
+    }
+}
+
+fn add_run_costs(mut active: ResMut<ActiveRuns>, mut costs: EventReader<RunCost>) {
+    for cost in costs.iter() {
+        // Costs can arrive before the car is spawned, e.g. the `spawn_racer` one
+        let (_, run) = active
+            .0
+            .entry(cost.model_id)
+            .or_insert_with(|| (None, RunRecord::new(cost.model_id, cost.model_id)));
+        run.fee += cost.fee;
+        if let Some(block) = cost.block_number {
+            run.first_block = Some(run.first_block.map_or(block, |first| first.min(block)));
+            run.last_block = Some(run.last_block.map_or(block, |last| last.max(block)));
+        }
+    }
+}
+
+fn finish_runs(
+    mut active: ResMut<ActiveRuns>,
+    mut history: ResMut<History>,
+    mut removed: RemovedComponents<Car>,
+    mut finished: EventWriter<RunFinished>,
+) {
+    for entity in removed.iter() {
+        let Some(model_id) = active
+            .0
+            .iter()
+            .find(|(_, (run_entity, _))| *run_entity == Some(entity))
+            .map(|(model_id, _)| *model_id)
+        else {
+            continue;
+        };
+        let Some((_, mut run)) = active.0.remove(&model_id) else {
+            continue;
+        };
+
+        run.finished_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let path = history.directory.join(format!(
+            "run-{}-{}.json",
+            run.finished_at,
+            felt_to_short_string(run.model_id)
+        ));
+        let result = fs::create_dir_all(&history.directory).and_then(|_| {
+            let json = serde_json::to_string_pretty(&run)?;
+            fs::write(&path, json)
+        });
+        if let Err(e) = result {
+            log::error!("Write run record {}: {e}", path.display());
+        }
+        finished.send(RunFinished(run.clone()));
+        history.runs.push(run);
+    }
+}
+
+fn history_screen_system(
+    mut contexts: EguiContexts,
+    actions: Res<ActionState>,
+    locale: Res<Locale>,
+    mut history: ResMut<History>,
+) {
+    if actions.just_pressed(Action::History) {
+        history.visible = !history.visible;
+    }
+    if !history.visible {
+        return;
+    }
+
+    egui::Window::new(locale.text("history.title"))
+        .default_pos(egui::pos2(20.0, 300.0))
+        .show(contexts.ctx_mut(), |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::Grid::new("history").striped(true).show(ui, |ui| {
+                    for header in [
+                        "history.model",
+                        "history.distance",
+                        "history.duration",
+                        "history.fee",
+                        "history.blocks",
+                    ] {
+                        ui.strong(locale.text(header));
+                    }
+                    ui.end_row();
+
+                    for run in history.runs.iter().rev() {
+                        ui.label(felt_to_short_string(run.model_id));
+                        ui.label(format!("{:.0}", run.distance));
+                        ui.label(locale.format(
+                            "hud.race_time",
+                            &[("time", &format!("{:.1}", run.duration))],
+                        ));
+                        ui.label(run.fee.to_string());
+                        ui.label(match (run.first_block, run.last_block) {
+                            (Some(first), Some(last)) => format!("{first}-{last}"),
+                            _ => "-".to_string(),
+                        });
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+}
+
+fn submitted_scores_system(
+    mut contexts: EguiContexts,
+    time: Res<Time>,
+    locale: Res<Locale>,
+    mut submitted: EventReader<ScoreSubmitted>,
+    mut scores: ResMut<Toasts<ScoreSubmitted>>,
+) {
+    scores.update(
+        submitted.iter(),
+        configs::SCORE_CONFIRMATION_SECONDS,
+        time.delta_seconds(),
+    );
+    if scores.is_empty() {
+        return;
+    }
+
+    egui::Window::new(locale.text("history.scores_recorded"))
+        .title_bar(false)
+        .default_pos(egui::pos2(configs::WINDOW_WIDTH - 300.0, 20.0))
+        .show(contexts.ctx_mut(), |ui| {
+            for score in scores.iter() {
+                let block = score
+                    .block_number
+                    .map_or(locale.text("history.pending").to_string(), |block| {
+                        locale.format("history.block", &[("block", &block)])
+                    });
+                ui.label(locale.format(
+                    "history.score_recorded",
+                    &[
+                        ("model", &felt_to_short_string(score.model_id)),
+                        ("distance", &score.distance),
+                        ("block", &block),
+                    ],
+                ));
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nn::Net;
+
+    #[test]
+    fn test_finished_run_is_recorded() {
+        let directory =
+            std::env::temp_dir().join(format!("drive-ai-history-{}", std::process::id()));
+        let model_id = FieldElement::ONE;
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugin(HistoryPlugin {
+            directory: directory.clone(),
+        });
+        let car = app
+            .world
+            .spawn((
+                Car,
+                Transform::from_xyz(0.0, 250.0, 0.0),
+                Model {
+                    nn: Net::new(vec![1, 1]),
+                    nn_outputs: Vec::new(),
+                    id: model_id,
+                },
+            ))
+            .id();
+        app.world.send_event(RunCost {
+            model_id,
+            fee: FieldElement::TWO,
+            block_number: Some(7),
+        });
+        app.update();
+        app.world.entity_mut(car).remove::<Car>();
+        app.update();
+
+        let runs = &app.world.resource::<History>().runs;
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].distance, 250.0);
+        assert_eq!(runs[0].fee, FieldElement::TWO);
+        assert_eq!(
+            (runs[0].first_block, runs[0].last_block),
+            (Some(7), Some(7))
+        );
+        assert_eq!(load_runs(&directory), *runs);
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
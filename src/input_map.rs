@@ -0,0 +1,557 @@
+use crate::accessibility::AccessibilityConfig;
+use crate::audio::Volumes;
+use crate::configs;
+use crate::display::DisplaySettings;
+use crate::locale::Locale;
+use bevy::input::InputSystem;
+use bevy::log;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy::window::{PrimaryWindow, WindowMode};
+use bevy_inspector_egui::{
+    bevy_egui::{EguiContext, EguiContexts},
+    egui,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Maps the keyboard and gamepads to [`Action`]s, bound in the `bindings` section of the
+/// `settings` file. Systems read the [`ActionState`] instead of the raw input.
+pub struct InputMapPlugin {
+    pub settings: PathBuf,
+}
+
+impl Plugin for InputMapPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = Settings::load(&self.settings).unwrap_or_else(|e| {
+            log::error!("Load settings {}: {e}", self.settings.display());
+            Settings::default()
+        });
+        let locale = Locale::load(&settings.language).unwrap_or_else(|e| {
+            log::error!("Load language {}: {e}", settings.language);
+            Locale::default()
+        });
+        app.insert_resource(settings.bindings)
+            .insert_resource(settings.audio)
+            .insert_resource(settings.accessibility)
+            .insert_resource(settings.display)
+            .insert_resource(locale)
+            .insert_resource(Language(settings.language))
+            .insert_resource(SettingsPath(self.settings.clone()))
+            .init_resource::<ActionState>()
+            .init_resource::<Rebinding>()
+            .add_system(
+                update_actions
+                    .in_base_set(CoreSet::PreUpdate)
+                    .after(InputSystem),
+            );
+    }
+}
+
+/// Lists the bindings and rebinds them, next to the audio volumes, accessibility options and
+/// display settings, toggled with [`Action::Bindings`].
+pub struct BindingsScreenPlugin;
+
+impl Plugin for BindingsScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Locale>()
+            .init_resource::<BindingsScreen>()
+            .add_system(bindings_screen_system);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Steers the car of a `--controller` run.
+    SteerLeft,
+    SteerRight,
+    /// Free camera of the photo mode.
+    CameraLeft,
+    CameraRight,
+    CameraUp,
+    CameraDown,
+    CameraRotateLeft,
+    CameraRotateRight,
+    CameraReset,
+    Pause,
+    PhotoMode,
+    PhysicsInspector,
+    TrainingPlot,
+    Heatmap,
+    History,
+    RaceBrowser,
+    Snapshot,
+    CaptureScreenshot,
+    CaptureSequence,
+    Bindings,
+    /// Developer console of the dojo world.
+    Console,
+}
+
+/// A key or a button of any gamepad, e.g. `{"key": "F5"}` or `{"gamepad": "South"}`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Binding {
+    Key(KeyCode),
+    Gamepad(GamepadButtonType),
+}
+
+impl Action {
+    /// Locale key of the action name, e.g. `actions.steer_left`.
+    fn text_key(self) -> String {
+        let name = serde_json::to_value(self).expect("Serialize action");
+        format!("actions.{}", name.as_str().unwrap_or_default())
+    }
+}
+
+impl Binding {
+    fn label(self, locale: &Locale) -> String {
+        match self {
+            Binding::Key(key) => format!("{key:?}"),
+            Binding::Gamepad(button) => {
+                locale.format("settings.gamepad", &[("button", &format!("{button:?}"))])
+            }
+        }
+    }
+}
+
+/// Inputs of each action, any of them triggers it.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Bindings(pub BTreeMap<Action, Vec<Binding>>);
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use Binding::{Gamepad, Key};
+        Self(BTreeMap::from([
+            (
+                Action::SteerLeft,
+                vec![
+                    Key(configs::STEER_LEFT_KEY),
+                    Gamepad(configs::STEER_LEFT_BUTTON),
+                ],
+            ),
+            (
+                Action::SteerRight,
+                vec![
+                    Key(configs::STEER_RIGHT_KEY),
+                    Gamepad(configs::STEER_RIGHT_BUTTON),
+                ],
+            ),
+            (
+                Action::CameraLeft,
+                vec![Key(KeyCode::Left), Key(KeyCode::A)],
+            ),
+            (
+                Action::CameraRight,
+                vec![Key(KeyCode::Right), Key(KeyCode::D)],
+            ),
+            (Action::CameraUp, vec![Key(KeyCode::Up), Key(KeyCode::W)]),
+            (
+                Action::CameraDown,
+                vec![Key(KeyCode::Down), Key(KeyCode::S)],
+            ),
+            (Action::CameraRotateLeft, vec![Key(KeyCode::Q)]),
+            (Action::CameraRotateRight, vec![Key(KeyCode::E)]),
+            (Action::CameraReset, vec![Key(configs::CAMERA_RESET_KEY)]),
+            (
+                Action::Pause,
+                vec![Key(configs::PAUSE_KEY), Gamepad(configs::PAUSE_BUTTON)],
+            ),
+            (Action::PhotoMode, vec![Key(configs::PHOTO_MODE_KEY)]),
+            (
+                Action::PhysicsInspector,
+                vec![Key(configs::PHYSICS_INSPECTOR_KEY)],
+            ),
+            (Action::TrainingPlot, vec![Key(configs::TRAINING_PLOT_KEY)]),
+            (Action::Heatmap, vec![Key(configs::HEATMAP_KEY)]),
+            (Action::History, vec![Key(configs::HISTORY_KEY)]),
+            (Action::RaceBrowser, vec![Key(configs::RACE_BROWSER_KEY)]),
+            (Action::Snapshot, vec![Key(configs::SNAPSHOT_KEY)]),
+            (
+                Action::CaptureScreenshot,
+                vec![Key(configs::CAPTURE_SCREENSHOT_KEY)],
+            ),
+            (
+                Action::CaptureSequence,
+                vec![Key(configs::CAPTURE_SEQUENCE_KEY)],
+            ),
+            (Action::Bindings, vec![Key(configs::BINDINGS_KEY)]),
+            (Action::Console, vec![Key(configs::CONSOLE_KEY)]),
+        ]))
+    }
+}
+
+impl Bindings {
+    /// Binds `binding` to `action` in place of its other inputs of the same device.
+    pub fn rebind(&mut self, action: Action, binding: Binding) {
+        let is_key = |binding: &Binding| matches!(binding, Binding::Key(_));
+        let bindings = self.0.entry(action).or_default();
+        bindings.retain(|other| is_key(other) != is_key(&binding));
+        bindings.push(binding);
+    }
+}
+
+/// User settings, saved as JSON.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct Settings {
+    pub bindings: Bindings,
+    pub audio: Volumes,
+    pub accessibility: AccessibilityConfig,
+    pub display: DisplaySettings,
+    /// Language of the UI texts, see [`Locale`].
+    pub language: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            bindings: Bindings::default(),
+            audio: Volumes::default(),
+            accessibility: AccessibilityConfig::default(),
+            display: DisplaySettings::default(),
+            language: configs::DEFAULT_LANGUAGE.to_string(),
+        }
+    }
+}
+
+impl Settings {
+    /// Defaults when there is no file yet. Actions missing from the file keep their default.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = match fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.to_string()),
+        };
+        let mut settings: Self = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+        for (action, bindings) in Bindings::default().0 {
+            settings.bindings.0.entry(action).or_insert(bindings);
+        }
+        Ok(settings)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| e.to_string())
+    }
+}
+
+#[derive(Resource)]
+struct SettingsPath(PathBuf);
+
+/// `language` setting the [`Locale`] was loaded from, saved back with the other settings.
+#[derive(Resource)]
+struct Language(String);
+
+/// Actions held and started this frame.
+#[derive(Resource, Default, Debug)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    /// An egui field has the keyboard, keys only trigger [`Action::Console`] meanwhile.
+    typing: bool,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    /// Whether the keys go to an egui field rather than the game, for systems reading raw keys.
+    pub fn is_typing(&self) -> bool {
+        self.typing
+    }
+}
+
+/// Run condition toggled on and off by `action`, like `input_toggle_active` for keys.
+pub fn action_toggle_active(
+    default: bool,
+    action: Action,
+) -> impl FnMut(Res<ActionState>) -> bool + Clone {
+    let mut active = default;
+    move |actions: Res<ActionState>| {
+        active ^= actions.just_pressed(action);
+        active
+    }
+}
+
+/// Action waiting for its next input, no action is triggered meanwhile.
+#[derive(Resource, Default, Debug)]
+pub struct Rebinding(pub Option<Action>);
+
+#[derive(Resource, Default)]
+struct BindingsScreen {
+    visible: bool,
+}
+
+fn update_actions(
+    keys: Option<Res<Input<KeyCode>>>,
+    buttons: Option<Res<Input<GamepadButton>>>,
+    mut egui_contexts: Query<&mut EguiContext, With<PrimaryWindow>>,
+    mut bindings: ResMut<Bindings>,
+    mut rebinding: ResMut<Rebinding>,
+    mut actions: ResMut<ActionState>,
+) {
+    actions.typing = egui_contexts
+        .iter_mut()
+        .any(|mut context| context.get_mut().wants_keyboard_input());
+    let mut pressed = Vec::new();
+    let mut just_pressed = Vec::new();
+    if let Some(keys) = keys {
+        pressed.extend(keys.get_pressed().map(|key| Binding::Key(*key)));
+        just_pressed.extend(keys.get_just_pressed().map(|key| Binding::Key(*key)));
+    }
+    if let Some(buttons) = buttons {
+        let button = |button: &GamepadButton| Binding::Gamepad(button.button_type);
+        pressed.extend(buttons.get_pressed().map(button));
+        just_pressed.extend(buttons.get_just_pressed().map(button));
+    }
+
+    actions.pressed.clear();
+    actions.just_pressed.clear();
+    if let Some(action) = rebinding.0 {
+        if let Some(binding) = just_pressed.first() {
+            log::info!("Bound {binding:?} to {action:?}");
+            bindings.rebind(action, *binding);
+            rebinding.0 = None;
+        }
+        return;
+    }
+
+    let typing = actions.typing;
+    for (action, action_bindings) in bindings.0.iter() {
+        // Typed keys are text, but the console can still be closed
+        let bound = |binding: &Binding| {
+            !typing || *action == Action::Console || !matches!(binding, Binding::Key(_))
+        };
+        if action_bindings
+            .iter()
+            .any(|binding| bound(binding) && pressed.contains(binding))
+        {
+            actions.pressed.insert(*action);
+        }
+        if action_bindings
+            .iter()
+            .any(|binding| bound(binding) && just_pressed.contains(binding))
+        {
+            actions.just_pressed.insert(*action);
+        }
+    }
+}
+
+fn bindings_screen_system(
+    mut contexts: EguiContexts,
+    locale: Res<Locale>,
+    actions: Res<ActionState>,
+    settings: Res<SettingsPath>,
+    language: Res<Language>,
+    mut bindings: ResMut<Bindings>,
+    mut volumes: ResMut<Volumes>,
+    mut accessibility: ResMut<AccessibilityConfig>,
+    mut display: ResMut<DisplaySettings>,
+    mut rebinding: ResMut<Rebinding>,
+    mut screen: ResMut<BindingsScreen>,
+) {
+    if actions.just_pressed(Action::Bindings) {
+        screen.visible = !screen.visible;
+    }
+    if !screen.visible {
+        return;
+    }
+
+    let mut save = false;
+    let mut defaults = false;
+    egui::Window::new(locale.text("settings.bindings"))
+        .default_pos(egui::pos2(20.0, 300.0))
+        .show(contexts.ctx_mut(), |ui| {
+            egui::Grid::new("bindings").striped(true).show(ui, |ui| {
+                for (action, action_bindings) in bindings.0.iter() {
+                    ui.label(locale.text(&action.text_key()));
+                    let labels: Vec<_> = action_bindings.iter().map(|b| b.label(&locale)).collect();
+                    ui.label(labels.join(", "));
+                    if rebinding.0 != Some(*action) {
+                        if ui.button(locale.text("settings.rebind")).clicked() {
+                            rebinding.0 = Some(*action);
+                        }
+                    } else if ui.button(locale.text("settings.press_a_key")).clicked() {
+                        rebinding.0 = None;
+                    }
+                    ui.end_row();
+                }
+            });
+            ui.collapsing(locale.text("settings.audio"), |ui| {
+                let volumes = &mut *volumes;
+                for (key, volume) in [
+                    ("settings.master_volume", &mut volumes.master),
+                    ("settings.music_volume", &mut volumes.music),
+                    ("settings.sfx_volume", &mut volumes.sfx),
+                ] {
+                    ui.add(egui::Slider::new(volume, 0.0..=1.0).text(locale.text(key)));
+                }
+            });
+            ui.collapsing(locale.text("settings.accessibility"), |ui| {
+                let accessibility = &mut *accessibility;
+                for (key, enabled) in [
+                    (
+                        "settings.colorblind_palette",
+                        &mut accessibility.colorblind_palette,
+                    ),
+                    ("settings.high_contrast", &mut accessibility.high_contrast),
+                    ("settings.reduced_motion", &mut accessibility.reduced_motion),
+                ] {
+                    ui.checkbox(enabled, locale.text(key));
+                }
+            });
+            ui.collapsing(locale.text("settings.display"), |ui| {
+                display_settings_ui(ui, &locale, &mut display)
+            });
+            ui.horizontal(|ui| {
+                save = ui.button(locale.text("settings.save")).clicked();
+                defaults = ui.button(locale.text("settings.defaults")).clicked();
+            });
+        });
+
+    if defaults {
+        *bindings = Bindings::default();
+    }
+    if save {
+        let settings_file = Settings {
+            bindings: bindings.clone(),
+            audio: *volumes,
+            accessibility: *accessibility,
+            display: *display,
+            language: language.0.clone(),
+        };
+        match settings_file.save(&settings.0) {
+            Ok(()) => log::info!("Saved settings to {}", settings.0.display()),
+            Err(e) => log::error!("Save settings {}: {e}", settings.0.display()),
+        }
+    }
+}
+
+/// Edits a copy of the settings, so the window is only changed when one of them is.
+fn display_settings_ui(ui: &mut egui::Ui, locale: &Locale, display: &mut ResMut<DisplaySettings>) {
+    let mut edited = **display;
+    let mode_label = |mode: WindowMode| match mode {
+        WindowMode::Windowed => locale.text("settings.windowed"),
+        WindowMode::BorderlessFullscreen => locale.text("settings.borderless_fullscreen"),
+        WindowMode::Fullscreen | WindowMode::SizedFullscreen => locale.text("settings.fullscreen"),
+    };
+    egui::ComboBox::from_label(locale.text("settings.window_mode"))
+        .selected_text(mode_label(edited.mode))
+        .show_ui(ui, |ui| {
+            for mode in [
+                WindowMode::Windowed,
+                WindowMode::BorderlessFullscreen,
+                WindowMode::Fullscreen,
+            ] {
+                ui.selectable_value(&mut edited.mode, mode, mode_label(mode));
+            }
+        });
+    let label = |(width, height): (u32, u32)| format!("{width}×{height}");
+    egui::ComboBox::from_label(locale.text("settings.resolution"))
+        .selected_text(label(edited.resolution))
+        .show_ui(ui, |ui| {
+            for resolution in configs::DISPLAY_RESOLUTIONS {
+                ui.selectable_value(&mut edited.resolution, resolution, label(resolution));
+            }
+        });
+    let monitor_label = |monitor: Option<usize>| match monitor {
+        Some(index) => locale.format("settings.monitor_index", &[("index", &index)]),
+        None => locale.text("settings.primary_monitor").to_string(),
+    };
+    egui::ComboBox::from_label(locale.text("settings.monitor"))
+        .selected_text(monitor_label(edited.monitor))
+        .show_ui(ui, |ui| {
+            let monitors = (0..configs::DISPLAY_MONITORS).map(Some);
+            for monitor in std::iter::once(None).chain(monitors) {
+                ui.selectable_value(&mut edited.monitor, monitor, monitor_label(monitor));
+            }
+        });
+    ui.checkbox(&mut edited.vsync, locale.text("settings.vsync"));
+    ui.horizontal(|ui| {
+        let mut limited = edited.frame_limit.is_some();
+        ui.checkbox(&mut limited, locale.text("settings.frame_limit"));
+        if limited {
+            let fps = edited
+                .frame_limit
+                .get_or_insert(configs::DISPLAY_DEFAULT_FRAME_LIMIT);
+            let range = configs::DISPLAY_MIN_FRAME_LIMIT..=f32::MAX;
+            let suffix = locale.text("settings.fps_suffix");
+            ui.add(egui::DragValue::new(fps).clamp_range(range).suffix(suffix));
+        } else {
+            edited.frame_limit = None;
+        }
+    });
+    if edited != **display {
+        **display = edited;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rebinding_replaces_the_key_of_an_action() {
+        let mut app = App::new();
+        app.init_resource::<Input<KeyCode>>()
+            .add_plugin(InputMapPlugin {
+                settings: PathBuf::from("missing-settings.json"),
+            });
+        let press = |app: &mut App, key: KeyCode| {
+            app.world.resource_mut::<Input<KeyCode>>().press(key);
+            app.update();
+            let mut keys = app.world.resource_mut::<Input<KeyCode>>();
+            keys.release(key);
+            keys.clear();
+        };
+
+        press(&mut app, configs::SNAPSHOT_KEY);
+        assert!(app
+            .world
+            .resource::<ActionState>()
+            .just_pressed(Action::Snapshot));
+
+        app.world.resource_mut::<Rebinding>().0 = Some(Action::Snapshot);
+        press(&mut app, KeyCode::K);
+        assert!(!app
+            .world
+            .resource::<ActionState>()
+            .just_pressed(Action::Snapshot));
+        assert_eq!(
+            app.world.resource::<Bindings>().0[&Action::Snapshot],
+            vec![Binding::Key(KeyCode::K)]
+        );
+
+        press(&mut app, configs::SNAPSHOT_KEY);
+        assert!(!app
+            .world
+            .resource::<ActionState>()
+            .just_pressed(Action::Snapshot));
+        press(&mut app, KeyCode::K);
+        assert!(app
+            .world
+            .resource::<ActionState>()
+            .just_pressed(Action::Snapshot));
+
+        // Serialized as the bindings section of the settings
+        let json = serde_json::to_value(Settings::default()).unwrap();
+        assert_eq!(json["bindings"]["snapshot"][0]["key"], "F5");
+        assert_eq!(json["audio"]["music"], 1.0);
+        assert_eq!(json["language"], configs::DEFAULT_LANGUAGE);
+        assert_eq!(json["accessibility"]["colorblind_palette"], false);
+        assert_eq!(json["display"]["mode"], "Windowed");
+    }
+}
@@ -0,0 +1,146 @@
+use crate::car::{Car, Model, SyncedVehicle};
+use crate::configs;
+use crate::conversions::felt_to_short_string;
+use crate::locale::Locale;
+use crate::sensors::{SensorSet, Sensors};
+use crate::world::{Decode, Vehicle};
+use bevy::prelude::*;
+use bevy::render::camera::{ScalingMode, Viewport};
+use bevy::window::PrimaryWindow;
+use bevy_inspector_egui::{bevy_egui::EguiContexts, egui};
+
+/// Corner inset zoomed on the car watched by the [`Sensors`], drawing its sensor rays and the
+/// steering the world applied for them, whatever the main camera follows.
+pub struct InsetPlugin;
+
+impl Plugin for InsetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Locale>()
+            .add_startup_system(spawn_inset_camera)
+            .add_systems(
+                (fit_inset_viewport, follow_inset_car, inset_overlay_system)
+                    .chain()
+                    .after(SensorSet),
+            );
+    }
+}
+
+#[derive(Component)]
+pub struct InsetCamera;
+
+fn spawn_inset_camera(mut commands: Commands) {
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                // Drawn over the main view
+                order: 10,
+                ..default()
+            },
+            projection: OrthographicProjection {
+                scaling_mode: ScalingMode::Fixed {
+                    width: INSET_VIEW,
+                    height: INSET_VIEW,
+                },
+                ..default()
+            },
+            ..default()
+        },
+        InsetCamera,
+    ));
+}
+
+/// Side of the world square shown in the inset.
+const INSET_VIEW: f32 = configs::INSET_SIZE * configs::INSET_SCALE;
+
+/// Bottom right corner of the window, in logical pixels, sized to the window height.
+fn inset_rect(window: &Window) -> egui::Rect {
+    let size = configs::INSET_SIZE * window.height() / configs::WINDOW_HEIGHT;
+    let max = egui::pos2(
+        window.width() - configs::INSET_MARGIN,
+        window.height() - configs::INSET_MARGIN,
+    );
+    egui::Rect::from_min_max(max - egui::Vec2::splat(size), max)
+}
+
+fn fit_inset_viewport(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut cameras: Query<&mut Camera, With<InsetCamera>>,
+) {
+    let (Ok(window), Ok(mut camera)) = (windows.get_single(), cameras.get_single_mut()) else {
+        return;
+    };
+    let rect = inset_rect(window);
+    let scale = window.scale_factor() as f32;
+    let viewport = Viewport {
+        physical_position: UVec2::new((rect.min.x * scale) as u32, (rect.min.y * scale) as u32),
+        physical_size: UVec2::splat((rect.width() * scale) as u32),
+        ..default()
+    };
+    let fitted = camera.viewport.as_ref().map_or(false, |current| {
+        current.physical_position == viewport.physical_position
+            && current.physical_size == viewport.physical_size
+    });
+    if !fitted {
+        camera.viewport = Some(viewport);
+    }
+}
+
+fn follow_inset_car(
+    sensors: Res<Sensors>,
+    cars: Query<&Transform, With<Car>>,
+    mut cameras: Query<&mut Transform, (With<InsetCamera>, Without<Car>)>,
+) {
+    let car = sensors.car.and_then(|car| cars.get(car).ok());
+    if let (Some(car), Ok(mut camera)) = (car, cameras.get_single_mut()) {
+        camera.translation.x = car.translation.x;
+        camera.translation.y = car.translation.y;
+    }
+}
+
+fn inset_overlay_system(
+    mut contexts: EguiContexts,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    sensors: Res<Sensors>,
+    locale: Res<Locale>,
+    cars: Query<(&Model, &SyncedVehicle), With<Car>>,
+) {
+    let (Ok(window), Some(car)) = (windows.get_single(), sensors.car) else {
+        return;
+    };
+    let Ok((model, synced)) = cars.get(car) else {
+        return;
+    };
+    let rect = inset_rect(window);
+    let ctx = contexts.ctx_mut();
+
+    // The inset camera is centered on the car, y up
+    let painter = ctx
+        .layer_painter(egui::LayerId::new(
+            egui::Order::Foreground,
+            egui::Id::new("inset_rays"),
+        ))
+        .with_clip_rect(rect);
+    painter.rect_stroke(rect, 0.0, egui::Stroke::new(2.0, egui::Color32::WHITE));
+    let zoom = rect.width() / INSET_VIEW;
+    for ray in sensors.rays.iter() {
+        let end = rect.center() + egui::vec2(ray.end.x, -ray.end.y) * zoom;
+        let color = if ray.hit.is_some() {
+            egui::Color32::RED
+        } else {
+            egui::Color32::GREEN
+        };
+        painter.line_segment([rect.center(), end], egui::Stroke::new(1.5, color));
+    }
+
+    // The model and the steering the world applied above the inset
+    egui::Area::new("inset_steer")
+        .fixed_pos(rect.left_top() - egui::vec2(0.0, 48.0))
+        .show(ctx, |ui| {
+            ui.set_width(rect.width());
+            ui.strong(felt_to_short_string(model.id));
+            if let Ok(vehicle) = Vehicle::decode(&synced.0) {
+                let steer = format!("{:+.2}", vehicle.steer.to_f32_exact());
+                ui.label(locale.format("sensors.steer", &[("steer", &steer)]));
+            }
+        });
+}
@@ -1,8 +1,10 @@
 mod enemy;
+mod generation;
 
 mod math;
 mod model;
 mod racer;
 mod rays;
+mod score;
 mod vehicle;
 use vehicle::{Vehicle, VehicleTrait};
@@ -1,11 +1,81 @@
+pub mod accessibility;
+pub mod audio;
+#[cfg(feature = "onchain")]
+pub mod backend;
+pub mod bench;
+pub mod boot;
+pub mod boss;
+pub mod camera_shake;
+pub mod capture;
 pub mod car;
+pub mod cli;
 pub mod configs;
+#[cfg(feature = "onchain")]
+pub mod console;
+#[cfg(feature = "onchain")]
+pub mod controller;
+pub mod controls_hud;
+pub mod conversions;
+pub mod countdown;
+#[cfg(feature = "onchain")]
+pub mod deploy;
+pub mod difficulty;
+pub mod display;
+#[cfg(feature = "onchain")]
 pub mod dojo;
 pub mod enemy;
+#[cfg(feature = "event-log")]
+pub mod event_log;
+pub mod export;
+pub mod finish;
+pub mod formation;
+pub mod free_camera;
+pub mod generation_stats;
 pub mod gui;
+pub mod heatmap;
+pub mod history;
+pub mod input_map;
+pub mod inset;
+pub mod locale;
+pub mod logging;
+pub mod metrics;
+pub mod near_miss;
 pub mod nn;
+pub mod photo;
 pub mod population;
+pub mod race;
+#[cfg(feature = "onchain")]
+pub mod race_browser;
+#[cfg(feature = "onchain")]
+pub mod relay;
+pub mod replay;
 pub mod resources;
+#[cfg(feature = "scripting")]
+pub mod scripting;
+pub mod sensors;
+pub mod shoulder;
+pub mod simulation;
+pub mod snapshot;
+pub mod speed_zone;
+#[cfg(feature = "onchain")]
+pub mod sync_scheduler;
+pub mod system_call;
+pub mod tasks;
+pub mod time_trial;
+pub mod toast;
+pub mod tournament;
+pub mod training_plot;
+pub mod world;
 
 pub use configs::*;
 pub use resources::*;
+
+// Plugins and events for apps embedding the client
+pub use car::{CarCrashed, CarPlugin, SpawnCar, UpdateCar};
+#[cfg(feature = "onchain")]
+pub use dojo::{
+    DojoConfig, DojoHudPlugin, DojoPlugin, LocalSigner, ReadOnly, RequestDrive, RequestSpawn,
+    SyncIntervals,
+};
+pub use enemy::{EnemyPlugin, SpawnEnemies, UpdateEnemies};
+pub use simulation::{SimulationPlugin, StandalonePlugin};
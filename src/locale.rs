@@ -0,0 +1,88 @@
+use crate::configs;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+
+/// Texts of [`configs::DEFAULT_LANGUAGE`], built in so every key has one.
+const DEFAULT_TEXTS: &str = include_str!("../assets/locales/en.json");
+
+/// UI texts of the `language` setting, read from `<language>.json` in [`configs::LOCALES_DIR`].
+/// Keys missing from it fall back to [`configs::DEFAULT_LANGUAGE`].
+#[derive(Resource, Debug)]
+pub struct Locale {
+    texts: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self {
+            texts: HashMap::new(),
+            fallback: serde_json::from_str(DEFAULT_TEXTS).expect("Parse default locale"),
+        }
+    }
+}
+
+impl Locale {
+    pub fn load(language: &str) -> Result<Self, String> {
+        if language == configs::DEFAULT_LANGUAGE {
+            return Ok(Self::default());
+        }
+        let path = Path::new(configs::LOCALES_DIR).join(format!("{language}.json"));
+        Self::parse(&fs::read_to_string(path).map_err(|e| e.to_string())?)
+    }
+
+    pub fn parse(json: &str) -> Result<Self, String> {
+        Ok(Self {
+            texts: serde_json::from_str(json).map_err(|e| e.to_string())?,
+            ..default()
+        })
+    }
+
+    /// Text of `key`, the key itself when no language has it.
+    pub fn text<'a>(&'a self, key: &'a str) -> &'a str {
+        self.texts
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .map_or(key, String::as_str)
+    }
+
+    /// Text of `key` with its `{name}` placeholders replaced by `args`.
+    pub fn format(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        let mut text = self.text(key).to_string();
+        for (name, value) in args {
+            text = text.replace(&format!("{{{name}}}"), &value.to_string());
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_texts_fall_back_to_the_default_language() {
+        let locale = Locale::parse(r#"{"results.close": "Fermer"}"#).unwrap();
+        assert_eq!(locale.text("results.close"), "Fermer");
+        assert_eq!(locale.text("results.draw"), "Draw");
+        assert_eq!(locale.text("missing.key"), "missing.key");
+        assert_eq!(
+            locale.format("results.champion", &[("model", &"boss")]),
+            "Champion: boss"
+        );
+        assert!(Locale::parse("[]").is_err());
+
+        // Every shipped language only translates keys the default one has
+        let fallback = Locale::default().fallback;
+        for entry in fs::read_dir(configs::LOCALES_DIR).unwrap() {
+            let json = fs::read_to_string(entry.unwrap().path()).unwrap();
+            let locale = Locale::parse(&json).unwrap();
+            for key in locale.texts.keys() {
+                assert!(fallback.contains_key(key), "Unknown key {key}");
+            }
+        }
+    }
+}
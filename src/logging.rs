@@ -0,0 +1,62 @@
+use crate::configs;
+use bevy::prelude::*;
+use tracing::error;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, reload, EnvFilter, Registry};
+
+/// Replaces bevy's `LogPlugin`: logs go to stdout and, as JSON lines, to a daily rotating file
+/// under [`configs::LOG_DIRECTORY`]. Disable `LogPlugin` in `DefaultPlugins` when adding this.
+pub struct LoggingPlugin;
+
+impl Plugin for LoggingPlugin {
+    fn build(&self, app: &mut App) {
+        let directives =
+            std::env::var("RUST_LOG").unwrap_or_else(|_| configs::LOG_FILTER.to_string());
+        let (filter, handle) = reload::Layer::new(EnvFilter::new(&directives));
+
+        let file_appender =
+            tracing_appender::rolling::daily(configs::LOG_DIRECTORY, configs::LOG_FILE_PREFIX);
+        let (file_writer, guard) = tracing_appender::non_blocking(file_appender);
+
+        let subscriber = tracing_subscriber::registry()
+            .with(filter)
+            .with(fmt::layer())
+            .with(fmt::layer().json().with_writer(file_writer));
+
+        if let Err(e) = subscriber.try_init() {
+            eprintln!("Could not set global tracing subscriber: {e}");
+        }
+
+        app.insert_resource(LogFilter { directives, handle })
+            .insert_resource(LogGuard(guard))
+            .add_system(apply_log_filter);
+    }
+}
+
+/// Per-module level filters, e.g. `info,steering::dojo=debug`.
+/// Changing `directives` at runtime reloads the filter on the next frame.
+#[derive(Resource)]
+pub struct LogFilter {
+    pub directives: String,
+    handle: reload::Handle<EnvFilter, Registry>,
+}
+
+/// Keeps the non-blocking file writer alive, dropping it flushes pending lines.
+#[derive(Resource)]
+struct LogGuard(#[allow(dead_code)] WorkerGuard);
+
+fn apply_log_filter(filter: Res<LogFilter>) {
+    if !filter.is_changed() || filter.is_added() {
+        return;
+    }
+
+    match EnvFilter::try_new(&filter.directives) {
+        Ok(env_filter) => {
+            if let Err(e) = filter.handle.reload(env_filter) {
+                error!(error = %e, "Reload log filter");
+            }
+        }
+        Err(e) => error!(directives = %filter.directives, error = %e, "Parse log filter"),
+    }
+}
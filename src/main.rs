@@ -1,79 +1,367 @@
-use bevy::{math::vec3, prelude::*};
-use bevy_inspector_egui::{bevy_egui::EguiPlugin, DefaultInspectorConfigPlugin};
+#[cfg(feature = "onchain")]
+use bevy::{app::ScheduleRunnerSettings, utils::Duration};
+use bevy::{log::LogPlugin, math::vec3, prelude::*};
+use bevy_inspector_egui::{
+    bevy_egui::EguiPlugin, quick::ResourceInspectorPlugin, DefaultInspectorConfigPlugin,
+};
 use bevy_pancam::{PanCam, PanCamPlugin};
+use bevy_prototype_debug_lines::DebugLinesPlugin;
 use bevy_rapier2d::prelude::*;
+use clap::Parser;
 use steering::{
-    car::{Car, CarPlugin},
+    accessibility::{ColorblindPalettePlugin, HighContrastPlugin},
+    audio::{MusicPlugin, TrafficAudioPlugin},
+    bench::BenchSyncPlugin,
+    boot::BootScreenPlugin,
+    boss::BossHudPlugin,
+    camera_shake::CameraShakePlugin,
+    capture::CapturePlugin,
+    car::Car,
+    cli::{Cli, Command},
     configs::*,
-    dojo::DojoPlugin,
-    enemy::EnemyPlugin,
-    population::PopulationPlugin,
-    MaxDistanceTravelled,
+    controls_hud::ControlsHudPlugin,
+    conversions::short_string_to_felt,
+    countdown::CountdownHudPlugin,
+    display::{road_projection, DisplayPlugin},
+    enemy::EnemyCount,
+    export::ExportPlugin,
+    finish::FinishResultsPlugin,
+    gui::{ModelSelectionPlugin, OverlayPlugin, OverlayStyle},
+    heatmap::HeatmapOverlayPlugin,
+    history::HistoryScreenPlugin,
+    input_map::{action_toggle_active, Action, ActionState, BindingsScreenPlugin, InputMapPlugin},
+    inset::InsetPlugin,
+    logging::LoggingPlugin,
+    near_miss::NearMissHudPlugin,
+    photo::{InputMode, PhotoModePlugin},
+    replay::{RecorderPlugin, ReplayPlugin},
+    sensors::SensorHudPlugin,
+    simulation::{SimulationPlugin, StandalonePlugin},
+    snapshot::SnapshotPlugin,
+    speed_zone::SpeedZoneMarkingsPlugin,
+    training_plot::TrainingPlotPlugin,
+    MaxDistanceTravelled, ModelRegistry, PhysicsConfig, Racers,
+};
+#[cfg(feature = "onchain")]
+use steering::{
+    console::DevConsolePlugin,
+    controller::ControllerPlugin,
+    deploy,
+    dojo::{DojoConfig, DojoHudPlugin, DojoPlugin, ReadOnly},
+    race::{HeadToHead, HeadToHeadPlugin, SplitScreenPlugin},
+    race_browser::RaceBrowserPlugin,
+    relay::{RelayPlugin, RelayViewerPlugin},
+    time_trial::TimeTrialPlugin,
+    tournament::TournamentPlugin,
 };
 
 fn main() {
-    App::new()
-        .insert_resource(FixedTime::new_from_secs(0.25))
-        .add_plugins(
-            DefaultPlugins
-                .set(ImagePlugin::default_nearest())
-                .set(WindowPlugin {
-                    primary_window: Some(Window {
-                        resizable: false,
-                        focused: true,
-                        resolution: (WINDOW_WIDTH, WINDOW_HEIGHT).into(),
-                        ..default()
-                    }),
+    let cli = Cli::parse();
+
+    let mut app = App::new();
+    app.add_plugin(LoggingPlugin);
+
+    match cli.command() {
+        Command::Play {
+            record,
+            models,
+            standalone,
+            #[cfg(feature = "onchain")]
+            controller,
+        } => {
+            if !models.is_empty() {
+                let registry =
+                    ModelRegistry(models.iter().map(|id| short_string_to_felt(id)).collect());
+                app.insert_resource(Racers(vec![registry.0[0]]))
+                    .insert_resource(registry);
+            }
+            add_window_plugins(&mut app, &cli);
+            app.add_plugin(SimulationPlugin);
+            add_racer_sync(&mut app, standalone);
+            app.add_plugin(ModelSelectionPlugin);
+            #[cfg(feature = "onchain")]
+            if controller {
+                app.add_plugin(ControllerPlugin);
+            }
+            if let Some(path) = record {
+                app.add_plugin(RecorderPlugin { path });
+            }
+        }
+        #[cfg(feature = "onchain")]
+        Command::Train { racers } => {
+            app.insert_resource(Racers::numbered(racers))
+                .insert_resource(ScheduleRunnerSettings::run_loop(Duration::from_secs_f32(
+                    TRAIN_TIME_STEP,
+                )))
+                .add_plugins(MinimalPlugins)
+                .add_plugin(AssetPlugin::default())
+                .add_plugin(TransformPlugin)
+                .add_plugin(HierarchyPlugin);
+            app.add_plugin(SimulationPlugin);
+            app.add_plugin(dojo_plugin());
+        }
+        Command::Replay { file } => {
+            add_window_plugins(&mut app, &cli);
+            app.add_plugin(SimulationPlugin);
+            app.add_plugin(ReplayPlugin { path: file });
+        }
+        Command::Export { file, output } => {
+            add_window_plugins(&mut app, &cli);
+            app.add_plugin(SimulationPlugin);
+            app.add_plugin(ReplayPlugin { path: file.clone() })
+                .add_plugin(ExportPlugin {
+                    output: output.unwrap_or_else(|| file.with_extension("gif")),
+                    replay: file,
+                });
+        }
+        #[cfg(feature = "onchain")]
+        Command::Races { model } => {
+            add_window_plugins(&mut app, &cli);
+            app.add_plugin(SimulationPlugin);
+            app.add_plugin(RaceBrowserPlugin { model });
+        }
+        #[cfg(feature = "onchain")]
+        Command::Spectate { racers } => {
+            add_window_plugins(&mut app, &cli);
+            app.add_plugin(SimulationPlugin);
+            if let Some(racers) = racers {
+                app.insert_resource(Racers::numbered(racers));
+            }
+            app.insert_resource(ReadOnly).add_plugin(dojo_plugin());
+        }
+        #[cfg(feature = "onchain")]
+        Command::Race { model_a, model_b } => {
+            add_window_plugins(&mut app, &cli);
+            app.add_plugin(SimulationPlugin);
+            app.add_plugin(HeadToHeadPlugin {
+                race: HeadToHead::new([
+                    short_string_to_felt(&model_a),
+                    short_string_to_felt(&model_b),
+                ]),
+            })
+            .add_plugin(SplitScreenPlugin)
+            .add_plugin(dojo_plugin());
+        }
+        #[cfg(feature = "onchain")]
+        Command::TimeTrial { model } => {
+            if let Some(model) = model {
+                app.insert_resource(Racers(vec![short_string_to_felt(&model)]));
+            }
+            add_window_plugins(&mut app, &cli);
+            app.add_plugin(SimulationPlugin);
+            app.add_plugin(TimeTrialPlugin {
+                bests: TIME_TRIAL_BESTS_PATH.into(),
+            })
+            .add_plugin(dojo_plugin());
+        }
+        #[cfg(feature = "onchain")]
+        Command::Tournament { models } => {
+            add_window_plugins(&mut app, &cli);
+            app.add_plugin(SimulationPlugin);
+            app.add_plugin(TournamentPlugin {
+                models: models.iter().map(|id| short_string_to_felt(id)).collect(),
+            })
+            .add_plugin(dojo_plugin());
+        }
+        #[cfg(feature = "onchain")]
+        Command::Watch { url, host } => {
+            add_window_plugins(&mut app, &cli);
+            app.add_plugin(SimulationPlugin);
+            app.add_plugin(RelayViewerPlugin { url, host });
+        }
+        #[cfg(feature = "onchain")]
+        Command::Deploy => {
+            if let Err(e) = deploy::deploy() {
+                error!("Deploy world: {e}");
+                std::process::exit(1);
+            }
+            return;
+        }
+    }
+
+    if cli.bench_sync {
+        app.add_plugin(BenchSyncPlugin);
+    }
+    #[cfg(feature = "event-log")]
+    if let Some(path) = cli.event_log.clone() {
+        app.add_plugin(steering::event_log::EventLogPlugin { path });
+    }
+    #[cfg(feature = "scripting")]
+    if let Some(path) = cli.script.clone() {
+        app.add_plugin(steering::scripting::ScriptingPlugin { path });
+    }
+    #[cfg(feature = "onchain")]
+    if cli.relay {
+        app.add_plugin(RelayPlugin);
+    }
+
+    let enemy_count = cli.enemies.unwrap_or(cli.difficulty.preset().enemy_count);
+    app.insert_resource(cli.difficulty)
+        .insert_resource(EnemyCount(enemy_count))
+        .insert_resource(cli)
+        .run();
+}
+
+/// Window, camera and debug tooling, for every subcommand but `train`.
+fn add_window_plugins(app: &mut App, cli: &Cli) {
+    let overlay = cli.overlay;
+
+    app.add_plugins(
+        DefaultPlugins
+            .set(ImagePlugin::default_nearest())
+            .set(WindowPlugin {
+                primary_window: Some(Window {
+                    resizable: false,
+                    focused: true,
+                    transparent: overlay == Some(OverlayStyle::Transparent),
+                    resolution: (WINDOW_WIDTH, WINDOW_HEIGHT).into(),
                     ..default()
                 }),
-        )
-        .add_plugin(PanCamPlugin::default())
-        // .add_plugin(WorldInspectorPlugin::new().run_if(input_toggle_active(false, KeyCode::Tab))) // remove eguiplugin
-        .add_plugin(DefaultInspectorConfigPlugin) // Requires egui plugin
-        .add_plugin(EguiPlugin)
-        .add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0))
-        // .add_plugin(LogDiagnosticsPlugin::default())
-        // .add_plugin(FrameTimeDiagnosticsPlugin::default())
-        .add_plugin(CarPlugin)
-        .add_plugin(EnemyPlugin)
-        .add_plugin(PopulationPlugin)
+                ..default()
+            })
+            .disable::<LogPlugin>(),
+    )
+    .add_plugin(InputMapPlugin {
+        settings: SETTINGS_PATH.into(),
+    })
+    .add_plugin(BindingsScreenPlugin)
+    .add_plugin(DisplayPlugin)
+    .add_plugin(BootScreenPlugin)
+    // .add_plugin(WorldInspectorPlugin::new().run_if(input_toggle_active(false, KeyCode::Tab))) // remove eguiplugin
+    .add_plugin(DefaultInspectorConfigPlugin) // Requires egui plugin
+    .add_plugin(EguiPlugin)
+    .add_plugin(
+        ResourceInspectorPlugin::<PhysicsConfig>::default()
+            .run_if(action_toggle_active(false, Action::PhysicsInspector)),
+    )
+    // .add_plugin(LogDiagnosticsPlugin::default())
+    // .add_plugin(FrameTimeDiagnosticsPlugin::default())
+    .add_plugin(CapturePlugin {
+        directory: cli
+            .capture
+            .clone()
+            .unwrap_or_else(|| CAPTURE_DIRECTORY.into()),
+        sequence: cli.capture.is_some(),
+    })
+    .add_plugin(SnapshotPlugin {
+        restore: cli.restore.clone(),
+    })
+    .add_plugin(HistoryScreenPlugin)
+    .add_plugin(TrainingPlotPlugin)
+    .add_plugin(FinishResultsPlugin)
+    .add_plugin(CountdownHudPlugin)
+    .add_plugin(NearMissHudPlugin)
+    .add_plugin(BossHudPlugin)
+    .add_plugin(SensorHudPlugin)
+    .add_plugin(ControlsHudPlugin)
+    .add_plugin(TrafficAudioPlugin)
+    .add_plugin(MusicPlugin)
+    .add_system(
+        bevy::window::close_on_esc.run_if(|actions: Res<ActionState>| !actions.is_typing()),
+    );
+    #[cfg(feature = "onchain")]
+    app.add_plugin(DojoHudPlugin).add_plugin(DevConsolePlugin);
+
+    if let Some(style) = overlay {
+        app.add_plugin(OverlayPlugin { style });
+        return;
+    }
+
+    app.add_plugin(PanCamPlugin::default())
+        .add_plugin(HeatmapOverlayPlugin)
+        .add_plugin(SpeedZoneMarkingsPlugin)
+        .add_plugin(PhotoModePlugin)
+        .add_plugin(CameraShakePlugin)
+        .add_plugin(InsetPlugin)
+        .add_plugin(DebugLinesPlugin::default())
+        .add_plugin(ColorblindPalettePlugin)
+        .add_plugin(HighContrastPlugin)
         // .add_plugin(GuiPlugin)
-        .add_plugin(DojoPlugin)
         .add_plugin(RapierDebugRenderPlugin::default())
         .insert_resource(ClearColor(Color::rgb_u8(36, 36, 36)))
         .insert_resource(ClearColor(Color::WHITE))
         // .insert_resource(Msaa::Off)
-        .add_startup_system(setup)
-        .add_system(bevy::window::close_on_esc)
-        .add_system(camera_follow_system)
+        .add_startup_system(setup_camera)
         // .add_system(settings_system)
-        .run();
+        .add_system(camera_follow_system.run_if(in_state(InputMode::Race)))
+        .add_system(reset_camera_framing_system.run_if(in_state(InputMode::Race)))
+        .add_system(toggle_pause_system.run_if(in_state(InputMode::Race)));
 }
 
-fn setup(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut rapier_config: ResMut<RapierConfiguration>,
-) {
-    rapier_config.gravity = Vec2::ZERO;
+/// Exits when the node settings of `configs` can't be used.
+#[cfg(feature = "onchain")]
+fn dojo_plugin() -> DojoPlugin {
+    match DojoConfig::load() {
+        Ok(config) => DojoPlugin::new(config),
+        Err(e) => {
+            error!("Load dojo config: {e}");
+            std::process::exit(1);
+        }
+    }
+}
 
+#[cfg(feature = "onchain")]
+fn add_racer_sync(app: &mut App, standalone: bool) {
+    if standalone {
+        app.add_plugin(StandalonePlugin);
+    } else {
+        app.add_plugin(dojo_plugin());
+    }
+}
+
+#[cfg(not(feature = "onchain"))]
+fn add_racer_sync(app: &mut App, _standalone: bool) {
+    app.add_plugin(StandalonePlugin);
+}
+
+fn setup_camera(mut commands: Commands) {
     commands
         .spawn(Camera2dBundle {
             transform: Transform::from_xyz(WINDOW_WIDTH / 2.0, WINDOW_HEIGHT / 2.0, 0.0),
+            projection: road_projection(),
             ..default()
         })
-        .insert(PanCam::default());
+        .insert(PanCam {
+            min_scale: CAMERA_MIN_SCALE,
+            max_scale: Some(CAMERA_MAX_SCALE),
+            min_x: Some(-CAMERA_FRAMING_MARGIN),
+            max_x: Some(WINDOW_WIDTH + CAMERA_FRAMING_MARGIN),
+            ..default()
+        });
+}
 
-    spawn_roads(&mut commands, &asset_server);
-    // spawn_bound_trucks(&mut commands, &asset_server);
+/// Puts the camera back over the middle of the road at the default zoom.
+fn reset_camera_framing_system(
+    actions: Res<ActionState>,
+    mut cameras: Query<(&mut Transform, &mut OrthographicProjection), With<PanCam>>,
+) {
+    if !actions.just_pressed(Action::CameraReset) {
+        return;
+    }
+    for (mut transform, mut projection) in cameras.iter_mut() {
+        transform.translation.x = WINDOW_WIDTH / 2.0;
+        projection.scale = 1.0;
+    }
+}
+
+/// Freezes the race, the photo mode keeps its own pause.
+fn toggle_pause_system(actions: Res<ActionState>, mut time: ResMut<Time>) {
+    if !actions.just_pressed(Action::Pause) {
+        return;
+    }
+    if time.is_paused() {
+        time.unpause();
+    } else {
+        time.pause();
+    }
 }
 
 fn camera_follow_system(
     // settings: Res<Settings>,
     max_distance_travelled: Res<MaxDistanceTravelled>,
-    mut cam_query: Query<(&Camera, &mut Transform), Without<Car>>,
+    mut cam_query: Query<&mut Transform, (With<PanCam>, Without<Car>)>,
 ) {
-    let (_, mut cam_transform) = cam_query.get_single_mut().unwrap();
+    let mut cam_transform = cam_query.get_single_mut().unwrap();
     // if settings.is_camera_follow {
     cam_transform.translation = cam_transform.translation.lerp(
         vec3(cam_transform.translation.x, max_distance_travelled.0, 0.0),
@@ -82,71 +370,6 @@ fn camera_follow_system(
     // }
 }
 
-fn spawn_roads(commands: &mut Commands, asset_server: &AssetServer) {
-    // Road
-    // let rx = WINDOW_WIDTH / 2.0 - 30.0;
-    let rx = ROAD_SPRITE_W / 2.0 * SPRITE_SCALE_FACTOR;
-    let mut ry = ROAD_SPRITE_H / 2.0 * SPRITE_SCALE_FACTOR;
-    for _ in 0..NUM_ROAD_TILES {
-        commands.spawn(SpriteBundle {
-            transform: Transform::from_xyz(rx, ry, -10.0)
-                .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
-            texture: asset_server.load("road.png"),
-            ..default()
-        });
-        ry += ROAD_SPRITE_H * SPRITE_SCALE_FACTOR;
-    }
-    let road_end_y = ry - ROAD_SPRITE_H * SPRITE_SCALE_FACTOR + 800.0;
-
-    // end checker board
-    commands.spawn(SpriteBundle {
-        transform: Transform::from_xyz(rx, road_end_y - 50.0, -5.0)
-            .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
-        texture: asset_server.load("end-point.png"),
-        ..default()
-    });
-
-    // Road colliders
-    // left
-    let ry = 5.0 * ROAD_SPRITE_H * SPRITE_SCALE_FACTOR;
-    let rx_min = ROAD_SPRITE_W / 2.0 * SPRITE_SCALE_FACTOR + 238.0;
-    commands.spawn((
-        SpriteBundle {
-            transform: Transform::from_xyz(rx_min, ry, 0.0).with_scale(vec3(0.5, 0.5, 1.0)),
-            ..default()
-        },
-        RigidBody::Fixed,
-        Collider::cuboid(
-            5.0,
-            ROAD_SPRITE_H * SPRITE_SCALE_FACTOR * NUM_ROAD_TILES as f32 * 5.0,
-        ),
-    ));
-
-    // right
-    let rx_max = ROAD_SPRITE_W * SPRITE_SCALE_FACTOR + 248.0;
-    commands.spawn((
-        SpriteBundle {
-            transform: Transform::from_xyz(rx_max, ry, 0.0).with_scale(vec3(0.5, 0.5, 1.0)),
-            ..default()
-        },
-        RigidBody::Fixed,
-        Collider::cuboid(
-            5.0,
-            ROAD_SPRITE_H * SPRITE_SCALE_FACTOR * NUM_ROAD_TILES as f32 * 5.0,
-        ),
-    ));
-
-    // top
-    commands.spawn((
-        SpriteBundle {
-            transform: Transform::from_xyz(600.0, road_end_y, 0.0).with_scale(vec3(0.5, 0.5, 1.0)),
-            ..default()
-        },
-        RigidBody::Fixed,
-        Collider::cuboid(500.0 * SPRITE_SCALE_FACTOR, 10.0),
-    ));
-}
-
 // fn settings_system(
 //     mut commands: Commands,
 //     mut settings: ResMut<Settings>,
@@ -0,0 +1,211 @@
+use crate::cli::Cli;
+use crate::configs;
+use crate::conversions::felt_to_short_string;
+use crate::resources::{RunStats, SimStats};
+use crate::tasks::tokio_tasks_plugin;
+use bevy::diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin};
+use bevy::log;
+use bevy::prelude::*;
+use bevy_tokio_tasks::{TokioTasksPlugin, TokioTasksRuntime};
+use prometheus::{
+    Encoder, Gauge, GaugeVec, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    Opts, Registry, TextEncoder,
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Exposes the [`Metrics`] registry over HTTP so long training runs can be scraped by Prometheus,
+/// on [`configs::METRICS_ENDPOINT`], or [`configs::METRICS_PUBLIC_ENDPOINT`] with
+/// `--metrics-public`.
+pub struct MetricsPlugin;
+
+impl Plugin for MetricsPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<TokioTasksPlugin>() {
+            app.add_plugin(tokio_tasks_plugin());
+        }
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugin(FrameTimeDiagnosticsPlugin);
+        }
+
+        app.init_resource::<Metrics>()
+            .add_startup_system(serve_metrics)
+            .add_system(record_frame_metrics);
+    }
+}
+
+/// Counters and histograms shared between the ECS and the dojo background tasks.
+/// All fields are cheap to clone, clones report to the same registry.
+#[derive(Resource, Clone)]
+pub struct Metrics {
+    registry: Registry,
+    /// Number of `drive` system executions sent to the world.
+    pub drive_calls: IntCounter,
+    /// Failed dojo calls, labelled by call name.
+    pub failures: IntCounterVec,
+    /// Latency of dojo calls in seconds, labelled by call name.
+    pub sync_latency: HistogramVec,
+    /// Sync ticks merged into a pending command, labelled by channel.
+    pub dropped_ticks: IntCounterVec,
+    pub fps: Gauge,
+    pub generation: IntGauge,
+    /// Distance covered by the current run in grid units, labelled by model.
+    pub run_distance: GaugeVec,
+    /// Speed in grid units per second, labelled by model.
+    pub run_speed: GaugeVec,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some("drive_ai".into()), None).unwrap();
+
+        let drive_calls = IntCounter::new("drive_calls_total", "Drive system executions").unwrap();
+        let failures = IntCounterVec::new(
+            Opts::new("dojo_failures_total", "Failed dojo calls"),
+            &["call"],
+        )
+        .unwrap();
+        let sync_latency = HistogramVec::new(
+            HistogramOpts::new("sync_latency_seconds", "Latency of dojo calls"),
+            &["call"],
+        )
+        .unwrap();
+        let dropped_ticks = IntCounterVec::new(
+            Opts::new(
+                "dropped_ticks_total",
+                "Sync ticks merged into a pending command",
+            ),
+            &["channel"],
+        )
+        .unwrap();
+        let fps = Gauge::new("fps", "Smoothed frames per second").unwrap();
+        let generation = IntGauge::new("generation", "Current training generation").unwrap();
+        let run_distance = GaugeVec::new(
+            Opts::new("run_distance", "Distance covered by the current run"),
+            &["model"],
+        )
+        .unwrap();
+        let run_speed = GaugeVec::new(
+            Opts::new("run_speed", "Speed of the current run"),
+            &["model"],
+        )
+        .unwrap();
+
+        registry.register(Box::new(drive_calls.clone())).unwrap();
+        registry.register(Box::new(failures.clone())).unwrap();
+        registry.register(Box::new(sync_latency.clone())).unwrap();
+        registry.register(Box::new(dropped_ticks.clone())).unwrap();
+        registry.register(Box::new(fps.clone())).unwrap();
+        registry.register(Box::new(generation.clone())).unwrap();
+        registry.register(Box::new(run_distance.clone())).unwrap();
+        registry.register(Box::new(run_speed.clone())).unwrap();
+
+        Self {
+            registry,
+            drive_calls,
+            failures,
+            sync_latency,
+            dropped_ticks,
+            fps,
+            generation,
+            run_distance,
+            run_speed,
+        }
+    }
+
+    /// Renders all metrics in the Prometheus text exposition format.
+    pub fn encode(&self) -> String {
+        let mut buffer = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut buffer) {
+            log::error!("Encode metrics: {e}");
+        }
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn record_frame_metrics(
+    metrics: Res<Metrics>,
+    diagnostics: Res<Diagnostics>,
+    sim_stats: Option<Res<SimStats>>,
+    run_stats: Option<Res<RunStats>>,
+) {
+    if let Some(fps) = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+    {
+        metrics.fps.set(fps);
+    }
+    if let Some(sim_stats) = sim_stats {
+        metrics.generation.set(sim_stats.generation_count as i64);
+    }
+    if let Some(run_stats) = run_stats {
+        for (model_id, stats) in run_stats.0.iter() {
+            let model = felt_to_short_string(*model_id);
+            metrics
+                .run_distance
+                .with_label_values(&[&model])
+                .set(stats.distance as f64);
+            metrics
+                .run_speed
+                .with_label_values(&[&model])
+                .set(stats.speed as f64);
+        }
+    }
+}
+
+fn serve_metrics(metrics: Res<Metrics>, cli: Option<Res<Cli>>, runtime: ResMut<TokioTasksRuntime>) {
+    let metrics = metrics.clone();
+    let endpoint = if cli.map_or(false, |cli| cli.metrics_public) {
+        configs::METRICS_PUBLIC_ENDPOINT
+    } else {
+        configs::METRICS_ENDPOINT
+    };
+
+    runtime.spawn_background_task(move |_ctx| async move {
+        let listener = match TcpListener::bind(endpoint).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                log::error!("Bind metrics endpoint {endpoint}: {e}");
+                return;
+            }
+        };
+
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let metrics = metrics.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = respond(stream, &metrics).await {
+                            log::error!("Serve metrics: {e}");
+                        }
+                    });
+                }
+                Err(e) => log::error!("Accept metrics connection: {e}"),
+            }
+        }
+    });
+}
+
+async fn respond(mut stream: TcpStream, metrics: &Metrics) -> std::io::Result<()> {
+    let mut request = [0u8; 1024];
+    let len = stream.read(&mut request).await?;
+
+    let response = if request[..len].starts_with(b"GET /metrics") {
+        let body = metrics.encode();
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    } else {
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+    };
+
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
@@ -0,0 +1,238 @@
+use crate::car::{Car, Model};
+use crate::configs;
+use crate::conversions::felt_to_short_string;
+use crate::enemy::Enemy;
+use crate::locale::Locale;
+use crate::toast::Toasts;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_inspector_egui::{bevy_egui::EguiContexts, egui};
+use bevy_rapier2d::prelude::*;
+use bevy_rapier2d::rapier::geometry::CollisionEventFlags;
+use starknet_ff::FieldElement;
+
+/// Surrounds every car with a sensor slightly larger than its collider, an enemy leaving it
+/// without a crash is a [`NearMiss`] worth [`configs::NEAR_MISS_POINTS`] style points.
+pub struct NearMissPlugin;
+
+impl Plugin for NearMissPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StyleScores>()
+            .add_event::<NearMiss>()
+            .add_systems(
+                (
+                    attach_near_miss_sensors,
+                    detect_near_misses,
+                    score_near_misses,
+                    remove_near_miss_sensors,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Pops up every [`NearMiss`] for [`configs::NEAR_MISS_POPUP_SECONDS`].
+pub struct NearMissHudPlugin;
+
+impl Plugin for NearMissHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Toasts<NearMiss>>()
+            .init_resource::<Locale>()
+            .add_event::<NearMiss>()
+            .add_system(near_miss_popups_system);
+    }
+}
+
+/// Child of a car, the sensor collider near misses are detected with.
+#[derive(Component)]
+pub struct NearMissSensor;
+
+/// An enemy went through the near miss sensor of a car without hitting it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NearMiss {
+    pub model_id: FieldElement,
+    pub enemy: Entity,
+}
+
+/// Style points of the current run of each model.
+#[derive(Resource, Default, Debug)]
+pub struct StyleScores(pub HashMap<FieldElement, u32>);
+
+fn attach_near_miss_sensors(
+    mut commands: Commands,
+    mut scores: ResMut<StyleScores>,
+    cars: Query<(Entity, &Model), Added<Car>>,
+) {
+    for (entity, model) in cars.iter() {
+        scores.0.insert(model.id, 0);
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn((
+                TransformBundle::default(),
+                Collider::cuboid(
+                    configs::CAR_COLLIDER.x + configs::NEAR_MISS_SENSOR_MARGIN,
+                    configs::CAR_COLLIDER.y + configs::NEAR_MISS_SENSOR_MARGIN,
+                ),
+                Sensor,
+                ActiveEvents::COLLISION_EVENTS,
+                configs::SENSOR_COLLISION_GROUPS,
+                configs::SENSOR_SOLVER_GROUPS,
+                NearMissSensor,
+            ));
+        });
+    }
+}
+
+fn detect_near_misses(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut near_misses: EventWriter<NearMiss>,
+    sensors: Query<&Parent, With<NearMissSensor>>,
+    cars: Query<&Model, With<Car>>,
+    enemies: Query<(), With<Enemy>>,
+) {
+    for collision_event in collision_events.iter() {
+        let CollisionEvent::Stopped(entity1, entity2, flags) = collision_event else {
+            continue;
+        };
+        if !flags.contains(CollisionEventFlags::SENSOR) {
+            continue;
+        }
+
+        for (sensor, enemy) in [(entity1, entity2), (entity2, entity1)] {
+            let Ok(parent) = sensors.get(*sensor) else {
+                continue;
+            };
+            if !enemies.contains(*enemy) {
+                continue;
+            }
+            // A car that crashed into the enemy has lost its `Car`
+            if let Ok(model) = cars.get(parent.get()) {
+                near_misses.send(NearMiss {
+                    model_id: model.id,
+                    enemy: *enemy,
+                });
+            }
+        }
+    }
+}
+
+fn score_near_misses(mut near_misses: EventReader<NearMiss>, mut scores: ResMut<StyleScores>) {
+    for near_miss in near_misses.iter() {
+        *scores.0.entry(near_miss.model_id).or_default() += configs::NEAR_MISS_POINTS;
+    }
+}
+
+/// Sensors of crashed cars would keep colliding on their own.
+fn remove_near_miss_sensors(
+    mut commands: Commands,
+    mut removed: RemovedComponents<Car>,
+    sensors: Query<(Entity, &Parent), With<NearMissSensor>>,
+) {
+    let removed: Vec<Entity> = removed.iter().collect();
+    if removed.is_empty() {
+        return;
+    }
+    for (sensor, parent) in sensors.iter() {
+        if removed.contains(&parent.get()) {
+            commands.entity(sensor).despawn_recursive();
+        }
+    }
+}
+
+fn near_miss_popups_system(
+    mut contexts: EguiContexts,
+    locale: Res<Locale>,
+    time: Res<Time>,
+    mut near_misses: EventReader<NearMiss>,
+    mut popups: ResMut<Toasts<NearMiss>>,
+    scores: Option<Res<StyleScores>>,
+) {
+    popups.update(
+        near_misses.iter(),
+        configs::NEAR_MISS_POPUP_SECONDS,
+        time.delta_seconds(),
+    );
+    if popups.is_empty() {
+        return;
+    }
+
+    egui::Window::new(locale.text("hud.near_misses"))
+        .title_bar(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, -200.0))
+        .show(contexts.ctx_mut(), |ui| {
+            for near_miss in popups.iter() {
+                let style = scores
+                    .as_ref()
+                    .and_then(|scores| scores.0.get(&near_miss.model_id).copied())
+                    .unwrap_or_default();
+                ui.heading(locale.format(
+                    "hud.near_miss",
+                    &[
+                        ("model", &felt_to_short_string(near_miss.model_id)),
+                        ("points", &configs::NEAR_MISS_POINTS),
+                        ("style", &style),
+                    ],
+                ));
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nn::Net;
+
+    #[test]
+    fn test_dodged_enemy_is_a_near_miss() {
+        let model_id = FieldElement::ONE;
+        let mut app = App::new();
+        app.add_event::<CollisionEvent>().add_plugin(NearMissPlugin);
+        let car = app
+            .world
+            .spawn((
+                Car,
+                Model {
+                    nn: Net::new(vec![1, 1]),
+                    nn_outputs: Vec::new(),
+                    id: model_id,
+                },
+            ))
+            .id();
+        let dodged = app.world.spawn(Enemy::default()).id();
+        let hit = app.world.spawn(Enemy::default()).id();
+        app.update();
+
+        let sensor = app
+            .world
+            .query_filtered::<Entity, With<NearMissSensor>>()
+            .single(&app.world);
+        app.world.send_event(CollisionEvent::Started(
+            sensor,
+            dodged,
+            CollisionEventFlags::SENSOR,
+        ));
+        app.world.send_event(CollisionEvent::Stopped(
+            dodged,
+            sensor,
+            CollisionEventFlags::SENSOR,
+        ));
+        app.update();
+        assert_eq!(
+            app.world.resource::<StyleScores>().0[&model_id],
+            configs::NEAR_MISS_POINTS
+        );
+
+        // Crashed: the enemy leaving the sensor is not a near miss
+        app.world.entity_mut(car).remove::<Car>();
+        app.world.send_event(CollisionEvent::Stopped(
+            sensor,
+            hit,
+            CollisionEventFlags::SENSOR,
+        ));
+        app.update();
+        assert_eq!(
+            app.world.resource::<StyleScores>().0[&model_id],
+            configs::NEAR_MISS_POINTS
+        );
+        assert!(app.world.get_entity(sensor).is_none());
+    }
+}
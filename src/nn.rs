@@ -43,6 +43,10 @@ impl Net {
         }
     }
 
+    pub fn n_inputs(&self) -> usize {
+        self.n_inputs
+    }
+
     pub fn predict(&self, inputs: &Vec<f64>) -> Vec<Vec<f64>> {
         if inputs.len() != self.n_inputs {
             panic!("Bad input size");
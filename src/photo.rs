@@ -0,0 +1,156 @@
+use crate::free_camera::{FreeCamera, FreeCameraPlugin};
+use crate::input_map::{Action, ActionState};
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{EguiRenderOutput, EguiSet};
+use bevy_pancam::PanCam;
+
+/// Pauses the race and hides the HUD while the main camera is flown freely to take shots,
+/// toggled with [`Action::PhotoMode`]. The camera is put back where it was on exit.
+pub struct PhotoModePlugin;
+
+impl Plugin for PhotoModePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<InputMode>()
+            .add_plugin(FreeCameraPlugin)
+            .add_system(toggle_photo_mode)
+            .add_system(enter_photo_mode.in_schedule(OnEnter(InputMode::Photo)))
+            .add_system(exit_photo_mode.in_schedule(OnExit(InputMode::Photo)))
+            .add_system(
+                hide_hud
+                    .in_base_set(CoreSet::PostUpdate)
+                    .after(EguiSet::ProcessOutput)
+                    .run_if(in_state(InputMode::Photo)),
+            );
+    }
+}
+
+/// What the keyboard and mouse drive.
+#[derive(States, Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+pub enum InputMode {
+    /// The race, with the camera following the lead car.
+    #[default]
+    Race,
+    /// The [`FreeCamera`], the race being paused.
+    Photo,
+}
+
+/// Main camera view to go back to when leaving the photo mode.
+#[derive(Resource)]
+struct RaceView {
+    camera: Entity,
+    transform: Transform,
+    scale: f32,
+}
+
+fn toggle_photo_mode(
+    actions: Res<ActionState>,
+    mode: Res<State<InputMode>>,
+    mut next_mode: ResMut<NextState<InputMode>>,
+) {
+    if actions.just_pressed(Action::PhotoMode) {
+        next_mode.set(match mode.0 {
+            InputMode::Race => InputMode::Photo,
+            InputMode::Photo => InputMode::Race,
+        });
+    }
+}
+
+fn enter_photo_mode(
+    mut commands: Commands,
+    mut time: ResMut<Time>,
+    mut cameras: Query<(Entity, &Transform, &OrthographicProjection, &mut PanCam)>,
+) {
+    time.pause();
+    let Ok((camera, transform, projection, mut pan_cam)) = cameras.get_single_mut() else {
+        return;
+    };
+    pan_cam.enabled = false;
+    commands.entity(camera).insert(FreeCamera);
+    commands.insert_resource(RaceView {
+        camera,
+        transform: *transform,
+        scale: projection.scale,
+    });
+}
+
+fn exit_photo_mode(
+    mut commands: Commands,
+    mut time: ResMut<Time>,
+    view: Option<Res<RaceView>>,
+    mut cameras: Query<(&mut Transform, &mut OrthographicProjection, &mut PanCam)>,
+) {
+    time.unpause();
+    let Some(view) = view else {
+        return;
+    };
+    if let Ok((mut transform, mut projection, mut pan_cam)) = cameras.get_mut(view.camera) {
+        *transform = view.transform;
+        projection.scale = view.scale;
+        pan_cam.enabled = true;
+    }
+    commands.entity(view.camera).remove::<FreeCamera>();
+    commands.remove_resource::<RaceView>();
+}
+
+/// Drops the egui output, windows keep their state for when the HUD comes back.
+fn hide_hud(mut outputs: Query<&mut EguiRenderOutput>) {
+    for mut output in outputs.iter_mut() {
+        output.paint_jobs.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configs;
+    use crate::input_map::InputMapPlugin;
+    use bevy::input::mouse::MouseWheel;
+    use std::path::PathBuf;
+
+    fn press(app: &mut App, key: KeyCode) {
+        app.world.resource_mut::<Input<KeyCode>>().press(key);
+        app.update();
+        let mut keys = app.world.resource_mut::<Input<KeyCode>>();
+        keys.release(key);
+        keys.clear();
+        // Applies the state transition
+        app.update();
+    }
+
+    #[test]
+    fn test_photo_mode_pauses_and_restores_the_view() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<Input<KeyCode>>()
+            .add_event::<MouseWheel>()
+            .add_plugin(InputMapPlugin {
+                settings: PathBuf::from("missing-settings.json"),
+            })
+            .add_plugin(PhotoModePlugin);
+        let transform = Transform::from_xyz(10.0, 20.0, 0.0);
+        let camera = app
+            .world
+            .spawn((
+                transform,
+                OrthographicProjection::default(),
+                PanCam::default(),
+            ))
+            .id();
+
+        press(&mut app, configs::PHOTO_MODE_KEY);
+        assert!(app.world.resource::<Time>().is_paused());
+        assert!(app.world.get::<FreeCamera>(camera).is_some());
+        assert!(!app.world.get::<PanCam>(camera).unwrap().enabled);
+
+        app.world
+            .get_mut::<Transform>(camera)
+            .unwrap()
+            .translation
+            .x = 500.0;
+        press(&mut app, configs::PHOTO_MODE_KEY);
+        assert!(!app.world.resource::<Time>().is_paused());
+        assert!(app.world.get::<FreeCamera>(camera).is_none());
+        assert_eq!(*app.world.get::<Transform>(camera).unwrap(), transform);
+        assert!(app.world.get::<PanCam>(camera).unwrap().enabled);
+    }
+}
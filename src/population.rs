@@ -1,10 +1,13 @@
-// use bevy::log;
+use bevy::log;
 use bevy::prelude::*;
+use bevy::utils::HashMap;
 // use rand::distributions::WeightedIndex;
 // use rand::prelude::Distribution;
 
 use crate::car::{Car, Fitness, Model};
-// use crate::enemy::{spawn_bound_trucks, BoundControlTruck, Enemy};
+use crate::enemy::{Enemy, EnemySeed};
+use crate::shoulder::ShoulderPenalty;
+// use crate::enemy::{spawn_bound_trucks, BoundControlTruck};
 // use crate::nn::Net;
 use crate::*;
 
@@ -13,32 +16,139 @@ pub struct PopulationPlugin;
 impl Plugin for PopulationPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(MaxDistanceTravelled(0.0))
+            .init_resource::<SimStats>()
+            .init_resource::<EnemySeed>()
+            .add_event::<GenerationEnded>()
+            .add_event::<ResetEnemies>()
+            .add_event::<RespawnPopulation>()
             // .add_startup_system(setup)
-            .add_system(population_stats_system);
-        // .add_systems((population_stats_system, generation_reset_system));
+            .add_systems(
+                (
+                    population_stats_system,
+                    detect_generation_end,
+                    advance_generation,
+                    reset_enemies,
+                )
+                    .chain()
+                    .in_set(GenerationSet),
+            );
     }
 }
 
+/// Generation bookkeeping, systems reacting to [`RespawnPopulation`] run after it.
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
+pub struct GenerationSet;
+
+/// Every racer of the current generation crashed.
+pub struct GenerationEnded {
+    pub generation: u32,
+    pub best_fitness: f32,
+    pub mean_fitness: f32,
+    /// Last fitness of every car of the generation.
+    pub fitnesses: Vec<f32>,
+}
+
+/// Despawns the enemies of every racer.
+pub struct ResetEnemies;
+
+/// The next generation starts: whoever owns the racers spawns them again.
+pub struct RespawnPopulation {
+    pub generation: u32,
+}
+
 fn population_stats_system(
-    // mut sim_stats: ResMut<SimStats>,
+    mut sim_stats: ResMut<SimStats>,
     mut max_distance_travelled: ResMut<MaxDistanceTravelled>,
     // mut brain_on_display: ResMut<BrainToDisplay>,
-    mut query: Query<(&Transform, &Model, &mut Fitness), With<Car>>,
+    mut query: Query<(&Transform, &Model, &mut Fitness, Option<&ShoulderPenalty>), With<Car>>,
 ) {
     let mut max_fitness = 0.0;
-    // sim_stats.num_cars_alive = query.iter().len();
+    sim_stats.num_cars_alive = query.iter().len();
 
-    for (transform, _brain, mut fitness) in query.iter_mut() {
-        fitness.0 = calc_fitness(transform);
+    for (transform, _brain, mut fitness, penalty) in query.iter_mut() {
+        let penalty = penalty.map_or(0.0, |penalty| penalty.0);
+        fitness.0 = (calc_fitness(transform) - penalty).max(0.0);
         if fitness.0 > max_fitness {
             max_fitness = fitness.0;
             // brain_on_display.0 = brain.nn_outputs.clone();
-            // sim_stats.max_current_score = fitness.0;
+            sim_stats.max_current_score = sim_stats.max_current_score.max(fitness.0);
             max_distance_travelled.0 = transform.translation.y;
         }
     }
 }
 
+/// Sends [`GenerationEnded`] once when the last car of the generation crashes.
+fn detect_generation_end(
+    sim_stats: Res<SimStats>,
+    cars: Query<(Entity, &Fitness), With<Car>>,
+    mut racing: Local<bool>,
+    // Last fitness of every car of the generation, crashed cars lose theirs
+    mut fitnesses: Local<HashMap<Entity, f32>>,
+    mut generation_ended: EventWriter<GenerationEnded>,
+) {
+    for (entity, fitness) in cars.iter() {
+        fitnesses.insert(entity, fitness.0);
+    }
+    if !cars.is_empty() {
+        *racing = true;
+    } else if *racing {
+        *racing = false;
+        let fitnesses: Vec<f32> = fitnesses.drain().map(|(_, fitness)| fitness).collect();
+        let mean_fitness = if fitnesses.is_empty() {
+            0.0
+        } else {
+            fitnesses.iter().sum::<f32>() / fitnesses.len() as f32
+        };
+        generation_ended.send(GenerationEnded {
+            generation: sim_stats.generation_count,
+            best_fitness: sim_stats.max_current_score,
+            mean_fitness,
+            fitnesses,
+        });
+    }
+}
+
+fn advance_generation(
+    mut sim_stats: ResMut<SimStats>,
+    mut max_distance_travelled: ResMut<MaxDistanceTravelled>,
+    mut enemy_seed: ResMut<EnemySeed>,
+    mut generation_ended: EventReader<GenerationEnded>,
+    mut reset_enemies: EventWriter<ResetEnemies>,
+    mut respawn_population: EventWriter<RespawnPopulation>,
+) {
+    for ended in generation_ended.iter() {
+        log::info!(
+            generation = ended.generation,
+            best_fitness = ended.best_fitness,
+            mean_fitness = ended.mean_fitness,
+            "Generation ended"
+        );
+        sim_stats.fitness.push(ended.best_fitness);
+        sim_stats.mean_fitness.push(ended.mean_fitness);
+        sim_stats.generation_count = ended.generation + 1;
+        // Every racer of the next generation faces the same traffic
+        *enemy_seed = EnemySeed::for_generation(sim_stats.generation_count);
+        sim_stats.max_current_score = 0.0;
+        max_distance_travelled.0 = 0.0;
+
+        reset_enemies.send(ResetEnemies);
+        respawn_population.send(RespawnPopulation {
+            generation: sim_stats.generation_count,
+        });
+    }
+}
+
+fn reset_enemies(
+    mut commands: Commands,
+    mut events: EventReader<ResetEnemies>,
+    enemies: Query<Entity, With<Enemy>>,
+) {
+    if events.iter().count() == 0 {
+        return;
+    }
+    enemies.for_each(|enemy| commands.entity(enemy).despawn_recursive());
+}
+
 // fn generation_reset_system(
 //     mut commands: Commands,
 //     asset_server: Res<AssetServer>,
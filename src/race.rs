@@ -0,0 +1,304 @@
+use crate::car::{Car, Model};
+use crate::configs;
+use crate::conversions::felt_to_short_string;
+use crate::display::framed_projection;
+use crate::locale::Locale;
+use crate::population::GenerationEnded;
+use crate::resources::{Racers, RunStats};
+use bevy::prelude::*;
+use bevy::render::camera::Viewport;
+use bevy::window::PrimaryWindow;
+use bevy_inspector_egui::{bevy_egui::EguiContexts, egui};
+use bevy_pancam::PanCam;
+use starknet_ff::FieldElement;
+
+/// Races two models against each other: they start on mirrored lanes of the same road, enemies
+/// included, and a split screen shows who went the furthest.
+pub struct HeadToHeadPlugin {
+    pub race: HeadToHead,
+}
+
+impl Plugin for HeadToHeadPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Racers(self.race.models.to_vec()))
+            .insert_resource(self.race.clone())
+            .init_resource::<RaceResults>()
+            .init_resource::<Locale>()
+            .add_event::<RaceFinished>()
+            .add_systems((track_distances, finish_race, results_system).chain());
+    }
+}
+
+/// Splits the window between the two racers of the [`HeadToHead`], each half with its own
+/// camera following its car and its own HUD.
+pub struct SplitScreenPlugin;
+
+impl Plugin for SplitScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Locale>()
+            .add_startup_system(spawn_split_cameras)
+            .add_systems((fit_split_viewports, follow_split_racers, split_hud_system));
+    }
+}
+
+/// Models racing head-to-head, read when spawning the racers on the world.
+#[derive(Resource, Clone, Debug)]
+pub struct HeadToHead {
+    pub models: [FieldElement; 2],
+    /// Enemy seed shared by both racers.
+    pub seed: FieldElement,
+}
+
+impl HeadToHead {
+    pub fn new(models: [FieldElement; 2]) -> Self {
+        Self {
+            models,
+            seed: models[0],
+        }
+    }
+
+    /// Start `x` of the racer at `index`, the second one mirrors the first around the middle
+    /// of the grid.
+    pub fn start_x(&self, index: usize, x: FieldElement) -> FieldElement {
+        if index == 0 {
+            x
+        } else {
+            FieldElement::from((configs::DOJO_GRID_WIDTH as u128) << 64) - x
+        }
+    }
+}
+
+/// Both racers crashed, `winner` is `None` on a draw.
+pub struct RaceFinished {
+    pub models: [FieldElement; 2],
+    pub winner: Option<FieldElement>,
+}
+
+/// Furthest `y` reached by each model in the current race, and the results of the last one.
+#[derive(Resource, Default, Debug)]
+pub struct RaceResults {
+    pub distances: Vec<(FieldElement, f32)>,
+    pub last: Option<Vec<(FieldElement, f32)>>,
+}
+
+impl RaceResults {
+    /// Model that went the furthest, `None` on a draw.
+    pub fn winner(results: &[(FieldElement, f32)]) -> Option<FieldElement> {
+        let mut sorted = results.to_vec();
+        sorted.sort_by(|a, b| b.1.total_cmp(&a.1));
+        match sorted.as_slice() {
+            [first, second, ..] if first.1 == second.1 => None,
+            [first, ..] => Some(first.0),
+            [] => None,
+        }
+    }
+}
+
+fn track_distances(
+    race: Res<HeadToHead>,
+    mut results: ResMut<RaceResults>,
+    cars: Query<(&Transform, &Model), With<Car>>,
+) {
+    if results.distances.is_empty() {
+        results.distances = race.models.iter().map(|id| (*id, 0.0)).collect();
+    }
+
+    for (transform, model) in cars.iter() {
+        if let Some((_, distance)) = results.distances.iter_mut().find(|(id, _)| *id == model.id) {
+            *distance = distance.max(transform.translation.y);
+        }
+    }
+}
+
+fn finish_race(
+    race: Res<HeadToHead>,
+    mut results: ResMut<RaceResults>,
+    mut ended: EventReader<GenerationEnded>,
+    mut finished: EventWriter<RaceFinished>,
+) {
+    if ended.iter().count() == 0 {
+        return;
+    }
+    let last = std::mem::take(&mut results.distances);
+    finished.send(RaceFinished {
+        models: race.models,
+        winner: RaceResults::winner(&last),
+    });
+    results.last = Some(last);
+}
+
+fn results_system(mut contexts: EguiContexts, locale: Res<Locale>, results: Res<RaceResults>) {
+    let (title, standings) = match &results.last {
+        Some(last) => ("results.title", last),
+        None => ("results.racing", &results.distances),
+    };
+    let winner = results.last.as_deref().map(RaceResults::winner);
+
+    egui::Window::new(locale.text("results.head_to_head"))
+        .default_pos(egui::pos2(configs::WINDOW_WIDTH / 2.0 - 150.0, 50.0))
+        .show(contexts.ctx_mut(), |ui| {
+            ui.heading(locale.text(title));
+            ui.columns(standings.len().max(1), |columns| {
+                for ((id, distance), ui) in standings.iter().zip(columns.iter_mut()) {
+                    ui.label(felt_to_short_string(*id));
+                    ui.label(format!("{distance:.0}"));
+                    if winner == Some(Some(*id)) {
+                        ui.strong(locale.text("results.winner"));
+                    }
+                }
+            });
+            if winner == Some(None) {
+                ui.label(locale.text("results.draw"));
+            }
+        });
+}
+
+/// Camera of the left (`0`) or right (`1`) half of the window.
+#[derive(Component)]
+pub struct SplitCamera(pub usize);
+
+fn spawn_split_cameras(mut commands: Commands) {
+    for index in 0..2 {
+        commands.spawn((
+            Camera2dBundle {
+                camera: Camera {
+                    order: 1 + index as isize,
+                    ..default()
+                },
+                transform: Transform::from_xyz(
+                    (configs::ROAD_X_MIN + configs::ROAD_X_MAX) / 2.0,
+                    configs::WINDOW_HEIGHT / 2.0,
+                    0.0,
+                ),
+                // Each half shows the whole road whatever the window size
+                projection: framed_projection(configs::ROAD_W),
+                ..default()
+            },
+            SplitCamera(index),
+        ));
+    }
+}
+
+/// Half of a window of `size` physical pixels shown by the [`SplitCamera`] at `index`.
+fn split_viewport(index: usize, size: UVec2) -> Viewport {
+    let half = size.x / 2;
+    Viewport {
+        physical_position: UVec2::new(half * index as u32, 0),
+        physical_size: UVec2::new(half, size.y),
+        ..default()
+    }
+}
+
+/// Sizes the halves to the window, in place of the single camera following the race.
+fn fit_split_viewports(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut main_cameras: Query<&mut Camera, (With<PanCam>, Without<SplitCamera>)>,
+    mut cameras: Query<(&mut Camera, &SplitCamera)>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let size = UVec2::new(window.physical_width(), window.physical_height());
+
+    for mut camera in main_cameras.iter_mut() {
+        if camera.is_active {
+            camera.is_active = false;
+        }
+    }
+    for (mut camera, split) in cameras.iter_mut() {
+        let viewport = split_viewport(split.0, size);
+        let fitted = camera.viewport.as_ref().map_or(false, |current| {
+            current.physical_position == viewport.physical_position
+                && current.physical_size == viewport.physical_size
+        });
+        if !fitted {
+            camera.viewport = Some(viewport);
+        }
+    }
+}
+
+fn follow_split_racers(
+    race: Res<HeadToHead>,
+    cars: Query<(&Transform, &Model), With<Car>>,
+    mut cameras: Query<(&mut Transform, &SplitCamera), Without<Car>>,
+) {
+    for (mut transform, split) in cameras.iter_mut() {
+        let Some((car, _)) = cars
+            .iter()
+            .find(|(_, model)| model.id == race.models[split.0])
+        else {
+            continue;
+        };
+        transform.translation.y += (car.translation.y - transform.translation.y) * 0.05;
+    }
+}
+
+fn split_hud_system(
+    mut contexts: EguiContexts,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    race: Res<HeadToHead>,
+    results: Res<RaceResults>,
+    run_stats: Option<Res<RunStats>>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+
+    for (index, model_id) in race.models.iter().enumerate() {
+        let distance = results
+            .distances
+            .iter()
+            .find(|(id, _)| id == model_id)
+            .map_or(0.0, |(_, distance)| *distance);
+        let speed = run_stats
+            .as_ref()
+            .and_then(|stats| stats.0.get(model_id))
+            .map_or(0.0, |stats| stats.speed);
+
+        egui::Area::new(format!("split_hud_{index}"))
+            .fixed_pos(egui::pos2(index as f32 * window.width() / 2.0 + 10.0, 10.0))
+            .show(contexts.ctx_mut(), |ui| {
+                ui.heading(felt_to_short_string(*model_id));
+                let distance = format!("{distance:.0}");
+                ui.label(locale.format("hud.travelled", &[("distance", &distance)]));
+                let speed = format!("{speed:.1}");
+                ui.label(locale.format("hud.speed", &[("speed", &speed)]));
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_winner_went_the_furthest() {
+        let a = FieldElement::ONE;
+        let b = FieldElement::TWO;
+
+        assert_eq!(RaceResults::winner(&[(a, 120.0), (b, 340.0)]), Some(b));
+        assert_eq!(RaceResults::winner(&[(a, 340.0), (b, 120.0)]), Some(a));
+        assert_eq!(RaceResults::winner(&[(a, 340.0), (b, 340.0)]), None);
+    }
+
+    #[test]
+    fn test_split_viewports_cover_the_window() {
+        let size = UVec2::new(1920, 1000);
+        let left = split_viewport(0, size);
+        let right = split_viewport(1, size);
+
+        assert_eq!(left.physical_position, UVec2::ZERO);
+        assert_eq!(right.physical_position, UVec2::new(960, 0));
+        assert_eq!(left.physical_size, UVec2::new(960, 1000));
+        assert_eq!(right.physical_size, left.physical_size);
+    }
+
+    #[test]
+    fn test_start_x_mirrors_lanes() {
+        let race = HeadToHead::new([FieldElement::ONE, FieldElement::TWO]);
+        let x = FieldElement::from(50_u128 << 64);
+
+        assert_eq!(race.start_x(0, x), x);
+        assert_eq!(race.start_x(1, x), FieldElement::from(350_u128 << 64));
+    }
+}
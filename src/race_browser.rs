@@ -0,0 +1,389 @@
+use crate::configs;
+use crate::conversions::{felt_to_short_string, short_string_to_felt};
+use crate::input_map::{Action, ActionState};
+use crate::locale::Locale;
+use crate::replay::{play_events, Replay, ReplayEvent, ReplayFrame};
+use crate::tasks::tokio_tasks_plugin;
+use crate::world::{Position, Racer, Vehicle, WorldComponent};
+use bevy::prelude::*;
+use bevy_inspector_egui::{bevy_egui::EguiContexts, egui};
+use bevy_tokio_tasks::{TokioTasksPlugin, TokioTasksRuntime};
+use eyre::{eyre, Result};
+use serde::Deserialize;
+use starknet::core::types::FieldElement;
+use starknet::core::utils::get_selector_from_name;
+use tokio::sync::mpsc;
+use tracing::error;
+
+/// Lists the past races of a model found in the Torii indexer, toggled with
+/// [`Action::RaceBrowser`], and plays the chosen one back as a replay.
+pub struct RaceBrowserPlugin {
+    /// Searched at startup.
+    pub model: Option<String>,
+}
+
+impl Plugin for RaceBrowserPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<TokioTasksPlugin>() {
+            app.add_plugin(tokio_tasks_plugin());
+        }
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        app.insert_resource(RaceBrowser {
+            visible: true,
+            model: self
+                .model
+                .clone()
+                .unwrap_or_else(|| configs::MODEL_NAME.to_string()),
+            races: Vec::new(),
+            status: None,
+            sender,
+            receiver,
+        })
+        .init_resource::<Locale>()
+        .init_resource::<Replay>()
+        .add_system(play_events)
+        .add_system(race_browser_system);
+        if self.model.is_some() {
+            app.add_startup_system(search_races);
+        }
+    }
+}
+
+/// A run of a model rebuilt from the indexed store events, from its `spawn` on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexedRace {
+    pub model_id: FieldElement,
+    /// When the indexer saw the spawn.
+    pub started_at: String,
+    pub frames: Vec<ReplayFrame>,
+}
+
+impl IndexedRace {
+    fn duration(&self) -> f32 {
+        self.frames.last().map_or(0.0, |frame| frame.time)
+    }
+}
+
+/// A `StoreSetRecord` event of the world, as indexed by Torii.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedEvent {
+    pub data: Vec<FieldElement>,
+    pub created_at: String,
+}
+
+/// Splits the `[table, keys_len, keys.., offset, values_len, values..]` data of a
+/// `StoreSetRecord` event into the component name, entity keys and values.
+fn parse_record(data: &[FieldElement]) -> Option<(FieldElement, &[FieldElement], &[FieldElement])> {
+    let (table, rest) = data.split_first()?;
+    let (keys_len, rest) = rest.split_first()?;
+    let keys_len = usize::try_from(u64::try_from(*keys_len).ok()?).ok()?;
+    let keys = rest.get(..keys_len)?;
+    // Skip the offset
+    let (values_len, values) = rest.get(keys_len + 1..)?.split_first()?;
+    let values_len = usize::try_from(u64::try_from(*values_len).ok()?).ok()?;
+    Some((*table, keys, values.get(..values_len)?))
+}
+
+/// Races of `model_id` in `events`, in indexing order. A `Racer` record starts a race, the
+/// `Vehicle` and `Position` records that follow make its frames. Indexed events are only
+/// timestamped to the second, so frames are spaced by [`configs::DOJO_DRIVE_INTERVAL`] per
+/// `Vehicle` record instead.
+pub fn races_from_events(model_id: FieldElement, events: &[IndexedEvent]) -> Vec<IndexedRace> {
+    let mut races: Vec<IndexedRace> = Vec::new();
+    let mut drives = 0;
+
+    for indexed in events {
+        let Some((table, keys, values)) = parse_record(&indexed.data) else {
+            continue;
+        };
+        if keys.first() != Some(&model_id) {
+            continue;
+        }
+
+        let table = felt_to_short_string(table);
+        if table == Racer::NAME {
+            races.push(IndexedRace {
+                model_id,
+                started_at: indexed.created_at.clone(),
+                frames: vec![ReplayFrame {
+                    time: 0.0,
+                    event: ReplayEvent::Spawn { model_id },
+                }],
+            });
+            drives = 0;
+            continue;
+        }
+        // Records of a race started before the first indexed event
+        let Some(race) = races.last_mut() else {
+            continue;
+        };
+
+        let event = match table.as_str() {
+            Vehicle::NAME => {
+                drives += 1;
+                ReplayEvent::Vehicle {
+                    model_id,
+                    vehicle: values.to_vec(),
+                }
+            }
+            Position::NAME if keys.len() == 2 => ReplayEvent::Enemy {
+                model_id,
+                enemy_id: keys[1],
+                position: values.to_vec(),
+            },
+            _ => continue,
+        };
+        race.frames.push(ReplayFrame {
+            time: drives as f32 * configs::DOJO_DRIVE_INTERVAL,
+            event,
+        });
+    }
+
+    races
+}
+
+const EVENTS_QUERY: &str = "query Events($keys: [String], $limit: Int) {
+    events(keys: $keys, limit: $limit) { edges { node { data createdAt } } }
+}";
+
+#[derive(Deserialize)]
+struct EventsResponse {
+    data: Option<EventsData>,
+    errors: Option<Vec<serde_json::Value>>,
+}
+
+#[derive(Deserialize)]
+struct EventsData {
+    events: EventsConnection,
+}
+
+#[derive(Deserialize)]
+struct EventsConnection {
+    edges: Vec<EventEdge>,
+}
+
+#[derive(Deserialize)]
+struct EventEdge {
+    node: IndexedEvent,
+}
+
+/// Races of `model_id` indexed by [`configs::TORII_GRAPHQL_ENDPOINT`], the latest first.
+pub async fn fetch_races(model_id: FieldElement) -> Result<Vec<IndexedRace>> {
+    let selector = get_selector_from_name("StoreSetRecord")?;
+    let request = serde_json::json!({
+        "query": EVENTS_QUERY,
+        "variables": {
+            "keys": [format!("{selector:#x}")],
+            "limit": configs::TORII_EVENTS_LIMIT,
+        },
+    });
+
+    let response: EventsResponse = reqwest::Client::new()
+        .post(configs::TORII_GRAPHQL_ENDPOINT)
+        .json(&request)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let Some(data) = response.data else {
+        return Err(eyre!("Torii events query failed: {:?}", response.errors));
+    };
+
+    let events: Vec<_> = data
+        .events
+        .edges
+        .into_iter()
+        .map(|edge| edge.node)
+        .collect();
+    let mut races = races_from_events(model_id, &events);
+    races.reverse();
+    Ok(races)
+}
+
+enum SearchStatus {
+    Searching,
+    Failed(String),
+}
+
+#[derive(Resource)]
+struct RaceBrowser {
+    visible: bool,
+    /// Model id being edited.
+    model: String,
+    races: Vec<IndexedRace>,
+    /// Search in progress or failed.
+    status: Option<SearchStatus>,
+    sender: mpsc::UnboundedSender<Result<Vec<IndexedRace>, String>>,
+    receiver: mpsc::UnboundedReceiver<Result<Vec<IndexedRace>, String>>,
+}
+
+impl RaceBrowser {
+    fn search(&mut self, runtime: &TokioTasksRuntime) {
+        let model_id = short_string_to_felt(&self.model);
+        let sender = self.sender.clone();
+        self.status = Some(SearchStatus::Searching);
+
+        runtime.spawn_background_task(move |_ctx| async move {
+            let races = fetch_races(model_id).await.map_err(|e| {
+                error!(error = %e, model = %felt_to_short_string(model_id), "Fetch indexed races");
+                e.to_string()
+            });
+            let _ = sender.send(races);
+        });
+    }
+}
+
+fn search_races(mut browser: ResMut<RaceBrowser>, runtime: Res<TokioTasksRuntime>) {
+    browser.search(&runtime);
+}
+
+fn race_browser_system(
+    mut contexts: EguiContexts,
+    actions: Res<ActionState>,
+    time: Res<Time>,
+    runtime: Res<TokioTasksRuntime>,
+    locale: Res<Locale>,
+    mut browser: ResMut<RaceBrowser>,
+    mut replay: ResMut<Replay>,
+) {
+    while let Ok(result) = browser.receiver.try_recv() {
+        match result {
+            Ok(races) => {
+                browser.races = races;
+                browser.status = None;
+            }
+            Err(e) => browser.status = Some(SearchStatus::Failed(e)),
+        }
+    }
+    if actions.just_pressed(Action::RaceBrowser) {
+        browser.visible = !browser.visible;
+    }
+    if !browser.visible {
+        return;
+    }
+
+    let mut search = false;
+    let mut load = None;
+    egui::Window::new(locale.text("races.title"))
+        .default_pos(egui::pos2(20.0, 300.0))
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label(locale.text("races.model"));
+                ui.text_edit_singleline(&mut browser.model);
+                search = ui.button(locale.text("races.search")).clicked();
+            });
+            match &browser.status {
+                Some(SearchStatus::Searching) => {
+                    ui.label(locale.text("races.searching"));
+                }
+                Some(SearchStatus::Failed(e)) => {
+                    ui.label(locale.format("races.search_failed", &[("error", e)]));
+                }
+                None => {}
+            }
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::Grid::new("races").striped(true).show(ui, |ui| {
+                    for header in ["races.started", "races.duration", "races.frames"] {
+                        ui.strong(locale.text(header));
+                    }
+                    ui.strong("");
+                    ui.end_row();
+
+                    for (i, race) in browser.races.iter().enumerate() {
+                        ui.label(&race.started_at);
+                        ui.label(locale.format(
+                            "hud.race_time",
+                            &[("time", &format!("{:.1}", race.duration()))],
+                        ));
+                        ui.label(race.frames.len().to_string());
+                        if ui.button(locale.text("races.load")).clicked() {
+                            load = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+
+    if search {
+        browser.search(&runtime);
+    }
+    if let Some(i) = load {
+        replay.load(browser.races[i].frames.clone(), time.elapsed_seconds());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(table: &str, keys: &[FieldElement], values: &[FieldElement]) -> IndexedEvent {
+        let mut data = vec![short_string_to_felt(table), keys.len().into()];
+        data.extend_from_slice(keys);
+        data.push(FieldElement::ZERO);
+        data.push(values.len().into());
+        data.extend_from_slice(values);
+        IndexedEvent {
+            data,
+            created_at: "2023-07-20 10:00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_races_are_split_on_spawn() {
+        let model_id = short_string_to_felt("model");
+        let other = short_string_to_felt("other");
+        let events = [
+            // Race started before the indexed events
+            record("Vehicle", &[model_id], &[FieldElement::ONE]),
+            record("Racer", &[model_id], &[FieldElement::ZERO, model_id]),
+            record("Vehicle", &[model_id], &[FieldElement::TWO]),
+            record("Vehicle", &[other], &[FieldElement::TWO]),
+            record(
+                "Position",
+                &[model_id, FieldElement::ONE],
+                &[FieldElement::THREE],
+            ),
+            record("Racer", &[model_id], &[FieldElement::ZERO, model_id]),
+        ];
+
+        let races = races_from_events(model_id, &events);
+        assert_eq!(races.len(), 2);
+        assert_eq!(
+            races[0].frames,
+            vec![
+                ReplayFrame {
+                    time: 0.0,
+                    event: ReplayEvent::Spawn { model_id },
+                },
+                ReplayFrame {
+                    time: configs::DOJO_DRIVE_INTERVAL,
+                    event: ReplayEvent::Vehicle {
+                        model_id,
+                        vehicle: vec![FieldElement::TWO],
+                    },
+                },
+                ReplayFrame {
+                    time: configs::DOJO_DRIVE_INTERVAL,
+                    event: ReplayEvent::Enemy {
+                        model_id,
+                        enemy_id: FieldElement::ONE,
+                        position: vec![FieldElement::THREE],
+                    },
+                },
+            ]
+        );
+        assert_eq!(races[1].frames.len(), 1);
+    }
+
+    #[test]
+    fn test_truncated_record_is_skipped() {
+        let mut event = record("Vehicle", &[FieldElement::ONE], &[FieldElement::TWO]);
+        event.data.pop();
+        assert!(parse_record(&event.data).is_none());
+    }
+}
@@ -18,8 +18,12 @@ use orion::operators::tensor::implementations::impl_tensor_fp::Tensor_fp;
 struct Racer {
     // Vehicle owner
     driver: ContractAddress,
-    // Model system name
+    // Model system driving the racer
     model: felt252,
+    // Enemies spawned on the road of the racer, at most `ENEMIES_NB`
+    enemies: u8,
+    // Block timestamp of the spawn, in seconds
+    spawned_at: u64,
 }
 
 #[derive(Serde, Drop)]
@@ -352,17 +356,22 @@ fn collision_check(vehicle: Vehicle, mut enemies: Array<Position>) {
 mod spawn_racer {
     use array::ArrayTrait;
     use traits::Into;
+    use starknet::get_block_timestamp;
     use cubit::types::FixedTrait;
     use cubit::types::{Vec2Trait, Vec2};
 
     use dojo::world::Context;
     use drive_ai::Vehicle;
+    use drive_ai::enemy::ENEMIES_NB;
 
     use super::{Racer, HALF_GRID_WIDTH};
 
     const FIFTY: u128 = 922337203685477580800;
 
-    fn execute(ctx: Context, model: felt252, position: Vec2) {
+    fn execute(
+        ctx: Context, model: felt252, network: felt252, position: Vec2, seed: felt252, enemies: u8
+    ) {
+        assert(enemies <= ENEMIES_NB, 'too many enemies');
         // let position = Vec2Trait::new(
         //     FixedTrait::new(HALF_GRID_WIDTH, false), FixedTrait::new(0, false)
         // );
@@ -371,7 +380,10 @@ mod spawn_racer {
             model.into(),
             (
                 Racer {
-                    driver: ctx.origin, model
+                    driver: ctx.origin,
+                    model: network,
+                    enemies,
+                    spawned_at: get_block_timestamp()
                     }, Vehicle {
                     position,
                     steer: FixedTrait::new(0_u128, false),
@@ -382,6 +394,8 @@ mod spawn_racer {
 
         let mut calldata = ArrayTrait::new();
         calldata.append(model);
+        calldata.append(seed);
+        calldata.append(enemies.into());
         ctx.world.execute('spawn_enemies', calldata.span());
 
         return ();
@@ -395,16 +409,17 @@ mod drive {
     use serde::Serde;
     use dojo::world::Context;
     use drive_ai::vehicle::{Controls, Vehicle, VehicleTrait};
-    use drive_ai::enemy::{Position, ENEMIES_NB};
+    use drive_ai::enemy::Position;
     use super::{Racer, Sensors, compute_sensors};
 
     fn execute(ctx: Context, model: felt252) {
         let mut vehicle = get !(ctx.world, model.into(), Vehicle);
+        let racer = get !(ctx.world, model.into(), Racer);
 
         let mut enemies = ArrayTrait::<Position>::new();
         let mut i: u8 = 0;
         loop {
-            if i == ENEMIES_NB {
+            if i == racer.enemies {
                 break ();
             }
             let key = (model, i).into();
@@ -415,10 +430,10 @@ mod drive {
 
         // 1. Compute sensors, reverts if there is a collision (game over)
         let sensors = compute_sensors(vehicle, enemies);
-        // 2. Run model forward pass
+        // 2. Run the forward pass of the racer's model
         let mut sensor_calldata = ArrayTrait::new();
         sensors.serialize(ref sensor_calldata);
-        let mut controls = ctx.world.execute('model', sensor_calldata.span());
+        let mut controls = ctx.world.execute(racer.model, sensor_calldata.span());
         let controls = serde::Serde::<Controls>::deserialize(ref controls).unwrap();
         // 3. Update car position
         vehicle.control(controls);
@@ -434,6 +449,51 @@ mod drive {
         // only read them once (pass them in here?)
         let mut calldata = ArrayTrait::new();
         calldata.append(model);
+        calldata.append(racer.enemies.into());
+        ctx.world.execute('move_enemies', calldata.span());
+    }
+}
+
+#[system]
+mod drive_manual {
+    use array::ArrayTrait;
+    use traits::Into;
+    use dojo::world::Context;
+    use drive_ai::vehicle::{Controls, Vehicle, VehicleTrait};
+    use drive_ai::enemy::Position;
+    use super::{Racer, Sensors, compute_sensors};
+
+    // Same as `drive` but the controls come from the caller instead of the model system.
+    fn execute(ctx: Context, model: felt252, controls: Controls) {
+        let mut vehicle = get !(ctx.world, model.into(), Vehicle);
+        let racer = get !(ctx.world, model.into(), Racer);
+
+        let mut enemies = ArrayTrait::<Position>::new();
+        let mut i: u8 = 0;
+        loop {
+            if i == racer.enemies {
+                break ();
+            }
+            let key = (model, i).into();
+            let position = get !(ctx.world, key, Position);
+            enemies.append(position);
+            i += 1;
+        }
+
+        // Reverts if there is a collision (game over)
+        compute_sensors(vehicle, enemies);
+
+        vehicle.control(controls);
+        vehicle.drive();
+        set !(
+            ctx.world,
+            model.into(),
+            (Vehicle { position: vehicle.position, steer: vehicle.steer, speed: vehicle.speed })
+        );
+
+        let mut calldata = ArrayTrait::new();
+        calldata.append(model);
+        calldata.append(racer.enemies.into());
         ctx.world.execute('move_enemies', calldata.span());
     }
 }
@@ -525,3 +585,32 @@ mod tests {
     #[available_gas(20000000)]
     fn test_collision_check() {}
 }
+
+#[system]
+mod report_collision {
+    use traits::Into;
+    use cubit::types::fixed::FixedTrait;
+    use dojo::world::Context;
+    use drive_ai::Vehicle;
+    use super::Racer;
+
+    /// Stops a vehicle the client saw crash, so the world agrees the run is over. Only the driver
+    /// of the racer can end its run.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - Context of the game.
+    /// * `model` - The AI model id of the crashed racer.
+    fn execute(ctx: Context, model: felt252) {
+        let racer = get !(ctx.world, model.into(), Racer);
+        assert(ctx.origin == racer.driver, 'not the driver');
+        let vehicle = get !(ctx.world, model.into(), Vehicle);
+        set !(
+            ctx.world,
+            model.into(),
+            (Vehicle {
+                position: vehicle.position, steer: vehicle.steer, speed: FixedTrait::new(0, false)
+            })
+        );
+    }
+}
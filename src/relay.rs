@@ -0,0 +1,404 @@
+use crate::configs;
+use crate::replay::{ReplayEvent, SyncEventReaders, SyncEventWriters};
+use crate::tasks::tokio_tasks_plugin;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_tokio_tasks::{TokioTasksPlugin, TokioTasksRuntime};
+use eyre::{eyre, Result};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use starknet::core::crypto::Signature;
+use starknet::core::types::FieldElement;
+use starknet::core::utils::starknet_keccak;
+use starknet::signers::{SigningKey, VerifyingKey};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, mpsc, watch};
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{error, info, info_span, warn, Instrument};
+
+/// WebSocket server on [`configs::RELAY_ENDPOINT`] streaming the synced race to viewers, so
+/// they don't each query the RPC node. Viewers prove their identity by signing a challenge and
+/// every event is signed with the key of the install, see [`configs::RELAY_KEY_PATH`].
+pub struct RelayPlugin;
+
+impl Plugin for RelayPlugin {
+    fn build(&self, app: &mut App) {
+        let signing_key = match install_key(Path::new(configs::RELAY_KEY_PATH)) {
+            Ok(key) => key,
+            Err(e) => {
+                error!(error = %e, path = configs::RELAY_KEY_PATH, "Load relay key");
+                return;
+            }
+        };
+        info!(
+            host = %format!("{:#x}", signing_key.verifying_key().scalar()),
+            "Relay host key, viewers pass it to `watch --host`"
+        );
+        if !app.is_plugin_added::<TokioTasksPlugin>() {
+            app.add_plugin(tokio_tasks_plugin());
+        }
+
+        let (live, _) = broadcast::channel(configs::RELAY_FRAMES_CAPACITY);
+        let (latest, _) = watch::channel(Vec::new());
+
+        app.insert_resource(RelayFrames {
+            signing_key,
+            live,
+            latest,
+            state: HashMap::default(),
+        })
+        .add_startup_system(serve_relay)
+        .add_system(broadcast_sync_events);
+    }
+}
+
+/// Follows the race streamed by a [`RelayPlugin`] host at `url` instead of syncing with the
+/// world, refusing hosts whose key isn't `host` and dropping the events it didn't sign.
+pub struct RelayViewerPlugin {
+    pub url: String,
+    /// Public key of the host.
+    pub host: FieldElement,
+}
+
+impl Plugin for RelayViewerPlugin {
+    fn build(&self, app: &mut App) {
+        let signing_key = match install_key(Path::new(configs::RELAY_KEY_PATH)) {
+            Ok(key) => key,
+            Err(e) => {
+                error!(error = %e, path = configs::RELAY_KEY_PATH, "Load relay key");
+                return;
+            }
+        };
+        info!(
+            viewer = %format!("{:#x}", signing_key.verifying_key().scalar()),
+            "Relay viewer key, the host lets it in from `RELAY_VIEWERS`"
+        );
+        if !app.is_plugin_added::<TokioTasksPlugin>() {
+            app.add_plugin(tokio_tasks_plugin());
+        }
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        app.insert_resource(RelayedEvents {
+            url: self.url.clone(),
+            host: self.host,
+            signing_key,
+            sender,
+            receiver,
+        })
+        .add_startup_system(watch_relay)
+        .add_system(apply_relayed_events);
+    }
+}
+
+/// First message of the host: viewers sign `challenge` with their key.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Challenge {
+    pub challenge: FieldElement,
+    /// Key the frames are signed with.
+    pub host: FieldElement,
+}
+
+/// Answer of a viewer to the [`Challenge`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Identity {
+    pub public_key: FieldElement,
+    pub r: FieldElement,
+    pub s: FieldElement,
+}
+
+/// A [`ReplayEvent`] as JSON, signed by the host over its keccak hash.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SignedFrame {
+    pub event: String,
+    pub r: FieldElement,
+    pub s: FieldElement,
+}
+
+impl SignedFrame {
+    fn sign(event: &ReplayEvent, signing_key: &SigningKey) -> Result<Self> {
+        let event = serde_json::to_string(event)?;
+        let signature = signing_key
+            .sign(&starknet_keccak(event.as_bytes()))
+            .map_err(|e| eyre!("Sign relay frame: {e}"))?;
+        Ok(Self {
+            event,
+            r: signature.r,
+            s: signature.s,
+        })
+    }
+
+    /// The event, if `host` signed it.
+    fn verify(&self, host: &VerifyingKey) -> Result<ReplayEvent> {
+        if !verify(host, starknet_keccak(self.event.as_bytes()), self.r, self.s) {
+            return Err(eyre!("Invalid relay frame signature"));
+        }
+        Ok(serde_json::from_str(&self.event)?)
+    }
+}
+
+/// Key of this install, generated into `path` the first time.
+fn install_key(path: &Path) -> Result<SigningKey> {
+    match fs::read_to_string(path) {
+        Ok(secret) => Ok(SigningKey::from_secret_scalar(FieldElement::from_str(
+            secret.trim(),
+        )?)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            let key = SigningKey::from_random();
+            let mut file = fs::OpenOptions::new();
+            file.write(true).create_new(true);
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut file, 0o600);
+            writeln!(file.open(path)?, "{:#x}", key.secret_scalar())?;
+            Ok(key)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn verify(key: &VerifyingKey, message: FieldElement, r: FieldElement, s: FieldElement) -> bool {
+    key.verify(&message, &Signature { r, s }).unwrap_or(false)
+}
+
+/// Viewers allowed in, any viewer proving its key when [`configs::RELAY_VIEWERS`] is empty.
+fn is_allowed(public_key: FieldElement) -> bool {
+    configs::RELAY_VIEWERS.is_empty()
+        || configs::RELAY_VIEWERS
+            .iter()
+            .any(|viewer| FieldElement::from_str(viewer).ok() == Some(public_key))
+}
+
+#[derive(Resource)]
+struct RelayFrames {
+    signing_key: SigningKey,
+    live: broadcast::Sender<String>,
+    /// Last frame of each car and enemy, sent to viewers joining mid-race.
+    latest: watch::Sender<Vec<String>>,
+    state: HashMap<(u8, FieldElement, FieldElement), String>,
+}
+
+fn serve_relay(frames: Res<RelayFrames>, runtime: ResMut<TokioTasksRuntime>) {
+    let host = frames.signing_key.verifying_key().scalar();
+    let live = frames.live.clone();
+    let latest = frames.latest.subscribe();
+
+    runtime.spawn_background_task(move |_ctx| async move {
+        let listener = match TcpListener::bind(configs::RELAY_ENDPOINT).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!(error = %e, endpoint = configs::RELAY_ENDPOINT, "Bind relay endpoint");
+                return;
+            }
+        };
+
+        loop {
+            match listener.accept().await {
+                Ok((stream, address)) => {
+                    let live = live.subscribe();
+                    let latest = latest.borrow().clone();
+                    tokio::spawn(
+                        async move {
+                            if let Err(e) = handle_viewer(stream, host, latest, live).await {
+                                warn!(error = %e, "Relay viewer");
+                            }
+                        }
+                        .instrument(info_span!("relay", %address)),
+                    );
+                }
+                Err(e) => error!(error = %e, "Accept relay viewer"),
+            }
+        }
+    });
+}
+
+async fn handle_viewer(
+    stream: TcpStream,
+    host: FieldElement,
+    latest: Vec<String>,
+    mut live: broadcast::Receiver<String>,
+) -> Result<()> {
+    let (mut sink, mut messages) = tokio_tungstenite::accept_async(stream).await?.split();
+
+    let challenge = FieldElement::from_byte_slice_be(&rand::random::<[u8; 31]>()).unwrap();
+    let hello = serde_json::to_string(&Challenge { challenge, host })?;
+    sink.send(Message::Text(hello)).await?;
+    let identity: Identity = match messages.next().await {
+        Some(Ok(Message::Text(text))) => serde_json::from_str(&text)?,
+        _ => return Err(eyre!("No identity")),
+    };
+    let key = VerifyingKey::from_scalar(identity.public_key);
+    if !verify(&key, challenge, identity.r, identity.s) {
+        return Err(eyre!("Invalid identity signature"));
+    }
+    if !is_allowed(identity.public_key) {
+        return Err(eyre!("Viewer {:#x} not allowed", identity.public_key));
+    }
+    info!(viewer = %format!("{:#x}", identity.public_key), "Relay viewer joined");
+
+    for frame in latest {
+        sink.send(Message::Text(frame)).await?;
+    }
+    loop {
+        tokio::select! {
+            message = messages.next() => match message {
+                Some(Ok(Message::Close(_))) | None => return Ok(()),
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.into()),
+            },
+            frame = live.recv() => match frame {
+                Ok(frame) => sink.send(Message::Text(frame)).await?,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!(skipped, "Relay viewer lagging behind");
+                }
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+        }
+    }
+}
+
+fn broadcast_sync_events(mut frames: ResMut<RelayFrames>, mut sync_events: SyncEventReaders) {
+    let events = sync_events.read();
+    if events.is_empty() {
+        return;
+    }
+
+    for event in events {
+        let frame = match SignedFrame::sign(&event, &frames.signing_key)
+            .and_then(|frame| serde_json::to_string(&frame).map_err(Into::into))
+        {
+            Ok(frame) => frame,
+            Err(e) => {
+                error!(error = %e, "Sign relay frame");
+                continue;
+            }
+        };
+        let key = match event {
+            // A new run starts over
+            ReplayEvent::Spawn { model_id } => {
+                frames.state.retain(|(_, model, _), _| *model != model_id);
+                (0, model_id, FieldElement::ZERO)
+            }
+            ReplayEvent::Vehicle { model_id, .. } => (1, model_id, FieldElement::ZERO),
+            ReplayEvent::Enemy {
+                model_id, enemy_id, ..
+            } => (2, model_id, enemy_id),
+        };
+        frames.state.insert(key, frame.clone());
+        // Only fails without viewers
+        let _ = frames.live.send(frame);
+    }
+
+    let mut latest: Vec<_> = frames.state.iter().collect();
+    latest.sort_by_key(|(key, _)| **key);
+    let latest = latest.into_iter().map(|(_, frame)| frame.clone()).collect();
+    frames.latest.send_replace(latest);
+}
+
+/// Events received from the host at `url`.
+#[derive(Resource)]
+struct RelayedEvents {
+    url: String,
+    host: FieldElement,
+    signing_key: SigningKey,
+    sender: mpsc::UnboundedSender<ReplayEvent>,
+    receiver: mpsc::UnboundedReceiver<ReplayEvent>,
+}
+
+fn watch_relay(relayed: Res<RelayedEvents>, runtime: ResMut<TokioTasksRuntime>) {
+    let url = relayed.url.clone();
+    let host = relayed.host;
+    let signing_key = relayed.signing_key.clone();
+    let sender = relayed.sender.clone();
+
+    runtime.spawn_background_task(move |_ctx| async move {
+        if let Err(e) = follow_relay(&url, host, &signing_key, sender).await {
+            error!(error = %e, %url, "Follow relay");
+        }
+    });
+}
+
+async fn follow_relay(
+    url: &str,
+    expected_host: FieldElement,
+    signing_key: &SigningKey,
+    events: mpsc::UnboundedSender<ReplayEvent>,
+) -> Result<()> {
+    let (stream, _) = tokio_tungstenite::connect_async(url).await?;
+    let (mut sink, mut messages) = stream.split();
+
+    let Challenge { challenge, host } = match messages.next().await {
+        Some(Ok(Message::Text(text))) => serde_json::from_str(&text)?,
+        _ => return Err(eyre!("No challenge")),
+    };
+    if host != expected_host {
+        return Err(eyre!("Unexpected relay host {host:#x}"));
+    }
+    let signature = signing_key
+        .sign(&challenge)
+        .map_err(|e| eyre!("Sign challenge: {e}"))?;
+    let identity = Identity {
+        public_key: signing_key.verifying_key().scalar(),
+        r: signature.r,
+        s: signature.s,
+    };
+    sink.send(Message::Text(serde_json::to_string(&identity)?))
+        .await?;
+    info!(%url, "Following relay");
+
+    let host = VerifyingKey::from_scalar(host);
+    while let Some(message) = messages.next().await {
+        let text = match message? {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+        match serde_json::from_str::<SignedFrame>(&text)
+            .map_err(eyre::Report::from)
+            .and_then(|frame| frame.verify(&host))
+        {
+            Ok(event) => {
+                if events.send(event).is_err() {
+                    break;
+                }
+            }
+            Err(e) => warn!(error = %e, "Drop relay frame"),
+        }
+    }
+    Ok(())
+}
+
+fn apply_relayed_events(mut relayed: ResMut<RelayedEvents>, mut sync_events: SyncEventWriters) {
+    sync_events.send(std::iter::from_fn(|| relayed.receiver.try_recv().ok()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tampered_frame_is_rejected() {
+        let host = SigningKey::from_random();
+        let event = ReplayEvent::Vehicle {
+            model_id: FieldElement::ONE,
+            vehicle: vec![FieldElement::TWO],
+        };
+
+        let mut frame = SignedFrame::sign(&event, &host).unwrap();
+        assert_eq!(frame.verify(&host.verifying_key()).unwrap(), event);
+
+        frame.event = frame.event.replace("\"2\"", "\"3\"");
+        assert!(frame.verify(&host.verifying_key()).is_err());
+
+        // The key of an install is generated once
+        let path = std::env::temp_dir().join(format!("drive-ai-relay-{}.key", std::process::id()));
+        let key = install_key(&path).unwrap();
+        assert_eq!(
+            install_key(&path).unwrap().secret_scalar(),
+            key.secret_scalar()
+        );
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,231 @@
+use crate::car::{SpawnCar, UpdateCar};
+use crate::enemy::{EnemyPosition, SpawnEnemies, UpdateEnemies};
+use bevy::ecs::system::SystemParam;
+use bevy::log;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use starknet_ff::FieldElement;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// A synced world change, as seen by the simulation.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ReplayEvent {
+    Spawn {
+        model_id: FieldElement,
+    },
+    Vehicle {
+        model_id: FieldElement,
+        vehicle: Vec<FieldElement>,
+    },
+    Enemy {
+        model_id: FieldElement,
+        enemy_id: FieldElement,
+        position: Vec<FieldElement>,
+    },
+}
+
+/// One line of a replay file: an event and the seconds elapsed since startup when it happened.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReplayFrame {
+    pub time: f32,
+    #[serde(flatten)]
+    pub event: ReplayEvent,
+}
+
+/// Records every spawn and sync event to `path` as JSON lines, to be played back by [`ReplayPlugin`].
+pub struct RecorderPlugin {
+    pub path: PathBuf,
+}
+
+impl Plugin for RecorderPlugin {
+    fn build(&self, app: &mut App) {
+        match File::create(&self.path) {
+            Ok(file) => {
+                app.insert_resource(Recorder(BufWriter::new(file)))
+                    .add_system(record_events);
+            }
+            Err(e) => log::error!("Create replay file {}: {e}", self.path.display()),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct Recorder(BufWriter<File>);
+
+/// Readers of the spawn and sync events, for recording or streaming them.
+#[derive(SystemParam)]
+pub struct SyncEventReaders<'w, 's> {
+    spawn_car: EventReader<'w, 's, SpawnCar>,
+    update_car: EventReader<'w, 's, UpdateCar>,
+    update_enemies: EventReader<'w, 's, UpdateEnemies>,
+}
+
+impl SyncEventReaders<'_, '_> {
+    /// Events sent since the last call.
+    pub fn read(&mut self) -> Vec<ReplayEvent> {
+        self.spawn_car
+            .iter()
+            .map(|e| ReplayEvent::Spawn {
+                model_id: e.model_id,
+            })
+            .chain(self.update_car.iter().map(|e| ReplayEvent::Vehicle {
+                model_id: e.model_id,
+                vehicle: e.vehicle.clone(),
+            }))
+            .chain(
+                self.update_enemies
+                    .iter()
+                    .flat_map(|e| e.0.iter())
+                    .map(|enemy| ReplayEvent::Enemy {
+                        model_id: enemy.model_id,
+                        enemy_id: enemy.enemy_id,
+                        position: enemy.values(),
+                    }),
+            )
+            .collect()
+    }
+}
+
+/// Writers turning recorded or streamed events back into spawn and sync events.
+#[derive(SystemParam)]
+pub struct SyncEventWriters<'w> {
+    spawn_car: EventWriter<'w, SpawnCar>,
+    spawn_enemies: EventWriter<'w, SpawnEnemies>,
+    update_car: EventWriter<'w, UpdateCar>,
+    update_enemies: EventWriter<'w, UpdateEnemies>,
+}
+
+impl SyncEventWriters<'_> {
+    /// Sends the `events`, the enemy positions among them in one batch.
+    pub fn send(&mut self, events: impl IntoIterator<Item = ReplayEvent>) {
+        let mut positions = Vec::new();
+        for event in events {
+            self.send_one(event, &mut positions);
+        }
+        if !positions.is_empty() {
+            self.update_enemies.send(UpdateEnemies(positions));
+        }
+    }
+
+    fn send_one(&mut self, event: ReplayEvent, positions: &mut Vec<EnemyPosition>) {
+        match event {
+            ReplayEvent::Spawn { model_id } => {
+                self.spawn_enemies.send(SpawnEnemies { model_id });
+                self.spawn_car.send(SpawnCar {
+                    model_id,
+                    position: None,
+                });
+            }
+            ReplayEvent::Vehicle { model_id, vehicle } => {
+                self.update_car.send(UpdateCar { model_id, vehicle })
+            }
+            ReplayEvent::Enemy {
+                model_id,
+                enemy_id,
+                position,
+            } => match EnemyPosition::decode(model_id, enemy_id, &position) {
+                Ok(position) => positions.push(position),
+                Err(e) => log::error!("Decode enemy position ({enemy_id}): {e}"),
+            },
+        }
+    }
+}
+
+fn record_events(
+    mut recorder: ResMut<Recorder>,
+    time: Res<Time>,
+    mut sync_events: SyncEventReaders,
+) {
+    let events = sync_events.read();
+    if events.is_empty() {
+        return;
+    }
+
+    let time = time.elapsed_seconds();
+    for event in events {
+        let frame = ReplayFrame { time, event };
+        if let Err(e) = serde_json::to_writer(&mut recorder.0, &frame)
+            .map_err(std::io::Error::from)
+            .and_then(|_| recorder.0.write_all(b"\n"))
+        {
+            log::error!("Record replay frame: {e}");
+        }
+    }
+    if let Err(e) = recorder.0.flush() {
+        log::error!("Flush replay file: {e}");
+    }
+}
+
+/// Plays back a file written by [`RecorderPlugin`] in place of the dojo sync.
+pub struct ReplayPlugin {
+    pub path: PathBuf,
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        let frames = match load_frames(&self.path) {
+            Ok(frames) => frames,
+            Err(e) => {
+                log::error!("Load replay file {}: {e}", self.path.display());
+                Vec::new()
+            }
+        };
+
+        let mut replay = Replay::default();
+        replay.load(frames, 0.0);
+        app.insert_resource(replay).add_system(play_events);
+    }
+}
+
+/// Reads the frames of a replay file, in recording order.
+pub fn load_frames(path: &Path) -> std::io::Result<Vec<ReplayFrame>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut frames = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        frames.push(serde_json::from_str(&line)?);
+    }
+    Ok(frames)
+}
+
+/// Frames being played back, timed from `start`.
+#[derive(Resource, Default)]
+pub struct Replay {
+    frames: Vec<ReplayFrame>,
+    next: usize,
+    start: f32,
+}
+
+impl Replay {
+    /// Plays `frames` from the beginning, `now` being the current seconds since startup.
+    pub fn load(&mut self, frames: Vec<ReplayFrame>, now: f32) {
+        self.frames = frames;
+        self.next = 0;
+        self.start = now;
+    }
+}
+
+pub(crate) fn play_events(
+    mut replay: ResMut<Replay>,
+    time: Res<Time>,
+    mut sync_events: SyncEventWriters,
+) {
+    let elapsed = time.elapsed_seconds() - replay.start;
+
+    let mut events = Vec::new();
+    while let Some(frame) = replay.frames.get(replay.next) {
+        if frame.time > elapsed {
+            break;
+        }
+
+        events.push(frame.event.clone());
+        replay.next += 1;
+    }
+    sync_events.send(events);
+}
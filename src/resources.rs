@@ -1,9 +1,17 @@
+use crate::configs;
+use crate::conversions::{felt_to_short_string, short_string_to_felt};
 use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_rapier2d::prelude::Damping;
+use starknet_ff::FieldElement;
 
 #[derive(Resource, Default)]
 pub struct SimStats {
     pub num_cars_alive: usize,
+    /// Best fitness of every generation.
     pub fitness: Vec<f32>,
+    /// Mean fitness of every generation.
+    pub mean_fitness: Vec<f32>,
     pub generation_count: u32,
     pub max_current_score: f32,
 }
@@ -15,6 +23,7 @@ pub struct Settings {
     pub start_next_generation: bool,
     pub restart_sim: bool,
     pub is_camera_follow: bool,
+    pub is_camera_shake: bool,
 }
 
 #[derive(Resource, Default)]
@@ -23,6 +32,124 @@ pub struct BrainToDisplay(pub Vec<Vec<f64>>);
 #[derive(Resource)]
 pub struct MaxDistanceTravelled(pub f32);
 
+/// Model ids of the racers to spawn, each one gets its own car and enemies.
+#[derive(Resource, Clone)]
+pub struct Racers(pub Vec<FieldElement>);
+
+impl Racers {
+    /// `count` racers named after [`configs::MODEL_NAME`] with an index suffix.
+    pub fn numbered(count: u32) -> Self {
+        Self(
+            (0..count)
+                .map(|i| short_string_to_felt(&format!("{}-{i}", configs::MODEL_NAME)))
+                .collect(),
+        )
+    }
+
+    /// Model system driving `model_id` on the world: the [`Racers::numbered`] ones share
+    /// [`configs::MODEL_NAME`], any other racer is named after its model.
+    pub fn model_system(model_id: FieldElement) -> FieldElement {
+        let name = felt_to_short_string(model_id);
+        let numbered = name
+            .strip_prefix(configs::MODEL_NAME)
+            .and_then(|rest| rest.strip_prefix('-'))
+            .map_or(false, |index| {
+                !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit())
+            });
+        if numbered {
+            short_string_to_felt(configs::MODEL_NAME)
+        } else {
+            model_id
+        }
+    }
+}
+
+impl Default for Racers {
+    fn default() -> Self {
+        Self(vec![short_string_to_felt(configs::MODEL_NAME)])
+    }
+}
+
+/// Cars stay where they are while present: `drive` is not sent, e.g. during a countdown.
+#[derive(Resource)]
+pub struct HoldDrive;
+
+/// No world is synced: cars and enemies move on their own, for a standalone arcade game.
+#[derive(Resource)]
+pub struct Standalone;
+
+/// Models the player can pick from, the selected one replaces [`Racers`] for the next run.
+#[derive(Resource, Clone)]
+pub struct ModelRegistry(pub Vec<FieldElement>);
+
+impl Default for ModelRegistry {
+    fn default() -> Self {
+        Self(
+            configs::MODEL_NAMES
+                .iter()
+                .map(|name| short_string_to_felt(name))
+                .collect(),
+        )
+    }
+}
+
+/// Distance covered and speed of the current run of each model, derived from its synced positions.
+#[derive(Resource, Default, Debug)]
+pub struct RunStats(pub HashMap<FieldElement, CarRunStats>);
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CarRunStats {
+    /// Grid units.
+    pub distance: f32,
+    /// Grid units per second.
+    pub speed: f32,
+    /// Last synced position, and the seconds since startup it was received at.
+    pub last: Option<(Vec2, f32)>,
+}
+
+impl CarRunStats {
+    pub fn record(&mut self, position: Vec2, at: f32) {
+        if let Some((last, last_at)) = self.last {
+            let step = position.distance(last);
+            self.distance += step;
+            if at > last_at {
+                self.speed = step / (at - last_at);
+            }
+        }
+        self.last = Some((position, at));
+    }
+}
+
+/// Rapier parameters of the enemies, applied when they are spawned and whenever it changes.
+#[derive(Resource, Reflect, Clone, Debug)]
+#[reflect(Resource)]
+pub struct PhysicsConfig {
+    pub enemy_mass: f32,
+    pub enemy_friction: f32,
+    pub enemy_linear_damping: f32,
+    pub enemy_angular_damping: f32,
+}
+
+impl PhysicsConfig {
+    pub fn enemy_damping(&self) -> Damping {
+        Damping {
+            linear_damping: self.enemy_linear_damping,
+            angular_damping: self.enemy_angular_damping,
+        }
+    }
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            enemy_mass: configs::ENEMY_MASS,
+            enemy_friction: configs::ENEMY_FRICTION,
+            enemy_linear_damping: configs::ENEMY_LINEAR_DAMPING,
+            enemy_angular_damping: configs::ENEMY_ANGULAR_DAMPING,
+        }
+    }
+}
+
 impl Default for Settings {
     fn default() -> Self {
         Self {
@@ -31,6 +158,7 @@ impl Default for Settings {
             start_next_generation: false,
             restart_sim: false,
             is_camera_follow: true,
+            is_camera_shake: true,
         }
     }
 }
@@ -0,0 +1,105 @@
+#[derive(Component, Serde, SerdeLen, Drop, Copy)]
+struct Score {
+    // Furthest distance reached, in grid units
+    distance: u128,
+    // Survival time of that run, in milliseconds
+    survival_time: u128,
+}
+
+#[system]
+mod submit_score {
+    use traits::Into;
+    use starknet::get_block_timestamp;
+    use cubit::types::fixed::ONE_u128;
+    use dojo::world::Context;
+    use drive_ai::Vehicle;
+    use drive_ai::racer::Racer;
+    use super::Score;
+
+    /// Records the result of a finished run from the world's own state, only the best distance
+    /// of a model is kept. The distance is the `y` its vehicle reached and the time is taken from
+    /// the spawn of the racer to the current block, as `finish_race` does. Only the driver of the
+    /// racer can submit its run.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - Context of the game.
+    /// * `model` - The AI model id of the racer.
+    fn execute(ctx: Context, model: felt252) {
+        let racer = get !(ctx.world, model.into(), Racer);
+        assert(ctx.origin == racer.driver, 'not the driver');
+
+        let vehicle = get !(ctx.world, model.into(), Vehicle);
+        let distance = if vehicle.position.y.sign {
+            0
+        } else {
+            vehicle.position.y.mag / ONE_u128
+        };
+        let survival_time: u128 = ((get_block_timestamp() - racer.spawned_at) * 1000).into();
+
+        let best = get !(ctx.world, model.into(), Score);
+        if distance <= best.distance {
+            return ();
+        }
+        set !(ctx.world, model.into(), (Score { distance, survival_time }));
+    }
+}
+
+/// Track `y` of the finish line, 1600 grid units as the client's `FINISH_LINE_Y`, in 64.64 fixed
+/// point.
+const FINISH_LINE_Y: u128 = 29514790517935282585600;
+
+#[derive(Component, Serde, SerdeLen, Drop, Copy)]
+struct Completion {
+    // Best time to the finish line, in milliseconds
+    best_time: u128,
+    // Runs that reached the finish line
+    finishes: u32,
+}
+
+#[system]
+mod finish_race {
+    use traits::Into;
+    use starknet::get_block_timestamp;
+    use cubit::types::fixed::FixedTrait;
+    use dojo::world::Context;
+    use drive_ai::Vehicle;
+    use drive_ai::racer::Racer;
+    use super::{Completion, FINISH_LINE_Y};
+
+    /// Records a run that reached the finish line and stops its vehicle, only the best time of a
+    /// model is kept. The time is taken from the spawn of the racer to the current block, reverts
+    /// if the vehicle is short of the finish line or already stopped.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - Context of the game.
+    /// * `model` - The AI model id of the racer.
+    fn execute(ctx: Context, model: felt252) {
+        let vehicle = get !(ctx.world, model.into(), Vehicle);
+        assert(vehicle.speed > FixedTrait::new(0, false), 'race over');
+        assert(vehicle.position.y >= FixedTrait::new(FINISH_LINE_Y, false), 'finish not reached');
+
+        let racer = get !(ctx.world, model.into(), Racer);
+        let time: u128 = ((get_block_timestamp() - racer.spawned_at) * 1000).into();
+        let completion = get !(ctx.world, model.into(), Completion);
+        let best_time = if completion.finishes == 0 || time < completion.best_time {
+            time
+        } else {
+            completion.best_time
+        };
+        set !(
+            ctx.world,
+            model.into(),
+            (Completion { best_time, finishes: completion.finishes + 1 })
+        );
+
+        set !(
+            ctx.world,
+            model.into(),
+            (Vehicle {
+                position: vehicle.position, steer: vehicle.steer, speed: FixedTrait::new(0, false)
+            })
+        );
+    }
+}
@@ -0,0 +1,318 @@
+use crate::car::{CarCrashed, Model};
+use crate::configs;
+use crate::conversions::felt_to_short_string;
+use crate::difficulty::Difficulty;
+use crate::enemy::{enemy_bundle, EnemyDefinitions, EnemyType, Lane, Lanes};
+use crate::resources::PhysicsConfig;
+use bevy::log;
+use bevy::prelude::*;
+#[cfg(feature = "onchain")]
+use bevy_tokio_tasks::{TokioTasksPlugin, TokioTasksRuntime};
+use clap::ValueEnum;
+use rhai::{CallFnOptions, Dynamic, Engine, FuncArgs, Scope, AST};
+#[cfg(feature = "onchain")]
+use starknet_ff::FieldElement;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "onchain")]
+use {
+    crate::backend::Backend, crate::console::parse_felt, crate::tasks::tokio_tasks_plugin,
+    tokio::sync::mpsc,
+};
+
+/// Runs the rhai script at `path`: its `on_spawn(model)`, `on_tick(seconds)` and
+/// `on_crash(model)` functions are called as cars spawn, every frame and as cars crash, and
+/// may call `spawn_enemy(lane, ahead)`, `set_difficulty(name)` and, in `onchain` builds,
+/// `execute(system, calldata)`.
+pub struct ScriptingPlugin {
+    pub path: PathBuf,
+}
+
+impl Plugin for ScriptingPlugin {
+    fn build(&self, app: &mut App) {
+        let source = match std::fs::read_to_string(&self.path) {
+            Ok(source) => source,
+            Err(e) => {
+                log::error!("Read script {}: {e}", self.path.display());
+                return;
+            }
+        };
+        let script = match Script::compile(&source) {
+            Ok(script) => script,
+            Err(e) => {
+                log::error!("Compile script {}: {e}", self.path.display());
+                return;
+            }
+        };
+
+        app.insert_resource(script)
+            .init_resource::<Lanes>()
+            .init_resource::<PhysicsConfig>()
+            .init_resource::<EnemyDefinitions>()
+            .add_event::<CarCrashed>()
+            .add_systems((run_script_hooks, apply_script_actions).chain());
+        #[cfg(feature = "onchain")]
+        {
+            if !app.is_plugin_added::<TokioTasksPlugin>() {
+                app.add_plugin(tokio_tasks_plugin());
+            }
+            app.add_system(send_script_calls.after(run_script_hooks));
+        }
+    }
+}
+
+/// What the script asked for, applied once its hooks ran.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScriptAction {
+    /// An enemy in `lane`, `ahead` pixels in front of the leading car.
+    SpawnEnemy {
+        lane: u32,
+        ahead: f32,
+    },
+    SetDifficulty(Difficulty),
+}
+
+#[derive(Resource)]
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    /// Top-level variables of the script.
+    scope: Scope<'static>,
+    actions: Arc<Mutex<Vec<ScriptAction>>>,
+    /// World systems to execute, with their calldata.
+    #[cfg(feature = "onchain")]
+    calls: Arc<Mutex<Vec<(String, Vec<FieldElement>)>>>,
+}
+
+impl Script {
+    /// Compiles `source` and runs its top-level statements.
+    pub fn compile(source: &str) -> Result<Self, String> {
+        let actions = Arc::new(Mutex::new(Vec::new()));
+        #[cfg(feature = "onchain")]
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut engine = Engine::new();
+        engine
+            .set_max_operations(configs::SCRIPT_MAX_OPERATIONS)
+            .set_max_call_levels(configs::SCRIPT_MAX_CALL_LEVELS)
+            .set_max_string_size(configs::SCRIPT_MAX_STRING_SIZE);
+        engine.on_print(|text| log::info!("Script: {text}"));
+
+        let queue = actions.clone();
+        engine.register_fn("spawn_enemy", move |lane: i64, ahead: f64| {
+            queue.lock().unwrap().push(ScriptAction::SpawnEnemy {
+                lane: lane.max(0) as u32,
+                ahead: ahead as f32,
+            });
+        });
+        let queue = actions.clone();
+        engine.register_fn(
+            "set_difficulty",
+            move |name: &str| match Difficulty::from_str(name, true) {
+                Ok(difficulty) => queue
+                    .lock()
+                    .unwrap()
+                    .push(ScriptAction::SetDifficulty(difficulty)),
+                Err(e) => log::error!("Set difficulty {name}: {e}"),
+            },
+        );
+        #[cfg(feature = "onchain")]
+        {
+            let queue = calls.clone();
+            engine.register_fn(
+                "execute",
+                move |system: &str, calldata: rhai::Array| match calldata
+                    .iter()
+                    .map(felt)
+                    .collect::<Result<Vec<_>, _>>()
+                {
+                    Ok(calldata) => queue.lock().unwrap().push((system.to_string(), calldata)),
+                    Err(e) => log::error!("Execute {system}: {e}"),
+                },
+            );
+        }
+
+        let ast = engine.compile(source).map_err(|e| e.to_string())?;
+        let mut scope = Scope::new();
+        engine
+            .run_ast_with_scope(&mut scope, &ast)
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            engine,
+            ast,
+            scope,
+            actions,
+            #[cfg(feature = "onchain")]
+            calls,
+        })
+    }
+
+    /// Calls the `hook` function of the script, when it has one.
+    pub fn call(&mut self, hook: &str, args: impl FuncArgs) {
+        if !self
+            .ast
+            .iter_functions()
+            .any(|function| function.name == hook)
+        {
+            return;
+        }
+        // The top-level statements already ran on compile
+        let options = CallFnOptions::new().eval_ast(false);
+        let result = self.engine.call_fn_with_options::<Dynamic>(
+            options,
+            &mut self.scope,
+            &self.ast,
+            hook,
+            args,
+        );
+        if let Err(e) = result {
+            log::error!("Run script {hook}: {e}");
+        }
+    }
+
+    pub fn take_actions(&self) -> Vec<ScriptAction> {
+        std::mem::take(&mut *self.actions.lock().unwrap())
+    }
+}
+
+/// A calldata item: an integer, or a string holding a hex or decimal felt or a short string.
+#[cfg(feature = "onchain")]
+fn felt(value: &Dynamic) -> Result<FieldElement, String> {
+    if let Ok(value) = value.as_int() {
+        return u64::try_from(value)
+            .map(FieldElement::from)
+            .map_err(|_| format!("Negative felt {value}"));
+    }
+    let text = value
+        .clone()
+        .into_string()
+        .map_err(|kind| format!("Expected an integer or a string, got {kind}"))?;
+    parse_felt(&text)
+}
+
+fn run_script_hooks(
+    time: Res<Time>,
+    mut script: ResMut<Script>,
+    mut crashes: EventReader<CarCrashed>,
+    spawned: Query<&Model, Added<Model>>,
+) {
+    for model in spawned.iter() {
+        script.call("on_spawn", (felt_to_short_string(model.id),));
+    }
+    script.call("on_tick", (time.elapsed_seconds_f64(),));
+    for crash in crashes.iter() {
+        script.call("on_crash", (felt_to_short_string(crash.model_id),));
+    }
+}
+
+fn apply_script_actions(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    lanes: Res<Lanes>,
+    physics: Res<PhysicsConfig>,
+    definitions: Res<EnemyDefinitions>,
+    script: Res<Script>,
+    mut difficulty: Option<ResMut<Difficulty>>,
+    cars: Query<&Transform, With<Model>>,
+) {
+    let lead_y = cars
+        .iter()
+        .map(|transform| transform.translation.y)
+        .reduce(f32::max)
+        .unwrap_or_default();
+    for action in script.take_actions() {
+        match action {
+            ScriptAction::SpawnEnemy { lane, ahead } => {
+                let lane = Lane(lane.min(lanes.count - 1));
+                let translation = Vec3::new(lanes.center_x(lane), lead_y + ahead, 0.0);
+                commands.spawn(enemy_bundle(
+                    EnemyType::Simple,
+                    translation,
+                    Vec2::ZERO,
+                    lane,
+                    &physics,
+                    &definitions,
+                    &asset_server,
+                ));
+            }
+            ScriptAction::SetDifficulty(set) => match difficulty.as_deref_mut() {
+                Some(difficulty) => *difficulty = set,
+                None => commands.insert_resource(set),
+            },
+        }
+    }
+}
+
+/// Queues the systems the script executes for a single background task, which sends them one
+/// after the other. Calls past [`configs::SCRIPT_CALL_QUEUE`] waiting ones are dropped.
+#[cfg(feature = "onchain")]
+fn send_script_calls(
+    script: Res<Script>,
+    backend: Option<Res<Backend>>,
+    runtime: Res<TokioTasksRuntime>,
+    mut sender: Local<Option<mpsc::Sender<(String, Vec<FieldElement>)>>>,
+) {
+    let calls = std::mem::take(&mut *script.calls.lock().unwrap());
+    if calls.is_empty() {
+        return;
+    }
+    let Some(backend) = backend else {
+        log::error!("Scripts execute systems through the dojo backend, add `DojoPlugin`");
+        return;
+    };
+    let sender = sender.get_or_insert_with(|| {
+        let (tx, mut rx) = mpsc::channel::<(String, Vec<FieldElement>)>(configs::SCRIPT_CALL_QUEUE);
+        let backend = backend.0.clone();
+        runtime.spawn_background_task(move |_ctx| async move {
+            while let Some((system, calldata)) = rx.recv().await {
+                if let Err(e) = backend.execute(&system, calldata).await {
+                    log::error!("Execute {system} from the script: {e}");
+                }
+            }
+        });
+        tx
+    });
+    for (system, calldata) in calls {
+        let name = system.clone();
+        if let Err(e) = sender.try_send((system, calldata)) {
+            log::error!("Drop script call {name}: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hooks_queue_the_actions_of_the_script() {
+        let mut script = Script::compile(
+            r#"
+            fn on_crash(model) {
+                print(`${model} crashed`);
+                spawn_enemy(2, 300.0);
+                set_difficulty("nightmare");
+            }
+            "#,
+        )
+        .unwrap();
+        script.call("on_tick", (1.0_f64,));
+        assert!(script.take_actions().is_empty());
+
+        script.call("on_crash", ("model".to_string(),));
+        assert_eq!(
+            script.take_actions(),
+            [
+                ScriptAction::SpawnEnemy {
+                    lane: 2,
+                    ahead: 300.0
+                },
+                ScriptAction::SetDifficulty(Difficulty::Nightmare),
+            ]
+        );
+        assert!(Script::compile("fn on_tick(").is_err());
+
+        // A runaway hook is aborted instead of freezing the frame
+        let mut runaway = Script::compile("fn on_tick(seconds) { loop {} }").unwrap();
+        runaway.call("on_tick", (1.0_f64,));
+    }
+}
@@ -0,0 +1,178 @@
+use crate::car::{Car, Model, SyncedVehicle};
+use crate::configs;
+use crate::conversions::felt_to_short_string;
+use crate::locale::Locale;
+use crate::resources::Racers;
+use crate::world::{Decode, Vehicle};
+use bevy::prelude::*;
+use bevy_inspector_egui::{bevy_egui::EguiContexts, egui};
+use bevy_rapier2d::prelude::*;
+
+/// Casts the sensor rays of the player car, or the leading one, every frame. Its network is fed
+/// their hit fractions by the car systems.
+pub struct SensorsPlugin;
+
+impl Plugin for SensorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Sensors>().add_systems(
+            (pick_watched_car, cast_sensor_rays)
+                .chain()
+                .in_set(SensorSet),
+        );
+    }
+}
+
+/// Where [`Sensors`] is updated, for the systems drawing them to run after.
+#[derive(SystemSet, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct SensorSet;
+
+/// Bars of the [`Sensors`] distances next to the steering the world applied, to correlate the
+/// inputs with the decisions.
+pub struct SensorHudPlugin;
+
+impl Plugin for SensorHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Locale>()
+            .add_system(sensor_hud_system.after(SensorSet));
+    }
+}
+
+/// Sensor rays of the watched car.
+#[derive(Resource, Default, Debug)]
+pub struct Sensors {
+    pub car: Option<Entity>,
+    pub rays: Vec<SensorRay>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SensorRay {
+    /// Where the ray stops, relative to the car.
+    pub end: Vec2,
+    /// Fraction of [`configs::RAYCAST_MAX_TOI`] the ray hit something at.
+    pub hit: Option<f32>,
+}
+
+fn pick_watched_car(
+    racers: Option<Res<Racers>>,
+    mut sensors: ResMut<Sensors>,
+    cars: Query<(Entity, &Transform, &Model), With<Car>>,
+) {
+    let player = racers.as_ref().and_then(|racers| racers.0.first().copied());
+    sensors.car = cars
+        .iter()
+        .find(|(_, _, model)| Some(model.id) == player)
+        .or_else(|| {
+            cars.iter()
+                .max_by(|a, b| a.1.translation.y.total_cmp(&b.1.translation.y))
+        })
+        .map(|(entity, _, _)| entity);
+}
+
+/// Directions of the sensor rays of a car turned by `angle` radians, fanned over
+/// [`configs::RAYCAST_SPREAD_ANGLE_DEG`] from [`configs::RAYCAST_START_ANGLE_DEG`].
+pub fn ray_directions(angle: f32) -> Vec<Vec2> {
+    let step = configs::RAYCAST_SPREAD_ANGLE_DEG / (configs::NUM_RAY_CASTS - 1) as f32;
+    (0..configs::NUM_RAY_CASTS)
+        .map(|i| {
+            let ray = (configs::RAYCAST_START_ANGLE_DEG + step * i as f32).to_radians();
+            Vec2::from_angle(angle + ray)
+        })
+        .collect()
+}
+
+fn cast_sensor_rays(
+    rapier_context: Res<RapierContext>,
+    mut sensors: ResMut<Sensors>,
+    cars: Query<&Transform, With<Car>>,
+) {
+    sensors.rays.clear();
+    let Some(car) = sensors.car else {
+        return;
+    };
+    let Ok(transform) = cars.get(car) else {
+        return;
+    };
+
+    let origin = transform.translation.truncate();
+    let (angle, _, _) = transform.rotation.to_euler(EulerRot::ZYX);
+    let filter = QueryFilter::default()
+        .exclude_sensors()
+        .exclude_rigid_body(car);
+    sensors.rays = ray_directions(angle)
+        .into_iter()
+        .map(|direction| {
+            let hit = rapier_context
+                .cast_ray(origin, direction, configs::RAYCAST_MAX_TOI, true, filter)
+                .map(|(_, toi)| toi / configs::RAYCAST_MAX_TOI);
+            SensorRay {
+                end: direction * configs::RAYCAST_MAX_TOI * hit.unwrap_or(1.0),
+                hit,
+            }
+        })
+        .collect();
+}
+
+fn sensor_hud_system(
+    mut contexts: EguiContexts,
+    locale: Res<Locale>,
+    sensors: Option<Res<Sensors>>,
+    cars: Query<(&Model, &SyncedVehicle), With<Car>>,
+) {
+    let Some(sensors) = sensors else {
+        return;
+    };
+    let Some(Ok((model, synced))) = sensors.car.map(|car| cars.get(car)) else {
+        return;
+    };
+
+    let step = configs::RAYCAST_SPREAD_ANGLE_DEG / (configs::NUM_RAY_CASTS - 1) as f32;
+    egui::Window::new(locale.text("sensors.title"))
+        .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(10.0, -10.0))
+        .show(contexts.ctx_mut(), |ui| {
+            ui.strong(felt_to_short_string(model.id));
+            for (i, ray) in sensors.rays.iter().enumerate() {
+                let angle = configs::RAYCAST_START_ANGLE_DEG + step * i as f32;
+                let distance = ray.hit.unwrap_or(1.0);
+                let angle = format!("{angle:.0}");
+                let text = match ray.hit {
+                    Some(hit) => locale.format(
+                        "sensors.hit",
+                        &[
+                            ("angle", &angle),
+                            (
+                                "distance",
+                                &format!("{:.0}", hit * configs::RAYCAST_MAX_TOI),
+                            ),
+                        ],
+                    ),
+                    None => locale.format("sensors.clear", &[("angle", &angle)]),
+                };
+                ui.add(egui::ProgressBar::new(distance).text(text));
+            }
+            if let Ok(vehicle) = Vehicle::decode(&synced.0) {
+                let steer = format!("{:+.2}", vehicle.steer.to_f32_exact());
+                ui.label(locale.format("sensors.steer", &[("steer", &steer)]));
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rays_fan_around_the_car() {
+        let rays = ray_directions(0.0);
+        assert_eq!(rays.len(), configs::NUM_RAY_CASTS as usize);
+        assert!(rays[0].abs_diff_eq(
+            Vec2::from_angle(configs::RAYCAST_START_ANGLE_DEG.to_radians()),
+            1e-6
+        ));
+        let last = configs::RAYCAST_START_ANGLE_DEG + configs::RAYCAST_SPREAD_ANGLE_DEG;
+        assert!(rays[rays.len() - 1].abs_diff_eq(Vec2::from_angle(last.to_radians()), 1e-6));
+
+        // Turning the car turns its rays
+        let turned = ray_directions(std::f32::consts::FRAC_PI_2);
+        assert!(turned[0].abs_diff_eq(rays[0].perp(), 1e-6));
+    }
+}
@@ -0,0 +1,98 @@
+use crate::car::Car;
+use crate::configs;
+use crate::enemy::Lanes;
+use crate::population::GenerationSet;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+/// Cars beyond the edges of the [`Lanes`] drive on the shoulder: they are slowed down by its
+/// friction and lose fitness for every second spent there, the walls only stand past it.
+pub struct ShoulderPlugin;
+
+impl Plugin for ShoulderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Lanes>()
+            .add_system(shoulder_system.before(GenerationSet));
+    }
+}
+
+/// Fitness a car lost on the shoulder during its run.
+#[derive(Component, Default, Debug)]
+pub struct ShoulderPenalty(pub f32);
+
+/// Whether `x` is beyond the edges of the lanes.
+pub fn on_shoulder(lanes: &Lanes, x: f32) -> bool {
+    x < lanes.x_min || x > lanes.x_max
+}
+
+fn shoulder_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    lanes: Res<Lanes>,
+    mut cars: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Damping,
+            Option<&mut ShoulderPenalty>,
+        ),
+        With<Car>,
+    >,
+) {
+    for (entity, transform, mut damping, penalty) in cars.iter_mut() {
+        let off_road = on_shoulder(&lanes, transform.translation.x);
+        let linear_damping = if off_road {
+            configs::SHOULDER_LINEAR_DAMPING
+        } else {
+            configs::CAR_LINEAR_DAMPING
+        };
+        if damping.linear_damping != linear_damping {
+            damping.linear_damping = linear_damping;
+        }
+        if !off_road {
+            continue;
+        }
+        let lost = configs::SHOULDER_FITNESS_PENALTY * time.delta_seconds();
+        match penalty {
+            Some(mut penalty) => penalty.0 += lost,
+            None => {
+                commands.entity(entity).insert(ShoulderPenalty(lost));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::utils::{Duration, Instant};
+
+    #[test]
+    fn test_shoulder_slows_and_penalizes() {
+        let mut app = App::new();
+        app.init_resource::<Time>().add_plugin(ShoulderPlugin);
+        let start = app.world.resource::<Time>().startup();
+        let lanes = Lanes::default();
+        let spawn = |app: &mut App, x: f32| {
+            app.world
+                .spawn((Car, Transform::from_xyz(x, 0.0, 0.0), Damping::default()))
+                .id()
+        };
+        let on_road = spawn(&mut app, (lanes.x_min + lanes.x_max) / 2.0);
+        let off_road = spawn(&mut app, lanes.x_max + 10.0);
+
+        for seconds in 1..=3 {
+            app.world
+                .resource_mut::<Time>()
+                .update_with_instant(start + Duration::from_secs(seconds));
+            app.update();
+        }
+
+        assert!(app.world.get::<ShoulderPenalty>(on_road).is_none());
+        let penalty = app.world.get::<ShoulderPenalty>(off_road).unwrap().0;
+        assert!((penalty - 2.0 * configs::SHOULDER_FITNESS_PENALTY).abs() < 1e-3);
+        let damping = |entity| app.world.get::<Damping>(entity).unwrap().linear_damping;
+        assert_eq!(damping(on_road), configs::CAR_LINEAR_DAMPING);
+        assert_eq!(damping(off_road), configs::SHOULDER_LINEAR_DAMPING);
+    }
+}
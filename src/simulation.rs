@@ -0,0 +1,227 @@
+use crate::boss::BossPlugin;
+use crate::car::{CarPlugin, SpawnCar};
+use crate::configs::*;
+use crate::countdown::CountdownPlugin;
+use crate::difficulty::DifficultyPlugin;
+use crate::enemy::{EnemyPlugin, SpawnEnemies};
+use crate::finish::FinishLinePlugin;
+use crate::formation::FormationPlugin;
+use crate::generation_stats::GenerationStatsPlugin;
+use crate::heatmap::HeatmapPlugin;
+use crate::history::HistoryPlugin;
+use crate::metrics::MetricsPlugin;
+use crate::near_miss::NearMissPlugin;
+use crate::population::{PopulationPlugin, RespawnPopulation};
+use crate::resources::{Racers, Standalone};
+use crate::sensors::SensorsPlugin;
+use crate::shoulder::ShoulderPlugin;
+use crate::speed_zone::SpeedZonePlugin;
+use bevy::{math::vec3, prelude::*};
+use bevy_rapier2d::prelude::*;
+
+/// Physics, road, cars and enemies, shared by all subcommands. Racers are spawned by
+/// `DojoPlugin` or [`StandalonePlugin`].
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FixedTime::new_from_secs(PHYSICS_TIME_STEP))
+            .add_plugin(
+                RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0)
+                    .with_default_system_setup(false),
+            )
+            // Stepped on the fixed timestep so runs behave the same at any frame rate
+            .edit_schedule(CoreSchedule::FixedUpdate, |schedule| {
+                schedule
+                    .configure_sets(
+                        (
+                            PhysicsSet::SyncBackend,
+                            PhysicsSet::SyncBackendFlush,
+                            PhysicsSet::StepSimulation,
+                            PhysicsSet::Writeback,
+                        )
+                            .chain(),
+                    )
+                    .add_systems(
+                        RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::SyncBackend)
+                            .in_base_set(PhysicsSet::SyncBackend),
+                    )
+                    .add_systems(
+                        RapierPhysicsPlugin::<NoUserData>::get_systems(
+                            PhysicsSet::SyncBackendFlush,
+                        )
+                        .in_base_set(PhysicsSet::SyncBackendFlush),
+                    )
+                    .add_systems(
+                        RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::StepSimulation)
+                            .in_base_set(PhysicsSet::StepSimulation),
+                    )
+                    .add_systems(
+                        RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::Writeback)
+                            .in_base_set(PhysicsSet::Writeback),
+                    );
+            })
+            .add_plugin(CarPlugin)
+            .add_plugin(SensorsPlugin)
+            .add_plugin(EnemyPlugin)
+            .add_plugin(DifficultyPlugin)
+            .add_plugin(ShoulderPlugin)
+            .add_plugin(FinishLinePlugin)
+            .add_plugin(CountdownPlugin)
+            .add_plugin(SpeedZonePlugin {
+                path: SPEED_ZONES_PATH.into(),
+            })
+            .add_plugin(PopulationPlugin)
+            .add_plugin(MetricsPlugin)
+            .add_plugin(NearMissPlugin)
+            .add_plugin(HistoryPlugin {
+                directory: RESULTS_DIRECTORY.into(),
+            })
+            .add_plugin(HeatmapPlugin {
+                path: HEATMAP_PATH.into(),
+            })
+            .add_plugin(GenerationStatsPlugin {
+                path: GENERATION_STATS_PATH.into(),
+            })
+            .add_startup_system(setup);
+    }
+}
+
+/// Racers spawned and respawned by the client, with scripted traffic instead of the world's.
+pub struct StandalonePlugin;
+
+impl Plugin for StandalonePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Standalone)
+            .init_resource::<Racers>()
+            .add_plugin(FormationPlugin {
+                path: FORMATIONS_PATH.into(),
+            })
+            .add_plugin(BossPlugin)
+            .add_startup_system(spawn_standalone_racer)
+            .add_system(respawn_standalone_racers);
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+) {
+    rapier_config.gravity = Vec2::ZERO;
+    rapier_config.timestep_mode = TimestepMode::Fixed {
+        dt: PHYSICS_TIME_STEP,
+        substeps: 1,
+    };
+
+    spawn_roads(&mut commands, &asset_server);
+    // spawn_bound_trucks(&mut commands, &asset_server);
+}
+
+/// Without a world to spawn the racers, the sprites are spawned right away instead.
+fn spawn_standalone_racer(
+    racers: Res<Racers>,
+    mut spawn_car: EventWriter<SpawnCar>,
+    mut spawn_enemies: EventWriter<SpawnEnemies>,
+) {
+    for model_id in racers.0.iter().copied() {
+        spawn_enemies.send(SpawnEnemies { model_id });
+        spawn_car.send(SpawnCar {
+            model_id,
+            position: None,
+        });
+    }
+}
+
+/// Without a world to evolve, the next generation starts as soon as the previous one ended.
+fn respawn_standalone_racers(
+    racers: Res<Racers>,
+    mut respawn_population: EventReader<RespawnPopulation>,
+    mut spawn_car: EventWriter<SpawnCar>,
+    mut spawn_enemies: EventWriter<SpawnEnemies>,
+) {
+    for _ in respawn_population.iter() {
+        for model_id in racers.0.iter().copied() {
+            spawn_enemies.send(SpawnEnemies { model_id });
+            spawn_car.send(SpawnCar {
+                model_id,
+                position: None,
+            });
+        }
+    }
+}
+
+/// Tile of the road sprite.
+#[derive(Component)]
+pub struct RoadTile;
+
+fn spawn_roads(commands: &mut Commands, asset_server: &AssetServer) {
+    // Road
+    // let rx = WINDOW_WIDTH / 2.0 - 30.0;
+    let rx = ROAD_SPRITE_W / 2.0 * SPRITE_SCALE_FACTOR;
+    let mut ry = ROAD_SPRITE_H / 2.0 * SPRITE_SCALE_FACTOR;
+    for _ in 0..NUM_ROAD_TILES {
+        commands.spawn((
+            SpriteBundle {
+                transform: Transform::from_xyz(rx, ry, -10.0)
+                    .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+                texture: asset_server.load("road.png"),
+                ..default()
+            },
+            RoadTile,
+        ));
+        ry += ROAD_SPRITE_H * SPRITE_SCALE_FACTOR;
+    }
+    let road_end_y = ry - ROAD_SPRITE_H * SPRITE_SCALE_FACTOR + 800.0;
+
+    // finish line checker board
+    commands.spawn(SpriteBundle {
+        transform: Transform::from_xyz(rx, FINISH_LINE_Y, -5.0)
+            .with_scale(Vec3::splat(SPRITE_SCALE_FACTOR)),
+        texture: asset_server.load("end-point.png"),
+        ..default()
+    });
+
+    // Road colliders, past the shoulders
+    // left
+    let ry = 5.0 * ROAD_SPRITE_H * SPRITE_SCALE_FACTOR;
+    let rx_min = ROAD_X_MIN - SHOULDER_WIDTH;
+    commands.spawn((
+        SpriteBundle {
+            transform: Transform::from_xyz(rx_min, ry, 0.0).with_scale(vec3(0.5, 0.5, 1.0)),
+            ..default()
+        },
+        RigidBody::Fixed,
+        BOUNDARY_COLLISION_GROUPS,
+        Collider::cuboid(
+            5.0,
+            ROAD_SPRITE_H * SPRITE_SCALE_FACTOR * NUM_ROAD_TILES as f32 * 5.0,
+        ),
+    ));
+
+    // right
+    let rx_max = ROAD_X_MAX + SHOULDER_WIDTH;
+    commands.spawn((
+        SpriteBundle {
+            transform: Transform::from_xyz(rx_max, ry, 0.0).with_scale(vec3(0.5, 0.5, 1.0)),
+            ..default()
+        },
+        RigidBody::Fixed,
+        BOUNDARY_COLLISION_GROUPS,
+        Collider::cuboid(
+            5.0,
+            ROAD_SPRITE_H * SPRITE_SCALE_FACTOR * NUM_ROAD_TILES as f32 * 5.0,
+        ),
+    ));
+
+    // top
+    commands.spawn((
+        SpriteBundle {
+            transform: Transform::from_xyz(600.0, road_end_y, 0.0).with_scale(vec3(0.5, 0.5, 1.0)),
+            ..default()
+        },
+        RigidBody::Fixed,
+        BOUNDARY_COLLISION_GROUPS,
+        Collider::cuboid(500.0 * SPRITE_SCALE_FACTOR, 10.0),
+    ));
+}
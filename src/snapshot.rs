@@ -0,0 +1,296 @@
+use crate::car::{Car, Model, SpawnCar, SyncedVehicle, UpdateCar};
+use crate::configs;
+use crate::enemy::{
+    Enemy, EnemyId, EnemyPosition, RacerId, SpawnEnemies, SyncedPosition, UpdateEnemies,
+};
+use crate::input_map::{Action, ActionState};
+use crate::resources::{MaxDistanceTravelled, SimStats};
+use crate::world::Position;
+use bevy::log;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use starknet_ff::FieldElement;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Saves the synced world with [`Action::Snapshot`] and, given a `restore` file,
+/// re-hydrates the racers, enemies and scores it contains at startup.
+pub struct SnapshotPlugin {
+    pub restore: Option<PathBuf>,
+}
+
+impl Plugin for SnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(path) = &self.restore {
+            match Snapshot::load(path) {
+                Ok(snapshot) => {
+                    app.insert_resource(RestoreSnapshot {
+                        snapshot,
+                        spawned: false,
+                    });
+                }
+                Err(e) => log::error!("Load snapshot {}: {e}", path.display()),
+            }
+        }
+
+        app.add_systems(
+            (restore_snapshot_scores, restore_snapshot)
+                .chain()
+                .distributive_run_if(resource_exists::<RestoreSnapshot>()),
+        )
+        .add_system(save_snapshot_on_key);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    pub racers: Vec<RacerSnapshot>,
+    pub scores: ScoresSnapshot,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RacerSnapshot {
+    pub model_id: FieldElement,
+    pub vehicle: Vec<FieldElement>,
+    pub enemies: Vec<EnemySnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EnemySnapshot {
+    pub enemy_id: FieldElement,
+    pub position: Vec<FieldElement>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ScoresSnapshot {
+    pub generation_count: u32,
+    pub fitness: Vec<f32>,
+    pub max_current_score: f32,
+    pub max_distance_travelled: f32,
+}
+
+impl Snapshot {
+    /// Collects the last synced state of every car and enemy, and the scores.
+    pub fn take(world: &mut World) -> Self {
+        let mut enemies =
+            world.query_filtered::<(&EnemyId, &RacerId, &SyncedPosition), With<Enemy>>();
+        let mut cars = world.query_filtered::<(&Model, &SyncedVehicle), With<Car>>();
+        let world = &*world;
+
+        let racers = cars
+            .iter(world)
+            .map(|(model, vehicle)| {
+                let mut racer_enemies: Vec<_> = enemies
+                    .iter(world)
+                    .filter(|(_, racer_id, _)| racer_id.0 == model.id)
+                    .filter_map(|(enemy_id, _, synced)| {
+                        let position = synced.0?;
+                        Some(EnemySnapshot {
+                            enemy_id: enemy_id.0,
+                            position: Position::encode_signed(position.x, position.y),
+                        })
+                    })
+                    .collect();
+                racer_enemies.sort_by_key(|enemy| enemy.enemy_id);
+
+                RacerSnapshot {
+                    model_id: model.id,
+                    vehicle: vehicle.0.clone(),
+                    enemies: racer_enemies,
+                }
+            })
+            .collect();
+
+        let mut scores = world
+            .get_resource::<SimStats>()
+            .map(|stats| ScoresSnapshot {
+                generation_count: stats.generation_count,
+                fitness: stats.fitness.clone(),
+                max_current_score: stats.max_current_score,
+                ..default()
+            })
+            .unwrap_or_default();
+        if let Some(distance) = world.get_resource::<MaxDistanceTravelled>() {
+            scores.max_distance_travelled = distance.0;
+        }
+
+        Self { racers, scores }
+    }
+
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Snapshot being re-hydrated: racers are spawned on the first frame, their synced state is
+/// applied on the next one once the entities exist.
+#[derive(Resource)]
+pub struct RestoreSnapshot {
+    pub snapshot: Snapshot,
+    pub spawned: bool,
+}
+
+fn restore_snapshot_scores(
+    restore: Res<RestoreSnapshot>,
+    sim_stats: Option<ResMut<SimStats>>,
+    max_distance_travelled: Option<ResMut<MaxDistanceTravelled>>,
+) {
+    let scores = &restore.snapshot.scores;
+    if let Some(mut sim_stats) = sim_stats {
+        sim_stats.generation_count = scores.generation_count;
+        sim_stats.fitness = scores.fitness.clone();
+        sim_stats.max_current_score = scores.max_current_score;
+    }
+    if let Some(mut max_distance_travelled) = max_distance_travelled {
+        max_distance_travelled.0 = scores.max_distance_travelled;
+    }
+}
+
+fn restore_snapshot(
+    mut commands: Commands,
+    mut restore: ResMut<RestoreSnapshot>,
+    mut spawn_car: EventWriter<SpawnCar>,
+    mut spawn_enemies: EventWriter<SpawnEnemies>,
+    mut update_car: EventWriter<UpdateCar>,
+    mut update_enemies: EventWriter<UpdateEnemies>,
+    cars: Query<(), With<Car>>,
+) {
+    if !restore.spawned {
+        for racer in restore.snapshot.racers.iter() {
+            spawn_enemies.send(SpawnEnemies {
+                model_id: racer.model_id,
+            });
+            spawn_car.send(SpawnCar {
+                model_id: racer.model_id,
+                position: None,
+            });
+        }
+        restore.spawned = true;
+        return;
+    }
+    if cars.iter().count() < restore.snapshot.racers.len() {
+        return;
+    }
+
+    let mut positions = Vec::new();
+    for racer in restore.snapshot.racers.iter() {
+        if !racer.vehicle.is_empty() {
+            update_car.send(UpdateCar {
+                model_id: racer.model_id,
+                vehicle: racer.vehicle.clone(),
+            });
+        }
+        for enemy in racer.enemies.iter() {
+            match EnemyPosition::decode(racer.model_id, enemy.enemy_id, &enemy.position) {
+                Ok(position) => positions.push(position),
+                Err(e) => log::error!("Decode enemy position ({}): {e}", enemy.enemy_id),
+            }
+        }
+    }
+    if !positions.is_empty() {
+        update_enemies.send(UpdateEnemies(positions));
+    }
+
+    log::info!(
+        "Restored {} racers from snapshot",
+        restore.snapshot.racers.len()
+    );
+    commands.remove_resource::<RestoreSnapshot>();
+}
+
+fn save_snapshot_on_key(world: &mut World) {
+    let pressed = world
+        .get_resource::<ActionState>()
+        .map_or(false, |actions| actions.just_pressed(Action::Snapshot));
+    if !pressed {
+        return;
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let path = Path::new(configs::SNAPSHOT_DIRECTORY).join(format!("snapshot-{timestamp}.json"));
+
+    match Snapshot::take(world).save(&path) {
+        Ok(()) => log::info!("Saved snapshot to {}", path.display()),
+        Err(e) => log::error!("Save snapshot {}: {e}", path.display()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::car::CarPlugin;
+    use crate::enemy::EnemyPlugin;
+    use bevy_rapier2d::prelude::CollisionEvent;
+
+    fn headless_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default())
+            .add_event::<CollisionEvent>()
+            .add_plugin(CarPlugin)
+            .add_plugin(EnemyPlugin)
+            .insert_resource(SimStats::default())
+            .insert_resource(MaxDistanceTravelled(0.0));
+        app
+    }
+
+    fn fixed(value: u128) -> FieldElement {
+        (value << 64).into()
+    }
+
+    #[test]
+    fn test_snapshot_restores_synced_state() {
+        let model_id = FieldElement::from_byte_slice_be(b"model").unwrap();
+        let snapshot = Snapshot {
+            racers: vec![RacerSnapshot {
+                model_id,
+                vehicle: vec![
+                    fixed(100),
+                    FieldElement::ZERO,
+                    fixed(500),
+                    FieldElement::ZERO,
+                    FieldElement::ZERO,
+                    FieldElement::ZERO,
+                    fixed(50),
+                    FieldElement::ZERO,
+                ],
+                enemies: (0..configs::DOJO_ENEMIES_NB)
+                    .map(|id| EnemySnapshot {
+                        enemy_id: id.into(),
+                        position: vec![(20 + id).into(), (100 * id).into()],
+                    })
+                    .collect(),
+            }],
+            scores: ScoresSnapshot {
+                generation_count: 3,
+                fitness: vec![1.0, 2.5],
+                max_current_score: 2.5,
+                max_distance_travelled: 420.0,
+            },
+        };
+
+        let mut app = headless_app();
+        app.add_plugin(SnapshotPlugin { restore: None })
+            .insert_resource(RestoreSnapshot {
+                snapshot: snapshot.clone(),
+                spawned: false,
+            });
+        for _ in 0..5 {
+            app.update();
+        }
+
+        assert!(!app.world.contains_resource::<RestoreSnapshot>());
+        assert_eq!(Snapshot::take(&mut app.world), snapshot);
+    }
+}
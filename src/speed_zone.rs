@@ -0,0 +1,153 @@
+use crate::configs;
+use bevy::log;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Loads the [`SpeedZones`] of `path`. Standalone cars and enemies driving on their own slow
+/// down in them, and controllers get the speed of their car as limited by the zone it is in.
+pub struct SpeedZonePlugin {
+    pub path: PathBuf,
+}
+
+impl Plugin for SpeedZonePlugin {
+    fn build(&self, app: &mut App) {
+        let zones = SpeedZones::load(&self.path).unwrap_or_else(|e| {
+            log::error!("Load speed zones {}: {e}", self.path.display());
+            SpeedZones::default()
+        });
+        app.insert_resource(zones);
+    }
+}
+
+/// Paints the [`SpeedZones`] across the road.
+pub struct SpeedZoneMarkingsPlugin;
+
+impl Plugin for SpeedZoneMarkingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(spawn_markings);
+    }
+}
+
+/// What a [`SpeedZone`] does to the vehicles in it, e.g. `{"max_speed": 40.0}`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ZoneEffect {
+    /// Pixels per second vehicles are capped at.
+    MaxSpeed(f32),
+    /// Share of their speed vehicles lose, from 0 to 1.
+    Drag(f32),
+}
+
+impl ZoneEffect {
+    /// `speed` in pixels per second once limited by the zone.
+    pub fn apply(self, speed: f32) -> f32 {
+        match self {
+            ZoneEffect::MaxSpeed(max) => speed.min(max),
+            ZoneEffect::Drag(drag) => speed * (1.0 - drag.clamp(0.0, 1.0)),
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            ZoneEffect::MaxSpeed(_) => configs::SPEED_ZONE_MAX_SPEED_COLOR,
+            ZoneEffect::Drag(_) => configs::SPEED_ZONE_DRAG_COLOR,
+        }
+    }
+}
+
+/// Stretch of the track between `from` and `to`, in track `y`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct SpeedZone {
+    pub from: f32,
+    pub to: f32,
+    pub effect: ZoneEffect,
+}
+
+/// Zones of the track, read from a JSON asset.
+#[derive(Resource, Deserialize, Default, Debug)]
+pub struct SpeedZones {
+    pub zones: Vec<SpeedZone>,
+}
+
+impl SpeedZones {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::parse(&json)
+    }
+
+    /// Parses and checks the zones are on the track, before [`configs::FINISH_LINE_Y`].
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let zones: Self = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        if let Some(zone) = zones.zones.iter().find(|zone| {
+            zone.from < 0.0 || zone.from >= zone.to || zone.to > configs::FINISH_LINE_Y
+        }) {
+            return Err(format!(
+                "Zone from y: {} to {} is off the track",
+                zone.from, zone.to
+            ));
+        }
+        Ok(zones)
+    }
+
+    /// Effect in force at track `y`, the first listed one where zones overlap.
+    pub fn effect_at(&self, y: f32) -> Option<ZoneEffect> {
+        self.zones
+            .iter()
+            .find(|zone| (zone.from..zone.to).contains(&y))
+            .map(|zone| zone.effect)
+    }
+
+    /// `velocity` at track `y`, its length limited by the zone there.
+    pub fn velocity_at(&self, y: f32, velocity: Vec2) -> Vec2 {
+        match self.effect_at(y) {
+            Some(effect) => velocity.clamp_length_max(effect.apply(velocity.length())),
+            None => velocity,
+        }
+    }
+}
+
+fn spawn_markings(mut commands: Commands, zones: Res<SpeedZones>) {
+    let center_x = (configs::ROAD_X_MIN + configs::ROAD_X_MAX) / 2.0;
+    for zone in zones.zones.iter() {
+        commands.spawn(SpriteBundle {
+            sprite: Sprite {
+                color: zone.effect.color(),
+                custom_size: Some(Vec2::new(configs::ROAD_W, zone.to - zone.from)),
+                ..default()
+            },
+            transform: Transform::from_xyz(center_x, (zone.from + zone.to) / 2.0, -5.0),
+            ..default()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shipped_zones_limit_speed() {
+        let zones = SpeedZones::parse(include_str!("../assets/speed_zones.json")).unwrap();
+        assert!(!zones.zones.is_empty());
+
+        let velocity = Vec2::new(0.0, 1000.0);
+        for zone in zones.zones.iter() {
+            let limited = zones.velocity_at(zone.from, velocity);
+            assert!(limited.length() < velocity.length());
+            assert_eq!(limited.x, 0.0);
+        }
+        assert_eq!(zones.velocity_at(-1.0, velocity), velocity);
+
+        assert_eq!(ZoneEffect::MaxSpeed(40.0).apply(100.0), 40.0);
+        assert_eq!(ZoneEffect::MaxSpeed(40.0).apply(10.0), 10.0);
+        assert_eq!(ZoneEffect::Drag(0.25).apply(100.0), 75.0);
+
+        let past_finish = format!(
+            r#"{{"zones": [{{"from": 0.0, "to": {}, "effect": {{"drag": 0.5}}}}]}}"#,
+            configs::FINISH_LINE_Y + 1.0
+        );
+        assert!(SpeedZones::parse(&past_finish).is_err());
+    }
+}
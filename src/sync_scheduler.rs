@@ -0,0 +1,172 @@
+use crate::configs;
+use crate::dojo::{DojoCommand, SyncIntervals};
+use bevy::prelude::*;
+use bevy::utils::Duration;
+
+/// When each sync command is due. Each has its own cadence, e.g. enemies are queried less
+/// often than cars are driven.
+#[derive(Component)]
+pub struct SyncScheduler {
+    /// Configured intervals, stretched on a slow node.
+    intervals: SyncIntervals,
+    spawn: Timer,
+    drive: Timer,
+    vehicle: Timer,
+    enemies: Timer,
+    /// Reads are due on the next tick whatever their timers.
+    resync: bool,
+}
+
+impl SyncScheduler {
+    pub fn new(intervals: SyncIntervals) -> Self {
+        let timer = |seconds| Timer::from_seconds(seconds, TimerMode::Repeating);
+        Self {
+            intervals,
+            spawn: timer(intervals.spawn),
+            drive: timer(intervals.drive),
+            vehicle: timer(intervals.vehicle),
+            enemies: timer(intervals.enemies),
+            resync: false,
+        }
+    }
+
+    /// Commands due after `dt`: only spawns while no car is alive, drives and reads otherwise.
+    pub fn tick(&mut self, dt: Duration, has_cars: bool) -> Vec<DojoCommand> {
+        for timer in [
+            &mut self.spawn,
+            &mut self.drive,
+            &mut self.vehicle,
+            &mut self.enemies,
+        ] {
+            timer.tick(dt);
+        }
+
+        if !has_cars {
+            return if self.spawn.just_finished() {
+                vec![DojoCommand::SpawnRacers]
+            } else {
+                vec![]
+            };
+        }
+
+        let resync = std::mem::take(&mut self.resync);
+        [
+            (DojoCommand::Drive, self.drive.just_finished()),
+            (
+                DojoCommand::UpdateVehicle,
+                self.vehicle.just_finished() || resync,
+            ),
+            (
+                DojoCommand::UpdateEnemies,
+                self.enemies.just_finished() || resync,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(command, due)| due.then_some(command))
+        .collect()
+    }
+
+    /// Makes the reads again on the next tick with cars, e.g. the ones dropped on a reorg.
+    pub fn resync(&mut self) {
+        self.resync = true;
+    }
+
+    /// Backs off from a slow node: spaces the ticks of each command at least
+    /// [`configs::DOJO_LATENCY_INTERVAL_FACTOR`] node latencies apart, so it isn't sent
+    /// commands it can't keep up with.
+    pub fn adapt(&mut self, latency: f32) {
+        let min_interval = latency * configs::DOJO_LATENCY_INTERVAL_FACTOR;
+        for (timer, interval) in [
+            (&mut self.spawn, self.intervals.spawn),
+            (&mut self.drive, self.intervals.drive),
+            (&mut self.vehicle, self.intervals.vehicle),
+            (&mut self.enemies, self.intervals.enemies),
+        ] {
+            timer.set_duration(Duration::from_secs_f32(interval.max(min_interval)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn intervals() -> SyncIntervals {
+        SyncIntervals {
+            spawn: 1.0,
+            drive: 0.5,
+            vehicle: 1.0,
+            enemies: 2.0,
+        }
+    }
+
+    fn secs(seconds: f32) -> Duration {
+        Duration::from_secs_f32(seconds)
+    }
+
+    #[test]
+    fn test_commands_are_due_on_their_own_intervals() {
+        let mut scheduler = SyncScheduler::new(intervals());
+        assert_eq!(scheduler.tick(secs(0.25), true), vec![]);
+        assert_eq!(scheduler.tick(secs(0.25), true), vec![DojoCommand::Drive]);
+        assert_eq!(
+            scheduler.tick(secs(0.5), true),
+            vec![DojoCommand::Drive, DojoCommand::UpdateVehicle]
+        );
+        assert_eq!(scheduler.tick(secs(0.5), true), vec![DojoCommand::Drive]);
+        assert_eq!(
+            scheduler.tick(secs(0.5), true),
+            vec![
+                DojoCommand::Drive,
+                DojoCommand::UpdateVehicle,
+                DojoCommand::UpdateEnemies
+            ]
+        );
+    }
+
+    #[test]
+    fn test_racers_are_spawned_only_without_cars() {
+        let mut scheduler = SyncScheduler::new(intervals());
+        assert_eq!(scheduler.tick(secs(0.5), false), vec![]);
+        assert_eq!(
+            scheduler.tick(secs(0.5), false),
+            vec![DojoCommand::SpawnRacers]
+        );
+        // Spawns are never due once the cars are alive
+        assert_eq!(
+            scheduler.tick(secs(1.0), true),
+            vec![
+                DojoCommand::Drive,
+                DojoCommand::UpdateVehicle,
+                DojoCommand::UpdateEnemies
+            ]
+        );
+
+        // A resync waits for the cars
+        scheduler.resync();
+        assert_eq!(scheduler.tick(secs(0.1), false), vec![]);
+        assert_eq!(
+            scheduler.tick(secs(0.1), true),
+            vec![DojoCommand::UpdateVehicle, DojoCommand::UpdateEnemies]
+        );
+        assert_eq!(scheduler.tick(secs(0.1), true), vec![]);
+    }
+
+    #[test]
+    fn test_intervals_back_off_from_a_slow_node() {
+        let mut scheduler = SyncScheduler::new(intervals());
+        scheduler.adapt(1.0);
+        let slowed = configs::DOJO_LATENCY_INTERVAL_FACTOR;
+        assert_eq!(scheduler.drive.duration().as_secs_f32(), slowed);
+        assert_eq!(
+            scheduler.enemies.duration().as_secs_f32(),
+            2.0_f32.max(slowed)
+        );
+        assert_eq!(scheduler.tick(secs(0.5), true), vec![]);
+
+        // Back to the configured intervals once the node is fast again
+        scheduler.adapt(0.0);
+        assert_eq!(scheduler.drive.duration().as_secs_f32(), 0.5);
+        assert_eq!(scheduler.enemies.duration().as_secs_f32(), 2.0);
+    }
+}
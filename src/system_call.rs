@@ -0,0 +1,210 @@
+use crate::conversions::{f32_to_fixed, fixed_to_f32, fixed_to_f32_exact, short_string_to_felt};
+use starknet_ff::FieldElement;
+
+/// Calldata of a world system. The constructors of the drive-ai systems, generated in
+/// [`crate::world`], take their arguments typed, so a call can't miss one or pass them in the
+/// wrong order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SystemCall {
+    pub system: &'static str,
+    pub calldata: Vec<FieldElement>,
+}
+
+/// A value encoded the way cairo serializes it into calldata.
+pub trait Calldata {
+    fn encode(&self, calldata: &mut Vec<FieldElement>);
+}
+
+impl Calldata for FieldElement {
+    fn encode(&self, calldata: &mut Vec<FieldElement>) {
+        calldata.push(*self);
+    }
+}
+
+macro_rules! impl_uint_calldata {
+    ($($uint:ty),*) => {
+        $(impl Calldata for $uint {
+            fn encode(&self, calldata: &mut Vec<FieldElement>) {
+                calldata.push(FieldElement::from(*self));
+            }
+        })*
+    };
+}
+
+impl_uint_calldata!(u8, u16, u32, u64, u128);
+
+impl Calldata for bool {
+    fn encode(&self, calldata: &mut Vec<FieldElement>) {
+        calldata.push(if *self {
+            FieldElement::ONE
+        } else {
+            FieldElement::ZERO
+        });
+    }
+}
+
+/// Signed 64.64 fixed point, a magnitude and a sign felt.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fixed {
+    pub mag: FieldElement,
+    pub negative: bool,
+}
+
+impl Fixed {
+    pub const ZERO: Self = Self {
+        mag: FieldElement::ZERO,
+        negative: false,
+    };
+
+    /// Non-negative value from its already encoded magnitude.
+    pub fn from_mag(mag: FieldElement) -> Self {
+        Self {
+            mag,
+            negative: false,
+        }
+    }
+
+    pub fn from_f32(value: f32) -> Self {
+        Self {
+            mag: f32_to_fixed(value.abs()),
+            negative: value < 0.0,
+        }
+    }
+
+    pub fn to_f32(self) -> f32 {
+        let value = fixed_to_f32(self.mag);
+        if self.negative {
+            -value
+        } else {
+            value
+        }
+    }
+
+    /// Value with its fractional part, see [`fixed_to_f32_exact`].
+    pub fn to_f32_exact(self) -> f32 {
+        let value = fixed_to_f32_exact(self.mag);
+        if self.negative {
+            -value
+        } else {
+            value
+        }
+    }
+}
+
+impl Calldata for Fixed {
+    fn encode(&self, calldata: &mut Vec<FieldElement>) {
+        calldata.push(self.mag);
+        calldata.push(if self.negative {
+            FieldElement::ONE
+        } else {
+            FieldElement::ZERO
+        });
+    }
+}
+
+/// Cairo `Vec2` of fixed point coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FixedVec2 {
+    pub x: Fixed,
+    pub y: Fixed,
+}
+
+impl Calldata for FixedVec2 {
+    fn encode(&self, calldata: &mut Vec<FieldElement>) {
+        self.x.encode(calldata);
+        self.y.encode(calldata);
+    }
+}
+
+/// ASCII string of at most 31 characters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShortString<'a>(pub &'a str);
+
+impl Calldata for ShortString<'_> {
+    fn encode(&self, calldata: &mut Vec<FieldElement>) {
+        calldata.push(short_string_to_felt(self.0));
+    }
+}
+
+/// Cairo enum without data, encoded as the index of its variant.
+pub trait CairoEnum: Copy {
+    fn variant_index(self) -> u8;
+}
+
+impl<T: CairoEnum> Calldata for T {
+    fn encode(&self, calldata: &mut Vec<FieldElement>) {
+        calldata.push(FieldElement::from(self.variant_index()));
+    }
+}
+
+impl SystemCall {
+    /// Call to any system, its arguments added with [`SystemCall::arg`].
+    pub fn new(system: &'static str) -> Self {
+        Self {
+            system,
+            calldata: Vec::new(),
+        }
+    }
+
+    pub fn arg(mut self, value: impl Calldata) -> Self {
+        value.encode(&mut self.calldata);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arguments_are_encoded_as_cairo_serializes_them() {
+        let model = short_string_to_felt("model");
+        let position = FixedVec2 {
+            x: Fixed::from_f32(2.0),
+            y: Fixed::from_f32(-0.5),
+        };
+        let racer = short_string_to_felt("model-0");
+        let call = SystemCall::spawn_racer(racer, model, position, FieldElement::TWO, 4);
+        assert_eq!(call.system, "spawn_racer");
+        assert_eq!(
+            call.calldata,
+            vec![
+                racer,
+                model,
+                FieldElement::from(2_u128 << 64),
+                FieldElement::ZERO,
+                FieldElement::from(1_u128 << 63),
+                FieldElement::ONE,
+                FieldElement::TWO,
+                FieldElement::from(4_u8),
+            ]
+        );
+
+        #[derive(Clone, Copy)]
+        enum Direction {
+            Right,
+        }
+        impl CairoEnum for Direction {
+            fn variant_index(self) -> u8 {
+                2
+            }
+        }
+        let call = SystemCall::drive_manual(model, Direction::Right);
+        assert_eq!(call.calldata, vec![model, FieldElement::TWO]);
+
+        let call = SystemCall::new("custom")
+            .arg(ShortString("model"))
+            .arg(7_u128)
+            .arg(3_u32)
+            .arg(true);
+        assert_eq!(
+            call.calldata,
+            vec![
+                model,
+                FieldElement::from(7_u8),
+                FieldElement::THREE,
+                FieldElement::ONE
+            ]
+        );
+    }
+}
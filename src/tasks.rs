@@ -0,0 +1,156 @@
+use crate::configs;
+use bevy::log;
+use bevy_tokio_tasks::TokioTasksPlugin;
+use std::future::Future;
+use std::pin::Pin;
+use tokio::runtime::Runtime;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+
+/// Tokio runtime of the background tasks, on [`configs::TOKIO_WORKER_THREADS`] threads.
+pub fn tokio_tasks_plugin() -> TokioTasksPlugin {
+    TokioTasksPlugin {
+        make_runtime: Box::new(|| {
+            let mut runtime = tokio::runtime::Builder::new_multi_thread();
+            runtime.enable_all();
+            if let Some(threads) = configs::TOKIO_WORKER_THREADS {
+                runtime.worker_threads(threads);
+            }
+            runtime.build().expect("Failed to create Tokio runtime")
+        }),
+    }
+}
+
+type Start = Box<dyn Fn() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
+/// Background task started again when it panics. What it must keep across restarts, e.g. its
+/// channel receiver, is shared with `start` rather than moved into the task.
+pub struct SupervisedTask {
+    pub name: &'static str,
+    handle: JoinHandle<()>,
+    start: Start,
+    stop: watch::Sender<bool>,
+    pub restarts: u32,
+}
+
+impl SupervisedTask {
+    pub fn spawn<F>(
+        runtime: &Runtime,
+        name: &'static str,
+        start: impl Fn(StopSignal) -> F + Send + Sync + 'static,
+    ) -> Self
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let (stop, signal) = watch::channel(false);
+        let start: Start = Box::new(move || Box::pin(start(StopSignal(signal.clone()))));
+        Self {
+            name,
+            handle: runtime.spawn(start()),
+            start,
+            stop,
+            restarts: 0,
+        }
+    }
+
+    /// Asks the task to end once the commands it was sent are done.
+    pub fn stop(&self) {
+        self.stop.send_replace(true);
+    }
+
+    /// Restarts the task if it panicked, `false` once it is done.
+    pub fn supervise(&mut self, runtime: &Runtime) -> bool {
+        if !self.handle.is_finished() {
+            return true;
+        }
+        match runtime.block_on(&mut self.handle) {
+            Err(e) if e.is_panic() => {
+                self.restarts += 1;
+                log::error!("Task {} panicked, restarting it: {e}", self.name);
+                self.handle = runtime.spawn((self.start)());
+                true
+            }
+            Err(e) => {
+                log::error!("Task {} cancelled: {e}", self.name);
+                false
+            }
+            Ok(()) => false,
+        }
+    }
+
+    pub fn into_handle(self) -> JoinHandle<()> {
+        self.handle
+    }
+}
+
+/// Received by a [`SupervisedTask`], set once it is asked to stop.
+#[derive(Clone)]
+pub struct StopSignal(watch::Receiver<bool>);
+
+impl StopSignal {
+    /// Resolves once the task is asked to stop.
+    pub async fn stopped(&mut self) {
+        let _ = self.0.wait_for(|stop| *stop).await;
+    }
+
+    /// Next command of `rx`. Once the task is asked to stop, `rx` is closed so only the commands
+    /// sent already are received.
+    pub async fn recv<T>(&mut self, rx: &mut mpsc::Receiver<T>) -> Option<T> {
+        tokio::select! {
+            command = rx.recv() => command,
+            _ = self.stopped() => {
+                rx.close();
+                rx.recv().await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use tokio::sync::Mutex;
+
+    #[test]
+    fn test_panicked_task_restarts_on_its_receiver() {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .build()
+            .unwrap();
+        let (tx, rx) = mpsc::channel::<u32>(4);
+        let (done_tx, mut done_rx) = mpsc::unbounded_channel();
+        let rx = Arc::new(Mutex::new(rx));
+        let panicked = Arc::new(AtomicBool::new(false));
+
+        let mut task = SupervisedTask::spawn(&runtime, "test", move |mut stop| {
+            let (rx, done_tx, panicked) = (rx.clone(), done_tx.clone(), panicked.clone());
+            async move {
+                let mut rx = rx.lock().await;
+                while let Some(value) = stop.recv(&mut rx).await {
+                    if !panicked.swap(true, Ordering::SeqCst) {
+                        panic!("First command");
+                    }
+                    done_tx.send(value).unwrap();
+                }
+            }
+        });
+        tx.try_send(1).unwrap();
+        tx.try_send(2).unwrap();
+
+        while task.restarts == 0 {
+            assert!(task.supervise(&runtime));
+        }
+        // The command queued behind the one that panicked is still handled
+        assert_eq!(runtime.block_on(done_rx.recv()), Some(2));
+
+        // Commands sent before the stop are still handled, the sender being alive
+        tx.try_send(3).unwrap();
+        task.stop();
+        while task.supervise(&runtime) {}
+        assert_eq!(task.restarts, 1);
+        assert_eq!(runtime.block_on(done_rx.recv()), Some(3));
+        assert!(tx.try_send(4).is_err());
+    }
+}
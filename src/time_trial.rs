@@ -0,0 +1,186 @@
+use crate::car::{Car, CarBundle, Model};
+use crate::configs;
+use crate::conversions::{dojo_to_bevy_coordinate, felt_to_short_string};
+use crate::countdown::{CountdownPlugin, StartCountdown};
+use crate::locale::Locale;
+use crate::population::RespawnPopulation;
+use crate::resources::Racers;
+use bevy::log;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_inspector_egui::{bevy_egui::EguiContexts, egui};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Races against the clock to [`configs::TIME_TRIAL_TRACK_LENGTH`]: cars are timed from the end
+/// of the start countdown until they cross the finish line. Personal bests are kept in `bests`.
+pub struct TimeTrialPlugin {
+    pub bests: PathBuf,
+}
+
+impl Plugin for TimeTrialPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<CountdownPlugin>() {
+            app.add_plugin(CountdownPlugin);
+        }
+        app.insert_resource(PersonalBests::load(&self.bests))
+            .insert_resource(TimeTrial::Starting)
+            .init_resource::<Locale>()
+            .add_systems((restart_trial, run_trial, time_trial_hud_system).chain());
+    }
+}
+
+#[derive(Resource, Debug, PartialEq)]
+pub enum TimeTrial {
+    /// Until the start countdown releases the cars.
+    Starting,
+    /// Seconds since the cars were released.
+    Racing(f32),
+    /// Time of the run, `None` when the car crashed before the finish line.
+    Finished(Option<f32>),
+}
+
+/// Best time per model, saved as JSON.
+#[derive(Resource, Default, Debug)]
+pub struct PersonalBests {
+    path: PathBuf,
+    pub times: HashMap<String, f32>,
+}
+
+impl PersonalBests {
+    pub fn load(path: &Path) -> Self {
+        let times = fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Self {
+            path: path.to_path_buf(),
+            times,
+        }
+    }
+
+    /// Keeps `time` if it beats the best of `model`, returns whether it did.
+    pub fn record(&mut self, model: &str, time: f32) -> bool {
+        if self.times.get(model).map_or(false, |best| *best <= time) {
+            return false;
+        }
+        self.times.insert(model.to_string(), time);
+        true
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(&self.times)?)
+    }
+}
+
+fn finish_line() -> f32 {
+    dojo_to_bevy_coordinate(0.0, configs::TIME_TRIAL_TRACK_LENGTH).1
+}
+
+fn restart_trial(
+    mut respawn_population: EventReader<RespawnPopulation>,
+    mut trial: ResMut<TimeTrial>,
+) {
+    if respawn_population.iter().count() > 0 {
+        *trial = TimeTrial::Starting;
+    }
+}
+
+fn run_trial(
+    mut commands: Commands,
+    time: Res<Time>,
+    countdown: Res<StartCountdown>,
+    mut trial: ResMut<TimeTrial>,
+    mut bests: ResMut<PersonalBests>,
+    cars: Query<(Entity, &Transform, &Model), With<Car>>,
+) {
+    match *trial {
+        TimeTrial::Starting if countdown.is_racing() => {
+            *trial = TimeTrial::Racing(0.0);
+        }
+        TimeTrial::Racing(elapsed) => {
+            let elapsed = elapsed + time.delta_seconds();
+            *trial = TimeTrial::Racing(elapsed);
+
+            if cars.is_empty() {
+                *trial = TimeTrial::Finished(None);
+                return;
+            }
+            let finish_line = finish_line();
+            for (entity, transform, model) in cars.iter() {
+                if transform.translation.y < finish_line {
+                    continue;
+                }
+
+                let model = felt_to_short_string(model.id);
+                if bests.record(&model, elapsed) {
+                    log::info!("New personal best for {model}: {elapsed:.2}s");
+                    if let Err(e) = bests.save() {
+                        log::error!("Save personal bests {}: {e}", bests.path.display());
+                    }
+                }
+                *trial = TimeTrial::Finished(Some(elapsed));
+                // Ends the run, the next one starts with a new countdown
+                commands.entity(entity).remove::<CarBundle>();
+                break;
+            }
+        }
+        _ => {}
+    }
+}
+
+fn time_trial_hud_system(
+    mut contexts: EguiContexts,
+    locale: Res<Locale>,
+    countdown: Res<StartCountdown>,
+    trial: Res<TimeTrial>,
+    bests: Res<PersonalBests>,
+    racers: Res<Racers>,
+) {
+    egui::Window::new(locale.text("hud.time_trial"))
+        .title_bar(false)
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 20.0))
+        .show(contexts.ctx_mut(), |ui| {
+            let text = match *trial {
+                TimeTrial::Starting => match *countdown {
+                    StartCountdown::Counting(left) => format!("{}", left.ceil()),
+                    _ => locale.text("hud.get_ready").to_string(),
+                },
+                TimeTrial::Racing(elapsed) => {
+                    locale.format("hud.race_time", &[("time", &format!("{elapsed:.2}"))])
+                }
+                TimeTrial::Finished(Some(time)) => {
+                    locale.format("hud.finished_in", &[("time", &format!("{time:.2}"))])
+                }
+                TimeTrial::Finished(None) => locale.text("hud.crashed").to_string(),
+            };
+            ui.heading(text);
+
+            for model_id in racers.0.iter() {
+                let model = felt_to_short_string(*model_id);
+                if let Some(best) = bests.times.get(&model) {
+                    let best = format!("{best:.2}");
+                    ui.label(locale.format("hud.best", &[("model", &model), ("time", &best)]));
+                }
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_personal_best_keeps_fastest_time() {
+        let mut bests = PersonalBests::default();
+
+        assert!(bests.record("model", 12.5));
+        assert!(!bests.record("model", 13.0));
+        assert!(bests.record("model", 11.0));
+        assert!(bests.record("other", 20.0));
+        assert_eq!(bests.times["model"], 11.0);
+    }
+}
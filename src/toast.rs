@@ -0,0 +1,53 @@
+use bevy::prelude::*;
+
+/// Events shown for a few seconds after they were sent, with the seconds left to show them.
+#[derive(Resource)]
+pub struct Toasts<T>(Vec<(T, f32)>);
+
+impl<T> Default for Toasts<T> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<T: Clone> Toasts<T> {
+    /// Shows the `sent` events for `seconds`, and drops the ones shown for long enough after
+    /// `delta` more seconds.
+    pub fn update<'a>(&mut self, sent: impl IntoIterator<Item = &'a T>, seconds: f32, delta: f32)
+    where
+        T: 'a,
+    {
+        self.0
+            .extend(sent.into_iter().map(|event| (event.clone(), seconds)));
+        self.0.retain_mut(|(_, left)| {
+            *left -= delta;
+            *left > 0.0
+        });
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.0.iter().map(|(event, _)| event)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toasts_are_shown_for_their_seconds() {
+        let mut toasts = Toasts::default();
+        toasts.update(&[1, 2], 1.0, 0.0);
+        toasts.update(&[3], 2.0, 0.5);
+        assert_eq!(toasts.iter().copied().collect::<Vec<_>>(), [1, 2, 3]);
+
+        toasts.update(&[], 1.0, 0.5);
+        assert_eq!(toasts.iter().copied().collect::<Vec<_>>(), [3]);
+        toasts.update(&[], 1.0, 1.0);
+        assert!(toasts.is_empty());
+    }
+}
@@ -0,0 +1,172 @@
+use crate::conversions::felt_to_short_string;
+use crate::locale::Locale;
+use crate::race::{HeadToHead, HeadToHeadPlugin, RaceFinished};
+use crate::resources::Racers;
+use bevy::log;
+use bevy::prelude::*;
+use bevy_inspector_egui::{bevy_egui::EguiContexts, egui};
+use starknet_ff::FieldElement;
+
+/// Single elimination bracket of head-to-head races, winners meet in the next round until one
+/// model is left.
+pub struct TournamentPlugin {
+    pub models: Vec<FieldElement>,
+}
+
+impl Plugin for TournamentPlugin {
+    fn build(&self, app: &mut App) {
+        let mut tournament = Tournament::new(self.models.clone());
+        let Some(first) = tournament.next_match() else {
+            log::error!("A tournament needs at least 2 models");
+            return;
+        };
+
+        app.add_plugin(HeadToHeadPlugin {
+            race: HeadToHead::new(first),
+        })
+        .insert_resource(tournament)
+        .add_systems((advance_tournament, bracket_system).chain());
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Match {
+    pub left: FieldElement,
+    /// `None` when `left` has no opponent and advances directly.
+    pub right: Option<FieldElement>,
+    pub winner: Option<FieldElement>,
+}
+
+#[derive(Resource, Debug)]
+pub struct Tournament {
+    pub rounds: Vec<Vec<Match>>,
+    pub champion: Option<FieldElement>,
+}
+
+impl Tournament {
+    pub fn new(models: Vec<FieldElement>) -> Self {
+        Self {
+            rounds: vec![Self::pair(&models)],
+            champion: None,
+        }
+    }
+
+    fn pair(models: &[FieldElement]) -> Vec<Match> {
+        models
+            .chunks(2)
+            .map(|pair| Match {
+                left: pair[0],
+                right: pair.get(1).copied(),
+                winner: None,
+            })
+            .collect()
+    }
+
+    /// Models of the next match to race, `None` once the tournament has a champion. Byes are
+    /// resolved and new rounds drawn along the way.
+    pub fn next_match(&mut self) -> Option<[FieldElement; 2]> {
+        loop {
+            let round = self.rounds.last_mut()?;
+            if let Some(m) = round.iter_mut().find(|m| m.winner.is_none()) {
+                match m.right {
+                    Some(right) => return Some([m.left, right]),
+                    None => m.winner = Some(m.left),
+                }
+                continue;
+            }
+
+            let winners: Vec<_> = round.iter().filter_map(|m| m.winner).collect();
+            if winners.len() <= 1 {
+                self.champion = winners.first().copied();
+                return None;
+            }
+            self.rounds.push(Self::pair(&winners));
+        }
+    }
+
+    /// Records the result of the match between `models`, on a draw the left model advances.
+    pub fn record(&mut self, models: [FieldElement; 2], winner: Option<FieldElement>) {
+        let current = self.rounds.last_mut().and_then(|round| {
+            round
+                .iter_mut()
+                .find(|m| m.winner.is_none() && [Some(m.left), m.right] == models.map(Some))
+        });
+        if let Some(m) = current {
+            m.winner = Some(winner.unwrap_or(m.left));
+        }
+    }
+}
+
+fn advance_tournament(
+    mut finished: EventReader<RaceFinished>,
+    mut tournament: ResMut<Tournament>,
+    mut race: ResMut<HeadToHead>,
+    mut racers: ResMut<Racers>,
+) {
+    for result in finished.iter() {
+        tournament.record(result.models, result.winner);
+
+        match tournament.next_match() {
+            Some(models) => {
+                *race = HeadToHead::new(models);
+                racers.0 = models.to_vec();
+            }
+            None => {
+                racers.0.clear();
+                if let Some(champion) = tournament.champion {
+                    log::info!("Tournament won by {}", felt_to_short_string(champion));
+                }
+            }
+        }
+    }
+}
+
+fn bracket_system(mut contexts: EguiContexts, locale: Res<Locale>, tournament: Res<Tournament>) {
+    egui::Window::new(locale.text("results.tournament"))
+        .default_pos(egui::pos2(20.0, 50.0))
+        .show(contexts.ctx_mut(), |ui| {
+            ui.columns(tournament.rounds.len(), |columns| {
+                for (round, ui) in tournament.rounds.iter().zip(columns.iter_mut()) {
+                    for m in round.iter() {
+                        for model in [Some(m.left), m.right].into_iter().flatten() {
+                            let name = felt_to_short_string(model);
+                            if m.winner == Some(model) {
+                                ui.strong(name);
+                            } else {
+                                ui.label(name);
+                            }
+                        }
+                        ui.separator();
+                    }
+                }
+            });
+            if let Some(champion) = tournament.champion {
+                let champion = felt_to_short_string(champion);
+                ui.heading(locale.format("results.champion", &[("model", &champion)]));
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bracket_runs_until_champion() {
+        let models: Vec<FieldElement> = (1_u32..=3).map(FieldElement::from).collect();
+        let mut tournament = Tournament::new(models.clone());
+
+        let first = tournament.next_match().unwrap();
+        assert_eq!(first, [models[0], models[1]]);
+        tournament.record(first, Some(models[1]));
+
+        // The third model had a bye and meets the winner of the first match
+        let second = tournament.next_match().unwrap();
+        assert_eq!(second, [models[1], models[2]]);
+        tournament.record(second, None);
+
+        assert_eq!(tournament.next_match(), None);
+        assert_eq!(tournament.champion, Some(models[1]));
+        assert_eq!(tournament.rounds.len(), 2);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::configs;
+use crate::input_map::{Action, ActionState};
+use crate::locale::Locale;
+use crate::resources::SimStats;
+use bevy::log;
+use bevy::prelude::*;
+use bevy_inspector_egui::{
+    bevy_egui::EguiContexts,
+    egui::{
+        self,
+        plot::{Legend, Line, Plot, PlotPoints},
+    },
+};
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+/// Charts the best and mean fitness of every generation, toggled with
+/// [`Action::TrainingPlot`], the series can be exported to [`configs::TRAINING_CSV_PATH`].
+pub struct TrainingPlotPlugin;
+
+impl Plugin for TrainingPlotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Locale>()
+            .init_resource::<TrainingPlot>()
+            .add_system(training_plot_system);
+    }
+}
+
+#[derive(Resource, Default)]
+struct TrainingPlot {
+    visible: bool,
+    /// Outcome of the last export.
+    status: Option<Result<(), String>>,
+}
+
+/// One line per generation, with a header.
+fn fitness_csv(best: &[f32], mean: &[f32]) -> String {
+    let mut csv = "generation,best_fitness,mean_fitness\n".to_string();
+    for (generation, best) in best.iter().enumerate() {
+        let mean = mean
+            .get(generation)
+            .map_or(String::new(), |mean| mean.to_string());
+        let _ = writeln!(csv, "{generation},{best},{mean}");
+    }
+    csv
+}
+
+fn export_csv(path: &Path, stats: &SimStats) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, fitness_csv(&stats.fitness, &stats.mean_fitness))
+}
+
+fn series(values: &[f32]) -> PlotPoints {
+    values
+        .iter()
+        .enumerate()
+        .map(|(generation, value)| [generation as f64, *value as f64])
+        .collect()
+}
+
+fn training_plot_system(
+    mut contexts: EguiContexts,
+    actions: Res<ActionState>,
+    locale: Res<Locale>,
+    sim_stats: Option<Res<SimStats>>,
+    mut plot: ResMut<TrainingPlot>,
+) {
+    if actions.just_pressed(Action::TrainingPlot) {
+        plot.visible = !plot.visible;
+    }
+    let (true, Some(sim_stats)) = (plot.visible, sim_stats) else {
+        return;
+    };
+
+    egui::Window::new(locale.text("training.title"))
+        .default_pos(egui::pos2(20.0, 20.0))
+        .show(contexts.ctx_mut(), |ui| {
+            Plot::new("training_plot")
+                .view_aspect(2.0)
+                .legend(Legend::default())
+                .show(ui, |plot_ui| {
+                    plot_ui.line(
+                        Line::new(series(&sim_stats.fitness)).name(locale.text("training.best")),
+                    );
+                    plot_ui.line(
+                        Line::new(series(&sim_stats.mean_fitness))
+                            .name(locale.text("training.mean")),
+                    );
+                });
+
+            ui.horizontal(|ui| {
+                if ui.button(locale.text("training.export_csv")).clicked() {
+                    let path = Path::new(configs::TRAINING_CSV_PATH);
+                    plot.status = Some(export_csv(path, &sim_stats).map_err(|e| {
+                        log::error!("Export training statistics {}: {e}", path.display());
+                        e.to_string()
+                    }));
+                }
+                match &plot.status {
+                    Some(Ok(())) => {
+                        let path = Path::new(configs::TRAINING_CSV_PATH).display();
+                        ui.label(locale.format("training.saved", &[("path", &path)]));
+                    }
+                    Some(Err(e)) => {
+                        ui.label(locale.format("training.export_failed", &[("error", e)]));
+                    }
+                    None => {}
+                }
+            });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fitness_csv_has_a_line_per_generation() {
+        assert_eq!(
+            fitness_csv(&[2.5, 4.0], &[1.0]),
+            "generation,best_fitness,mean_fitness\n0,2.5,1\n1,4,\n"
+        );
+    }
+}
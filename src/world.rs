@@ -0,0 +1,194 @@
+use crate::conversions::felt_to_i128;
+use crate::system_call::{Calldata, Fixed, FixedVec2, SystemCall};
+use eyre::eyre;
+use starknet_ff::FieldElement;
+
+// Components and system constructors generated by `build.rs` from the manifest of `sozo build`
+include!(concat!(env!("OUT_DIR"), "/world.rs"));
+
+/// Component read by name from the world.
+pub trait WorldComponent: Decode {
+    const NAME: &'static str;
+}
+
+/// A value decoded from the felts cairo serializes it to.
+pub trait Decode: Sized {
+    /// Felts taken by the value.
+    const SIZE: usize;
+    fn decode(values: &[FieldElement]) -> eyre::Result<Self>;
+}
+
+/// Decodes a `T` from the front of `values` and skips past it.
+fn take<T: Decode>(values: &mut &[FieldElement]) -> eyre::Result<T> {
+    if values.len() < T::SIZE {
+        return Err(eyre!(
+            "Expected {} more values, got {}",
+            T::SIZE,
+            values.len()
+        ));
+    }
+    let (value, rest) = values.split_at(T::SIZE);
+    *values = rest;
+    T::decode(value)
+}
+
+impl Position {
+    /// Coordinates read as signed: a position off the left of the grid or behind its start
+    /// comes as a felt close to the field modulus, out of range of the `u128` members.
+    pub fn decode_signed(values: &[FieldElement]) -> eyre::Result<(f32, f32)> {
+        if values.len() != Self::SIZE {
+            return Err(eyre!(
+                "Expected {} values, got {}",
+                Self::SIZE,
+                values.len()
+            ));
+        }
+        let coordinate = |value: FieldElement| {
+            felt_to_i128(value)
+                .map(|coordinate| coordinate as f32)
+                .ok_or_else(|| eyre!("{value} out of range of a coordinate"))
+        };
+        Ok((coordinate(values[0])?, coordinate(values[1])?))
+    }
+
+    /// Values of the coordinates read by [`Position::decode_signed`], e.g. to record them.
+    pub fn encode_signed(x: f32, y: f32) -> Vec<FieldElement> {
+        [x, y]
+            .into_iter()
+            .map(|coordinate| {
+                let magnitude = FieldElement::from(coordinate.abs() as u128);
+                if coordinate < 0.0 {
+                    FieldElement::ZERO - magnitude
+                } else {
+                    magnitude
+                }
+            })
+            .collect()
+    }
+}
+
+/// Decodes a cairo `Array<T>` or `Span<T>`, its length followed by its packed items, e.g. the
+/// positions of all the enemies of a racer read in one call.
+pub fn decode_array<T: Decode>(values: &[FieldElement]) -> eyre::Result<Vec<T>> {
+    let mut values = values;
+    let len: u32 = take(&mut values)?;
+    let items = (0..len)
+        .map(|_| take(&mut values))
+        .collect::<eyre::Result<Vec<T>>>()?;
+    if !values.is_empty() {
+        return Err(eyre!("{} values left after {len} items", values.len()));
+    }
+    Ok(items)
+}
+
+impl Decode for FieldElement {
+    const SIZE: usize = 1;
+    fn decode(values: &[FieldElement]) -> eyre::Result<Self> {
+        values.first().copied().ok_or_else(|| eyre!("Missing felt"))
+    }
+}
+
+impl Decode for bool {
+    const SIZE: usize = 1;
+    fn decode(values: &[FieldElement]) -> eyre::Result<Self> {
+        Ok(FieldElement::decode(values)? != FieldElement::ZERO)
+    }
+}
+
+macro_rules! decode_uint {
+    ($($uint:ty),*) => {
+        $(impl Decode for $uint {
+            const SIZE: usize = 1;
+            fn decode(values: &[FieldElement]) -> eyre::Result<Self> {
+                let value = FieldElement::decode(values)?;
+                <$uint>::try_from(value)
+                    .map_err(|_| eyre!("{value} out of range of {}", stringify!($uint)))
+            }
+        })*
+    };
+}
+
+decode_uint!(u8, u16, u32, u64, u128);
+
+impl Decode for Fixed {
+    const SIZE: usize = 2;
+    fn decode(values: &[FieldElement]) -> eyre::Result<Self> {
+        let mut values = values;
+        Ok(Fixed {
+            mag: take(&mut values)?,
+            negative: take(&mut values)?,
+        })
+    }
+}
+
+impl Decode for FixedVec2 {
+    const SIZE: usize = 2 * Fixed::SIZE;
+    fn decode(values: &[FieldElement]) -> eyre::Result<Self> {
+        let mut values = values;
+        Ok(FixedVec2 {
+            x: take(&mut values)?,
+            y: take(&mut values)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_components_decode_in_member_order() {
+        let values = [
+            FieldElement::from(100_u128 << 64),
+            FieldElement::ZERO,
+            FieldElement::from(500_u128 << 64),
+            FieldElement::ZERO,
+            FieldElement::from(1_u128 << 64),
+            FieldElement::ONE,
+            FieldElement::from(3_u128 << 64),
+            FieldElement::ZERO,
+        ];
+        let vehicle = Vehicle::decode(&values).unwrap();
+        assert_eq!(Vehicle::NAME, "Vehicle");
+        assert_eq!(Vehicle::SIZE, values.len());
+        assert_eq!(vehicle.position.x.to_f32(), 100.0);
+        assert_eq!(vehicle.position.y.to_f32(), 500.0);
+        assert_eq!(vehicle.steer.to_f32(), -1.0);
+        assert_eq!(vehicle.speed.to_f32(), 3.0);
+
+        let position = Position::decode(&[FieldElement::TWO, FieldElement::THREE]).unwrap();
+        assert_eq!((position.x, position.y), (2, 3));
+        let behind = [FieldElement::TWO, FieldElement::ZERO - FieldElement::THREE];
+        assert_eq!(Position::decode_signed(&behind).unwrap(), (2.0, -3.0));
+        assert_eq!(Position::encode_signed(2.0, -3.0), behind);
+        assert!(Position::decode(&behind).is_err());
+        assert!(Vehicle::decode(&values[..7]).is_err());
+
+        let call = SystemCall::submit_score(FieldElement::ONE);
+        assert_eq!(call.system, "submit_score");
+        assert_eq!(call.calldata, [FieldElement::ONE]);
+    }
+
+    #[test]
+    fn test_packed_positions_decode_in_order() {
+        let packed: Vec<FieldElement> = [3_u32, 20, 100, 60, 140, 100, 180]
+            .into_iter()
+            .map(FieldElement::from)
+            .collect();
+        let positions: Vec<(u128, u128)> = decode_array::<Position>(&packed)
+            .unwrap()
+            .into_iter()
+            .map(|position| (position.x, position.y))
+            .collect();
+        assert_eq!(positions, vec![(20, 100), (60, 140), (100, 180)]);
+
+        assert!(decode_array::<Position>(&[FieldElement::ZERO])
+            .unwrap()
+            .is_empty());
+        // Truncated, or with more values than the length says
+        assert!(decode_array::<Position>(&packed[..6]).is_err());
+        let mut extra = packed.clone();
+        extra.push(FieldElement::ONE);
+        assert!(decode_array::<Position>(&extra).is_err());
+    }
+}
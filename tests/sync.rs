@@ -0,0 +1,262 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::CollisionEvent;
+use bevy_rapier2d::rapier::geometry::CollisionEventFlags;
+use starknet::core::types::FieldElement;
+use starknet::core::utils::cairo_short_string_to_felt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use steering::backend::{Backend, MockBackend};
+use steering::car::{Car, CarBundle, CarCrashed, CarPlugin};
+use steering::configs;
+use steering::conversions::dojo_to_bevy_coordinate;
+use steering::dojo::{DojoPlugin, Funds};
+use steering::enemy::{Enemy, EnemyCount, EnemyId, EnemyPlugin, EnemySeed};
+use steering::population::PopulationPlugin;
+
+fn headless_app(backend: Arc<MockBackend>) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(AssetPlugin::default())
+        .add_event::<CollisionEvent>()
+        .insert_resource(Backend(backend))
+        .add_plugin(CarPlugin)
+        .add_plugin(EnemyPlugin)
+        .add_plugin(PopulationPlugin)
+        .add_plugin(DojoPlugin::default());
+    app
+}
+
+fn update_until(app: &mut App, mut done: impl FnMut(&mut World) -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !done(&mut app.world) {
+        assert!(Instant::now() < deadline, "timed out waiting for sync");
+        app.update();
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+fn model_id() -> FieldElement {
+    cairo_short_string_to_felt(configs::MODEL_NAME).unwrap()
+}
+
+fn fixed(value: u128) -> FieldElement {
+    (value << 64).into()
+}
+
+fn enemy_position(id: u32) -> (u32, u32) {
+    (20 + 40 * id, 100 * id)
+}
+
+#[test]
+fn spawn_racer_spawns_car_and_enemies() {
+    let backend = Arc::new(MockBackend::default());
+    let mut app = headless_app(backend.clone());
+
+    update_until(&mut app, |world| {
+        world.query_filtered::<(), With<Car>>().iter(world).count() == 1
+    });
+
+    let enemies = app
+        .world
+        .query_filtered::<&EnemyId, With<Enemy>>()
+        .iter(&app.world)
+        .count();
+    assert_eq!(enemies, configs::DOJO_ENEMIES_NB as usize);
+
+    let (system, calldata) = &backend.executions()[0];
+    assert_eq!(system, "spawn_racer");
+    assert_eq!(calldata[0], model_id());
+    // Racers of a generation share the enemy seed of that generation
+    assert_eq!(calldata[5], EnemySeed::for_generation(0).0);
+    assert_eq!(calldata[6], FieldElement::from(configs::DOJO_ENEMIES_NB));
+}
+
+#[test]
+fn sync_applies_positions_and_drives() {
+    let backend = Arc::new(MockBackend::default());
+    backend.set_entity(
+        "Vehicle",
+        vec![model_id()],
+        vec![
+            fixed(100),
+            FieldElement::ZERO,
+            fixed(500),
+            FieldElement::ZERO,
+            FieldElement::ZERO,
+            FieldElement::ZERO,
+            fixed(50),
+            FieldElement::ZERO,
+        ],
+    );
+    for id in 0..configs::DOJO_ENEMIES_NB {
+        let (x, y) = enemy_position(id);
+        backend.set_entity(
+            "Position",
+            vec![model_id(), id.into()],
+            vec![x.into(), y.into()],
+        );
+    }
+    let mut app = headless_app(backend.clone());
+
+    let (car_x, car_y) = dojo_to_bevy_coordinate(100.0, 500.0);
+    update_until(&mut app, |world| {
+        world
+            .query_filtered::<&Transform, With<Car>>()
+            .iter(world)
+            .any(|t| {
+                (t.translation.x - car_x).abs() < 1e-3 && (t.translation.y - car_y).abs() < 1e-3
+            })
+    });
+    update_until(&mut app, |_| {
+        backend
+            .executions()
+            .iter()
+            .any(|(system, calldata)| system == "drive" && *calldata == vec![model_id()])
+    });
+    update_until(&mut app, |world| {
+        world
+            .query::<(&Transform, &EnemyId)>()
+            .iter(world)
+            .all(|(transform, enemy_id)| {
+                let id = (0..configs::DOJO_ENEMIES_NB)
+                    .find(|id| FieldElement::from(*id) == enemy_id.0)
+                    .unwrap();
+                let (x, y) = enemy_position(id);
+                let (x, y) = dojo_to_bevy_coordinate(x as f32, y as f32);
+                (transform.translation.x - x).abs() < 1e-3
+                    && (transform.translation.y - y).abs() < 1e-3
+            })
+    });
+}
+
+#[test]
+fn crashed_generation_evolves_and_respawns() {
+    let backend = Arc::new(MockBackend::default());
+    let mut app = headless_app(backend.clone());
+
+    update_until(&mut app, |world| {
+        world.query_filtered::<(), With<Car>>().iter(world).count() == 1
+    });
+    let car = app
+        .world
+        .query_filtered::<Entity, With<Car>>()
+        .single(&app.world);
+    // Applies to the next generation only
+    app.world.insert_resource(EnemyCount(4));
+    app.world.entity_mut(car).remove::<CarBundle>();
+
+    update_until(&mut app, |world| {
+        let spawns = backend
+            .executions()
+            .iter()
+            .filter(|(system, _)| system == "spawn_racer")
+            .count();
+        spawns == 2 && world.query_filtered::<(), With<Car>>().iter(world).count() == 1
+    });
+
+    let systems: Vec<_> = backend
+        .executions()
+        .into_iter()
+        .map(|(system, _)| system)
+        .filter(|system| system != "drive")
+        .collect();
+    assert_eq!(systems, ["spawn_racer", "evolve", "spawn_racer"]);
+    let spawns: Vec<_> = backend
+        .executions()
+        .into_iter()
+        .filter(|(system, _)| system == "spawn_racer")
+        .map(|(_, calldata)| (calldata[5], calldata[6]))
+        .collect();
+    assert_eq!(
+        spawns,
+        [
+            (
+                EnemySeed::for_generation(0).0,
+                FieldElement::from(configs::DOJO_ENEMIES_NB)
+            ),
+            (EnemySeed::for_generation(1).0, FieldElement::from(4_u8))
+        ]
+    );
+
+    let enemies = app
+        .world
+        .query_filtered::<(), With<Enemy>>()
+        .iter(&app.world)
+        .count();
+    assert_eq!(enemies, 4);
+    assert_eq!(
+        app.world.resource::<steering::SimStats>().generation_count,
+        1
+    );
+}
+
+#[test]
+fn client_collision_is_reported() {
+    let backend = Arc::new(MockBackend::default());
+    let mut app = headless_app(backend.clone());
+
+    update_until(&mut app, |world| {
+        world.query_filtered::<(), With<Car>>().iter(world).count() == 1
+    });
+    let car = app
+        .world
+        .query_filtered::<Entity, With<Car>>()
+        .single(&app.world);
+    let enemy = app
+        .world
+        .query_filtered::<Entity, With<Enemy>>()
+        .iter(&app.world)
+        .next()
+        .unwrap();
+    app.world.send_event(CollisionEvent::Started(
+        car,
+        enemy,
+        CollisionEventFlags::empty(),
+    ));
+
+    update_until(&mut app, |_| {
+        backend
+            .executions()
+            .iter()
+            .any(|(system, calldata)| system == "report_collision" && *calldata == vec![model_id()])
+    });
+}
+
+#[test]
+fn pending_commands_are_flushed_on_exit() {
+    let backend = Arc::new(MockBackend::default());
+    let mut app = headless_app(backend.clone());
+
+    update_until(&mut app, |world| {
+        world.query_filtered::<(), With<Car>>().iter(world).count() == 1
+    });
+    let model_id = model_id();
+    app.world.send_event(CarCrashed {
+        model_id,
+        position: Vec2::ZERO,
+    });
+    app.world.send_event(AppExit);
+    app.update();
+
+    assert!(backend
+        .executions()
+        .iter()
+        .any(|(system, calldata)| system == "report_collision" && *calldata == vec![model_id]));
+}
+
+#[test]
+fn poor_account_sends_no_transaction() {
+    let backend = Arc::new(MockBackend::default());
+    backend.set_balance(0);
+    let mut app = headless_app(backend.clone());
+
+    update_until(&mut app, |world| {
+        *world.resource::<Funds>() == Funds::Insufficient { balance: 0 }
+    });
+    for _ in 0..10 {
+        app.update();
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert!(backend.executions().is_empty());
+}